@@ -0,0 +1,75 @@
+//! Rows of controls that wrap onto further rows when the window is too
+//! narrow for them.
+
+use iced::{Align, Column, Element, Row, Text};
+
+/// Rows grow no wider than this, so sliders filling a row stay usable on
+/// very wide windows.
+const MAX_ROW_WIDTH: u32 = 1400;
+
+/// Controls collected in groups. A group, such as a label with the control
+/// it labels, is never split across rows.
+pub struct Bar<'a, Message> {
+    groups: Vec<Vec<Element<'a, Message>>>,
+    /// Whether the next item joins the last group.
+    glued: bool,
+}
+
+impl<'a, Message: 'a> Bar<'a, Message> {
+    pub fn new() -> Bar<'a, Message> {
+        Bar {
+            groups: vec![],
+            glued: false,
+        }
+    }
+
+    /// Starts a new group with `item`, or adds it to the group of the label
+    /// just before it.
+    pub fn push(mut self, item: impl Into<Element<'a, Message>>) -> Bar<'a, Message> {
+        match self.groups.last_mut() {
+            Some(group) if self.glued => group.push(item.into()),
+            _ => self.groups.push(vec![item.into()]),
+        }
+        self.glued = false;
+        self
+    }
+
+    /// Adds `item` to the last group.
+    pub fn then(mut self, item: impl Into<Element<'a, Message>>) -> Bar<'a, Message> {
+        self.glued = true;
+        self.push(item)
+    }
+
+    /// Starts a new group with a label, which the next item joins.
+    pub fn label(self, text: impl Into<String>) -> Bar<'a, Message> {
+        self.styled_label(Text::new(text))
+    }
+
+    /// Like [`label`](Self::label), with a text styled by the caller.
+    pub fn styled_label(self, text: Text) -> Bar<'a, Message> {
+        let mut bar = self.push(text);
+        bar.glued = true;
+        bar
+    }
+
+    /// Lays the groups out in rows of at most `per_row` groups.
+    pub fn wrap(self, per_row: usize) -> Element<'a, Message> {
+        let mut column = Column::new()
+            .padding(10)
+            .spacing(10)
+            .align_items(Align::Center);
+        let mut groups = self.groups.into_iter().peekable();
+        while groups.peek().is_some() {
+            column = column.push(
+                groups.by_ref().take(per_row.max(1)).flatten().fold(
+                    Row::new()
+                        .spacing(20)
+                        .max_width(MAX_ROW_WIDTH)
+                        .align_items(Align::Center),
+                    Row::push,
+                ),
+            );
+        }
+        column.into()
+    }
+}
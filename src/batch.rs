@@ -0,0 +1,116 @@
+//! Rendering every session in a directory to a PNG without opening the
+//! window, a few files at a time. A file that fails is reported and the
+//! others go on.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use iced::Size;
+use sierpinski_triangle_emulator::Error;
+
+use crate::{session::Session, SierpinskiGraph, MAX_EXTENDED_ITER};
+
+/// Files rendered at once at most, however many cores there are. Each one
+/// holds all its points and its image while it renders.
+const MAX_JOBS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct Batch {
+    /// Where the sessions are read from, every `.json` file in it.
+    pub dir: PathBuf,
+    /// Where the images go; next to their sessions without one.
+    pub out_dir: Option<PathBuf>,
+    /// Iterations every session is rendered with instead of its own max
+    /// iter.
+    pub iterations: Option<usize>,
+    pub canvas: [u32; 2],
+}
+
+impl Batch {
+    /// The session files in the directory, in path order.
+    pub fn sessions(&self) -> io::Result<Vec<PathBuf>> {
+        let mut sessions = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|e| e == "json") {
+                sessions.push(path);
+            }
+        }
+        sessions.sort();
+        Ok(sessions)
+    }
+
+    /// Renders each of `sessions` on a few threads and returns where every
+    /// image went, or why it didn't, in the order of `sessions`. `done`
+    /// hears about each file as it finishes, from the thread it rendered on.
+    pub fn run(
+        &self,
+        sessions: &[PathBuf],
+        done: impl Fn(&Path, &Result<PathBuf, Error>) + Sync,
+    ) -> Vec<Result<PathBuf, Error>> {
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(sessions.iter().map(|_| None).collect::<Vec<_>>());
+        let jobs = thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(MAX_JOBS)
+            .min(sessions.len());
+        thread::scope(|scope| {
+            for _ in 0..jobs {
+                scope.spawn(|| {
+                    let mut i = next.fetch_add(1, Ordering::Relaxed);
+                    while let Some(session) = sessions.get(i) {
+                        let result = self.render(session);
+                        done(session, &result);
+                        results.lock().unwrap()[i] = Some(result);
+                        i = next.fetch_add(1, Ordering::Relaxed);
+                    }
+                });
+            }
+        });
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.expect("every session is rendered"))
+            .collect()
+    }
+
+    /// Where the image of the session at `path` goes.
+    pub fn output(&self, path: &Path) -> PathBuf {
+        let image = path.with_extension("png");
+        match (&self.out_dir, image.file_name()) {
+            (Some(dir), Some(name)) => dir.join(name),
+            _ => image,
+        }
+    }
+
+    /// Draws the session at `path` as the window exports it, with every
+    /// seed, the border and the vertices, after the same limits a session
+    /// loaded in the window gets.
+    fn render(&self, path: &Path) -> Result<PathBuf, Error> {
+        let session = Session::load(path)?;
+        let mut graph = SierpinskiGraph::new();
+        graph.bound = Size::new(self.canvas[0] as f32, self.canvas[1] as f32);
+        graph.apply_session(&session);
+        // A game that can't be played fails here; the window would just
+        // draw nothing.
+        graph.game().try_build()?;
+        let max_iter = self
+            .iterations
+            .map_or(session.max_iter, |n| n.min(i32::MAX as usize) as i32)
+            .clamp(0, MAX_EXTENDED_ITER);
+        graph.max_iter = max_iter;
+        graph.extend_to(graph.point_count(max_iter));
+        graph.cur_iter = max_iter;
+        let out = self.output(path);
+        graph.export_png(&out, None, false)?;
+        Ok(out)
+    }
+}
@@ -0,0 +1,248 @@
+//! Points that leave a canvas of a given `[width, height]`.
+//!
+//! Rotations and ratios above one can throw the orbit off the canvas. These
+//! helpers count such points, pin them to the edge, or find a viewport
+//! that shows them all.
+
+/// Whether `point` lies on the canvas, which spans `0..width` by
+/// `0..height`.
+pub fn contains([width, height]: [f32; 2], [x, y]: [f32; 2]) -> bool {
+    (0.0..width).contains(&x) && (0.0..height).contains(&y)
+}
+
+/// How many canvas sides off the canvas a point may get before
+/// [`diverged`] gives up on its orbit.
+pub const DIVERGENCE: f32 = 100.0;
+
+/// Whether `point` is NaN, infinite, or further out than [`DIVERGENCE`]
+/// times the larger side of the canvas in either direction. An orbit that
+/// gets there won't come back, and its points would only break drawing.
+///
+/// ```
+/// use sierpinski_triangle_emulator::{bounds, ChaosGame};
+///
+/// let size = [100.0, 100.0];
+/// assert!(!bounds::diverged(size, [-5000.0, 50.0]));
+/// assert!(bounds::diverged(size, [20000.0, 50.0]));
+/// assert!(bounds::diverged(size, [f32::NAN, 50.0]));
+///
+/// // Every step of a ratio of 3 lands twice as far from the vertex as it
+/// // started, so the orbit runs off; generation stops cleanly before the
+/// // first point that would break drawing.
+/// let points = ChaosGame::new([[50.0, 10.0], [10.0, 90.0], [90.0, 90.0]])
+///     .ratio(3.0)
+///     .seed(1)
+///     .build()
+///     .take(100_000)
+///     .take_while(|p| !bounds::diverged(size, *p))
+///     .collect::<Vec<_>>();
+/// assert!(points.len() < 100);
+/// assert!(points.iter().all(|[x, y]| x.is_finite() && y.is_finite()));
+/// ```
+pub fn diverged([width, height]: [f32; 2], [x, y]: [f32; 2]) -> bool {
+    let limit = DIVERGENCE * width.max(height);
+    !(x.abs() <= limit && y.abs() <= limit)
+}
+
+/// How many of `points` lie off the canvas.
+///
+/// ```
+/// use sierpinski_triangle_emulator::bounds;
+///
+/// let points = [[10.0, 10.0], [-1.0, 5.0], [5.0, 100.0], [99.0, 0.0]];
+/// assert_eq!(bounds::count_outside([100.0, 100.0], points), 2);
+/// ```
+pub fn count_outside(size: [f32; 2], points: impl IntoIterator<Item = [f32; 2]>) -> usize {
+    points.into_iter().filter(|p| !contains(size, *p)).count()
+}
+
+/// Pins `point` to the nearest pixel on the canvas; points on the canvas
+/// are returned unchanged.
+///
+/// ```
+/// use sierpinski_triangle_emulator::bounds;
+///
+/// assert_eq!(bounds::clamp([100.0, 50.0], [-20.0, 30.0]), [0.0, 30.0]);
+/// assert_eq!(bounds::clamp([100.0, 50.0], [120.0, 80.0]), [99.0, 49.0]);
+/// assert_eq!(bounds::clamp([100.0, 50.0], [12.5, 7.5]), [12.5, 7.5]);
+/// ```
+pub fn clamp([width, height]: [f32; 2], [x, y]: [f32; 2]) -> [f32; 2] {
+    [
+        x.clamp(0.0, (width - 1.0).max(0.0)),
+        y.clamp(0.0, (height - 1.0).max(0.0)),
+    ]
+}
+
+/// A uniform scale followed by a translation, mapping canvas coordinates to
+/// the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub scale: f32,
+    pub offset: [f32; 2],
+}
+
+impl Viewport {
+    pub const IDENTITY: Viewport = Viewport {
+        scale: 1.0,
+        offset: [0.0, 0.0],
+    };
+
+    /// The most [`frame`](Self::frame) zooms in.
+    pub const MAX_FRAME_ZOOM: f32 = 8.0;
+
+    /// The viewport showing the whole canvas and every one of `points`,
+    /// centred on the canvas. It is the identity when all points are on the
+    /// canvas already.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::bounds::Viewport;
+    ///
+    /// let viewport = Viewport::fit([100.0, 100.0], [[-100.0, 50.0], [50.0, 50.0]]);
+    /// assert_eq!(viewport.scale, 0.5);
+    /// assert_eq!(viewport.apply([-100.0, 50.0]), [0.0, 50.0]);
+    /// assert_eq!(viewport.apply([100.0, 100.0]), [100.0, 75.0]);
+    /// assert_eq!(viewport.invert(viewport.apply([30.0, 40.0])), [30.0, 40.0]);
+    /// ```
+    pub fn fit(size: [f32; 2], points: impl IntoIterator<Item = [f32; 2]>) -> Viewport {
+        let [width, height] = size;
+        let (min, max) = points
+            .into_iter()
+            .filter(|[x, y]| x.is_finite() && y.is_finite())
+            .fold(
+                ([0.0_f32, 0.0_f32], [width, height]),
+                |(min, max), [x, y]| {
+                    (
+                        [min[0].min(x), min[1].min(y)],
+                        [max[0].max(x), max[1].max(y)],
+                    )
+                },
+            );
+        let (content_width, content_height) = (max[0] - min[0], max[1] - min[1]);
+        if content_width <= width && content_height <= height {
+            return Viewport::IDENTITY;
+        }
+        let scale = (width / content_width).min(height / content_height);
+        Viewport {
+            scale,
+            offset: [
+                (width - content_width * scale) / 2.0 - min[0] * scale,
+                (height - content_height * scale) / 2.0 - min[1] * scale,
+            ],
+        }
+    }
+
+    /// The viewport zooming in or out so the box from `min` to `max` fills
+    /// the canvas but for a `margin` on the tighter pair of sides, centred.
+    /// It zooms in by at most [`MAX_FRAME_ZOOM`](Self::MAX_FRAME_ZOOM), so
+    /// a box of a single point stays in place.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::bounds::Viewport;
+    ///
+    /// // A small box in a corner is enlarged into the middle.
+    /// let viewport = Viewport::frame([100.0, 100.0], [0.0, 0.0], [40.0, 20.0], 10.0);
+    /// assert_eq!(viewport.scale, 2.0);
+    /// assert_eq!(viewport.apply([0.0, 0.0]), [10.0, 30.0]);
+    /// assert_eq!(viewport.apply([40.0, 20.0]), [90.0, 70.0]);
+    ///
+    /// // One spread past the canvas is shrunk back onto it.
+    /// let viewport = Viewport::frame([100.0, 100.0], [-100.0, 0.0], [100.0, 100.0], 0.0);
+    /// assert_eq!(viewport.scale, 0.5);
+    /// assert_eq!(viewport.apply([-100.0, 0.0]), [0.0, 25.0]);
+    ///
+    /// let point = Viewport::frame([100.0, 100.0], [20.0, 20.0], [20.0, 20.0], 10.0);
+    /// assert_eq!(point.scale, Viewport::MAX_FRAME_ZOOM);
+    /// assert_eq!(point.apply([20.0, 20.0]), [50.0, 50.0]);
+    /// ```
+    pub fn frame([width, height]: [f32; 2], min: [f32; 2], max: [f32; 2], margin: f32) -> Viewport {
+        let (room_width, room_height) = (
+            (width - 2.0 * margin).max(1.0),
+            (height - 2.0 * margin).max(1.0),
+        );
+        let (content_width, content_height) = (max[0] - min[0], max[1] - min[1]);
+        let scale = (room_width / content_width)
+            .min(room_height / content_height)
+            .min(Self::MAX_FRAME_ZOOM);
+        let center = [(min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0];
+        Viewport {
+            scale,
+            offset: [
+                width / 2.0 - center[0] * scale,
+                height / 2.0 - center[1] * scale,
+            ],
+        }
+    }
+
+    /// The viewport shrinking the canvas uniformly so a `margin` is left
+    /// free on the tighter pair of sides, centred.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::bounds::Viewport;
+    ///
+    /// let inset = Viewport::inset([100.0, 200.0], 10.0);
+    /// assert_eq!(inset.scale, 0.8);
+    /// assert_eq!(inset.apply([0.0, 0.0]), [10.0, 20.0]);
+    /// assert_eq!(inset.apply([100.0, 200.0]), [90.0, 180.0]);
+    /// assert_eq!(Viewport::inset([100.0, 200.0], 0.0), Viewport::IDENTITY);
+    /// ```
+    pub fn inset([width, height]: [f32; 2], margin: f32) -> Viewport {
+        if margin <= 0.0 || width <= 2.0 * margin || height <= 2.0 * margin {
+            return Viewport::IDENTITY;
+        }
+        let scale = ((width - 2.0 * margin) / width).min((height - 2.0 * margin) / height);
+        Viewport {
+            scale,
+            offset: [
+                (width - width * scale) / 2.0,
+                (height - height * scale) / 2.0,
+            ],
+        }
+    }
+
+    /// This viewport followed by `outer`.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::bounds::Viewport;
+    ///
+    /// let inner = Viewport { scale: 0.5, offset: [10.0, 0.0] };
+    /// let outer = Viewport { scale: 2.0, offset: [0.0, 5.0] };
+    /// let p = [3.0, 4.0];
+    /// assert_eq!(inner.then(outer).apply(p), outer.apply(inner.apply(p)));
+    /// ```
+    pub fn then(self, outer: Viewport) -> Viewport {
+        Viewport {
+            scale: self.scale * outer.scale,
+            offset: outer.apply(self.offset),
+        }
+    }
+
+    /// `point` moved to the corner of the screen pixel it is nearest to, so
+    /// a square of one screen pixel drawn there covers that pixel exactly.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::bounds::Viewport;
+    ///
+    /// let viewport = Viewport { scale: 0.5, offset: [10.0, 0.0] };
+    /// let snapped = viewport.snap([3.3, 4.9]);
+    /// assert_eq!(viewport.apply(snapped), [12.0, 2.0]);
+    /// assert_eq!(Viewport::IDENTITY.snap([3.5, 4.49]), [4.0, 4.0]);
+    /// ```
+    pub fn snap(&self, point: [f32; 2]) -> [f32; 2] {
+        let [x, y] = self.apply(point);
+        self.invert([x.round(), y.round()])
+    }
+
+    pub fn apply(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        [
+            x * self.scale + self.offset[0],
+            y * self.scale + self.offset[1],
+        ]
+    }
+
+    pub fn invert(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        [
+            (x - self.offset[0]) / self.scale,
+            (y - self.offset[1]) / self.scale,
+        ]
+    }
+}
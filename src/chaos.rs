@@ -0,0 +1,884 @@
+use std::fmt::{self, Display};
+
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    geometry,
+    rng::{Generator, Source},
+    Error,
+};
+
+/// Which vertices may be picked next, given the previous pick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Restriction {
+    /// Every vertex can be picked every step.
+    None,
+    /// The vertex picked last step can't be picked again.
+    NoRepeat,
+}
+
+impl Restriction {
+    pub const ALL: [Restriction; 2] = [Restriction::None, Restriction::NoRepeat];
+}
+
+impl Display for Restriction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Restriction::None => write!(f, "none"),
+            Restriction::NoRepeat => write!(f, "no repeat"),
+        }
+    }
+}
+
+/// How the vertex of each step is chosen.
+///
+/// ```
+/// use sierpinski_triangle_emulator::{ChaosGame, Rule, Selection};
+///
+/// let triangle = [[0.0, 0.0], [4.0, 0.0], [0.0, 4.0]];
+/// let game = ChaosGame::new(triangle).selection(Selection::ManualSequence(vec![1, 2, 0]));
+/// let mut orbit = game.build();
+/// let picks = (0..7).map(|_| orbit.step().unwrap().vertex).collect::<Vec<_>>();
+/// // The list repeats, and every orbit follows it the same way.
+/// assert_eq!(picks, [1, 2, 0, 1, 2, 0, 1]);
+/// assert_eq!(game.points(20), game.points(20));
+///
+/// // With the midpoint rule each step takes a vertex and its partner.
+/// let mut orbit = game.clone().rule(Rule::Midpoint).build();
+/// let step = orbit.step().unwrap();
+/// assert_eq!((step.vertex, step.partner), (1, Some(2)));
+///
+/// // Picks of vertices that don't exist are skipped.
+/// let skipping = ChaosGame::new(triangle).selection(Selection::ManualSequence(vec![5, 2]));
+/// assert_eq!(skipping.build().step().unwrap().vertex, 2);
+/// let none = ChaosGame::new(triangle).selection(Selection::ManualSequence(vec![3]));
+/// assert!(none.build().step().is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Selection {
+    /// Drawn from the generator by weight, restriction and quota.
+    #[default]
+    Random,
+    /// The vertices of the list in order, over and over. Nothing is drawn
+    /// for the picks, so weights, restriction, quota and seed don't matter
+    /// to them.
+    ManualSequence(Vec<usize>),
+}
+
+impl Selection {
+    /// The same selection after renumbering the vertices: `kept` holds the
+    /// old index of each new vertex in order. Picks of vertices that aren't
+    /// kept are dropped.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::Selection;
+    ///
+    /// let sequence = Selection::ManualSequence(vec![0, 3, 1, 3]);
+    /// assert_eq!(sequence.renumbered(&[1, 3]), Selection::ManualSequence(vec![1, 0, 1]));
+    /// assert_eq!(Selection::Random.renumbered(&[1, 3]), Selection::Random);
+    /// ```
+    pub fn renumbered(&self, kept: &[usize]) -> Selection {
+        match self {
+            Selection::Random => Selection::Random,
+            Selection::ManualSequence(picks) => Selection::ManualSequence(
+                picks
+                    .iter()
+                    .filter_map(|pick| kept.iter().position(|old| old == pick))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The same selection after inserting a vertex as `index`, which none of
+    /// the picks are of: picks from `index` on move up by one.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::Selection;
+    ///
+    /// let sequence = Selection::ManualSequence(vec![0, 1, 2, 1]);
+    /// assert_eq!(sequence.inserted(1), Selection::ManualSequence(vec![0, 2, 3, 2]));
+    /// assert_eq!(sequence.inserted(3), sequence);
+    /// ```
+    pub fn inserted(&self, index: usize) -> Selection {
+        match self {
+            Selection::Random => Selection::Random,
+            Selection::ManualSequence(picks) => Selection::ManualSequence(
+                picks
+                    .iter()
+                    .map(|pick| if *pick >= index { pick + 1 } else { *pick })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Selection::Random => write!(f, "random"),
+            Selection::ManualSequence(picks) => {
+                let picks = picks.iter().map(usize::to_string).collect::<Vec<_>>();
+                write!(f, "{}", picks.join(","))
+            }
+        }
+    }
+}
+
+/// What each step moves towards.
+///
+/// ```
+/// use sierpinski_triangle_emulator::{ChaosGame, Rule};
+///
+/// let mut orbit = ChaosGame::new([[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]])
+///     .rule(Rule::Midpoint)
+///     .seed(7)
+///     .build();
+/// let steps = (0..6).map(|_| orbit.step().unwrap()).collect::<Vec<_>>();
+/// // The first step goes halfway from vertex 0 to the midpoint of 1 and 0.
+/// assert_eq!(steps[0].point, [0.25, 0.0]);
+/// let picks = steps
+///     .iter()
+///     .map(|step| (step.vertex, step.partner.unwrap()))
+///     .collect::<Vec<_>>();
+/// assert_eq!(picks, [(1, 0), (0, 1), (0, 1), (1, 2), (1, 0), (2, 1)]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Rule {
+    /// A single picked vertex.
+    Vertex,
+    /// The midpoint of two distinct vertices, both picked by weight. Needs at
+    /// least two vertices, with fewer the orbit is empty.
+    Midpoint,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 2] = [Rule::Vertex, Rule::Midpoint];
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Vertex => write!(f, "vertex"),
+            Rule::Midpoint => write!(f, "midpoint of two"),
+        }
+    }
+}
+
+/// A configured chaos game: starting from a point, repeatedly pick a vertex
+/// and move a fraction of the way towards it.
+///
+/// Cloning a configured game forks it; every `build` starts a fresh orbit,
+/// so with a [`seed`](ChaosGame::seed) all orbits are identical.
+///
+/// ```
+/// use sierpinski_triangle_emulator::ChaosGame;
+///
+/// let points = ChaosGame::new([[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]])
+///     .seed(42)
+///     .points(10_000);
+///
+/// assert_eq!(points.len(), 10_000);
+/// for [x, y] in points {
+///     assert!(x >= 0.0 && y >= 0.0 && x + y <= 1.0 + 1e-6);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChaosGame {
+    vertices: Vec<[f32; 2]>,
+    ratios: Vec<f32>,
+    weights: Vec<f32>,
+    rule: Rule,
+    restriction: Restriction,
+    selection: Selection,
+    rotation: f32,
+    anchors: Vec<Option<[f32; 2]>>,
+    anchor_blend: f32,
+    quota: usize,
+    jitter: f32,
+    seed: Option<u64>,
+    generator: Generator,
+    start: Option<[f32; 2]>,
+}
+
+impl ChaosGame {
+    /// The most points [`try_points`](Self::try_points) generates at once.
+    pub const MAX_POINTS: usize = 100_000_000;
+
+    /// Steps in a round of balanced sampling, see [`quota`](Self::quota).
+    pub const QUOTA_ROUND: usize = 1000;
+
+    /// A classic chaos game over `vertices`: ratio one half, every vertex
+    /// equally likely, no restriction.
+    pub fn new(vertices: impl IntoIterator<Item = [f32; 2]>) -> ChaosGame {
+        ChaosGame {
+            vertices: vertices.into_iter().collect(),
+            ratios: vec![],
+            weights: vec![],
+            rule: Rule::Vertex,
+            restriction: Restriction::None,
+            selection: Selection::Random,
+            rotation: 0.0,
+            anchors: vec![],
+            anchor_blend: 0.5,
+            quota: 0,
+            jitter: 0.0,
+            seed: None,
+            generator: Generator::ChaCha,
+            start: None,
+        }
+    }
+
+    /// How far to move towards the picked vertex, for every vertex.
+    pub fn ratio(self, ratio: f32) -> ChaosGame {
+        self.ratios([ratio])
+    }
+
+    /// Per-vertex ratios. Vertices past the end of `ratios` use its last
+    /// entry, or one half if it is empty.
+    pub fn ratios(mut self, ratios: impl IntoIterator<Item = f32>) -> ChaosGame {
+        self.ratios = ratios.into_iter().collect();
+        self
+    }
+
+    /// Relative probabilities of picking each vertex. Missing weights count
+    /// as 1. If no vertex can be picked with these weights (all zero,
+    /// negative or NaN) every vertex is equally likely.
+    pub fn weights(mut self, weights: impl IntoIterator<Item = f32>) -> ChaosGame {
+        self.weights = weights.into_iter().collect();
+        self
+    }
+
+    pub fn rule(mut self, rule: Rule) -> ChaosGame {
+        self.rule = rule;
+        self
+    }
+
+    /// Applies to the first pick of a step only.
+    pub fn restriction(mut self, restriction: Restriction) -> ChaosGame {
+        self.restriction = restriction;
+        self
+    }
+
+    /// Picks the vertices at random, the default, or by a fixed list; see
+    /// [`Selection`]. A list without a single existing vertex makes the
+    /// orbit empty.
+    pub fn selection(mut self, selection: Selection) -> ChaosGame {
+        self.selection = selection;
+        self
+    }
+
+    /// Rotates every new point by `degrees` about the centroid of the
+    /// vertices after moving towards the picked vertex. Independent of the
+    /// restriction; zero leaves the points untouched.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::{ChaosGame, Restriction, Step};
+    ///
+    /// let game = ChaosGame::new([[0.0, 0.0], [90.0, 10.0], [20.0, 70.0]])
+    ///     .weights([1.0, 2.0, 1.0])
+    ///     .restriction(Restriction::NoRepeat)
+    ///     .seed(8);
+    /// let steps = |game: &ChaosGame| {
+    ///     let mut orbit = game.build();
+    ///     (0..1_000).map(|_| orbit.step().unwrap()).collect::<Vec<Step>>()
+    /// };
+    /// assert_eq!(steps(&game.clone().rotation(0.0)), steps(&game));
+    ///
+    /// // Turning moves the points but draws the same vertices.
+    /// let turned = steps(&game.clone().rotation(30.0));
+    /// assert_ne!(turned, steps(&game));
+    /// assert!(turned.iter().zip(steps(&game)).all(|(a, b)| a.vertex == b.vertex));
+    /// ```
+    pub fn rotation(mut self, degrees: f32) -> ChaosGame {
+        self.rotation = degrees;
+        self
+    }
+
+    /// A secondary anchor point for every vertex that has one, which bends
+    /// the steps towards it as described at [`anchored_target`]. Vertices
+    /// past the end of `anchors` have none, so without any anchors the game
+    /// is unchanged.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::ChaosGame;
+    ///
+    /// let game = ChaosGame::new([[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]).seed(3);
+    /// let unanchored = game.clone().anchors([None, None, None]);
+    /// assert_eq!(unanchored.points(100), game.points(100));
+    ///
+    /// let anchored = game.clone().anchors([None, Some([1.0, 1.0]), None]);
+    /// assert_ne!(anchored.points(100), game.points(100));
+    /// ```
+    pub fn anchors(mut self, anchors: impl IntoIterator<Item = Option<[f32; 2]>>) -> ChaosGame {
+        self.anchors = anchors.into_iter().collect();
+        self
+    }
+
+    /// The `blend` passed to [`anchored_target`], one half by default.
+    pub fn anchor_blend(mut self, blend: f32) -> ChaosGame {
+        self.anchor_blend = blend;
+        self
+    }
+
+    /// Balanced sampling: within every round of
+    /// [`QUOTA_ROUND`](Self::QUOTA_ROUND) steps each vertex is the first
+    /// pick at least `quota` times. Picks follow the weights until the round
+    /// has no steps to spare, then go to the vertex furthest short of its
+    /// quota, which the step reports as [`forced`](Step::forced). A forced
+    /// pick keeps to the restriction unless the only vertex left short is
+    /// the one picked last. The quota is capped so every vertex fits into a
+    /// round; zero, the default, leaves the orbit as without it.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::ChaosGame;
+    ///
+    /// let game = ChaosGame::new([[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]])
+    ///     .weights([100.0, 1.0, 1.0])
+    ///     .seed(5);
+    /// let steps = |game: &ChaosGame| {
+    ///     let mut orbit = game.build();
+    ///     (0..3000).map(|_| orbit.step().unwrap()).collect::<Vec<_>>()
+    /// };
+    ///
+    /// let balanced = steps(&game.clone().quota(50));
+    /// for round in balanced.chunks(ChaosGame::QUOTA_ROUND) {
+    ///     for vertex in 0..3 {
+    ///         assert!(round.iter().filter(|step| step.vertex == vertex).count() >= 50);
+    ///     }
+    /// }
+    /// assert!(balanced.iter().any(|step| step.forced));
+    ///
+    /// // Without a quota the same seed gives the plain weighted orbit.
+    /// assert_eq!(steps(&game.clone().quota(0)), steps(&game));
+    /// assert!(steps(&game).iter().all(|step| !step.forced));
+    /// ```
+    pub fn quota(mut self, quota: u32) -> ChaosGame {
+        self.quota = quota as usize;
+        self
+    }
+
+    /// Moves every new point by a random offset with this standard
+    /// deviation on each axis before the next step starts from it, which
+    /// softens the attractor into a grainier, organic shape. Zero, the
+    /// default, leaves the orbit exact and draws nothing extra from the
+    /// seed. [`replay`](Self::replay) and [`images`](Self::images) don't
+    /// jitter.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::ChaosGame;
+    ///
+    /// let game = ChaosGame::new([[0.0, 0.0], [100.0, 0.0], [0.0, 100.0]]).seed(2);
+    /// assert_eq!(game.clone().jitter(0.0).points(500), game.points(500));
+    ///
+    /// let exact = game.points(5000);
+    /// let jittered = game.clone().jitter(1.0).points(5000);
+    /// assert_ne!(jittered, exact);
+    /// // The offsets being small, the points still hug the triangle.
+    /// assert!(jittered.iter().all(|[x, y]| x + y < 110.0 && *x > -10.0 && *y > -10.0));
+    /// ```
+    pub fn jitter(mut self, std_dev: f32) -> ChaosGame {
+        self.jitter = std_dev;
+        self
+    }
+
+    /// Makes orbits reproducible. Without a seed every orbit is different.
+    pub fn seed(mut self, seed: u64) -> ChaosGame {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The random number generator the picks are drawn from, ChaCha by
+    /// default.
+    pub fn generator(mut self, generator: Generator) -> ChaosGame {
+        self.generator = generator;
+        self
+    }
+
+    /// Where the orbit starts, the first vertex by default. The start point
+    /// itself is not yielded.
+    pub fn start(mut self, start: [f32; 2]) -> ChaosGame {
+        self.start = Some(start);
+        self
+    }
+
+    /// The same game over `vertices` instead. Per-vertex settings don't
+    /// carry over to other vertices, so every vertex becomes equally likely,
+    /// takes the first ratio and has no anchor.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::ChaosGame;
+    ///
+    /// let square = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+    /// let game = ChaosGame::new([[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]])
+    ///     .ratios([0.4, 0.6, 0.6])
+    ///     .weights([5.0, 1.0, 1.0])
+    ///     .seed(2);
+    /// assert_eq!(
+    ///     game.with_vertices(square).points(100),
+    ///     ChaosGame::new(square).ratio(0.4).seed(2).points(100)
+    /// );
+    /// ```
+    pub fn with_vertices(mut self, vertices: impl IntoIterator<Item = [f32; 2]>) -> ChaosGame {
+        self.vertices = vertices.into_iter().collect();
+        self.ratios.truncate(1);
+        self.weights.clear();
+        self.anchors.clear();
+        self
+    }
+
+    pub fn vertices(&self) -> &[[f32; 2]] {
+        &self.vertices
+    }
+
+    /// A new orbit of this game. It is empty if there are no vertices.
+    pub fn build(&self) -> Orbit {
+        let n = self.vertices.len();
+        let mut weights = (0..n)
+            .map(|i| self.weights.get(i).copied().unwrap_or(1.0))
+            .collect::<Vec<_>>();
+        if WeightedIndex::new(&weights).is_err() {
+            weights = vec![1.0; n];
+        }
+        let any = WeightedIndex::new(&weights).ok();
+        let excluding = match (self.rule, self.restriction) {
+            (Rule::Vertex, Restriction::None) => vec![],
+            _ => (0..n)
+                .map(|last| {
+                    let mut weights = weights.clone();
+                    weights[last] = 0.0;
+                    WeightedIndex::new(weights).ok()
+                })
+                .collect(),
+        };
+        let centroid = geometry::centroid(&self.vertices).unwrap_or_default();
+        let rotation = (self.rotation != 0.0).then(|| {
+            let (sin, cos) = self.rotation.to_radians().sin_cos();
+            Rotation { centroid, sin, cos }
+        });
+        Orbit {
+            vertices: self.vertices.clone(),
+            ratios: (0..n)
+                .map(|i| {
+                    self.ratios
+                        .get(i)
+                        .or_else(|| self.ratios.last())
+                        .copied()
+                        .unwrap_or(0.5)
+                })
+                .collect(),
+            anchors: (0..n)
+                .map(|i| self.anchors.get(i).copied().flatten())
+                .collect(),
+            anchor_blend: self.anchor_blend,
+            rule: self.rule,
+            restriction: self.restriction,
+            sequence: match &self.selection {
+                Selection::Random => None,
+                Selection::ManualSequence(picks) => {
+                    Some(picks.iter().copied().filter(|pick| *pick < n).collect())
+                }
+            },
+            next: 0,
+            quota: self.quota.min(Self::QUOTA_ROUND / n.max(1)),
+            jitter: self.jitter.max(0.0),
+            round: Round {
+                step: 0,
+                counts: vec![0; n],
+            },
+            any,
+            excluding,
+            rotation,
+            rng: Source::new(self.generator, self.seed),
+            current: self.start.or_else(|| self.vertices.first().copied()),
+            last: None,
+        }
+    }
+
+    /// The first `n` points of a new orbit.
+    pub fn points(&self, n: usize) -> Vec<[f32; 2]> {
+        self.build().take(n).collect()
+    }
+
+    /// Like [`build`](Self::build), but an error rather than an empty orbit
+    /// when there are too few vertices for the rule: one, or two for
+    /// [`Rule::Midpoint`].
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::{ChaosGame, Error, Rule};
+    ///
+    /// let none = ChaosGame::new([]);
+    /// assert!(matches!(
+    ///     none.try_build(),
+    ///     Err(Error::DegenerateVertices { found: 0, needed: 1 })
+    /// ));
+    /// let single = ChaosGame::new([[1.0, 1.0]]).rule(Rule::Midpoint);
+    /// assert!(matches!(
+    ///     single.try_build(),
+    ///     Err(Error::DegenerateVertices { found: 1, needed: 2 })
+    /// ));
+    /// assert!(ChaosGame::new([[1.0, 1.0]]).try_build().is_ok());
+    /// ```
+    pub fn try_build(&self) -> Result<Orbit, Error> {
+        let needed = match self.rule {
+            Rule::Vertex => 1,
+            Rule::Midpoint => 2,
+        };
+        let found = self.vertices.len();
+        if found < needed {
+            return Err(Error::DegenerateVertices { found, needed });
+        }
+        Ok(self.build())
+    }
+
+    /// Like [`points`](Self::points), but an error for too few vertices or
+    /// more than [`MAX_POINTS`](Self::MAX_POINTS) points.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::{ChaosGame, Error};
+    ///
+    /// let game = ChaosGame::new([[0.0, 0.0], [4.0, 0.0], [0.0, 4.0]]).seed(1);
+    /// assert_eq!(game.try_points(10).unwrap(), game.points(10));
+    /// assert!(matches!(
+    ///     game.try_points(ChaosGame::MAX_POINTS + 1),
+    ///     Err(Error::TooManyPoints { .. })
+    /// ));
+    /// assert!(ChaosGame::new([]).try_points(10).is_err());
+    /// ```
+    pub fn try_points(&self, n: usize) -> Result<Vec<[f32; 2]>, Error> {
+        if n > Self::MAX_POINTS {
+            return Err(Error::TooManyPoints {
+                requested: n,
+                limit: Self::MAX_POINTS,
+            });
+        }
+        Ok(self.try_build()?.take(n).collect())
+    }
+
+    /// The steps of a new orbit moving exactly by `picks` instead of drawing
+    /// them, each a vertex and, for [`Rule::Midpoint`], its partner. The
+    /// rule, ratios, rotation, anchors and start apply as usual; weights,
+    /// restriction and seed don't, as nothing is drawn. It stops early at
+    /// the first pick of a vertex that doesn't exist.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::ChaosGame;
+    ///
+    /// let game = ChaosGame::new([[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]).seed(9);
+    /// let drawn = game.build().take(50).collect::<Vec<_>>();
+    /// let picks = {
+    ///     let mut orbit = game.build();
+    ///     (0..50).map(|_| orbit.step().unwrap().vertex).collect::<Vec<_>>()
+    /// };
+    /// let replayed = game.replay(picks.iter().map(|vertex| (*vertex, None)));
+    /// assert_eq!(replayed.iter().map(|step| step.point).collect::<Vec<_>>(), drawn);
+    ///
+    /// assert_eq!(game.replay([(0, None), (3, None), (1, None)]).len(), 1);
+    /// ```
+    pub fn replay(&self, picks: impl IntoIterator<Item = (usize, Option<usize>)>) -> Vec<Step> {
+        let orbit = self.build();
+        let n = self.vertices.len();
+        let mut current = match orbit.current {
+            Some(current) => current,
+            None => return vec![],
+        };
+        let mut steps = vec![];
+        for (vertex, partner) in picks {
+            if vertex >= n || partner.is_some_and(|partner| partner >= n) {
+                break;
+            }
+            let partner = match self.rule {
+                Rule::Vertex => None,
+                Rule::Midpoint => partner,
+            };
+            current = orbit.apply(vertex, partner, current);
+            steps.push(Step {
+                point: current,
+                vertex,
+                partner,
+                forced: false,
+            });
+        }
+        steps
+    }
+
+    /// The image of `shape` under each of the maps a step can apply: one
+    /// per vertex, or with [`Rule::Midpoint`] one per pair of distinct
+    /// vertices. The attractor is the shape these copies tile, so drawing
+    /// them over the vertex polygon shows where the fractal comes from.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::ChaosGame;
+    ///
+    /// let triangle = [[0.0, 0.0], [4.0, 0.0], [0.0, 4.0]];
+    /// let images = ChaosGame::new(triangle).images(&triangle);
+    /// // Three half-size copies, each in the corner of its vertex.
+    /// assert_eq!(images.len(), 3);
+    /// assert_eq!(images[0], [[0.0, 0.0], [2.0, 0.0], [0.0, 2.0]]);
+    /// assert_eq!(images[1], [[2.0, 0.0], [4.0, 0.0], [2.0, 2.0]]);
+    /// ```
+    pub fn images(&self, shape: &[[f32; 2]]) -> Vec<Vec<[f32; 2]>> {
+        let orbit = self.build();
+        let n = self.vertices.len();
+        let maps = match self.rule {
+            Rule::Vertex => (0..n).map(|i| (i, None)).collect::<Vec<_>>(),
+            // The midpoint of a pair is the same whichever is picked first.
+            Rule::Midpoint => (0..n)
+                .flat_map(|i| (i + 1..n).map(move |j| (i, Some(j))))
+                .collect(),
+        };
+        maps.into_iter()
+            .map(|(vertex, partner)| {
+                shape
+                    .iter()
+                    .map(|p| orbit.apply(vertex, partner, *p))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// One step of an [`Orbit`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    pub point: [f32; 2],
+    /// Index of the vertex the step moved towards.
+    pub vertex: usize,
+    /// The second vertex with [`Rule::Midpoint`], the step moved towards the
+    /// midpoint of both.
+    pub partner: Option<usize>,
+    /// Whether `vertex` was picked to meet the [`quota`](ChaosGame::quota)
+    /// rather than by weight.
+    pub forced: bool,
+}
+
+/// How far an [`Orbit`] is into the current round of balanced sampling.
+#[derive(Debug, Clone)]
+struct Round {
+    step: usize,
+    /// How often each vertex was the first pick this round.
+    counts: Vec<usize>,
+}
+
+/// The endless sequence of points of a [`ChaosGame`].
+#[derive(Debug, Clone)]
+pub struct Orbit {
+    vertices: Vec<[f32; 2]>,
+    ratios: Vec<f32>,
+    anchors: Vec<Option<[f32; 2]>>,
+    anchor_blend: f32,
+    rule: Rule,
+    restriction: Restriction,
+    /// The picks of [`Selection::ManualSequence`] that exist, and the index
+    /// of the one the next step takes.
+    sequence: Option<Vec<usize>>,
+    next: usize,
+    /// Zero without balanced sampling.
+    quota: usize,
+    round: Round,
+    jitter: f32,
+    any: Option<WeightedIndex<f32>>,
+    /// Index `i` is the distribution over every vertex but `i`, if any can be
+    /// picked at all. Empty when neither rule nor restriction needs it.
+    excluding: Vec<Option<WeightedIndex<f32>>>,
+    rotation: Option<Rotation>,
+    rng: Source,
+    current: Option<[f32; 2]>,
+    last: Option<usize>,
+}
+
+impl Orbit {
+    /// Like [`next`](Iterator::next), but also tells which vertex was picked.
+    pub fn step(&mut self) -> Option<Step> {
+        let current = self.current?;
+        let (vertex, partner, forced) = match &self.sequence {
+            Some(_) => self.follow()?,
+            None => self.draw()?,
+        };
+        let mut point = self.apply(vertex, partner, current);
+        if self.jitter > 0.0 {
+            // Box-Muller: two uniform draws make two independent normal ones.
+            let radius = (-2.0 * (1.0 - self.rng.gen::<f32>()).ln()).sqrt() * self.jitter;
+            let (sin, cos) = (self.rng.gen::<f32>() * std::f32::consts::TAU).sin_cos();
+            point = [point[0] + radius * cos, point[1] + radius * sin];
+        }
+        self.current = Some(point);
+        self.last = Some(vertex);
+        Some(Step {
+            point,
+            vertex,
+            partner,
+            forced,
+        })
+    }
+
+    /// The next vertex and partner of the manual sequence, read two at a
+    /// time with [`Rule::Midpoint`].
+    fn follow(&mut self) -> Option<(usize, Option<usize>, bool)> {
+        let sequence = self.sequence.as_ref()?;
+        let mut take = || {
+            let pick = *sequence.get(self.next)?;
+            self.next = (self.next + 1) % sequence.len();
+            Some(pick)
+        };
+        let vertex = take()?;
+        let partner = match self.rule {
+            Rule::Vertex => None,
+            Rule::Midpoint => take(),
+        };
+        Some((vertex, partner, false))
+    }
+
+    /// A vertex and partner drawn from the generator, and whether the quota
+    /// forced the vertex.
+    fn draw(&mut self) -> Option<(usize, Option<usize>, bool)> {
+        let dist = match (self.restriction, self.last) {
+            (Restriction::NoRepeat, Some(last)) => self.excluding[last].as_ref(),
+            _ => None,
+        }
+        .or(self.any.as_ref())?;
+        let forced = self.forced_pick();
+        let vertex = match forced {
+            Some(vertex) => vertex,
+            None => dist.sample(&mut self.rng),
+        };
+        if self.quota > 0 {
+            self.round.counts[vertex] += 1;
+            self.round.step += 1;
+            if self.round.step == ChaosGame::QUOTA_ROUND {
+                self.round.step = 0;
+                self.round.counts.iter_mut().for_each(|count| *count = 0);
+            }
+        }
+        let partner = match self.rule {
+            Rule::Vertex => None,
+            Rule::Midpoint => Some(self.excluding[vertex].as_ref()?.sample(&mut self.rng)),
+        };
+        Some((vertex, partner, forced.is_some()))
+    }
+
+    /// The vertex the quota needs picked this step, if the rest of the round
+    /// only just leaves room for the vertices still short of it.
+    fn forced_pick(&self) -> Option<usize> {
+        if self.quota == 0 {
+            return None;
+        }
+        let short = |vertex: usize| self.quota.saturating_sub(self.round.counts[vertex]);
+        let n = self.vertices.len();
+        let deficit = (0..n).map(short).sum::<usize>();
+        if deficit == 0 || deficit < ChaosGame::QUOTA_ROUND - self.round.step {
+            return None;
+        }
+        let furthest = |skip: Option<usize>| {
+            (0..n)
+                .filter(|vertex| short(*vertex) > 0 && Some(*vertex) != skip)
+                .max_by_key(|vertex| (short(*vertex), std::cmp::Reverse(*vertex)))
+        };
+        match self.restriction {
+            Restriction::NoRepeat => furthest(self.last).or_else(|| furthest(None)),
+            Restriction::None => furthest(None),
+        }
+    }
+
+    /// Where a step from `current` lands after picking `vertex`, and
+    /// `partner` with [`Rule::Midpoint`].
+    fn apply(&self, vertex: usize, partner: Option<usize>, current: [f32; 2]) -> [f32; 2] {
+        let ([x, y], ratio) = match partner {
+            None => (self.target(vertex, current), self.ratios[vertex]),
+            Some(partner) => {
+                let ([ax, ay], [bx, by]) =
+                    (self.target(vertex, current), self.target(partner, current));
+                (
+                    [(ax + bx) / 2.0, (ay + by) / 2.0],
+                    (self.ratios[vertex] + self.ratios[partner]) / 2.0,
+                )
+            }
+        };
+        let point = [
+            current[0] + (x - current[0]) * ratio,
+            current[1] + (y - current[1]) * ratio,
+        ];
+        match &self.rotation {
+            Some(rotation) => rotation.apply(point),
+            None => point,
+        }
+    }
+
+    /// What a step from `current` towards `vertex` moves towards.
+    fn target(&self, vertex: usize, current: [f32; 2]) -> [f32; 2] {
+        match self.anchors[vertex] {
+            Some(anchor) => {
+                anchored_target(self.vertices[vertex], anchor, self.anchor_blend, current)
+            }
+            None => self.vertices[vertex],
+        }
+    }
+}
+
+/// Where a step from `current` towards `vertex` moves when the vertex has
+/// an `anchor`: a point on the segment from the vertex towards the anchor,
+/// the further along the further `current` is from the vertex, reaching
+/// `blend` of the way once `current` is as far from the vertex as the
+/// anchor is. Because the pull depends on where the orbit is, the maps are
+/// no longer affine and straight edges of the attractor bend.
+///
+/// ```
+/// use sierpinski_triangle_emulator::anchored_target;
+///
+/// let (vertex, anchor) = ([0.0, 0.0], [10.0, 0.0]);
+/// // Right at the vertex there is no pull.
+/// assert_eq!(anchored_target(vertex, anchor, 0.5, [0.0, 0.0]), [0.0, 0.0]);
+/// // Half the anchor's distance away, half the blend.
+/// assert_eq!(anchored_target(vertex, anchor, 0.5, [0.0, 5.0]), [2.5, 0.0]);
+/// // From further away the pull stays at the full blend.
+/// assert_eq!(anchored_target(vertex, anchor, 0.5, [0.0, 30.0]), [5.0, 0.0]);
+/// assert_eq!(anchored_target(vertex, anchor, 1.0, [0.0, 30.0]), anchor);
+/// // A zero blend, or an anchor on the vertex, leaves the vertex.
+/// assert_eq!(anchored_target(vertex, anchor, 0.0, [0.0, 30.0]), vertex);
+/// assert_eq!(anchored_target(vertex, vertex, 0.5, [0.0, 30.0]), vertex);
+/// ```
+pub fn anchored_target(
+    vertex: [f32; 2],
+    anchor: [f32; 2],
+    blend: f32,
+    current: [f32; 2],
+) -> [f32; 2] {
+    let reach = geometry::distance(vertex, anchor);
+    if reach == 0.0 {
+        return vertex;
+    }
+    let t = blend * (geometry::distance(current, vertex) / reach).min(1.0);
+    [
+        vertex[0] + (anchor[0] - vertex[0]) * t,
+        vertex[1] + (anchor[1] - vertex[1]) * t,
+    ]
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rotation {
+    centroid: [f32; 2],
+    sin: f32,
+    cos: f32,
+}
+
+impl Rotation {
+    fn apply(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let [cx, cy] = self.centroid;
+        let (dx, dy) = (x - cx, y - cy);
+        [
+            cx + dx * self.cos - dy * self.sin,
+            cy + dx * self.sin + dy * self.cos,
+        ]
+    }
+}
+
+impl Iterator for Orbit {
+    type Item = [f32; 2];
+
+    fn next(&mut self) -> Option<[f32; 2]> {
+        self.step().map(|step| step.point)
+    }
+}
@@ -0,0 +1,205 @@
+//! Preferences that belong to the user rather than to a session, kept in
+//! the user's config directory next to the recent sessions.
+
+use std::{
+    fmt::{self, Display},
+    fs, io,
+    ops::Not,
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+use sierpinski_triangle_emulator::{i18n::Language, numeric};
+
+/// Set to anything but `0` to run with animations off whatever the config
+/// file says, for setups that ask for reduced motion.
+const REDUCED_MOTION: &str = "SIERPINSKI_REDUCED_MOTION";
+
+/// The range [`Config::scale`] is kept in.
+const SCALES: std::ops::RangeInclusive<f64> = 0.5..=4.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Whether anything moves by itself: playback, vertex tweens and the
+    /// orbit replay. Without it every change happens at once. See
+    /// [`animated`](Self::animated) for whether they run.
+    pub animations: bool,
+    /// What the current iteration does when the max iteration changes.
+    pub cur_iter_policy: CurIterPolicy,
+    pub right_click: RightClick,
+    /// How much larger than the monitor asks for everything is drawn, for
+    /// displays that report the wrong scale, and how many pixels PNG exports
+    /// get per canvas pixel. `None` is 1.
+    pub scale_factor: Option<f64>,
+    /// What the labels are written in. Chinese needs a font with its
+    /// glyphs, which is only looked for at launch.
+    pub language: Language,
+    /// Decimal places of the coordinates and lengths the interface shows,
+    /// up to [`numeric::MAX_PLACES`].
+    pub decimals: usize,
+    /// Whether placing the third vertex of a fresh canvas starts a first
+    /// run of 5 000 iterations, so new users don't face an empty canvas.
+    pub suggest_points: bool,
+    /// Set by the reduced motion variable. Turns animations off for this run
+    /// only, so it is never saved.
+    #[serde(skip)]
+    pub reduced_motion: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            animations: true,
+            cur_iter_policy: CurIterPolicy::Hold,
+            right_click: RightClick::Menu,
+            scale_factor: None,
+            language: Language::English,
+            decimals: 1,
+            suggest_points: true,
+            reduced_motion: false,
+        }
+    }
+}
+
+/// How the current iteration follows a change of the max iteration, unless
+/// the sliders are linked or the percentage slider was used last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CurIterPolicy {
+    /// Stays where it is, so raising the max leaves the new points hidden.
+    Hold,
+    /// Moves to the new max, showing every point.
+    FollowMax,
+    /// Keeps its share of the max.
+    Proportional,
+}
+
+impl CurIterPolicy {
+    pub const ALL: [CurIterPolicy; 3] = [
+        CurIterPolicy::Hold,
+        CurIterPolicy::FollowMax,
+        CurIterPolicy::Proportional,
+    ];
+
+    /// The current iteration after the max goes from `old_max` to `new_max`,
+    /// never past `new_max`.
+    pub fn apply(self, cur_iter: i32, old_max: i32, new_max: i32) -> i32 {
+        match self {
+            CurIterPolicy::Hold => cur_iter.min(new_max),
+            CurIterPolicy::FollowMax => new_max,
+            CurIterPolicy::Proportional if old_max > 0 => {
+                (cur_iter as f64 * new_max as f64 / old_max as f64).round() as i32
+            }
+            CurIterPolicy::Proportional => new_max,
+        }
+    }
+}
+
+impl Display for CurIterPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CurIterPolicy::Hold => write!(f, "hold"),
+            CurIterPolicy::FollowMax => write!(f, "follow max"),
+            CurIterPolicy::Proportional => write!(f, "proportional"),
+        }
+    }
+}
+
+/// What a plain right-click on the canvas does. The other one is on
+/// Shift+right-click.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RightClick {
+    /// Opens the context menu.
+    Menu,
+    /// Removes the last vertex, as right-clicks did before the menu.
+    RemoveLast,
+}
+
+impl RightClick {
+    pub const ALL: [RightClick; 2] = [RightClick::Menu, RightClick::RemoveLast];
+}
+
+impl Display for RightClick {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RightClick::Menu => write!(f, "context menu"),
+            RightClick::RemoveLast => write!(f, "remove last vertex"),
+        }
+    }
+}
+
+impl Config {
+    /// The stored preferences, the defaults if there are none yet or they
+    /// can't be read, with the reduced motion variable read alongside.
+    pub fn load() -> Config {
+        let config: Config = file()
+            .and_then(|file| fs::read_to_string(file).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Config {
+            reduced_motion: std::env::var(REDUCED_MOTION).is_ok_and(|value| value != "0"),
+            ..config
+        }
+    }
+
+    /// Whether animations run: when the preference asks for them and the
+    /// reduced motion variable doesn't rule them out.
+    pub fn animated(&self) -> bool {
+        self.animations && self.reduced_motion.not()
+    }
+
+    /// The scale factor, 1 without one and within 0.5 to 4 with one.
+    pub fn scale(&self) -> f64 {
+        match self.scale_factor {
+            Some(scale) if scale.is_finite() => scale.clamp(*SCALES.start(), *SCALES.end()),
+            _ => 1.0,
+        }
+    }
+
+    /// The decimal places, at most [`numeric::MAX_PLACES`].
+    pub fn places(&self) -> usize {
+        self.decimals.min(numeric::MAX_PLACES)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let file =
+            file().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::from)?;
+        fs::write(file, json)
+    }
+}
+
+/// The platform's config directory for this application.
+pub fn dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .or_else(|| std::env::var_os("APPDATA"))
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("sierpinski-triangle-emulator"))
+}
+
+/// `config.json` in the config directory.
+fn file() -> Option<PathBuf> {
+    Some(dir()?.join("config.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cur_iter_follows_a_new_max_by_the_policy() {
+        assert_eq!(CurIterPolicy::Hold.apply(300, 1000, 2000), 300);
+        // Lowering the max below it pulls it down, whatever the policy.
+        assert_eq!(CurIterPolicy::Hold.apply(800, 1000, 500), 500);
+        assert_eq!(CurIterPolicy::FollowMax.apply(300, 1000, 2000), 2000);
+        assert_eq!(CurIterPolicy::FollowMax.apply(800, 1000, 500), 500);
+        assert_eq!(CurIterPolicy::Proportional.apply(300, 1000, 2000), 600);
+        assert_eq!(CurIterPolicy::Proportional.apply(800, 1000, 500), 400);
+        // With no old max there is no share to keep.
+        assert_eq!(CurIterPolicy::Proportional.apply(0, 0, 500), 500);
+    }
+}
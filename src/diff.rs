@@ -0,0 +1,173 @@
+//! What changed between two versions of a document, found by walking their
+//! JSON side by side.
+//!
+//! Objects are compared key by key and lists index by index, so an entry
+//! appended to a list shows as one addition while one inserted in the
+//! middle shows as the entries after it changing. Lists of numbers, such as
+//! points and colours, are values of their own and compare whole.
+
+use std::{
+    fmt::{self, Display},
+    ops::Not,
+};
+
+use serde_json::Value;
+
+/// One difference, at the path of keys and indices leading to it, such as
+/// `circle.radius` or `fix_points[2]`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added {
+        path: String,
+        value: Value,
+    },
+    Removed {
+        path: String,
+        value: Value,
+    },
+    Changed {
+        path: String,
+        from: Value,
+        to: Value,
+    },
+}
+
+impl Change {
+    pub fn path(&self) -> &str {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Changed { path, .. } => path,
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added { path, value } => write!(f, "{} added: {}", path, Shown(value)),
+            Change::Removed { path, value } => write!(f, "{} removed: {}", path, Shown(value)),
+            Change::Changed { path, from, to } => {
+                write!(f, "{} {} \u{2192} {}", path, Shown(from), Shown(to))
+            }
+        }
+    }
+}
+
+/// A value as JSON, but a string without its quotes.
+struct Shown<'a>(&'a Value);
+
+impl Display for Shown<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Value::String(text) => write!(f, "{}", text),
+            value => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Every difference from `before` to `after`, in the order of the paths.
+///
+/// ```
+/// use serde_json::json;
+/// use sierpinski_triangle_emulator::diff::{diff, Change};
+///
+/// let saved = json!({
+///     "fix_points": [[0.0, 0.0], [10.0, 0.0], [5.0, 8.0]],
+///     "ratio": 0.5,
+///     "circle": { "count": 3, "radius": 90.0 },
+/// });
+/// assert!(diff(&saved, &saved).is_empty());
+///
+/// // A scalar setting.
+/// let mut current = saved.clone();
+/// current["ratio"] = json!(0.45);
+/// assert_eq!(diff(&saved, &current)[0].to_string(), "ratio 0.5 \u{2192} 0.45");
+/// current["rule"] = json!("Midpoint");
+/// assert_eq!(diff(&saved, &current)[1].to_string(), "rule added: Midpoint");
+///
+/// // A vertex moved, one appended, and a nested setting.
+/// current["rule"] = json!(null);
+/// current["fix_points"] = json!([[0.0, 0.0], [12.0, 0.0], [5.0, 8.0], [5.0, 3.0]]);
+/// current["circle"]["radius"] = json!(80.0);
+/// let changes = diff(&saved, &current);
+/// assert_eq!(
+///     changes.iter().map(Change::path).collect::<Vec<_>>(),
+///     ["circle.radius", "fix_points[1]", "fix_points[3]", "ratio", "rule"]
+/// );
+/// assert_eq!(
+///     changes[2],
+///     Change::Added { path: "fix_points[3]".into(), value: json!([5.0, 3.0]) }
+/// );
+///
+/// // The last vertex removed, and a key gone.
+/// let current = json!({ "fix_points": [[0.0, 0.0], [10.0, 0.0]], "ratio": 0.5 });
+/// let changes = diff(&saved, &current);
+/// assert_eq!(changes[0].to_string(), "circle removed: {\"count\":3,\"radius\":90.0}");
+/// assert_eq!(changes[1].to_string(), "fix_points[2] removed: [5.0,8.0]");
+/// assert_eq!(changes.len(), 2);
+/// ```
+pub fn diff(before: &Value, after: &Value) -> Vec<Change> {
+    let mut changes = vec![];
+    walk(String::new(), before, after, &mut changes);
+    changes
+}
+
+fn walk(path: String, before: &Value, after: &Value, changes: &mut Vec<Change>) {
+    let key = |name: &str| match path.as_str() {
+        "" => name.to_string(),
+        _ => format!("{}.{}", path, name),
+    };
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            let mut names = before.keys().chain(after.keys()).collect::<Vec<_>>();
+            names.sort();
+            names.dedup();
+            for name in names {
+                match (before.get(name), after.get(name)) {
+                    (Some(b), Some(a)) => walk(key(name), b, a, changes),
+                    (Some(value), None) => changes.push(Change::Removed {
+                        path: key(name),
+                        value: value.clone(),
+                    }),
+                    (None, Some(value)) => changes.push(Change::Added {
+                        path: key(name),
+                        value: value.clone(),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(before), Value::Array(after))
+            if is_entry_list(before) || is_entry_list(after) =>
+        {
+            for i in 0..before.len().max(after.len()) {
+                let path = format!("{}[{}]", path, i);
+                match (before.get(i), after.get(i)) {
+                    (Some(b), Some(a)) => walk(path, b, a, changes),
+                    (Some(value), None) => changes.push(Change::Removed {
+                        path,
+                        value: value.clone(),
+                    }),
+                    (None, Some(value)) => changes.push(Change::Added {
+                        path,
+                        value: value.clone(),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if before != after => changes.push(Change::Changed {
+            path,
+            from: before.clone(),
+            to: after.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// Whether a list holds entries to compare one by one rather than being a
+/// value of numbers.
+fn is_entry_list(list: &[Value]) -> bool {
+    list.iter().any(|value| value.is_number().not())
+}
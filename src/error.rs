@@ -0,0 +1,83 @@
+//! The errors of the engine and of the files around it.
+
+use std::{fmt, io};
+
+use crate::sequence;
+
+/// ```
+/// use std::{error::Error as _, io};
+///
+/// use sierpinski_triangle_emulator::{sequence::Sequence, Error};
+///
+/// let parse = Error::Parse {
+///     line: 3,
+///     message: "unknown command `jump`".to_string(),
+/// };
+/// assert_eq!(parse.to_string(), "line 3: unknown command `jump`");
+///
+/// let io = Error::from(io::Error::new(io::ErrorKind::NotFound, "no such file"));
+/// assert_eq!(io.to_string(), "no such file");
+/// assert!(io.source().is_some());
+///
+/// let sequence = Error::from(Sequence::decode(b"GIF89a").unwrap_err());
+/// assert!(matches!(sequence, Error::Sequence(_)));
+/// assert!(sequence.to_string().starts_with("invalid sequence file"));
+/// ```
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file failed.
+    Io(io::Error),
+    /// Line `line` of a file, counting from 1, doesn't parse.
+    Parse { line: usize, message: String },
+    /// A file holds settings this version can't use.
+    InvalidConfig(String),
+    /// More points were asked for than can be generated at once.
+    TooManyPoints { requested: usize, limit: usize },
+    /// Fewer vertices than the rule needs.
+    DegenerateVertices { found: usize, needed: usize },
+    /// A vertex-choice sequence is malformed or doesn't fit the vertices.
+    Sequence(sequence::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            Error::InvalidConfig(message) => write!(f, "{}", message),
+            Error::TooManyPoints { requested, limit } => write!(
+                f,
+                "{} points are too many, at most {} can be generated at once",
+                requested, limit
+            ),
+            Error::DegenerateVertices { found, needed } => write!(
+                f,
+                "the game needs at least {} vertices, there are {}",
+                needed, found
+            ),
+            Error::Sequence(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Sequence(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<sequence::Error> for Error {
+    fn from(e: sequence::Error) -> Error {
+        Error::Sequence(e)
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    fmt::{self, Display},
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use iced::{Color, Point, Size};
+use sierpinski_triangle_emulator::render::RgbaImage;
+
+/// A CPU-side RGBA image the graph can be rasterized into for export. The
+/// points themselves are drawn by
+/// [`render_onto`](sierpinski_triangle_emulator::render::render_onto), so
+/// every exporter draws them the same way.
+pub struct Raster {
+    image: RgbaImage,
+}
+
+impl From<RgbaImage> for Raster {
+    fn from(image: RgbaImage) -> Raster {
+        Raster { image }
+    }
+}
+
+impl Raster {
+    pub fn new(size: Size<f32>, background: Color) -> Raster {
+        Raster {
+            image: RgbaImage::new(size.width as u32, size.height as u32, rgba8(background)),
+        }
+    }
+
+    pub fn image_mut(&mut self) -> &mut RgbaImage {
+        &mut self.image
+    }
+
+    /// Sets the pixel containing `point`, ignoring points outside the image.
+    pub fn plot(&mut self, point: Point, color: Color) {
+        self.image.plot([point.x, point.y], rgba8(color));
+    }
+
+    /// Composites `color` over the pixel containing `point`, ignoring points
+    /// outside the image.
+    pub fn blend(&mut self, point: Point, color: Color) {
+        self.image.blend([point.x, point.y], rgba8(color));
+    }
+
+    /// Sets every pixel within `width` pixels of the edge of the image.
+    pub fn frame(&mut self, width: u32, color: Color) {
+        let (w, h) = (self.image.width, self.image.height);
+        for y in 0..h {
+            for x in 0..w {
+                if x < width || y < width || x + width >= w || y + width >= h {
+                    self.plot(Point::new(x as f32, y as f32), color);
+                }
+            }
+        }
+    }
+
+    /// Shrinks the image by `factor`, averaging each `factor` by `factor`
+    /// block into one pixel. Colours are weighted by alpha so transparent
+    /// pixels don't tint their neighbours.
+    pub fn downsample(&self, factor: u32) -> Raster {
+        let factor = factor.max(1);
+        let source = &self.image;
+        let (width, height) = (source.width / factor, source.height / factor);
+        let mut data = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0_f32; 4];
+                for dy in 0..factor {
+                    for dx in 0..factor {
+                        let (px, py) = ((x * factor + dx) as usize, (y * factor + dy) as usize);
+                        let offset = (py * source.width as usize + px) * 4;
+                        let pixel = &source.data[offset..offset + 4];
+                        let alpha = pixel[3] as f32;
+                        for c in 0..3 {
+                            sum[c] += pixel[c] as f32 * alpha;
+                        }
+                        sum[3] += alpha;
+                    }
+                }
+                let alpha = sum[3];
+                for c in &sum[0..3] {
+                    data.push(if alpha > 0.0 {
+                        (c / alpha).round() as u8
+                    } else {
+                        0
+                    });
+                }
+                data.push((alpha / (factor * factor) as f32).round() as u8);
+            }
+        }
+        Raster {
+            image: RgbaImage {
+                width,
+                height,
+                data,
+            },
+        }
+    }
+
+    pub fn fill_circle(&mut self, center: Point, radius: f32, color: Color) {
+        self.image
+            .fill_circle([center.x, center.y], radius, rgba8(color));
+    }
+
+    /// Writes the image as a PNG, embedding `metadata` as `tEXt` chunks.
+    pub fn write_png(&self, path: &Path, metadata: &[(&str, String)]) -> io::Result<()> {
+        let file = File::create(path)?;
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(file), self.image.width, self.image.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        for (keyword, text) in metadata {
+            encoder.add_text_chunk(keyword.to_string(), text.clone())?;
+        }
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.image.data)?;
+        Ok(())
+    }
+}
+
+/// Text formats for exporting points to 3D tools such as Blender or MeshLab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointCloud {
+    Obj,
+    Ply,
+}
+
+impl PointCloud {
+    pub const ALL: [PointCloud; 2] = [PointCloud::Obj, PointCloud::Ply];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            PointCloud::Obj => "obj",
+            PointCloud::Ply => "ply",
+        }
+    }
+
+    /// Writes `vertices` only, without any faces.
+    pub fn write(self, path: &Path, vertices: &[[f32; 3]]) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        match self {
+            PointCloud::Obj => {
+                writeln!(out, "# Sierpinski Triangle Emulator point cloud")?;
+                for [x, y, z] in vertices {
+                    writeln!(out, "v {} {} {}", x, y, z)?;
+                }
+            }
+            PointCloud::Ply => {
+                writeln!(out, "ply")?;
+                writeln!(out, "format ascii 1.0")?;
+                writeln!(out, "comment Sierpinski Triangle Emulator point cloud")?;
+                writeln!(out, "element vertex {}", vertices.len())?;
+                writeln!(out, "property float x")?;
+                writeln!(out, "property float y")?;
+                writeln!(out, "property float z")?;
+                writeln!(out, "end_header")?;
+                for [x, y, z] in vertices {
+                    writeln!(out, "{} {} {}", x, y, z)?;
+                }
+            }
+        }
+        out.flush()
+    }
+}
+
+impl Display for PointCloud {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointCloud::Obj => write!(f, "OBJ"),
+            PointCloud::Ply => write!(f, "PLY"),
+        }
+    }
+}
+
+pub fn rgba8(color: Color) -> [u8; 4] {
+    [color.r, color.g, color.b, color.a].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
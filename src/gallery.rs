@@ -0,0 +1,320 @@
+//! Contact sheets: one layout played again and again with a parameter
+//! stepped from tile to tile, laid out in a grid with each tile labelled, so
+//! the interesting values stand out before exploring them one by one.
+//!
+//! Every tile plays the same seed, unless the seed is what varies, so tiles
+//! differ by the parameter alone and the same sheet comes out every time.
+
+use std::fmt::{self, Display};
+
+use crate::{
+    render::{self, RenderStyle, RgbaImage},
+    ChaosGame, Error, Restriction,
+};
+
+/// The lowest and highest ratio a ratio sheet steps through.
+pub const RATIOS: (f32, f32) = (0.4, 0.7);
+/// The lowest and highest rotation a rotation sheet steps through, in
+/// degrees.
+pub const ROTATIONS: (f32, f32) = (0.0, 60.0);
+/// The fewest and most vertices a vertex count sheet steps through.
+pub const VERTEX_COUNTS: (f32, f32) = (3.0, 8.0);
+/// The first and last seed a seed sheet steps through.
+pub const SEEDS: (f32, f32) = (0.0, 15.0);
+
+/// Pixels of background between the tiles and around the sheet.
+const GAP: u32 = 4;
+/// Font pixels in pixels of the sheet.
+const LABEL_SCALE: u32 = 2;
+
+/// What changes from tile to tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parameter {
+    Ratio,
+    Rotation,
+    Restriction,
+    /// Regular polygons of that many vertices centred in the canvas, in
+    /// place of the vertices.
+    VertexCount,
+    Seed,
+}
+
+impl Parameter {
+    pub const ALL: [Parameter; 5] = [
+        Parameter::Ratio,
+        Parameter::Rotation,
+        Parameter::Restriction,
+        Parameter::VertexCount,
+        Parameter::Seed,
+    ];
+
+    /// The values a sheet steps through unless it is given others. The
+    /// restrictions are a list of modes rather than a range.
+    pub fn range(self) -> Option<(f32, f32)> {
+        match self {
+            Parameter::Ratio => Some(RATIOS),
+            Parameter::Rotation => Some(ROTATIONS),
+            Parameter::Restriction => None,
+            Parameter::VertexCount => Some(VERTEX_COUNTS),
+            Parameter::Seed => Some(SEEDS),
+        }
+    }
+
+    /// `count` values evenly spread over the [`range`](Self::range) of the
+    /// parameter, both ends included. Restrictions have as many values as
+    /// there are modes, however many are asked for.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::gallery::{Parameter, Variation};
+    ///
+    /// let ratios = Parameter::Ratio.variations(4);
+    /// assert_eq!(ratios.first(), Some(&Variation::Ratio(0.4)));
+    /// assert_eq!(ratios.last(), Some(&Variation::Ratio(0.7)));
+    /// assert_eq!(ratios[1].to_string(), "ratio 0.50");
+    /// assert_eq!(Parameter::Restriction.variations(16).len(), 2);
+    /// assert_eq!(Parameter::Rotation.variations(1), [Variation::Rotation(0.0)]);
+    /// ```
+    pub fn variations(self, count: usize) -> Vec<Variation> {
+        self.variations_over(self.range().unwrap_or_default(), count)
+    }
+
+    /// `count` values evenly spread from the first of `range` to the
+    /// second, both included. Vertex counts and seeds are rounded to whole
+    /// numbers, and a value repeating the one before is left out, so a
+    /// narrow range gives fewer values. Restrictions ignore the range.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::gallery::{Parameter, Variation};
+    ///
+    /// let counts = Parameter::VertexCount.variations_over((3.0, 6.0), 9);
+    /// assert_eq!(counts.len(), 4);
+    /// assert_eq!(counts[1].to_string(), "vertices 4");
+    /// assert_eq!(
+    ///     Parameter::Seed.variations_over((10.0, 0.0), 3),
+    ///     [Variation::Seed(10), Variation::Seed(5), Variation::Seed(0)]
+    /// );
+    /// assert_eq!(Parameter::Ratio.variations_over((0.2, 0.3), 2)[1], Variation::Ratio(0.3));
+    /// ```
+    pub fn variations_over(self, (low, high): (f32, f32), count: usize) -> Vec<Variation> {
+        let spread = (0..count).map(move |i| match count {
+            1 => low,
+            _ => low + (high - low) * i as f32 / (count - 1) as f32,
+        });
+        let mut variations = match self {
+            Parameter::Ratio => spread.map(Variation::Ratio).collect::<Vec<_>>(),
+            Parameter::Rotation => spread.map(Variation::Rotation).collect(),
+            Parameter::Restriction => Restriction::ALL
+                .iter()
+                .take(count)
+                .map(|r| Variation::Restriction(*r))
+                .collect(),
+            Parameter::VertexCount => spread
+                .map(|n| Variation::VertexCount(n.round().max(1.0) as usize))
+                .collect(),
+            Parameter::Seed => spread
+                .map(|seed| Variation::Seed(seed.round().max(0.0) as u64))
+                .collect(),
+        };
+        variations.dedup();
+        variations
+    }
+}
+
+impl Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Parameter::Ratio => write!(f, "ratio"),
+            Parameter::Rotation => write!(f, "rotation"),
+            Parameter::Restriction => write!(f, "restriction"),
+            Parameter::VertexCount => write!(f, "vertices"),
+            Parameter::Seed => write!(f, "seed"),
+        }
+    }
+}
+
+/// The value of the parameter in one tile. It displays as the tile label.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Variation {
+    Ratio(f32),
+    Rotation(f32),
+    Restriction(Restriction),
+    VertexCount(usize),
+    Seed(u64),
+}
+
+impl Variation {
+    /// `game` with this value of the parameter, on a canvas of `canvas`
+    /// pixels.
+    pub fn apply(self, game: ChaosGame, canvas: [f32; 2]) -> ChaosGame {
+        match self {
+            Variation::Ratio(ratio) => game.ratio(ratio),
+            Variation::Rotation(degrees) => game.rotation(degrees),
+            Variation::Restriction(restriction) => game.restriction(restriction),
+            Variation::VertexCount(count) => game.with_vertices(polygon(count, canvas)),
+            Variation::Seed(seed) => game.seed(seed),
+        }
+    }
+}
+
+/// `count` vertices evenly spaced on a circle centred in the canvas, 90% of
+/// the way to its nearer sides, the first one on top.
+fn polygon(count: usize, [width, height]: [f32; 2]) -> Vec<[f32; 2]> {
+    let radius = width.min(height) * 0.45;
+    (0..count)
+        .map(|i| {
+            let angle =
+                -std::f32::consts::FRAC_PI_2 + std::f32::consts::TAU * i as f32 / count as f32;
+            [
+                width / 2.0 + radius * angle.cos(),
+                height / 2.0 + radius * angle.sin(),
+            ]
+        })
+        .collect()
+}
+
+impl Display for Variation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Variation::Ratio(ratio) => write!(f, "ratio {:.2}", ratio),
+            Variation::Rotation(degrees) => write!(f, "rotation {:.0}", degrees),
+            Variation::Restriction(restriction) => write!(f, "{}", restriction),
+            Variation::VertexCount(count) => write!(f, "vertices {}", count),
+            Variation::Seed(seed) => write!(f, "seed {}", seed),
+        }
+    }
+}
+
+/// How a contact sheet is laid out and how much each tile plays.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sheet {
+    pub parameter: Parameter,
+    /// The first and last value of the parameter, its own
+    /// [`range`](Parameter::range) without one.
+    pub range: Option<(f32, f32)>,
+    /// Tiles asked for; see [`Parameter::variations_over`].
+    pub tiles: usize,
+    /// Points played in each tile.
+    pub iterations: usize,
+    /// Side of each square tile, in pixels.
+    pub tile_size: u32,
+}
+
+impl Sheet {
+    /// The values of the parameter in the tiles, in order.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::gallery::{Parameter, Sheet, Variation};
+    ///
+    /// let mut sheet = Sheet {
+    ///     parameter: Parameter::Seed,
+    ///     range: None,
+    ///     tiles: 4,
+    ///     iterations: 1000,
+    ///     tile_size: 50,
+    /// };
+    /// assert_eq!(sheet.variations()[1], Variation::Seed(5));
+    /// sheet.range = Some((100.0, 103.0));
+    /// assert_eq!(sheet.variations()[1], Variation::Seed(101));
+    /// ```
+    pub fn variations(&self) -> Vec<Variation> {
+        match self.range {
+            Some(range) => self.parameter.variations_over(range, self.tiles),
+            None => self.parameter.variations(self.tiles),
+        }
+    }
+
+    /// The columns and rows of the grid of `tiles` tiles, as square as it
+    /// gets with no empty row.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::gallery::Sheet;
+    ///
+    /// assert_eq!(Sheet::grid(16), (4, 4));
+    /// assert_eq!(Sheet::grid(5), (3, 2));
+    /// assert_eq!(Sheet::grid(2), (2, 1));
+    /// ```
+    pub fn grid(tiles: usize) -> (u32, u32) {
+        let columns = (tiles as f32).sqrt().ceil().max(1.0) as usize;
+        (columns as u32, tiles.div_ceil(columns).max(1) as u32)
+    }
+
+    /// Plays `game` once per variation, its points on a canvas of
+    /// `canvas` pixels scaled down into the tiles, and composites the tiles
+    /// into one image. The label takes the colour of the first point.
+    /// `progress` hears how many tiles are done after each of them, from
+    /// the thread the sheet renders on.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::{
+    ///     gallery::{Parameter, Sheet},
+    ///     render::{PointShape, RenderStyle},
+    ///     ChaosGame,
+    /// };
+    ///
+    /// let game = ChaosGame::new([[50.0, 5.0], [5.0, 95.0], [95.0, 95.0]]).seed(3);
+    /// let sheet = Sheet {
+    ///     parameter: Parameter::Ratio,
+    ///     range: None,
+    ///     tiles: 4,
+    ///     iterations: 2000,
+    ///     tile_size: 50,
+    /// };
+    /// let style = RenderStyle {
+    ///     background: [255, 255, 255, 255],
+    ///     colors: vec![[0, 0, 0, 255]],
+    ///     point_size: 1,
+    ///     shape: PointShape::Square,
+    /// };
+    /// let mut done = vec![];
+    /// let image = sheet.render(&game, [100.0, 100.0], &style, |n| done.push(n)).unwrap();
+    /// assert_eq!((image.width, image.height), (112, 112));
+    /// assert_eq!(done, [1, 2, 3, 4]);
+    ///
+    /// // Same game, same sheet.
+    /// assert_eq!(image, sheet.render(&game, [100.0, 100.0], &style, |_| {}).unwrap());
+    /// ```
+    pub fn render(
+        &self,
+        game: &ChaosGame,
+        [width, height]: [f32; 2],
+        style: &RenderStyle,
+        mut progress: impl FnMut(usize),
+    ) -> Result<RgbaImage, Error> {
+        let variations = self.variations();
+        let (columns, rows) = Sheet::grid(variations.len());
+        let tile = self.tile_size.max(1);
+        let mut sheet = RgbaImage::new(
+            columns * (tile + GAP) + GAP,
+            rows * (tile + GAP) + GAP,
+            style.background,
+        );
+        let scale = tile as f32 / width.max(height).max(1.0);
+        let offset = [
+            (tile as f32 - width * scale) / 2.0,
+            (tile as f32 - height * scale) / 2.0,
+        ];
+        let label = style.colors.first().copied().unwrap_or([0, 0, 0, 255]);
+        for (i, variation) in variations.iter().enumerate() {
+            let points = variation
+                .apply(game.clone(), [width, height])
+                .try_points(self.iterations)?
+                .into_iter()
+                .map(|[x, y]| [x * scale + offset[0], y * scale + offset[1]])
+                .collect::<Vec<_>>();
+            let mut image = render::render_at(&points, points.len(), style, (tile, tile));
+            image.text(
+                [LABEL_SCALE as f32, LABEL_SCALE as f32],
+                &variation.to_string(),
+                LABEL_SCALE,
+                label,
+            );
+            let (column, row) = (i as u32 % columns, i as u32 / columns);
+            sheet.blit(
+                &image,
+                [GAP + column * (tile + GAP), GAP + row * (tile + GAP)],
+            );
+            progress(i + 1);
+        }
+        Ok(sheet)
+    }
+}
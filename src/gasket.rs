@@ -0,0 +1,88 @@
+/// A triangle given by its three corners.
+pub type Triangle = [[f32; 2]; 3];
+
+/// The triangles kept after `level` steps of the deterministic Sierpinski
+/// construction: each step replaces every triangle with the three corner
+/// triangles spanned by its edge midpoints, dropping the middle one.
+///
+/// There are `3^level` of them; their outlines include every edge of the
+/// earlier levels.
+///
+/// ```
+/// use sierpinski_triangle_emulator::gasket;
+///
+/// let triangles = gasket::triangles([[0.0, 0.0], [4.0, 0.0], [0.0, 4.0]], 2);
+/// assert_eq!(triangles.len(), 9);
+/// assert_eq!(triangles[0], [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]);
+/// ```
+pub fn triangles(corners: Triangle, level: u32) -> Vec<Triangle> {
+    (0..level).fold(vec![corners], |triangles, _| {
+        triangles.iter().flat_map(|t| split(*t)).collect()
+    })
+}
+
+fn split([a, b, c]: Triangle) -> [Triangle; 3] {
+    let mid = |p: [f32; 2], q: [f32; 2]| [(p[0] + q[0]) / 2.0, (p[1] + q[1]) / 2.0];
+    let (ab, bc, ca) = (mid(a, b), mid(b, c), mid(c, a));
+    [[a, ab, ca], [ab, b, bc], [ca, bc, c]]
+}
+
+/// The triangles after `level` steps as an SVG document of filled
+/// `<polygon>`s on a `size` canvas, for crisp output at any scale. Colours
+/// are RGBA; a fully transparent `background` leaves it out.
+///
+/// ```
+/// use sierpinski_triangle_emulator::gasket;
+///
+/// let svg = gasket::to_svg(
+///     [[0.0, 0.0], [4.0, 0.0], [0.0, 4.0]],
+///     1,
+///     [4.0, 4.0],
+///     [0, 0, 0, 255],
+///     [255, 255, 255, 0],
+/// );
+/// assert!(svg.starts_with("<svg "));
+/// assert_eq!(svg.matches("<polygon").count(), 3);
+/// assert!(svg.contains(r#"<polygon points="0,0 2,0 0,2"/>"#));
+/// assert!(!svg.contains("<rect"));
+/// ```
+pub fn to_svg(
+    corners: Triangle,
+    level: u32,
+    [width, height]: [f32; 2],
+    fill: [u8; 4],
+    background: [u8; 4],
+) -> String {
+    let color = |[r, g, b, a]: [u8; 4]| {
+        format!(
+            r##"fill="#{:02x}{:02x}{:02x}" fill-opacity="{}""##,
+            r,
+            g,
+            b,
+            a as f32 / 255.0
+        )
+    };
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    svg.push('\n');
+    if background[3] > 0 {
+        svg += &format!(
+            "<rect width=\"{}\" height=\"{}\" {}/>\n",
+            width,
+            height,
+            color(background)
+        );
+    }
+    svg += &format!("<g {}>\n", color(fill));
+    for [a, b, c] in triangles(corners, level) {
+        svg += &format!(
+            "<polygon points=\"{},{} {},{} {},{}\"/>\n",
+            a[0], a[1], b[0], b[1], c[0], c[1]
+        );
+    }
+    svg += "</g>\n</svg>\n";
+    svg
+}
@@ -0,0 +1,282 @@
+//! Measurements of the polygon formed by the vertices in order, closing
+//! back from the last vertex to the first, and snapping for placing a
+//! vertex in an exact relation to another.
+
+/// The edges from each vertex to the next, the last one closing the
+/// polygon. A single vertex has no edges, two have one edge each way.
+pub fn edges(vertices: &[[f32; 2]]) -> impl Iterator<Item = ([f32; 2], [f32; 2])> + '_ {
+    let n = if vertices.len() < 2 {
+        0
+    } else {
+        vertices.len()
+    };
+    (0..n).map(move |i| (vertices[i], vertices[(i + 1) % n]))
+}
+
+pub fn distance([ax, ay]: [f32; 2], [bx, by]: [f32; 2]) -> f32 {
+    (bx - ax).hypot(by - ay)
+}
+
+/// Length of every edge, in the order of [`edges`].
+///
+/// ```
+/// use sierpinski_triangle_emulator::geometry;
+///
+/// let lengths = geometry::edge_lengths(&[[0.0, 0.0], [3.0, 0.0], [3.0, 4.0]]);
+/// assert_eq!(lengths, [3.0, 4.0, 5.0]);
+/// ```
+pub fn edge_lengths(vertices: &[[f32; 2]]) -> Vec<f32> {
+    edges(vertices).map(|(a, b)| distance(a, b)).collect()
+}
+
+pub fn perimeter(vertices: &[[f32; 2]]) -> f32 {
+    edge_lengths(vertices).iter().sum()
+}
+
+/// The shoelace formula. It is positive when the vertices run
+/// counter-clockwise with y pointing up, which is clockwise on screen where
+/// y points down. For a self-intersecting order the lobes winding opposite
+/// ways cancel, so the result can be negative or zero whatever the
+/// orientation.
+///
+/// ```
+/// use sierpinski_triangle_emulator::geometry::signed_area;
+///
+/// let square = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+/// assert_eq!(signed_area(&square), 1.0);
+/// let reversed = [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]];
+/// assert_eq!(signed_area(&reversed), -1.0);
+///
+/// // A bowtie: the larger lobe winds clockwise.
+/// let bowtie = [[0.0, 0.0], [4.0, 4.0], [4.0, 0.0], [0.0, 2.0]];
+/// assert_eq!(signed_area(&bowtie), -4.0);
+/// ```
+pub fn signed_area(vertices: &[[f32; 2]]) -> f32 {
+    edges(vertices)
+        .map(|([ax, ay], [bx, by])| ax * by - bx * ay)
+        .sum::<f32>()
+        / 2.0
+}
+
+/// The average of the vertices, `None` without any.
+///
+/// ```
+/// use sierpinski_triangle_emulator::geometry::centroid;
+///
+/// assert_eq!(centroid(&[[0.0, 0.0], [3.0, 0.0], [0.0, 3.0]]), Some([1.0, 1.0]));
+/// assert_eq!(centroid(&[]), None);
+/// ```
+pub fn centroid(vertices: &[[f32; 2]]) -> Option<[f32; 2]> {
+    if vertices.is_empty() {
+        return None;
+    }
+    let n = vertices.len() as f32;
+    let [x, y] = vertices
+        .iter()
+        .fold([0.0, 0.0], |[x, y], [vx, vy]| [x + vx, y + vy]);
+    Some([x / n, y / n])
+}
+
+/// Indices of the vertices within `tolerance` of an earlier vertex that is
+/// kept, in increasing order. Removing them leaves one vertex per cluster.
+///
+/// ```
+/// use sierpinski_triangle_emulator::geometry::near_duplicates;
+///
+/// let vertices = [[0.0, 0.0], [10.0, 0.0], [0.5, 0.0], [10.0, 0.0], [1.2, 0.0]];
+/// assert_eq!(near_duplicates(&vertices, 1.0), [2, 3]);
+/// ```
+pub fn near_duplicates(vertices: &[[f32; 2]], tolerance: f32) -> Vec<usize> {
+    let mut kept: Vec<[f32; 2]> = vec![];
+    let mut duplicates = vec![];
+    for (i, v) in vertices.iter().enumerate() {
+        if kept.iter().any(|k| distance(*k, *v) <= tolerance) {
+            duplicates.push(i);
+        } else {
+            kept.push(*v);
+        }
+    }
+    duplicates
+}
+
+/// The smallest convex polygon containing every one of `points`, its
+/// vertices in order. Points on its edges are left out.
+///
+/// ```
+/// use sierpinski_triangle_emulator::geometry::convex_hull;
+///
+/// let points = [[0.0, 0.0], [2.0, 1.0], [4.0, 0.0], [4.0, 4.0], [2.0, 4.0], [0.0, 4.0]];
+/// assert_eq!(
+///     convex_hull(&points),
+///     [[0.0, 0.0], [4.0, 0.0], [4.0, 4.0], [0.0, 4.0]]
+/// );
+/// assert_eq!(convex_hull(&[[1.0, 1.0]]), [[1.0, 1.0]]);
+/// ```
+pub fn convex_hull(points: &[[f32; 2]]) -> Vec<[f32; 2]> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+    let cross = |o: [f32; 2], a: [f32; 2], b: [f32; 2]| {
+        (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+    };
+    // Andrew's monotone chain: the lower half left to right, then the upper
+    // half back, each dropping points that don't turn the same way.
+    let mut hull: Vec<[f32; 2]> = vec![];
+    for pass in 0..2 {
+        let start = hull.len();
+        let points: Box<dyn Iterator<Item = &[f32; 2]>> = if pass == 0 {
+            Box::new(sorted.iter())
+        } else {
+            Box::new(sorted.iter().rev())
+        };
+        for p in points {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], *p) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(*p);
+        }
+        // The last point starts the other half.
+        hull.pop();
+    }
+    hull
+}
+
+/// How many times larger `b` is than `a`, both given as `[width, height]`:
+/// the square root of the ratio of their areas, so a copy drawn a little
+/// wider and a little flatter still measures about right. `None` when `a`
+/// has no area.
+///
+/// ```
+/// use sierpinski_triangle_emulator::geometry::scale_ratio;
+///
+/// assert_eq!(scale_ratio([400.0, 300.0], [200.0, 150.0]), Some(0.5));
+/// assert_eq!(scale_ratio([100.0, 100.0], [50.0, 200.0]), Some(1.0));
+/// assert_eq!(scale_ratio([0.0, 100.0], [50.0, 50.0]), None);
+/// ```
+pub fn scale_ratio([aw, ah]: [f32; 2], [bw, bh]: [f32; 2]) -> Option<f32> {
+    let area = aw * ah;
+    (area > 0.0).then(|| (bw * bh / area).sqrt())
+}
+
+/// The ratios of the widths and of the heights of `b` to `a`, NaN or
+/// infinite where `a` is zero.
+///
+/// ```
+/// use sierpinski_triangle_emulator::geometry::axis_ratios;
+///
+/// assert_eq!(axis_ratios([400.0, 300.0], [200.0, 75.0]), [0.5, 0.25]);
+/// ```
+pub fn axis_ratios([aw, ah]: [f32; 2], [bw, bh]: [f32; 2]) -> [f32; 2] {
+    [bw / aw, bh / ah]
+}
+
+/// Whether two edges that don't share a vertex cross or touch.
+///
+/// ```
+/// use sierpinski_triangle_emulator::geometry::is_self_intersecting;
+///
+/// let square = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+/// assert!(!is_self_intersecting(&square));
+/// let bowtie = [[0.0, 0.0], [1.0, 1.0], [1.0, 0.0], [0.0, 1.0]];
+/// assert!(is_self_intersecting(&bowtie));
+/// ```
+pub fn is_self_intersecting(vertices: &[[f32; 2]]) -> bool {
+    let n = vertices.len();
+    if n < 4 {
+        return false;
+    }
+    let edges = edges(vertices).collect::<Vec<_>>();
+    (0..n).any(|i| {
+        // Skip the neighbouring edges, which always share a vertex.
+        (i + 2..n)
+            .filter(|j| (j + 1) % n != i)
+            .any(|j| segments_intersect(edges[i], edges[j]))
+    })
+}
+
+fn segments_intersect((a, b): ([f32; 2], [f32; 2]), (c, d): ([f32; 2], [f32; 2])) -> bool {
+    let cross = |o: [f32; 2], p: [f32; 2], q: [f32; 2]| {
+        (p[0] - o[0]) * (q[1] - o[1]) - (p[1] - o[1]) * (q[0] - o[0])
+    };
+    let on_segment = |p: [f32; 2], q: [f32; 2], r: [f32; 2]| {
+        r[0] >= p[0].min(q[0])
+            && r[0] <= p[0].max(q[0])
+            && r[1] >= p[1].min(q[1])
+            && r[1] <= p[1].max(q[1])
+    };
+    let (d1, d2) = (cross(c, d, a), cross(c, d, b));
+    let (d3, d4) = (cross(a, b, c), cross(a, b, d));
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        return true;
+    }
+    (d1 == 0.0 && on_segment(c, d, a))
+        || (d2 == 0.0 && on_segment(c, d, b))
+        || (d3 == 0.0 && on_segment(a, b, c))
+        || (d4 == 0.0 && on_segment(a, b, d))
+}
+
+/// The direction from `origin` to `point` in degrees, from the x axis
+/// towards the y axis, in `-180..=180`.
+///
+/// ```
+/// use sierpinski_triangle_emulator::geometry::angle;
+///
+/// assert_eq!(angle([1.0, 1.0], [2.0, 1.0]), 0.0);
+/// assert_eq!(angle([1.0, 1.0], [1.0, 3.0]), 90.0);
+/// ```
+pub fn angle([ox, oy]: [f32; 2], [x, y]: [f32; 2]) -> f32 {
+    (y - oy).atan2(x - ox).to_degrees()
+}
+
+/// `point` turned about `origin` to the nearest multiple of `step` degrees
+/// of [`angle`], at the same distance.
+///
+/// ```
+/// use sierpinski_triangle_emulator::geometry::{angle, distance, snap_angle};
+///
+/// let snapped = snap_angle([0.0, 0.0], [10.0, 1.0], 15.0);
+/// assert!((angle([0.0, 0.0], snapped)).abs() < 1e-4);
+/// assert!((distance([0.0, 0.0], snapped) - 101.0_f32.sqrt()).abs() < 1e-4);
+///
+/// // 52° is nearer 45° than 60°.
+/// let (sin, cos) = 52.0_f32.to_radians().sin_cos();
+/// let snapped = snap_angle([5.0, 5.0], [5.0 + 20.0 * cos, 5.0 + 20.0 * sin], 15.0);
+/// assert!((angle([5.0, 5.0], snapped) - 45.0).abs() < 1e-3);
+/// assert_eq!(snap_angle([1.0, 1.0], [1.0, 1.0], 15.0), [1.0, 1.0]);
+/// ```
+pub fn snap_angle(origin: [f32; 2], point: [f32; 2], step: f32) -> [f32; 2] {
+    let length = distance(origin, point);
+    if length == 0.0 || step <= 0.0 {
+        return point;
+    }
+    let (sin, cos) = ((angle(origin, point) / step).round() * step)
+        .to_radians()
+        .sin_cos();
+    [origin[0] + length * cos, origin[1] + length * sin]
+}
+
+/// `point` moved along the ray from `origin` through it to `length` away
+/// from `origin`. A `point` on `origin` has no direction and stays.
+///
+/// ```
+/// use sierpinski_triangle_emulator::geometry::at_distance;
+///
+/// assert_eq!(at_distance([1.0, 1.0], [4.0, 5.0], 10.0), [7.0, 9.0]);
+/// assert_eq!(at_distance([1.0, 1.0], [1.0, 1.0], 10.0), [1.0, 1.0]);
+/// ```
+pub fn at_distance(origin: [f32; 2], point: [f32; 2], length: f32) -> [f32; 2] {
+    let current = distance(origin, point);
+    if current == 0.0 {
+        return point;
+    }
+    let scale = length / current;
+    [
+        origin[0] + (point[0] - origin[0]) * scale,
+        origin[1] + (point[1] - origin[1]) * scale,
+    ]
+}
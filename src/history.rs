@@ -0,0 +1,202 @@
+//! Undo and redo over snapshots of whatever state an application wants to
+//! step back through.
+
+use std::time::{Duration, Instant};
+
+/// Edits of the same kind closer together than this make one entry, so
+/// dragging a slider undoes in one go.
+pub const COALESCE: Duration = Duration::from_millis(500);
+
+/// How many edits can be undone.
+const CAPACITY: usize = 100;
+
+/// Where a gesture such as a drag is, from the press to the release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Gesture {
+    None,
+    /// Pressed, nothing edited yet.
+    Started,
+    /// The top entry holds the state from before the press.
+    Recorded,
+}
+
+#[derive(Debug, Clone)]
+struct Entry<T, K> {
+    /// The state before the edit.
+    before: T,
+    /// What kind of edit it was, `None` for ones that never coalesce.
+    kind: Option<K>,
+    /// When the edit, or the last one coalesced into it, happened.
+    at: Instant,
+}
+
+/// The states before each edit, most recent last, and the states undone
+/// since the last edit.
+///
+/// ```
+/// use std::time::{Duration, Instant};
+///
+/// use sierpinski_triangle_emulator::history::History;
+///
+/// let mut history = History::default();
+/// let start = Instant::now();
+/// let mut ratio = 0.5;
+///
+/// // A slider drag: three steps in quick succession.
+/// for (i, value) in [0.55, 0.6, 0.65].into_iter().enumerate() {
+///     history.record(ratio, Some("ratio"), start + Duration::from_millis(100 * i as u64));
+///     ratio = value;
+/// }
+/// // Another edit a while later.
+/// history.record(ratio, Some("ratio"), start + Duration::from_secs(5));
+/// ratio = 0.3;
+///
+/// ratio = history.undo(ratio).unwrap();
+/// assert_eq!(ratio, 0.65);
+/// ratio = history.undo(ratio).unwrap();
+/// assert_eq!(ratio, 0.5);
+/// assert_eq!(history.undo(ratio), None);
+///
+/// ratio = history.redo(ratio).unwrap();
+/// assert_eq!(ratio, 0.65);
+///
+/// // A new edit drops what was undone.
+/// history.record(ratio, Some("ratio"), start + Duration::from_secs(9));
+/// assert!(!history.can_redo());
+/// ```
+#[derive(Debug, Clone)]
+pub struct History<T, K> {
+    undo: Vec<Entry<T, K>>,
+    redo: Vec<T>,
+    gesture: Gesture,
+}
+
+impl<T, K> Default for History<T, K> {
+    fn default() -> Self {
+        History {
+            undo: vec![],
+            redo: vec![],
+            gesture: Gesture::None,
+        }
+    }
+}
+
+impl<T, K: PartialEq> History<T, K> {
+    /// Records an edit at `now` that changed the state from `before`.
+    /// Following an edit of the same `kind` within [`COALESCE`] it extends
+    /// that entry instead, which keeps its earlier state. Edits without a
+    /// kind always make an entry of their own.
+    ///
+    /// During a gesture every edit after the first amends the entry the
+    /// first one made, whatever its kind and however long the gesture takes.
+    pub fn record(&mut self, before: T, kind: Option<K>, now: Instant) {
+        self.redo.clear();
+        match self.gesture {
+            Gesture::Recorded => {
+                if let Some(last) = self.undo.last_mut() {
+                    last.at = now;
+                    return;
+                }
+            }
+            Gesture::Started => self.gesture = Gesture::Recorded,
+            Gesture::None => {}
+        }
+        if let Some(last) = self
+            .undo
+            .last_mut()
+            .filter(|_| self.gesture == Gesture::None)
+        {
+            if kind.is_some()
+                && last.kind == kind
+                && now.saturating_duration_since(last.at) < COALESCE
+            {
+                last.at = now;
+                return;
+            }
+        }
+        self.undo.push(Entry {
+            before,
+            kind,
+            at: now,
+        });
+        if self.undo.len() > CAPACITY {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Starts a gesture, such as dragging a slider from press to release,
+    /// that undoes in one step. See [`record`](Self::record).
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use sierpinski_triangle_emulator::history::History;
+    ///
+    /// let mut history = History::default();
+    /// let start = Instant::now();
+    /// let mut weight = 1.0;
+    ///
+    /// // A slow drag of 50 steps, far more than COALESCE apart.
+    /// history.begin_gesture();
+    /// for i in 1..=50 {
+    ///     history.record(weight, Some("weight"), start + Duration::from_secs(i));
+    ///     weight = 1.0 + i as f32 / 10.0;
+    /// }
+    /// history.end_gesture();
+    /// // An edit right after the release starts an entry of its own.
+    /// history.record(weight, Some("weight"), start + Duration::from_secs(50));
+    /// weight = 0.5;
+    ///
+    /// weight = history.undo(weight).unwrap();
+    /// assert_eq!(weight, 6.0);
+    /// weight = history.undo(weight).unwrap();
+    /// assert_eq!(weight, 1.0);
+    /// assert!(!history.can_undo());
+    /// ```
+    pub fn begin_gesture(&mut self) {
+        self.gesture = Gesture::Started;
+    }
+
+    /// Ends the gesture. Later edits make entries of their own, even of the
+    /// same kind right away.
+    pub fn end_gesture(&mut self) {
+        if self.gesture == Gesture::Recorded {
+            if let Some(last) = self.undo.last_mut() {
+                last.kind = None;
+            }
+        }
+        self.gesture = Gesture::None;
+    }
+
+    /// The state before the last edit, remembering `current` for
+    /// [`redo`](Self::redo). `None` when there is nothing to undo.
+    ///
+    /// Undoing or redoing during a gesture ends it.
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        self.end_gesture();
+        let entry = self.undo.pop()?;
+        self.redo.push(current);
+        Some(entry.before)
+    }
+
+    /// The state the last undo left, remembering `current` for
+    /// [`undo`](Self::undo). `None` when there is nothing to redo.
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        self.end_gesture();
+        let after = self.redo.pop()?;
+        self.undo.push(Entry {
+            before: current,
+            kind: None,
+            at: Instant::now(),
+        });
+        Some(after)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
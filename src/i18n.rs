@@ -0,0 +1,549 @@
+//! The strings of the user interface in every language bundled with it.
+//!
+//! Each language is a JSON file in `src/i18n`, embedded at build time and
+//! read the first time it is asked for, that maps every [`Key`] in snake
+//! case to its text. Texts with values in them have `{}` placeholders that
+//! [`substitute`] replaces in order, or `{0}`, `{1}` and so on where a
+//! language puts them in another order. Numbers come formatted already, so
+//! grouping digits works the same in every language.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    sync::OnceLock,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A language the interface can be shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Chinese,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Chinese];
+
+    /// The text of `key`, in English where this language has none.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::i18n::{substitute, Key, Language};
+    ///
+    /// assert_eq!(Language::English.text(Key::Undo), "undo");
+    /// assert_eq!(Language::Chinese.text(Key::Undo), "撤销");
+    ///
+    /// // Every key has a text in every language, with the same values in it.
+    /// let placeholders = |text: &str| {
+    ///     let mut count = 0;
+    ///     substitute(text, |_| { count += 1; String::new() });
+    ///     count
+    /// };
+    /// for language in Language::ALL {
+    ///     assert_eq!(language.bundle_len(), Key::ALL.len(), "{}", language);
+    ///     for key in Key::ALL {
+    ///         let text = language.text(key);
+    ///         assert!(!text.is_empty(), "{:?} in {}", key, language);
+    ///         assert_eq!(
+    ///             placeholders(text),
+    ///             placeholders(Language::English.text(key)),
+    ///             "{:?} in {}",
+    ///             key,
+    ///             language
+    ///         );
+    ///     }
+    /// }
+    /// ```
+    pub fn text(self, key: Key) -> &'static str {
+        match bundle(self).get(&key) {
+            Some(text) => text,
+            None if self != Language::English => Language::English.text(key),
+            None => "",
+        }
+    }
+
+    /// The text of `key` with `values` in place of its placeholders.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::i18n::{Key, Language};
+    ///
+    /// assert_eq!(Language::English.fill(Key::MaxIter, &[&"1,000"]), "max iter: 1,000");
+    /// assert_eq!(
+    ///     Language::English.fill(Key::OutOfBounds, &[&3, &40]),
+    ///     "3 of 40 visible points out of bounds"
+    /// );
+    /// // Chinese names the visible points first.
+    /// assert_eq!(
+    ///     Language::Chinese.fill(Key::OutOfBounds, &[&3, &40]),
+    ///     "40 个可见点中有 3 个超出边界"
+    /// );
+    /// ```
+    pub fn fill(self, key: Key, values: &[&dyn Display]) -> String {
+        substitute(self.text(key), |i| {
+            values
+                .get(i)
+                .map_or_else(String::new, |value| value.to_string())
+        })
+    }
+
+    /// How many texts the language's file has.
+    pub fn bundle_len(self) -> usize {
+        bundle(self).len()
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Language::English => write!(f, "English"),
+            Language::Chinese => write!(f, "中文"),
+        }
+    }
+}
+
+/// `template` with each placeholder replaced by `value` of its index.
+/// A brace without a placeholder in it stays as it is.
+pub fn substitute(template: &str, mut value: impl FnMut(usize) -> String) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        filled.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let index = after.find('}').and_then(|close| {
+            let inside = &after[..close];
+            let index = match inside {
+                "" => next,
+                digits => digits.parse().ok()?,
+            };
+            Some((index, close))
+        });
+        match index {
+            Some((index, close)) => {
+                filled.push_str(&value(index));
+                next = index + 1;
+                rest = &after[close + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = after;
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// The texts of `language`, read from its file on first use. The files are
+/// part of the build, so one that doesn't parse is a bug the tests catch.
+fn bundle(language: Language) -> &'static HashMap<Key, String> {
+    static BUNDLES: [OnceLock<HashMap<Key, String>>; 2] = [OnceLock::new(), OnceLock::new()];
+    let (cell, json) = match language {
+        Language::English => (&BUNDLES[0], include_str!("i18n/en.json")),
+        Language::Chinese => (&BUNDLES[1], include_str!("i18n/zh.json")),
+    };
+    cell.get_or_init(|| serde_json::from_str(json).expect("a bundled language parses"))
+}
+
+/// Declares [`Key`] and [`Key::ALL`] from one list, so no key can be left
+/// out of the tests that go through all of them.
+macro_rules! keys {
+    ($($key:ident,)*) => {
+        /// Every text in the interface that has a translation.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        pub enum Key {
+            $($key,)*
+        }
+
+        impl Key {
+            pub const ALL: [Key; [$(Key::$key),*].len()] = [$(Key::$key),*];
+        }
+    };
+}
+
+keys! {
+    Undo,
+    Redo,
+    Tool,
+    RightClick,
+    Language,
+    Decimals,
+    Marker,
+    Colour,
+    Points,
+    PointCount,
+    Size,
+    SizeByDensity,
+    PixelSnap,
+    Sectors,
+    Radius,
+    ClearMask,
+    Crop,
+    Export,
+    CopyPoints,
+    MeasureRatio,
+    MeasureNoArea,
+    MeasureCopy,
+    MeasureShape,
+    CircleOf,
+    CircleRadius,
+    Phase,
+    Preset,
+    Fit,
+    Subdivision,
+    ShowMaps,
+    Centres,
+    Transitions,
+    AddCentroid,
+    Convergence,
+    Loupe,
+    Levels,
+    ExportSvg,
+    Theme,
+    Background,
+    Transparent,
+    Border,
+    BorderWidth,
+    Margin,
+    Ratio,
+    Rule,
+    Restriction,
+    Sequence,
+    RandomPicks,
+    Rotation,
+    Jitter,
+    AnchorBlend,
+    ClearAnchors,
+    Reseed,
+    Seeds,
+    Start,
+    Generator,
+    Balanced,
+    Quota,
+    AutoApply,
+    Apply,
+    PendingChanges,
+    ShowLastPick,
+    Ghost,
+    PinAsGhost,
+    ClearGhost,
+    IncludeInExports,
+    Slots,
+    StoreSlot,
+    FlipSlots,
+    ShowingSlot,
+    SlotEdited,
+    Session,
+    File,
+    Save,
+    Load,
+    MergeDuplicates,
+    Changes,
+    NoChanges,
+    Tutorial,
+    Script,
+    Run,
+    Record,
+    Choices,
+    Import,
+    ReplayingChoices,
+    StopReplaying,
+    PlaceVertexAt,
+    CoordinateRange,
+    TutorialStep,
+    Skip,
+    Exit,
+    Recovered,
+    Restore,
+    Discard,
+    NoChangesSinceSave,
+    RevertAll,
+    Close,
+    RecentEntry,
+    RecentSessions,
+    StartEmpty,
+    Vertices,
+    VertexColour,
+    WeightShort,
+    On,
+    Lock,
+    Before,
+    After,
+    Delete,
+    ResetColours,
+    UnlockAll,
+    Legend,
+    Sector,
+    First,
+    Halfway,
+    Latest,
+    Sparse,
+    Dense,
+    Ramp,
+    CountScale,
+    ClipPercentile,
+    PointsPerSeed,
+    MaxIter,
+    Link,
+    CurIterOnChange,
+    CurIter,
+    Add,
+    AddPoints,
+    MoveCurIterToEnd,
+    RefineWhenIdle,
+    Refining,
+    JumpToEnd,
+    Pause,
+    Play,
+    Animations,
+    SuggestPoints,
+    CaptureOnComplete,
+    Directory,
+    Supersample,
+    AdditiveColour,
+    ExportPoints,
+    ExportStats,
+    SvgBudget,
+    Gallery,
+    Varying,
+    Tiles,
+    PointsPerTile,
+    From,
+    To,
+    StopReplay,
+    ReplayOrbit,
+    Speed,
+    Trail,
+    Weight,
+    Share,
+    ShareWithQuota,
+    OffCanvas,
+    AutoFrame,
+    OutOfBounds,
+    PixelsCovered,
+    Edges,
+    Perimeter,
+    SelfIntersecting,
+    RestartForFont,
+    UndoTip,
+    RedoTip,
+    RightClickTip,
+    DecimalsTip,
+    PointSizeTip,
+    SizeByDensityTip,
+    PixelSnapTip,
+    CountScaleTip,
+    ClipPercentileTip,
+    CircleOfTip,
+    FitTip,
+    SubdivisionTip,
+    ShowMapsTip,
+    CentresTip,
+    TransitionsTip,
+    AddCentroidTip,
+    ConvergenceTip,
+    LoupeTip,
+    ExportSvgTip,
+    MarginTip,
+    RatioMinusTip,
+    RatioTextTip,
+    RatioPlusTip,
+    RatioTip,
+    RuleTip,
+    RestrictionTip,
+    SequenceTip,
+    RotationTip,
+    JitterTip,
+    AnchorBlendTip,
+    ReseedTip,
+    SeedsTip,
+    StartTip,
+    GeneratorTip,
+    BalancedTip,
+    QuotaTip,
+    AutoApplyTip,
+    ShowLastPickTip,
+    MergeDuplicatesTip,
+    ChangesTip,
+    TutorialTip,
+    PinAsGhostTip,
+    IncludeInExportsTip,
+    StoreSlotTip,
+    FlipSlotsTip,
+    RecordTip,
+    ExportChoicesTip,
+    ImportChoicesTip,
+    RevertAllTip,
+    VertexColourTip,
+    UnlockAllTip,
+    LinkTip,
+    CurIterOnChangeTip,
+    CurFractionTip,
+    AddPointsTip,
+    RefineWhenIdleTip,
+    AnimationsTip,
+    SuggestPointsTip,
+    CaptureOnCompleteTip,
+    SupersampleTip,
+    AdditiveColourTip,
+    ExportPointsTip,
+    ExportStatsTip,
+    SvgBudgetTip,
+    ExportSvgPointsTip,
+    GalleryTip,
+    FromTip,
+    ToTip,
+    SpeedTip,
+    TrailTip,
+    OutOfBoundsTip,
+    AutoFrameTip,
+    PixelsCoveredTip,
+    RatioPlaceholder,
+    JustNow,
+    MinutesAgo,
+    HoursAgo,
+    DaysAgo,
+    TutorialTriangle,
+    TutorialPlaceVertices,
+    TutorialRaiseMaxIter,
+    TutorialScrubCurIter,
+    TutorialFillIn,
+    TutorialTooManyVertices,
+    TutorialInALine,
+    TutorialFinished,
+    PreferencesNotSaved,
+    Inserted,
+    LockedVertex,
+    VertexLocked,
+    VertexUnlocked,
+    InsertionArmed,
+    InsertionCancelled,
+    Copied,
+    TypeCoordinates,
+    PlacedVertex,
+    OffCanvasPoint,
+    Saved,
+    NotSaved,
+    NothingSelected,
+    CopiedPoints,
+    RatioNotChanged,
+    SequenceOutOfRange,
+    SequenceNotChanged,
+    MidpointNeedsTwo,
+    Anchored,
+    AnchorRemoved,
+    PinnedGhost,
+    SubdivisionNeedsThree,
+    AddedCentroid,
+    ConvergenceHelp,
+    NoFixPoints,
+    AtTheLimit,
+    NoPointsAdded,
+    AddedPoints,
+    GalleryRange,
+    RenderingGallery,
+    GalleryFailed,
+    RecentMissing,
+    RecoveryNotWritten,
+    Reverted,
+    StoredSlot,
+    FlippedSlot,
+    EmptySlot,
+    RestoredRecovery,
+    RecoveryNotRemoved,
+    RecordedSteps,
+    NothingToUndo,
+    NothingToRedo,
+    RandomAgain,
+    LockedLayout,
+    Loaded,
+    MergedDuplicates,
+    DuplicateVertices,
+    NotLoaded,
+    RecentNotSaved,
+    RatioAboveOneClip,
+    RatioAboveOneClamp,
+    RatioAboveOneRescale,
+    SavedChoices,
+    ChoicesNotExported,
+    Replaying,
+    NotImported,
+    Running,
+    NotRun,
+    NoAntialiasing,
+    GlFallback,
+    PrunedRecent,
+    StoppedReplaying,
+    SingleSeedOnly,
+    DivergedRatio,
+    DivergedVertices,
+    AddVertexHere,
+    RemoveNearestVertex,
+    StartConvergenceHere,
+    CopyCoordinatesHere,
+    WeightReadout,
+    PlacesAt,
+    GasketNeedsThree,
+    ToolPlace,
+    ToolEraser,
+    ToolSelect,
+    ToolMeasure,
+    RightClickMenu,
+    RightClickRemoveLast,
+    VertexMarkerFilledCircle,
+    VertexMarkerRing,
+    VertexMarkerCross,
+    VertexMarkerSquare,
+    PointColoringUniform,
+    PointColoringSector,
+    PointColoringSeed,
+    PointColoringForced,
+    PointColoringVertex,
+    PointColoringRecency,
+    PointColoringDensity,
+    PointShapeSquare,
+    PointShapeCircle,
+    PointShapeSoft,
+    RampViridis,
+    RampGrayscale,
+    RampFire,
+    CountScaleLinear,
+    CountScaleLog,
+    PresetTriangle,
+    PresetSquare,
+    PresetPentagon,
+    PresetHexagon,
+    ThemeLight,
+    ThemeDark,
+    BorderColorPoints,
+    BorderColorGrey,
+    BorderColorBlue,
+    BorderColorRed,
+    RuleVertex,
+    RuleMidpoint,
+    RestrictionNone,
+    RestrictionNoRepeat,
+    OrbitStartScattered,
+    OrbitStartVertices,
+    CurIterPolicyHold,
+    CurIterPolicyFollowMax,
+    CurIterPolicyProportional,
+    ParameterRatio,
+    ParameterRotation,
+    ParameterRestriction,
+    ParameterVertexCount,
+    ParameterSeed,
+    OutOfBoundsClip,
+    OutOfBoundsClamp,
+    OutOfBoundsRescale,
+    VertexAdded,
+    VertexRemoved,
+    VertexMoved,
+    SlotStored,
+    SlotChange,
+    SavedThinned,
+    SuggestedRun,
+    ExportTooLarge,
+}
@@ -0,0 +1,73 @@
+//! Choosing the layout of the controls for a window size. Nothing here
+//! changes what is drawn, only where and how large.
+
+/// Windows at least this wide keep the vertex list beside the canvas,
+/// narrower ones stack it below.
+pub const WIDE: u32 = 1200;
+
+/// The canvas is never shown smaller than this on its shorter side.
+pub const MIN_CANVAS: f32 = 300.0;
+
+/// Rough width of a control together with its label, for wrapping rows.
+const GROUP_WIDTH: u32 = 190;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    Narrow,
+    Wide,
+}
+
+impl Breakpoint {
+    /// The breakpoint for a window `width` pixels wide.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::layout::{Breakpoint, WIDE};
+    ///
+    /// assert_eq!(Breakpoint::for_width(640), Breakpoint::Narrow);
+    /// assert_eq!(Breakpoint::for_width(WIDE - 1), Breakpoint::Narrow);
+    /// assert_eq!(Breakpoint::for_width(WIDE), Breakpoint::Wide);
+    /// assert_eq!(Breakpoint::for_width(2560), Breakpoint::Wide);
+    /// ```
+    pub fn for_width(width: u32) -> Breakpoint {
+        if width >= WIDE {
+            Breakpoint::Wide
+        } else {
+            Breakpoint::Narrow
+        }
+    }
+}
+
+/// How many groups of controls fit on one row of a window `width` pixels
+/// wide, at least two so a label is never alone with nothing to label.
+///
+/// ```
+/// use sierpinski_triangle_emulator::layout::groups_per_row;
+///
+/// assert_eq!(groups_per_row(0), 2);
+/// assert_eq!(groups_per_row(640), 3);
+/// assert_eq!(groups_per_row(2560), 13);
+/// ```
+pub fn groups_per_row(width: u32) -> usize {
+    (width / GROUP_WIDTH).max(2) as usize
+}
+
+/// The factor to show a canvas of `size` at so it fits `available`: never
+/// larger than it is, and never so small that its shorter side drops below
+/// [`MIN_CANVAS`] (unless it is smaller than that to begin with).
+///
+/// ```
+/// use sierpinski_triangle_emulator::layout::canvas_scale;
+///
+/// assert_eq!(canvas_scale([600.0, 600.0], [2000.0, 1400.0]), 1.0);
+/// assert_eq!(canvas_scale([600.0, 600.0], [450.0, 1400.0]), 0.75);
+/// assert_eq!(canvas_scale([600.0, 600.0], [100.0, 1400.0]), 0.5);
+/// assert_eq!(canvas_scale([200.0, 200.0], [100.0, 100.0]), 1.0);
+/// ```
+pub fn canvas_scale(
+    [width, height]: [f32; 2],
+    [available_width, available_height]: [f32; 2],
+) -> f32 {
+    let fit = (available_width / width).min(available_height / height);
+    let floor = (MIN_CANVAS / width.min(height)).min(1.0);
+    fit.clamp(floor, 1.0)
+}
@@ -0,0 +1,36 @@
+//! The chaos game engine behind the Sierpinski Triangle Emulator.
+//!
+//! [`ChaosGame`] configures a game and builds [`Orbit`]s, plain iterators of
+//! points that know nothing about iced and can be driven from any thread.
+//! [`gasket`] has the deterministic construction of the Sierpinski triangle,
+//! [`bounds`] deals with points that leave the canvas and [`geometry`]
+//! measures the polygon of the vertices. [`numeric`] validates numbers typed
+//! into text inputs, [`render`] rasterizes points for exports, [`svg`]
+//! writes them as vector graphics and [`gallery`] lays out contact sheets of
+//! a parameter stepped from tile to tile. [`rng`] has the random number
+//! generators orbits can draw from. [`layout`] picks how the controls
+//! fit a window, [`sequence`] stores the vertex picks of a run, [`stats`]
+//! sums it up, [`history`] keeps the states to undo to and [`diff`] finds
+//! what changed between two of them saved as JSON. [`i18n`] has the strings
+//! of the interface in every bundled language. Whatever can fail returns an
+//! [`Error`].
+
+pub mod bounds;
+mod chaos;
+pub mod diff;
+mod error;
+pub mod gallery;
+pub mod gasket;
+pub mod geometry;
+pub mod history;
+pub mod i18n;
+pub mod layout;
+pub mod numeric;
+pub mod render;
+pub mod rng;
+pub mod sequence;
+pub mod stats;
+pub mod svg;
+
+pub use chaos::{anchored_target, ChaosGame, Orbit, Restriction, Rule, Selection, Step};
+pub use error::Error;
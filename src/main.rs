@@ -1,34 +1,1330 @@
-use std::{fmt::Debug, ops::Not};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{self, Debug, Display},
+    mem::{self, Discriminant},
+    ops::Not,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use iced::{
-    canvas::{event, Cache, Event, Path, Program, Stroke},
-    executor, slider, Application, Canvas, Color, Column, Command, Length, Point, Row, Settings,
-    Size, Slider, Text,
+    button,
+    canvas::{self, event, Cache, Event, Frame, Path, Program, Stroke},
+    executor, pick_list, scrollable, slider, text_input, time, tooltip, Application, Button,
+    Canvas, Checkbox, Color, Column, Command, Length, PickList, Point, ProgressBar, Radio,
+    Rectangle, Row, Scrollable, Settings, Size, Slider, Subscription, Text, TextInput, Tooltip,
+    Vector,
+};
+
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    rngs::StdRng,
+    Rng, SeedableRng,
+};
+use serde::{Deserialize, Serialize};
+use sierpinski_triangle_emulator::{
+    bounds::{self, Viewport},
+    diff::{self, Change},
+    gallery::{Parameter, Sheet},
+    gasket, geometry,
+    history::History,
+    i18n::{Key, Language},
+    layout::{self, Breakpoint},
+    numeric::{self, Fixed},
+    render::{self, Accumulation, CountScale, DensityGrid, PointGrid, PointShape, RenderStyle},
+    rng::Generator,
+    sequence::Sequence,
+    stats::{self, Report},
+    svg, ChaosGame, Error, Orbit, Restriction, Rule, Selection,
+};
+
+mod bar;
+mod batch;
+mod config;
+mod export;
+mod palette;
+mod preset;
+mod press;
+mod recent;
+mod recovery;
+mod script;
+mod session;
+mod thumbnail;
+mod transition;
+mod tutorial;
+mod wheel;
+
+use bar::Bar;
+use batch::Batch;
+use config::{Config, CurIterPolicy, RightClick};
+use export::{PointCloud, Raster};
+use palette::{Ramp, Theme, FIX_POINT_COLOR};
+use preset::{Circle, Preset};
+use press::Press;
+use recent::Recent;
+use script::Step;
+use session::Session;
+use session::Slot;
+use thumbnail::{Preview, Thumbnail};
+use transition::Transition;
+use wheel::Wheel;
+
+/// Upper bound of the iteration sliders, unless points were added beyond it.
+const MAX_ITER: i32 = 10000;
+
+/// Upper bound of the iterations reachable by adding points.
+const MAX_EXTENDED_ITER: i32 = 10_000_000;
+
+/// Points generated per tick while adding points, so the UI keeps
+/// responding and can show progress.
+const EXTEND_CHUNK: usize = 20_000;
+
+/// With at least this many points, rule edits from dragging a slider wait
+/// for [`DEBOUNCE`] without further edits before regenerating. Smaller
+/// sessions regenerate on every edit.
+const DEBOUNCE_MIN_POINTS: usize = 20_000;
+const DEBOUNCE: Duration = Duration::from_millis(80);
+
+/// A clock the app needs running: how often it ticks and what each tick
+/// sends.
+type Timer = (Duration, fn(Instant) -> Message);
+
+/// How many points the newest layer of the canvas holds at most before it
+/// is merged into the layer below.
+const TAIL_LAYER_LEN: usize = 2000;
+
+/// The most points a ghost keeps, every n-th point is kept beyond that.
+const MAX_GHOST_POINTS: usize = 100_000;
+
+/// The factors PNG exports can be rendered larger by before shrinking them
+/// back, for anti-aliasing.
+const SUPERSAMPLE: [u8; 3] = [1, 2, 4];
+
+/// The most points an SVG export may hold, to choose from. Longer runs are
+/// thinned evenly to stay under it.
+const SVG_BUDGETS: [usize; 5] = [10_000, 50_000, 100_000, 500_000, 1_000_000];
+
+/// Iterations added per batch while refining when idle, to choose from.
+const REFINE_BATCHES: [usize; 3] = [1_000, 5_000, 20_000];
+
+/// How long without input before refining starts.
+const IDLE: Duration = Duration::from_secs(1);
+
+/// Refining stops at this many iterations, so a rule edit after a long
+/// pause doesn't have to regenerate without end.
+const REFINE_LIMIT: i32 = 200_000;
+
+/// Ticks of 16 ms playback takes from the first iteration to the max.
+const PLAY_TICKS: i32 = 240;
+
+/// The max iteration placing the third vertex suggests, played up to in
+/// [`SUGGESTION_TICKS`] (about a second).
+const SUGGESTED_ITER: i32 = 5_000;
+const SUGGESTION_TICKS: i32 = 60;
+
+/// Degrees a new vertex snaps to about the last one with Shift held.
+const SNAP_ANGLE: f32 = 15.0;
+
+/// Tiles and points per tile a gallery can have.
+const GALLERY_TILES: [usize; 4] = [4, 9, 16, 25];
+const GALLERY_ITERATIONS: [usize; 4] = [5_000, 20_000, 50_000, 200_000];
+/// The contact sheet the gallery starts out with, in the window and on the
+/// command line.
+const GALLERY: Sheet = Sheet {
+    parameter: Parameter::Ratio,
+    range: None,
+    tiles: 16,
+    iterations: 20_000,
+    tile_size: 200,
 };
 
-use rand::Rng;
+/// Loaded vertices closer than this many pixels to an earlier one count as
+/// duplicates.
+const DUPLICATE_TOLERANCE: f32 = 1.0;
+
+/// Typed ratios must lie strictly between zero and this. Ratios above one
+/// push the points away from the vertices, off the canvas sooner or later.
+const MAX_RATIO: f32 = 1.5;
+
+/// How far the ratio buttons move the ratio, the larger step with Shift.
+const RATIO_STEP: f32 = 0.005;
+const RATIO_SHIFT_STEP: f32 = 0.05;
+
+/// Segments every hull edge is split into before mapping it.
+const MAP_EDGE_STEPS: usize = 12;
+
+/// The narrowest the iteration sliders get, however narrow the window.
+const MIN_SLIDER_WIDTH: u32 = 160;
+
+/// The names of the parameter slots.
+const SLOT_NAMES: [&str; 2] = ["A", "B"];
+
+/// The width of the chord of the most frequent transition between vertices.
+const TRANSITION_WIDTH: f32 = 4.0;
+
+/// Length of the dashes and gaps between a vertex and its anchor.
+const ANCHOR_DASH: f32 = 4.0;
+
+/// Upper bound of the number of orbits generated side by side.
+const MAX_SEEDS: u8 = 8;
+
+/// Upper bound and starting value of the balanced sampling quota, in first
+/// picks per [`ChaosGame::QUOTA_ROUND`] steps.
+const MAX_QUOTA: u32 = 300;
+const DEFAULT_QUOTA: u32 = 50;
+
+/// Radius of the ring around each fix point showing its share of the picks.
+/// Dragging within [`WEIGHT_RING_REACH`] of it changes the weight.
+const WEIGHT_RING_RADIUS: f32 = 13.0;
+const WEIGHT_RING_REACH: f32 = 4.0;
+
+/// Pressing this close to a vertex with the place tool drags it rather than
+/// adding another, well inside its weight ring.
+const VERTEX_GRAB_RADIUS: f32 = 6.0;
+
+/// How much the weight changes per pixel dragged up on a weight ring.
+const WEIGHT_PER_PIXEL: f32 = 0.02;
+
+/// How many points the convergence demo follows from its start.
+const CONVERGENCE_POINTS: usize = 50;
+
+/// Beyond this many screen pixels per canvas pixel, snapping points to
+/// pixels would visibly move them, so it stops.
+const PIXEL_SNAP_MAX_SCALE: f32 = 2.0;
+
+/// Largest side of a point, in pixels.
+const MAX_POINT_SIZE: u8 = 6;
+
+/// Side of the cells points are counted in when sizing them by density.
+const DENSITY_CELL: f32 = 8.0;
+
+/// The decimal places the coordinates and lengths can be shown with.
+const DECIMALS: [usize; numeric::MAX_PLACES + 1] = [0, 1, 2, 3, 4];
+
+/// Side of the cells points are counted in for colouring them by density.
+const HEAT_CELL: f32 = 2.0;
+
+/// The percentiles the densest colour of the heatmap can be clipped at.
+const CLIP_PERCENTILES: std::ops::RangeInclusive<f32> = 50.0..=100.0;
+
+/// Radius of the loupe on screen, and the gap between it and the corner of
+/// the canvas, in pixels.
+const LOUPE_RADIUS: f32 = 80.0;
+const LOUPE_GAP: f32 = 10.0;
+
+/// The magnifications the loupe steps through with the mouse wheel.
+const LOUPE_ZOOMS: std::ops::RangeInclusive<f32> = 2.0..=8.0;
+
+/// Side of the cells points are filed in to find those under the loupe.
+const LOUPE_CELL: f32 = 16.0;
+
+/// Width and row height of the canvas context menu, in screen pixels.
+const MENU_WIDTH: f32 = 220.0;
+const MENU_ROW: f32 = 24.0;
+
+/// The canvas size accepted from `--width` and `--height`, in pixels.
+const CANVAS_SIZES: std::ops::RangeInclusive<u32> = 200..=4096;
+/// The widest and tallest a PNG export is rasterized, supersampling
+/// included. The largest canvas at the largest scale and supersampling
+/// would be 16 GiB.
+const MAX_RASTER_SIDE: f32 = 16_384.0;
+
+/// Set to a count to write that many points of every run to stderr, as
+/// `--debug-orbit` does.
+const DEBUG_ORBIT: &str = "SIERPINSKI_DEBUG_ORBIT";
+
+/// A font file to draw the labels with when Chinese is picked, ahead of
+/// [`CJK_FONTS`].
+const FONT: &str = "SIERPINSKI_FONT";
+
+/// Where the usual system fonts with Chinese glyphs are, tried in order.
+const CJK_FONTS: [&str; 7] = [
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/System/Library/Fonts/PingFang.ttc",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "C:\\Windows\\Fonts\\msyh.ttc",
+];
+
+/// Room left around the attractor by auto-frame, in canvas pixels.
+const AUTO_FRAME_MARGIN: f32 = 20.0;
+
+/// Room around the canvas in the window for the vertex list beside it and
+/// the first rows of controls.
+const WINDOW_MARGIN: (u32, u32) = (560, 320);
+
+const VERTEX_LIST_WIDTH: u16 = 480;
+
+/// Width of the window not available to the canvas and the vertex list,
+/// for the scroll bar and some air.
+const WINDOW_PADDING: f32 = 40.0;
 
 fn main() -> iced::Result {
-    SierpinskiEmulator::run(Settings {
-        antialiasing: true,
+    let mut flags = Flags::default();
+    let mut canvas = [600, 600];
+    for (i, variable) in ["SIERPINSKI_WIDTH", "SIERPINSKI_HEIGHT"].iter().enumerate() {
+        if let Ok(value) = std::env::var(variable) {
+            canvas[i] = parse_canvas_size(variable, &value);
+        }
+    }
+    if let Ok(value) = std::env::var(DEBUG_ORBIT) {
+        match numeric::parse_count(&value) {
+            Ok(n) => flags.debug_orbit = n,
+            Err(e) => exit_with_usage(&format!("{}: {}", DEBUG_ORBIT, e)),
+        }
+    }
+    let mut gallery = None;
+    let mut sheet = GALLERY;
+    let (mut batch, mut out_dir, mut iterations) = (None, None, None);
+    let (mut from, mut to) = (String::new(), String::new());
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--width" | "--height" => match args.next() {
+                Some(value) => {
+                    canvas[(arg == "--height") as usize] = parse_canvas_size(&arg, &value)
+                }
+                None => exit_with_usage(&format!("{} needs a number of pixels", arg)),
+            },
+            "--script" => match args.next() {
+                Some(path) => flags.script = Some(PathBuf::from(path)),
+                None => exit_with_usage("--script needs a file"),
+            },
+            "--session" => match args.next() {
+                Some(path) => flags.session = Some(PathBuf::from(path)),
+                None => exit_with_usage("--session needs a file"),
+            },
+            "--gallery" => match args.next() {
+                Some(path) => gallery = Some(PathBuf::from(path)),
+                None => exit_with_usage("--gallery needs a file"),
+            },
+            "--batch" => match args.next() {
+                Some(path) => batch = Some(PathBuf::from(path)),
+                None => exit_with_usage("--batch needs a directory"),
+            },
+            "--out-dir" => match args.next() {
+                Some(path) => out_dir = Some(PathBuf::from(path)),
+                None => exit_with_usage("--out-dir needs a directory"),
+            },
+            "--iters" => match args.next().map(|v| numeric::parse_count(&v)) {
+                Some(Ok(n)) => iterations = Some(n),
+                Some(Err(e)) => exit_with_usage(&format!("--iters: {}", e)),
+                None => exit_with_usage("--iters needs a count"),
+            },
+            "--debug-orbit" => match args.next().map(|v| numeric::parse_count(&v)) {
+                Some(Ok(n)) => flags.debug_orbit = n,
+                Some(Err(e)) => exit_with_usage(&format!("--debug-orbit: {}", e)),
+                None => exit_with_usage("--debug-orbit needs a count"),
+            },
+            "--vary" => {
+                let value = args.next().unwrap_or_default();
+                match Parameter::ALL.iter().find(|p| p.to_string() == value) {
+                    Some(parameter) => sheet.parameter = *parameter,
+                    None => exit_with_usage(
+                        "--vary needs ratio, rotation, restriction, vertices or seed",
+                    ),
+                }
+            }
+            "--from" | "--to" => match args.next() {
+                Some(value) if arg == "--from" => from = value,
+                Some(value) => to = value,
+                None => exit_with_usage(&format!("{} needs a value", arg)),
+            },
+            "--tiles" | "--tile-iter" => match args.next().map(|v| numeric::parse_count(&v)) {
+                Some(Ok(n)) if n > 0 => match arg.as_str() {
+                    "--tiles" => sheet.tiles = n,
+                    _ => sheet.iterations = n,
+                },
+                Some(Err(e)) => exit_with_usage(&format!("{}: {}", arg, e)),
+                _ => exit_with_usage(&format!("{} needs a positive count", arg)),
+            },
+            _ => exit_with_usage(&format!("unknown argument `{}`", arg)),
+        }
+    }
+
+    match gallery_range(sheet.parameter, &from, &to) {
+        Ok(range) => sheet.range = range,
+        Err(e) => exit_with_usage(&format!("--from/--to: {}", e)),
+    }
+    if (from.is_empty() && to.is_empty()).not() && sheet.range.is_none() {
+        exit_with_usage(&format!(
+            "--from and --to don't apply to varying {}",
+            sheet.parameter
+        ));
+    }
+    match (&batch, flags.session.is_some() || gallery.is_some()) {
+        (Some(_), true) => exit_with_usage("--batch renders its own sessions, without a --session"),
+        (None, _) if out_dir.is_some() || iterations.is_some() => {
+            exit_with_usage("--out-dir and --iters only apply to --batch")
+        }
+        _ => {}
+    }
+
+    // Broken files given on the command line fail before the window opens,
+    // so whatever started the emulator can tell from the exit code.
+    if let Some(path) = &flags.session {
+        if let Err(e) = Session::load(path) {
+            exit_with_error(path, &e);
+        }
+    }
+    if let Some(path) = &flags.script {
+        if let Err(e) = script::load(path) {
+            exit_with_error(path, &e);
+        }
+    }
+
+    // A batch and a gallery render without opening the window at all.
+    if let Some(dir) = batch {
+        std::process::exit(run_batch(&Batch {
+            dir,
+            out_dir,
+            iterations,
+            canvas,
+        }));
+    }
+    if let Some(out) = &gallery {
+        let session = match &flags.session {
+            Some(path) => Session::load(path).unwrap_or_else(|e| exit_with_error(path, &e)),
+            None => exit_with_usage("--gallery needs a --session to lay out"),
+        };
+        let style = session.style();
+        let tiles = sheet.variations().len();
+        let canvas = [canvas[0] as f32, canvas[1] as f32];
+        match write_gallery(out, &sheet, &session.game(), canvas, &style, |n| {
+            eprintln!("rendered tile {} of {}", n, tiles)
+        }) {
+            Ok(()) => return Ok(()),
+            Err(e) => exit_with_error(out, &e),
+        }
+    }
+
+    flags.canvas = Size::new(canvas[0] as f32, canvas[1] as f32);
+    flags.window = (canvas[0] + WINDOW_MARGIN.0, canvas[1] + WINDOW_MARGIN.1);
+    run(flags)
+}
+
+/// The first font of [`FONT`] and [`CJK_FONTS`] that can be read, kept for
+/// the rest of the run.
+fn cjk_font() -> Option<&'static [u8]> {
+    let font = std::env::var_os(FONT).map(PathBuf::from);
+    font.into_iter()
+        .chain(CJK_FONTS.iter().map(PathBuf::from))
+        .find_map(|path| std::fs::read(path).ok())
+        .map(|bytes| &*Box::leak(bytes.into_boxed_slice()))
+}
+
+/// Opens the window, falling back to less demanding renderers when no
+/// graphics adapter supports the one asked for.
+fn run(mut flags: Flags) -> iced::Result {
+    let window = flags.window;
+    // The default font has no Chinese glyphs and the renderer only takes
+    // one font, so it's swapped for the whole run.
+    let default_font = match Config::load().language {
+        Language::English => None,
+        Language::Chinese => cjk_font(),
+    };
+    flags.cjk_font = default_font.is_some();
+    let settings = |antialiasing, flags| Settings {
+        default_font,
+        antialiasing,
+        window: iced::window::Settings {
+            size: window,
+            ..iced::window::Settings::default()
+        },
+        flags,
+        // The recovery file is removed on the way out, see
+        // `Message::CloseRequested`.
+        exit_on_close_request: false,
         ..Settings::default()
-    })
+    };
+    // The renderer looks for a graphics adapter before the window exists,
+    // so running again after it found none is safe. Without antialiasing
+    // it settles for a low power adapter, and the GL backend also runs in
+    // software under Mesa.
+    match SierpinskiEmulator::run(settings(true, flags.clone())) {
+        Err(iced::Error::GraphicsAdapterNotFound) => {}
+        result => return result,
+    }
+    eprintln!("no graphics adapter supports antialiasing, retrying without it");
+    flags.degraded = Some(Key::NoAntialiasing);
+    match SierpinskiEmulator::run(settings(false, flags.clone())) {
+        Err(iced::Error::GraphicsAdapterNotFound) if std::env::var_os("WGPU_BACKEND").is_none() => {
+        }
+        result => return result,
+    }
+    eprintln!("no graphics adapter found, retrying with the GL backend");
+    std::env::set_var("WGPU_BACKEND", "gl");
+    flags.degraded = Some(Key::GlFallback);
+    SierpinskiEmulator::run(settings(false, flags))
+}
+
+fn parse_canvas_size(name: &str, value: &str) -> u32 {
+    match numeric::parse_count(value).map(canvas_size) {
+        Ok(Some(size)) => size,
+        Ok(None) => exit_with_usage(&format!(
+            "{} must be between {} and {} pixels",
+            name,
+            CANVAS_SIZES.start(),
+            CANVAS_SIZES.end()
+        )),
+        Err(e) => exit_with_usage(&format!("{}: {}", name, e)),
+    }
+}
+
+/// `size` if it is one of [`CANVAS_SIZES`]. Sizes past `u32` are out of
+/// range too, rather than wrapping into it.
+fn canvas_size(size: usize) -> Option<u32> {
+    u32::try_from(size)
+        .ok()
+        .filter(|size| CANVAS_SIZES.contains(size))
+}
+
+fn exit_with_usage(error: &str) -> ! {
+    eprintln!("error: {}", error);
+    eprintln!(
+        "usage: sierpinski-triangle-emulator [--session <file>] [--script <file>] \
+         [--width <px>] [--height <px>] [--debug-orbit <n>]"
+    );
+    eprintln!(
+        "       sierpinski-triangle-emulator --session <file> --gallery <file.png> \
+         [--vary ratio|rotation|restriction|vertices|seed] [--from <v>] [--to <v>] \
+         [--tiles <n>] [--tile-iter <n>]"
+    );
+    eprintln!(
+        "       sierpinski-triangle-emulator --batch <dir> [--out-dir <dir>] [--iters <n>] \
+         [--width <px>] [--height <px>]"
+    );
+    eprintln!("SIERPINSKI_WIDTH and SIERPINSKI_HEIGHT set the canvas size too, the flags win");
+    eprintln!("{} does what --debug-orbit does", DEBUG_ORBIT);
+    eprintln!(
+        "{} names a font with Chinese glyphs for the Chinese labels",
+        FONT
+    );
+    std::process::exit(2)
+}
+
+/// Renders the contact sheet of `game` and saves it as a PNG at `path`,
+/// with the tile values in its metadata.
+fn write_gallery(
+    path: &std::path::Path,
+    sheet: &Sheet,
+    game: &ChaosGame,
+    canvas: [f32; 2],
+    style: &RenderStyle,
+    progress: impl FnMut(usize),
+) -> Result<(), Error> {
+    let image = sheet.render(game, canvas, style, progress)?;
+    let fix_points = game
+        .vertices()
+        .iter()
+        .map(|[x, y]| format!("{},{}", x, y))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let tiles = sheet
+        .variations()
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    Raster::from(image).write_png(
+        path,
+        &[
+            ("Software", "Sierpinski Triangle Emulator".to_string()),
+            ("FixPoints", fix_points),
+            ("Gallery", sheet.parameter.to_string()),
+            ("Tiles", tiles),
+            ("TileIter", sheet.iterations.to_string()),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Renders `batch`, telling about every file as it finishes and summing up
+/// at the end. The exit code is 1 if any file failed. Only a directory
+/// that can't be read or created stops the batch.
+fn run_batch(batch: &Batch) -> i32 {
+    let sessions = batch
+        .sessions()
+        .unwrap_or_else(|e| exit_with_error(&batch.dir, &e.into()));
+    if let Some(dir) = &batch.out_dir {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            exit_with_error(dir, &e.into());
+        }
+    }
+    let results = batch.run(&sessions, |session, result| match result {
+        Ok(image) => eprintln!("rendered {} to {}", session.display(), image.display()),
+        Err(e) => eprintln!("failed {}: {}", session.display(), e),
+    });
+    let failed = sessions
+        .iter()
+        .zip(&results)
+        .filter_map(|(session, result)| result.as_ref().err().map(|e| (session, e)))
+        .collect::<Vec<_>>();
+    println!(
+        "{} succeeded, {} failed",
+        results.len() - failed.len(),
+        failed.len()
+    );
+    for (session, e) in &failed {
+        println!("  {}: {}", session.display(), e);
+    }
+    failed.is_empty().not() as i32
+}
+
+/// The range a gallery of `parameter` steps through from the ends as typed,
+/// falling back to the parameter's own for an empty one. `None` when both
+/// are empty.
+fn gallery_range(parameter: Parameter, from: &str, to: &str) -> Result<Option<(f32, f32)>, String> {
+    let (low, high) = match parameter.range() {
+        Some(range) if (from.trim().is_empty() && to.trim().is_empty()).not() => range,
+        _ => return Ok(None),
+    };
+    let end = |text: &str, default: f32| match text.trim() {
+        "" => Ok(default),
+        text => numeric::parse_decimal(text),
+    };
+    Ok(Some((end(from, low)?, end(to, high)?)))
+}
+
+/// A line of the changes list in `language`, with vertices by their labels.
+fn describe_change(change: &Change, language: Language) -> String {
+    let vertex = change
+        .path()
+        .strip_prefix("fix_points[")
+        .and_then(|rest| rest.strip_suffix(']'))
+        .and_then(|i| i.parse::<usize>().ok());
+    let at = |value: &serde_json::Value| {
+        let coordinates = value
+            .as_array()
+            .map(|xy| xy.iter().filter_map(|v| v.as_f64()).collect::<Vec<_>>());
+        match coordinates.as_deref() {
+            Some([x, y]) => format!("{},{}", x, y),
+            _ => value.to_string(),
+        }
+    };
+    match (vertex, change) {
+        (Some(i), Change::Added { value, .. }) => {
+            language.fill(Key::VertexAdded, &[&vertex_label(i), &at(value)])
+        }
+        (Some(i), Change::Removed { value, .. }) => {
+            language.fill(Key::VertexRemoved, &[&vertex_label(i), &at(value)])
+        }
+        (Some(i), Change::Changed { from, to, .. }) => {
+            language.fill(Key::VertexMoved, &[&vertex_label(i), &at(from), &at(to)])
+        }
+        _ => {
+            let slot = (0..SLOT_NAMES.len()).find_map(|i| {
+                let prefix = format!("slots[{}]", i);
+                change.path().starts_with(&prefix).then_some((i, prefix))
+            });
+            match slot {
+                Some((i, prefix)) if change.path() == prefix => {
+                    language.fill(Key::SlotStored, &[&SLOT_NAMES[i]])
+                }
+                Some((i, prefix)) => change
+                    .to_string()
+                    .replacen(
+                        &format!("{}.", prefix),
+                        &language.fill(Key::SlotChange, &[&SLOT_NAMES[i]]),
+                        1,
+                    )
+                    .replace('_', " "),
+                None => change.to_string().replace('_', " "),
+            }
+        }
+    }
+}
+
+/// Exits with the [`exit_code`] of `error` in the file at `path`.
+fn exit_with_error(path: &std::path::Path, error: &Error) -> ! {
+    eprintln!("error: {}: {}", path.display(), error);
+    std::process::exit(exit_code(error))
+}
+
+/// The `sysexits.h` code for `error`.
+fn exit_code(error: &Error) -> i32 {
+    match error {
+        Error::Io(_) => 74,
+        Error::InvalidConfig(_) => 78,
+        Error::Parse { .. }
+        | Error::TooManyPoints { .. }
+        | Error::DegenerateVertices { .. }
+        | Error::Sequence(_) => 65,
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Flags {
+    /// Script to run once the window is up.
+    script: Option<PathBuf>,
+    /// Session to load on startup instead of offering the recent ones.
+    session: Option<PathBuf>,
+    canvas: Size<f32>,
+    /// The initial window size.
+    window: (u32, u32),
+    /// Why the renderer runs with less than asked for, if it does.
+    degraded: Option<Key>,
+    /// How many points of every run are written to stderr.
+    debug_orbit: usize,
+    /// Whether the labels are drawn with a font that has Chinese glyphs.
+    cjk_font: bool,
+}
+
+impl Default for Flags {
+    fn default() -> Flags {
+        Flags {
+            script: None,
+            session: None,
+            canvas: Size::new(600.0, 600.0),
+            window: (600 + WINDOW_MARGIN.0, 600 + WINDOW_MARGIN.1),
+            degraded: None,
+            debug_orbit: 0,
+            cjk_font: false,
+        }
+    }
 }
 
 #[derive(Debug)]
 struct SierpinskiEmulator {
     graph: SierpinskiGraph,
     max_iter_state: slider::State,
+    max_iter_wheel: wheel::State,
     cur_iter_state: slider::State,
+    cur_iter_wheel: wheel::State,
+    cur_fraction_state: slider::State,
+    eraser_radius_state: slider::State,
+    clear_mask_state: button::State,
+    export_selection_state: button::State,
+    copy_selection_state: button::State,
+    vertex_marker_state: pick_list::State<Labelled<VertexMarker>>,
+    point_coloring_state: pick_list::State<Labelled<PointColoring>>,
+    ramp_state: pick_list::State<Labelled<Ramp>>,
+    count_scale_state: pick_list::State<Labelled<CountScale>>,
+    clip_percentile_state: slider::State,
+    out_of_bounds_state: pick_list::State<Labelled<OutOfBounds>>,
+    cur_iter_policy_state: pick_list::State<Labelled<CurIterPolicy>>,
+    right_click_state: pick_list::State<Labelled<RightClick>>,
+    decimals_state: pick_list::State<usize>,
+    language_state: pick_list::State<Language>,
+    theme_state: pick_list::State<Labelled<Theme>>,
+    background_states: [slider::State; 3],
+    border_width_state: slider::State,
+    border_color_state: pick_list::State<Labelled<BorderColor>>,
+    border_margin_state: slider::State,
+    sector_count_state: slider::State,
+    preset_state: pick_list::State<Labelled<Preset>>,
+    circle_states: [slider::State; 3],
+    ratio_state: slider::State,
+    ratio_wheel: wheel::State,
+    ratio_minus_state: button::State,
+    ratio_plus_state: button::State,
+    ratio_input_state: text_input::State,
+    rule_state: pick_list::State<Labelled<Rule>>,
+    restriction_state: pick_list::State<Labelled<Restriction>>,
+    sequence_input_state: text_input::State,
+    rotation_state: slider::State,
+    jitter_state: slider::State,
+    reseed_state: button::State,
+    seed_count_state: slider::State,
+    quota_state: slider::State,
+    orbit_start_state: pick_list::State<Labelled<OrbitStart>>,
+    generator_state: pick_list::State<Generator>,
+    anchor_blend_state: slider::State,
+    clear_anchors_state: button::State,
+    apply_rules_state: button::State,
+    pin_ghost_state: button::State,
+    clear_ghost_state: button::State,
+    fit_state: button::State,
+    add_centroid_state: button::State,
+    subdivision_level_state: slider::State,
+    export_gasket_state: button::State,
+    play_state: button::State,
+    replay_state: button::State,
+    replay_speed_state: slider::State,
+    replay_trail_state: slider::State,
+    capture_dir_state: text_input::State,
+    point_cloud_state: pick_list::State<PointCloud>,
+    supersample_state: pick_list::State<u8>,
+    svg_budget_state: pick_list::State<usize>,
+    refine_batch_state: pick_list::State<usize>,
+    point_shape_state: pick_list::State<Labelled<PointShape>>,
+    point_size_state: slider::State,
+    export_svg_state: button::State,
+    gallery_parameter_state: pick_list::State<Labelled<Parameter>>,
+    gallery_tiles_state: pick_list::State<usize>,
+    gallery_iterations_state: pick_list::State<usize>,
+    gallery_from_state: text_input::State,
+    gallery_to_state: text_input::State,
+    gallery_state: button::State,
+    export_points_state: button::State,
+    export_stats_state: button::State,
+    session_path_state: text_input::State,
+    weight_states: Vec<slider::State>,
+    scroll_state: scrollable::State,
+    script_path_state: text_input::State,
+    load_script_state: button::State,
+    choices_path_state: text_input::State,
+    export_choices_state: button::State,
+    import_choices_state: button::State,
+    stop_replaying_state: button::State,
+    undo_state: button::State,
+    redo_state: button::State,
+    recent_states: Vec<button::State>,
+    vertex_row_states: Vec<VertexRowState>,
+    start_empty_state: button::State,
+    save_session_state: button::State,
+    extend_input_state: text_input::State,
+    extend_state: button::State,
+    load_session_state: button::State,
+    preset: Option<Preset>,
+    circle: Circle,
+    /// The theme the colours were last set from, until edited by hand.
+    theme: Option<Theme>,
+    /// User preferences shared by every session.
+    config: Config,
+    /// See [`Flags::cjk_font`].
+    cjk_font: bool,
+    /// The window size the layout is chosen for.
+    window: (u32, u32),
+    history: History<Snapshot, UndoKind>,
+    transition: Option<Transition>,
+    /// Points being added beyond the max iteration, a chunk per tick.
+    extension: Option<Extension>,
+    extend_text: String,
+    /// Move `cur_iter` to the new end once points have been added.
+    advance_on_extend: bool,
+    /// Keep adding points in batches of `refine_batch` iterations while
+    /// there is no input, up to [`REFINE_LIMIT`].
+    refine_when_idle: bool,
+    refine_batch: usize,
+    /// When the last message came that wasn't from a timer.
+    last_input: Instant,
+    /// Whether the last refinement tick added points.
+    refining: bool,
+    playing: bool,
+    /// Whether playback runs in [`SUGGESTION_TICKS`] rather than
+    /// [`PLAY_TICKS`], for the suggested first run.
+    quick_play: bool,
+    /// Whether placing the third vertex may still suggest a first run. Off
+    /// once it has, or once the iteration controls have been used.
+    suggestion_pending: bool,
+    capture_on_complete: bool,
+    capture_dir: String,
+    point_cloud: PointCloud,
+    svg_budget: usize,
+    /// The contact sheet the gallery button renders, and the one rendering.
+    gallery: Sheet,
+    /// The ends of the range the gallery steps through as typed, the
+    /// parameter's own where empty.
+    gallery_from: String,
+    gallery_to: String,
+    gallery_job: Option<GalleryJob>,
+    /// Keep `cur_iter` equal to `max_iter`, whichever slider moves.
+    link_sliders: bool,
+    /// Index the next placed vertex is inserted at.
+    insert_at: Option<usize>,
+    /// What is typed into the coordinate entry, `None` while it is closed.
+    coordinate_text: Option<String>,
+    coordinate_input_state: text_input::State,
+    /// `cur_iter` to restore once the peek at `max_iter` ends.
+    peek_restore: Option<i32>,
+    /// Percentage of `max_iter` that `cur_iter` follows, from the last use
+    /// of the percentage slider until `cur_iter` is set directly.
+    cur_fraction: Option<f32>,
+    crop_to_selection: bool,
+    session_path: String,
+    script_path: String,
+    /// Where vertex choices are exported to and imported from.
+    choices_path: String,
+    /// Steps of the running script that have not been performed yet.
+    script: VecDeque<Step>,
+    script_delay: Duration,
+    /// Regenerate as soon as a rule changes; otherwise edits are staged in
+    /// `pending_rules` until applied.
+    auto_apply: bool,
+    pending_rules: Option<Rules>,
+    /// When to apply `pending_rules` with auto-apply on, pushed back by
+    /// every further edit.
+    apply_at: Option<Instant>,
+    /// Actions recorded since recording was switched on.
+    recording: Option<Vec<Step>>,
+    status: String,
+    recent: Recent,
+    /// Offer the recent sessions above the empty canvas, until one is
+    /// picked or dismissed.
+    show_recent: bool,
+    /// The session a run that didn't exit cleanly left behind, offered
+    /// until it is restored or discarded. Nothing autosaves meanwhile.
+    recovered: Option<Session>,
+    /// Set once the window asked to close, which ends the run.
+    exiting: bool,
+    /// The session as last autosaved, or as it was on startup.
+    autosaved: String,
+    /// The session as last saved or loaded, which the changes are listed
+    /// against.
+    saved: Option<String>,
+    show_changes: bool,
+    /// The parameter sets to flip between, see [`Slot`].
+    slots: [Option<Slot>; 2],
+    /// The slot last stored into or flipped to, which a flip leaves.
+    active_slot: Option<usize>,
+    store_slot_states: [button::State; 2],
+    flip_slot_state: button::State,
+    changes_state: button::State,
+    revert_state: button::State,
+    close_changes_state: button::State,
+    restore_state: button::State,
+    discard_recovery_state: button::State,
+    /// Previews of the recent sessions by path, made again when an entry's
+    /// timestamp moves on.
+    thumbnails: HashMap<PathBuf, Thumbnail>,
+    /// Drop near-duplicate vertices from loaded sessions rather than only
+    /// warning about them.
+    merge_duplicates: bool,
+    /// The ratio being typed, until it is submitted. The input shows the
+    /// current ratio otherwise.
+    ratio_text: Option<String>,
+    /// The manual sequence as typed, until it is submitted.
+    sequence_text: Option<String>,
+    /// The keyboard modifiers held, for buttons that step further with Shift.
+    modifiers: iced::keyboard::Modifiers,
+    /// The tutorial under way, watching the messages handled.
+    tutorial: Option<tutorial::Progress>,
+    tutorial_state: button::State,
+    reset_vertex_colors_state: button::State,
+    unlock_all_state: button::State,
+    skip_tutorial_step_state: button::State,
+    exit_tutorial_state: button::State,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     SetMaxIter(i32),
+    SetSuggestPoints(bool),
     SetCurIter(i32),
+    SetLinkSliders(bool),
+    /// Sets `cur_iter` to a percentage of `max_iter`, kept when the latter
+    /// changes.
+    SetCurFraction(f32),
+    /// Holding Space shows every generated point, releasing it restores the
+    /// current iteration.
+    Peek(bool),
     DrawCurIter(i32),
+    /// Adds a vertex, or inserts it where armed, and drops the generated
+    /// points. The third vertex of a fresh drawing starts a first run of
+    /// [`SUGGESTED_ITER`] iterations unless "suggest points" is off; otherwise
+    /// both iterations go back to 0.
     AddFixPoint(Point),
+    /// Moves vertex `index` to a point, dragged there on the canvas. Every
+    /// move between [`BeginGesture`](Message::BeginGesture) and
+    /// [`EndGesture`](Message::EndGesture) undoes as one.
+    MoveFixPoint(usize, Point),
+    /// Removes the last vertex.
     RemoveFixPoint,
+    SelectVertex(Option<usize>),
+    SetVertexEnabled(usize, bool),
+    SetVertexLocked(usize, bool),
+    /// Locks the selected vertex, or unlocks it (Ctrl+L).
+    ToggleSelectedLock,
+    UnlockAll,
+    DeleteVertex(usize),
+    /// The next click on the canvas inserts a vertex at this index instead of
+    /// appending one, `None` cancels.
+    ArmInsertion(Option<usize>),
+    /// Opens the canvas context menu, `None` dismisses it.
+    SetContextMenu(Option<ContextMenu>),
+    /// Puts `x,y` on the clipboard, as the coordinate entry takes it.
+    CopyCoordinates(Point),
+    SetRightClick(RightClick),
+    SetDecimals(usize),
+    SetLanguage(Language),
+    /// Opens a text input taking `x,y` to place a vertex without the mouse.
+    OpenCoordinateEntry,
+    SetCoordinateText(String),
+    SubmitCoordinates,
+    SelectTool(Tool),
+    SetEraserRadius(f32),
+    EraseAt(Point),
+    ClearMask,
+    SetSelection(Option<Rectangle>),
+    /// Sets measuring rectangle 0 or 1, dropping the ones after it.
+    SetMeasure(usize, Option<Rectangle>),
+    SetCropToSelection(bool),
+    ExportSelection,
+    CopySelection,
+    SetVertexMarker(VertexMarker),
+    SetPointColoring(PointColoring),
+    SetRamp(Ramp),
+    SetCountScale(CountScale),
+    SetClipPercentile(f32),
+    SelectTheme(Theme),
+    SetBackground(Color),
+    SetSectorCount(u8),
+    SetOutOfBounds(OutOfBounds),
+    SetAutoFrame(bool),
+    SetBorder(BorderStyle),
+    SelectPreset(Preset),
+    /// Places the vertices on a circle with these controls.
+    SetCircle(Circle),
+    SetRatio(f32),
+    /// Moves the ratio one step down (-1) or up (+1).
+    NudgeRatio(i32),
+    SetRatioText(String),
+    SubmitRatioText,
+    SetSequenceText(String),
+    SubmitSequenceText,
+    ModifiersChanged(iced::keyboard::Modifiers),
+    SetRule(Rule),
+    SetRestriction(Restriction),
+    SetRotation(f32),
+    SetJitter(f32),
+    /// Places or removes the anchor of a fix point.
+    SetAnchor(usize, Option<Point>),
+    ClearAnchors,
+    SetAnchorBlend(f32),
+    PinGhost,
+    ClearGhost,
+    SetGhostInExports(bool),
+    SetAutoApply(bool),
+    ApplyRules,
+    /// A rule slider or weight ring was let go: ends the drag's gesture and
+    /// regenerates now rather than after the debounce.
+    ReleaseRuleSlider,
+    DebounceTick(Instant),
+    Reseed,
+    SetSeedCount(u8),
+    SetOrbitStart(OrbitStart),
+    /// Gives a vertex the next colour of the palette.
+    CycleVertexColor(usize),
+    ResetVertexColors,
+    SetGenerator(Generator),
+    SetBalanced(bool),
+    SetQuota(u32),
+    FitToCanvas,
+    SetShowSubdivision(bool),
+    SetShowLastPick(bool),
+    SetDensitySizing(bool),
+    SetPointShape(PointShape),
+    SetPointSize(u8),
+    SetPixelSnap(bool),
+    SetShowMaps(bool),
+    SetShowCenters(bool),
+    SetShowTransitions(bool),
+    /// Appends the centroid of the vertices as another vertex.
+    AddCentroidVertex,
+    /// Shows the first points of an orbit from a start off the attractor.
+    SetShowConvergence(bool),
+    /// Moves where the convergence demo starts, `None` back to the centroid.
+    PinConvergenceStart(Option<Point>),
+    SetLoupe(bool),
+    /// Holding M shows the loupe until released.
+    HoldLoupe(bool),
+    SetLoupeZoom(f32),
+    SetSubdivisionLevel(u32),
+    /// Turns every animation on or off, finishing the running ones at once.
+    SetAnimations(bool),
+    SetCurIterPolicy(CurIterPolicy),
+    /// The window was resized to this width and height.
+    WindowResized(u32, u32),
+    /// The window is being closed. Only a crash leaves the recovery file
+    /// behind, or a recovered session nobody decided about.
+    CloseRequested,
+    TransitionTick(Instant),
+    SetExtendText(String),
+    /// Adds this many points beyond the max iteration.
+    ExtendBy(usize),
+    ExtendTick,
+    SetAdvanceOnExtend(bool),
+    /// Tells where a snapped click would place a vertex.
+    ShowSnap(String),
+    SetRefineWhenIdle(bool),
+    SetRefineBatch(usize),
+    /// Adds a batch of points if there has been no input for [`IDLE`].
+    RefineTick,
+    TogglePlayback,
+    /// Starts or stops following the orbit point by point.
+    ToggleReplay,
+    ReplayTick,
+    SetReplaySpeed(f32),
+    SetReplayTrail(u8),
+    Tick,
+    SetCaptureOnComplete(bool),
+    SetCaptureDir(String),
+    SetPointCloud(PointCloud),
+    SetSupersample(u8),
+    SetAdditiveExport(bool),
+    ExportPointCloud,
+    /// Writes the statistics of the visible points as JSON.
+    ExportStats,
+    SetSvgBudget(usize),
+    /// Saves the visible points as SVG squares, thinned to the budget.
+    ExportPointsSvg,
+    /// Saves the subdivision outlines as filled SVG triangles.
+    ExportGasketSvg,
+    SetGalleryParameter(Parameter),
+    SetGalleryTiles(usize),
+    SetGalleryIterations(usize),
+    SetGalleryFrom(String),
+    SetGalleryTo(String),
+    /// Starts rendering the gallery contact sheet on a thread of its own.
+    RenderGallery,
+    /// Checks on the gallery being rendered.
+    GalleryTick,
+    SetSessionPath(String),
+    SetMergeDuplicates(bool),
+    OpenRecent(PathBuf),
+    StartEmpty,
+    /// Writes the session to the recovery file if it changed.
+    AutoSave,
+    RestoreRecovery,
+    /// Shows or hides what changed since the last save or load.
+    ToggleChanges,
+    /// Goes back to the session as last saved or loaded.
+    RevertSession,
+    /// Stores the current parameters into slot A (0) or B (1).
+    StoreSlot(usize),
+    /// Switches to the parameters of the other slot.
+    FlipSlot,
+    DiscardRecovery,
+    StartTutorial,
+    SkipTutorialStep,
+    ExitTutorial,
+    SaveSession,
+    LoadSession,
+    SetVertexWeight(usize, f32),
+    ExportPng(PathBuf),
+    SetScriptPath(String),
+    LoadScript,
+    ScriptStep,
+    SetRecording(bool),
+    SetChoicesPath(String),
+    /// Saves the vertex picked for every point to `choices_path`.
+    ExportChoices,
+    /// Makes the points follow the picks in `choices_path`.
+    ImportChoices,
+    StopReplaying,
+    Undo,
+    Redo,
+    /// A drag started on a slider or weight ring. Its edits up to
+    /// [`EndGesture`](Message::EndGesture) undo in one step.
+    BeginGesture,
+    EndGesture,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Place,
+    Eraser,
+    Select,
+    /// Two rectangles around corresponding features, to compare their size.
+    Measure,
+}
+
+impl Tool {
+    const ALL: [Tool; 4] = [Tool::Place, Tool::Eraser, Tool::Select, Tool::Measure];
+}
+
+impl Display for Tool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tool::Place => write!(f, "place"),
+            Tool::Eraser => write!(f, "eraser"),
+            Tool::Select => write!(f, "select"),
+            Tool::Measure => write!(f, "measure"),
+        }
+    }
+}
+
+/// How fix points are rendered on the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VertexMarker {
+    FilledCircle,
+    Ring,
+    Cross,
+    Square,
+}
+
+impl VertexMarker {
+    const ALL: [VertexMarker; 4] = [
+        VertexMarker::FilledCircle,
+        VertexMarker::Ring,
+        VertexMarker::Cross,
+        VertexMarker::Square,
+    ];
+}
+
+impl Display for VertexMarker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VertexMarker::FilledCircle => write!(f, "filled circle"),
+            VertexMarker::Ring => write!(f, "ring"),
+            VertexMarker::Cross => write!(f, "cross"),
+            VertexMarker::Square => write!(f, "square"),
+        }
+    }
+}
+
+/// How generated points are coloured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PointColoring {
+    Uniform,
+    /// By angular sector around the centroid of the fix points.
+    Sector,
+    /// By the orbit that generated them, when several seeds run side by side.
+    Seed,
+    /// Points whose vertex balanced sampling forced tinted, the rest dimmed.
+    Forced,
+    /// By the vertex each point jumped towards, in the colour of its marker.
+    Vertex,
+    /// By how recent each point is along a ramp. Newer points are drawn
+    /// over older ones, so each pixel shows when the orbit last visited it.
+    Recency,
+    /// By how many visible points share its cell, as a heatmap along a
+    /// ramp.
+    Density,
+}
+
+impl PointColoring {
+    const ALL: [PointColoring; 7] = [
+        PointColoring::Uniform,
+        PointColoring::Sector,
+        PointColoring::Seed,
+        PointColoring::Forced,
+        PointColoring::Vertex,
+        PointColoring::Recency,
+        PointColoring::Density,
+    ];
+}
+
+impl Display for PointColoring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointColoring::Uniform => write!(f, "uniform"),
+            PointColoring::Sector => write!(f, "sector"),
+            PointColoring::Seed => write!(f, "by seed"),
+            PointColoring::Forced => write!(f, "quota-forced"),
+            PointColoring::Vertex => write!(f, "by vertex"),
+            PointColoring::Recency => write!(f, "by recency"),
+            PointColoring::Density => write!(f, "by density"),
+        }
+    }
+}
+
+/// Where the orbits start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrbitStart {
+    /// The first orbit on the first vertex, any others at points drawn from
+    /// their seed inside the box around the vertices.
+    Scattered,
+    /// Every orbit on a vertex drawn from its seed, by the vertex weights.
+    Vertices,
+}
+
+impl OrbitStart {
+    const ALL: [OrbitStart; 2] = [OrbitStart::Scattered, OrbitStart::Vertices];
+}
+
+impl Display for OrbitStart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrbitStart::Scattered => write!(f, "scattered"),
+            OrbitStart::Vertices => write!(f, "on vertices"),
+        }
+    }
+}
+
+/// What to draw for generated points that leave the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutOfBounds {
+    /// Leave them out.
+    Clip,
+    /// Pin them to the nearest edge.
+    Clamp,
+    /// Zoom out until every point fits.
+    Rescale,
+}
+
+impl OutOfBounds {
+    const ALL: [OutOfBounds; 3] = [OutOfBounds::Clip, OutOfBounds::Clamp, OutOfBounds::Rescale];
+}
+
+impl Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutOfBounds::Clip => write!(f, "clip"),
+            OutOfBounds::Clamp => write!(f, "clamp"),
+            OutOfBounds::Rescale => write!(f, "rescale"),
+        }
+    }
+}
+
+/// The frame around the canvas, also drawn into exports.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BorderStyle {
+    pub shown: bool,
+    /// Stroke width in pixels, from 0.5 to 4.
+    pub width: f32,
+    pub color: BorderColor,
+    /// Free space inside the border, in pixels. The drawing shrinks to fit
+    /// so points near the edge stay clear of the stroke.
+    pub margin: f32,
+}
+
+impl Default for BorderStyle {
+    fn default() -> BorderStyle {
+        BorderStyle {
+            shown: true,
+            width: 1.0,
+            color: BorderColor::Points,
+            margin: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BorderColor {
+    /// The colour of uniformly coloured points.
+    Points,
+    Grey,
+    Blue,
+    Red,
+}
+
+impl BorderColor {
+    const ALL: [BorderColor; 4] = [
+        BorderColor::Points,
+        BorderColor::Grey,
+        BorderColor::Blue,
+        BorderColor::Red,
+    ];
+
+    fn color(self, foreground: Color) -> Color {
+        match self {
+            BorderColor::Points => foreground,
+            BorderColor::Grey => palette::GUIDE_COLOR,
+            BorderColor::Blue => FIX_POINT_COLOR,
+            BorderColor::Red => palette::OVERLAY_COLOR,
+        }
+    }
+}
+
+impl Display for BorderColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BorderColor::Points => write!(f, "like points"),
+            BorderColor::Grey => write!(f, "grey"),
+            BorderColor::Blue => write!(f, "blue"),
+            BorderColor::Red => write!(f, "red"),
+        }
+    }
 }
 
 impl Application for SierpinskiEmulator {
@@ -36,14 +1332,205 @@ impl Application for SierpinskiEmulator {
 
     type Message = Message;
 
-    type Flags = ();
+    type Flags = Flags;
 
-    fn new(_flags: Self::Flags) -> (Self, iced::Command<Self::Message>) {
-        let emulator = SierpinskiEmulator {
+    fn new(flags: Self::Flags) -> (Self, iced::Command<Self::Message>) {
+        let mut emulator = SierpinskiEmulator {
             max_iter_state: slider::State::default(),
+            max_iter_wheel: wheel::State::default(),
             cur_iter_state: slider::State::default(),
+            cur_iter_wheel: wheel::State::default(),
+            cur_fraction_state: slider::State::default(),
+            eraser_radius_state: slider::State::default(),
+            clear_mask_state: button::State::default(),
+            export_selection_state: button::State::default(),
+            copy_selection_state: button::State::default(),
+            vertex_marker_state: pick_list::State::default(),
+            point_coloring_state: pick_list::State::default(),
+            ramp_state: pick_list::State::default(),
+            count_scale_state: pick_list::State::default(),
+            clip_percentile_state: slider::State::default(),
+            out_of_bounds_state: pick_list::State::default(),
+            cur_iter_policy_state: pick_list::State::default(),
+            right_click_state: pick_list::State::default(),
+            decimals_state: pick_list::State::default(),
+            language_state: pick_list::State::default(),
+            theme_state: pick_list::State::default(),
+            background_states: Default::default(),
+            border_width_state: slider::State::default(),
+            border_color_state: pick_list::State::default(),
+            border_margin_state: slider::State::default(),
+            sector_count_state: slider::State::default(),
+            preset_state: pick_list::State::default(),
+            circle_states: Default::default(),
+            ratio_state: slider::State::default(),
+            ratio_wheel: wheel::State::default(),
+            ratio_minus_state: button::State::default(),
+            ratio_plus_state: button::State::default(),
+            ratio_input_state: text_input::State::default(),
+            rule_state: pick_list::State::default(),
+            restriction_state: pick_list::State::default(),
+            sequence_input_state: text_input::State::default(),
+            rotation_state: slider::State::default(),
+            jitter_state: slider::State::default(),
+            reseed_state: button::State::default(),
+            seed_count_state: slider::State::default(),
+            quota_state: slider::State::default(),
+            orbit_start_state: pick_list::State::default(),
+            generator_state: pick_list::State::default(),
+            anchor_blend_state: slider::State::default(),
+            clear_anchors_state: button::State::default(),
+            apply_rules_state: button::State::default(),
+            pin_ghost_state: button::State::default(),
+            clear_ghost_state: button::State::default(),
+            fit_state: button::State::default(),
+            add_centroid_state: button::State::default(),
+            subdivision_level_state: slider::State::default(),
+            export_gasket_state: button::State::default(),
+            play_state: button::State::default(),
+            replay_state: button::State::default(),
+            replay_speed_state: slider::State::default(),
+            replay_trail_state: slider::State::default(),
+            capture_dir_state: text_input::State::default(),
+            point_cloud_state: pick_list::State::default(),
+            supersample_state: pick_list::State::default(),
+            svg_budget_state: pick_list::State::default(),
+            refine_batch_state: pick_list::State::default(),
+            point_shape_state: pick_list::State::default(),
+            point_size_state: slider::State::default(),
+            gallery_parameter_state: pick_list::State::default(),
+            gallery_tiles_state: pick_list::State::default(),
+            gallery_iterations_state: pick_list::State::default(),
+            gallery_from_state: text_input::State::default(),
+            gallery_to_state: text_input::State::default(),
+            gallery_state: button::State::default(),
+            export_svg_state: button::State::default(),
+            export_points_state: button::State::default(),
+            export_stats_state: button::State::default(),
+            session_path_state: text_input::State::default(),
+            weight_states: vec![],
+            scroll_state: scrollable::State::default(),
+            script_path_state: text_input::State::default(),
+            load_script_state: button::State::default(),
+            choices_path_state: text_input::State::default(),
+            export_choices_state: button::State::default(),
+            import_choices_state: button::State::default(),
+            stop_replaying_state: button::State::default(),
+            undo_state: button::State::default(),
+            redo_state: button::State::default(),
+            recent_states: Vec::new(),
+            vertex_row_states: Vec::new(),
+            start_empty_state: button::State::default(),
+            save_session_state: button::State::default(),
+            extend_input_state: text_input::State::default(),
+            extend_state: button::State::default(),
+            load_session_state: button::State::default(),
+            preset: None,
+            circle: Circle::default(),
+            theme: Some(Theme::Light),
+            config: Config::load(),
+            cjk_font: false,
+            window: (0, 0),
+            history: History::default(),
+            transition: None,
+            extension: None,
+            extend_text: "100000".to_string(),
+            advance_on_extend: true,
+            refine_when_idle: false,
+            refine_batch: REFINE_BATCHES[1],
+            last_input: Instant::now(),
+            refining: false,
+            quick_play: false,
+            suggestion_pending: true,
+            playing: false,
+            capture_on_complete: false,
+            capture_dir: ".".to_string(),
+            point_cloud: PointCloud::Ply,
+            svg_budget: 100_000,
+            gallery: GALLERY,
+            gallery_from: String::new(),
+            gallery_to: String::new(),
+            gallery_job: None,
+            link_sliders: false,
+            peek_restore: None,
+            insert_at: None,
+            coordinate_text: None,
+            coordinate_input_state: text_input::State::default(),
+            cur_fraction: None,
+            crop_to_selection: false,
+            session_path: "session.json".to_string(),
+            script_path: "script.txt".to_string(),
+            choices_path: "choices.bin".to_string(),
+            script: VecDeque::new(),
+            script_delay: Duration::ZERO,
+            auto_apply: true,
+            pending_rules: None,
+            apply_at: None,
+            recording: None,
+            status: String::new(),
+            recent: Recent::load(),
+            show_recent: false,
+            recovered: recovery::load(),
+            exiting: false,
+            autosaved: String::new(),
+            saved: None,
+            show_changes: false,
+            slots: [None, None],
+            active_slot: None,
+            store_slot_states: Default::default(),
+            flip_slot_state: button::State::default(),
+            changes_state: button::State::default(),
+            revert_state: button::State::default(),
+            close_changes_state: button::State::default(),
+            restore_state: button::State::default(),
+            discard_recovery_state: button::State::default(),
+            thumbnails: HashMap::new(),
+            merge_duplicates: false,
+            ratio_text: None,
+            sequence_text: None,
+            modifiers: Default::default(),
+            tutorial: None,
+            tutorial_state: button::State::default(),
+            reset_vertex_colors_state: button::State::default(),
+            unlock_all_state: button::State::default(),
+            skip_tutorial_step_state: button::State::default(),
+            exit_tutorial_state: button::State::default(),
             graph: SierpinskiGraph::new(),
         };
+        emulator.graph.bound = flags.canvas;
+        emulator.graph.right_click = emulator.config.right_click;
+        emulator.graph.decimals = emulator.config.places();
+        emulator.graph.language = emulator.config.language;
+        emulator.graph.debug_orbit = flags.debug_orbit;
+        emulator.cjk_font = flags.cjk_font;
+        emulator.graph.export_scale = emulator.config.scale() as f32;
+        emulator.resize(flags.window);
+        let missing = emulator.recent.prune();
+        if missing.is_empty().not() {
+            emulator.status = emulator.fill(Key::PrunedRecent, &[&missing.len()]);
+            emulator.save_recent();
+        }
+        emulator.show_recent = flags.session.is_none()
+            && flags.script.is_none()
+            && emulator.recent.entries.is_empty().not();
+        emulator.update_thumbnails();
+        if let Some(session) = flags.session {
+            emulator.session_path = session.display().to_string();
+            emulator.load_session();
+        }
+        if let Some(script) = flags.script {
+            emulator.script_path = script.display().to_string();
+            emulator.load_script();
+        }
+        emulator.autosaved = emulator.session().to_json().unwrap_or_default();
+        if let Some(degraded) = flags.degraded {
+            let degraded = emulator.text(degraded);
+            emulator.status = if emulator.status.is_empty() {
+                degraded.to_string()
+            } else {
+                format!("{}; {}", degraded, emulator.status)
+            };
+        }
         (emulator, Command::none())
     }
 
@@ -54,189 +1541,7613 @@ impl Application for SierpinskiEmulator {
     fn update(
         &mut self,
         message: Self::Message,
-        _clipboard: &mut iced::Clipboard,
+        clipboard: &mut iced::Clipboard,
     ) -> iced::Command<Self::Message> {
-        match message {
-            Message::SetMaxIter(max_iter) => {
-                self.graph.max_iter = max_iter;
-                while self.graph.random_points.len() < max_iter as usize {
-                    let p = self.graph.gen_rand_point();
-                    self.graph.random_points.push(p);
-                }
-            }
-            Message::SetCurIter(cur_iter) => {
-                if cur_iter > self.graph.max_iter {
-                    self.graph.cur_iter = self.graph.max_iter;
-                } else {
-                    self.graph.cur_iter = cur_iter;
-                }
-            }
-            Message::AddFixPoint(point) => {
-                self.graph.fix_points.push(point);
-                self.graph.random_points.clear();
-                self.graph.max_iter = 0;
-                self.graph.cur_iter = 0;
-            }
-            Message::RemoveFixPoint => {
-                self.graph.fix_points.pop();
-                self.graph.random_points.clear();
-                self.graph.max_iter = 0;
-                self.graph.cur_iter = 0;
-            }
-            Message::DrawCurIter(cur_iter) => {
-                self.graph.cur_iter = cur_iter;
-            }
-        }
-        self.graph.redraw();
+        self.dispatch(message, clipboard)
+    }
 
-        Command::none()
+    /// The window already follows the scale of the monitor it is on, in
+    /// every widget and canvas size. This is only the config's adjustment
+    /// on top.
+    fn scale_factor(&self) -> f64 {
+        self.config.scale()
+    }
+
+    fn should_exit(&self) -> bool {
+        self.exiting
+    }
+
+    fn subscription(&self) -> Subscription<Self::Message> {
+        let mut subscriptions = self
+            .timers()
+            .into_iter()
+            .map(|(every, message)| time::every(every).map(message))
+            .collect::<Vec<_>>();
+        subscriptions.push(iced_native::subscription::events_with(shortcut));
+        Subscription::batch(subscriptions)
     }
 
     fn view(&mut self) -> iced::Element<'_, Self::Message> {
+        let tutorial_hint = self
+            .tutorial
+            .as_ref()
+            .and_then(|progress| progress.current())
+            .and_then(|step| step.goal.hint(&self.facts()));
+        let changes = self.changes();
+        let language = self.config.language;
+        let slot_text = match self.active_slot {
+            Some(i) if self.slots[i].as_ref() == Some(&self.slot()) => {
+                language.fill(Key::ShowingSlot, &[&SLOT_NAMES[i]])
+            }
+            Some(i) => language.fill(Key::SlotEdited, &[&SLOT_NAMES[i]]),
+            None => String::new(),
+        };
         let bound = self.graph.bound;
+        let breakpoint = Breakpoint::for_width(self.window.0);
+        let per_row = layout::groups_per_row(self.window.0);
+        // The canvas as large as it is shown.
+        let shown = Size::new(
+            bound.width * self.graph.display_scale,
+            bound.height * self.graph.display_scale,
+        );
         let max_iter = self.graph.max_iter;
         let cur_iter = self.graph.cur_iter;
+        let fraction = if max_iter > 0 {
+            cur_iter as f32 / max_iter as f32 * 100.0
+        } else {
+            0.0
+        };
         let fix_point_is_empty = self.graph.fix_points.is_empty();
+        let counts = self.graph.selection_counts();
+        let rules = self.rules();
+        let weights = (0..counts.len())
+            .map(|i| rules.weights.get(i).copied().unwrap_or(1.0))
+            .collect::<Vec<_>>();
+        let applied_weights = (0..counts.len())
+            .map(|i| self.graph.weight(i))
+            .collect::<Vec<_>>();
+        let quota_share = self.graph.quota_share();
+        let pending = self.pending_rules.is_some();
+        let vertices = self.graph.vertices();
+        let out_of_bounds = self.graph.out_of_bounds;
+        let auto_frame = self.graph.auto_frame;
+        let outside = self.graph.count_outside();
+        let visible = self.graph.visible_points().count();
+        let occupied = self.graph.occupied_pixels();
+        let screen_pixels = (bound.width * self.graph.display_scale).floor()
+            * (bound.height * self.graph.display_scale).floor();
+        let tool = self.graph.tool;
+        let eraser_radius = self.graph.eraser_radius;
+        let has_selection = self.graph.selection.is_some();
+        let measures = self.graph.measures.clone();
+        let crop_to_selection = self.crop_to_selection;
+        let vertex_marker = self.graph.vertex_marker;
+        let point_coloring = self.graph.point_coloring;
+        let density_sizing = self.graph.density_sizing;
+        let sector_count = self.graph.sector_count;
+        let ramp = self.graph.ramp;
+        let decimals = self.graph.decimals;
+        let count_scale = self.graph.count_scale;
+        let clip_percentile = self.graph.clip_percentile;
+        let playing = self.playing;
+        // Adding points can take the iterations past the usual slider range.
+        let iter_limit = MAX_ITER.max(max_iter);
+        let extending = self.extension.map(|extension| {
+            let from = self.graph.point_count(extension.from) as f32;
+            let to = self.graph.point_count(extension.to) as f32;
+            ((self.graph.random_points.len() as f32 - from) / (to - from).max(1.0)).clamp(0.0, 1.0)
+        });
+        let preset = self.preset;
+        let sequence_text = self
+            .sequence_text
+            .clone()
+            .unwrap_or_else(|| match &rules.selection {
+                Selection::Random => String::new(),
+                selection => selection.to_string(),
+            });
+        let Rules {
+            ratio,
+            rule,
+            restriction,
+            rotation,
+            anchor_blend,
+            jitter,
+            ..
+        } = rules;
+        let has_anchors = self.graph.anchors.iter().any(Option::is_some);
+        let show_last_pick = self.graph.show_last_pick;
+        let seed_count = self.graph.seed_count;
+        let (balanced, quota) = (self.graph.balanced, self.graph.quota);
+        let orbit_start = self.graph.orbit_start;
+        let generator = self.graph.generator;
+        let seed_counts = self.graph.seed_counts();
+        let animations = self.config.animated();
+        let suggest_points = self.config.suggest_points;
+        let show_subdivision = self.graph.show_subdivision && self.graph.fix_points.len() == 3;
+        let subdivision_level = self.graph.subdivision_level;
+        let capture_on_complete = self.capture_on_complete;
+        let point_cloud = self.point_cloud;
+        let replaying = self.graph.replay.is_some();
+        let replay_speed = self.graph.replay_speed;
+        let replay_trail = self.graph.replay_trail;
+        let supersample = self.graph.supersample;
+        let additive_export = self.graph.additive_export;
+        let svg_budget = self.svg_budget;
+        let gallery = self.gallery;
+        let gallery_progress = self
+            .gallery_job
+            .as_ref()
+            .map(|job| job.done.load(Ordering::Relaxed) as f32 / job.tiles.max(1) as f32);
+        let link_sliders = self.link_sliders;
+        let cur_iter_policy = self.config.cur_iter_policy;
 
-        let mut content = Column::new()
-            .width(Length::Fill)
-            .align_items(iced::Align::Center)
-            .push(
-                Canvas::new(&mut self.graph)
-                    .width(Length::Units(bound.width as u16))
-                    .height(Length::Units(bound.height as u16)),
-            );
-        if fix_point_is_empty.not() {
-            content = content
+        let mut undo = Button::new(&mut self.undo_state, Text::new(language.text(Key::Undo)));
+        if self.history.can_undo() {
+            undo = undo.on_press(Message::Undo);
+        }
+        let mut redo = Button::new(&mut self.redo_state, Text::new(language.text(Key::Redo)));
+        if self.history.can_redo() {
+            redo = redo.on_press(Message::Redo);
+        }
+        let edit_bar = Bar::new()
+            .push(tip(undo, language.text(Key::UndoTip)))
+            .then(tip(redo, language.text(Key::RedoTip)));
+        let mut toolbar =
+            Tool::ALL
+                .iter()
+                .fold(edit_bar.label(language.text(Key::Tool)), |bar, t| {
+                    bar.push(Radio::new(
+                        *t,
+                        language.text(t.key()),
+                        Some(tool),
+                        Message::SelectTool,
+                    ))
+                });
+        toolbar = toolbar.label(language.text(Key::RightClick)).push(tip(
+            pick_list(
+                &mut self.right_click_state,
+                &RightClick::ALL[..],
+                Some(self.config.right_click),
+                language,
+                Message::SetRightClick,
+            ),
+            language.text(Key::RightClickTip),
+        ));
+        toolbar = toolbar.label(language.text(Key::Decimals)).push(tip(
+            PickList::new(
+                &mut self.decimals_state,
+                &DECIMALS[..],
+                Some(decimals),
+                Message::SetDecimals,
+            ),
+            language.text(Key::DecimalsTip),
+        ));
+        toolbar = toolbar
+            .label(language.text(Key::Language))
+            .push(PickList::new(
+                &mut self.language_state,
+                &Language::ALL[..],
+                Some(language),
+                Message::SetLanguage,
+            ));
+        toolbar = toolbar.label(language.text(Key::Marker)).push(pick_list(
+            &mut self.vertex_marker_state,
+            &VertexMarker::ALL[..],
+            Some(vertex_marker),
+            language,
+            Message::SetVertexMarker,
+        ));
+        toolbar = toolbar.label(language.text(Key::Colour)).push(pick_list(
+            &mut self.point_coloring_state,
+            &PointColoring::ALL[..],
+            Some(point_coloring),
+            language,
+            Message::SetPointColoring,
+        ));
+        toolbar = toolbar
+            .label(language.text(Key::Points))
+            .push(pick_list(
+                &mut self.point_shape_state,
+                &PointShape::ALL[..],
+                Some(self.graph.point_shape),
+                language,
+                Message::SetPointShape,
+            ))
+            .label(language.fill(Key::Size, &[&self.graph.point_size]))
+            .push(tip(
+                Slider::new(
+                    &mut self.point_size_state,
+                    1..=MAX_POINT_SIZE,
+                    self.graph.point_size,
+                    Message::SetPointSize,
+                )
+                .width(Length::FillPortion(1)),
+                language.text(Key::PointSizeTip),
+            ));
+        toolbar = toolbar.push(tip(
+            Checkbox::new(
+                density_sizing,
+                language.text(Key::SizeByDensity),
+                Message::SetDensitySizing,
+            ),
+            language.text(Key::SizeByDensityTip),
+        ));
+        toolbar = toolbar.push(tip(
+            Checkbox::new(
+                self.graph.pixel_snap,
+                language.text(Key::PixelSnap),
+                Message::SetPixelSnap,
+            ),
+            language.text(Key::PixelSnapTip),
+        ));
+        if matches!(
+            point_coloring,
+            PointColoring::Recency | PointColoring::Density
+        ) {
+            toolbar = toolbar.label(language.text(Key::Ramp)).push(pick_list(
+                &mut self.ramp_state,
+                &Ramp::ALL[..],
+                Some(ramp),
+                language,
+                Message::SetRamp,
+            ));
+        }
+        if point_coloring == PointColoring::Density {
+            toolbar = toolbar
+                .label(language.text(Key::CountScale))
+                .push(tip(
+                    pick_list(
+                        &mut self.count_scale_state,
+                        &CountScale::ALL[..],
+                        Some(count_scale),
+                        language,
+                        Message::SetCountScale,
+                    ),
+                    language.text(Key::CountScaleTip),
+                ))
+                .label(language.fill(Key::ClipPercentile, &[&clip_percentile]))
+                .push(tip(
+                    Slider::new(
+                        &mut self.clip_percentile_state,
+                        CLIP_PERCENTILES,
+                        clip_percentile,
+                        Message::SetClipPercentile,
+                    )
+                    .width(Length::FillPortion(1)),
+                    language.text(Key::ClipPercentileTip),
+                ));
+        }
+        if point_coloring == PointColoring::Sector {
+            toolbar = toolbar
+                .label(language.fill(Key::Sectors, &[&sector_count]))
                 .push(
-                    Row::new()
-                        .padding(10)
-                        .spacing(20)
-                        .push(Text::new(format!("max iter: {:?}", max_iter)))
-                        .push(
-                            Slider::new(
-                                &mut self.max_iter_state,
-                                0..=10000,
-                                max_iter,
-                                Message::SetMaxIter,
-                            )
-                            .width(Length::Units(bound.width as u16)),
-                        ),
+                    Slider::new(
+                        &mut self.sector_count_state,
+                        3..=12,
+                        sector_count,
+                        Message::SetSectorCount,
+                    )
+                    .width(Length::FillPortion(1)),
+                );
+        }
+        if tool == Tool::Eraser {
+            toolbar = toolbar
+                .label(language.fill(Key::Radius, &[&format!("{:.0}", eraser_radius)]))
+                .push(
+                    Slider::new(
+                        &mut self.eraser_radius_state,
+                        1.0..=100.0,
+                        eraser_radius,
+                        Message::SetEraserRadius,
+                    )
+                    .width(Length::FillPortion(2)),
                 )
                 .push(
-                    Row::new()
-                        .padding(10)
-                        .spacing(20)
-                        .push(Text::new(format!("cur iter: {:?}", cur_iter)))
-                        .push(
-                            Slider::new(
-                                &mut self.cur_iter_state,
-                                0..=10000,
-                                cur_iter,
-                                Message::SetCurIter,
-                            )
-                            .width(Length::Units(bound.width as u16)),
+                    Button::new(
+                        &mut self.clear_mask_state,
+                        Text::new(language.text(Key::ClearMask)),
+                    )
+                    .on_press(Message::ClearMask),
+                );
+        }
+        if tool == Tool::Measure {
+            toolbar = toolbar.push(Text::new(match measures.as_slice() {
+                [first, second] => {
+                    let size = |r: &Rectangle| [r.width, r.height];
+                    let [width, height] = geometry::axis_ratios(size(first), size(second));
+                    match geometry::scale_ratio(size(first), size(second)) {
+                        Some(measured) => language.fill(
+                            Key::MeasureRatio,
+                            &[
+                                &format!("{:.3}", measured),
+                                &format!("{:.3}", width),
+                                &format!("{:.3}", height),
+                                &format!("{:.3}", 1.0 - ratio),
+                            ],
                         ),
+                        None => language.text(Key::MeasureNoArea).to_string(),
+                    }
+                }
+                [_] => language.text(Key::MeasureCopy).to_string(),
+                _ => language.text(Key::MeasureShape).to_string(),
+            }));
+        }
+        if tool == Tool::Select && has_selection {
+            toolbar = toolbar
+                .push(Checkbox::new(
+                    crop_to_selection,
+                    language.text(Key::Crop),
+                    Message::SetCropToSelection,
+                ))
+                .push(
+                    Button::new(
+                        &mut self.export_selection_state,
+                        Text::new(language.text(Key::Export)),
+                    )
+                    .on_press(Message::ExportSelection),
+                )
+                .push(
+                    Button::new(
+                        &mut self.copy_selection_state,
+                        Text::new(language.text(Key::CopyPoints)),
+                    )
+                    .on_press(Message::CopySelection),
                 );
         }
-        content.into()
-    }
+
+        let circle = self.circle;
+        let [count_state, radius_state, phase_state] = &mut self.circle_states;
+        let circle_bar = Bar::new()
+            .label(language.fill(Key::CircleOf, &[&circle.count]))
+            .push(tip(
+                Press::new(
+                    Slider::new(count_state, Circle::COUNTS, circle.count, move |count| {
+                        Message::SetCircle(Circle { count, ..circle })
+                    })
+                    .on_release(Message::EndGesture)
+                    .width(Length::FillPortion(2)),
+                    Message::BeginGesture,
+                ),
+                language.text(Key::CircleOfTip),
+            ))
+            .label(language.fill(Key::CircleRadius, &[&format!("{:.0}", circle.radius)]))
+            .push(Press::new(
+                Slider::new(radius_state, 10.0..=100.0, circle.radius, move |radius| {
+                    Message::SetCircle(Circle { radius, ..circle })
+                })
+                .on_release(Message::EndGesture)
+                .width(Length::FillPortion(2)),
+                Message::BeginGesture,
+            ))
+            .label(language.fill(Key::Phase, &[&format!("{:.0}", circle.phase)]))
+            .push(Press::new(
+                Slider::new(phase_state, 0.0..=360.0, circle.phase, move |phase| {
+                    Message::SetCircle(Circle { phase, ..circle })
+                })
+                .on_release(Message::EndGesture)
+                .step(1.0)
+                .width(Length::FillPortion(2)),
+                Message::BeginGesture,
+            ));
+
+        let mut layout_bar = Bar::new()
+            .label(language.text(Key::Preset))
+            .push(pick_list(
+                &mut self.preset_state,
+                &Preset::ALL[..],
+                preset,
+                language,
+                Message::SelectPreset,
+            ))
+            .push(tip(
+                Button::new(&mut self.fit_state, Text::new(language.text(Key::Fit)))
+                    .on_press(Message::FitToCanvas),
+                language.text(Key::FitTip),
+            ));
+        layout_bar = layout_bar.push(tip(
+            Checkbox::new(
+                show_subdivision,
+                language.text(Key::Subdivision),
+                Message::SetShowSubdivision,
+            ),
+            language.text(Key::SubdivisionTip),
+        ));
+        layout_bar = layout_bar.push(tip(
+            Checkbox::new(
+                self.graph.show_maps,
+                language.text(Key::ShowMaps),
+                Message::SetShowMaps,
+            ),
+            language.text(Key::ShowMapsTip),
+        ));
+        layout_bar = layout_bar.push(tip(
+            Checkbox::new(
+                self.graph.show_centers,
+                language.text(Key::Centres),
+                Message::SetShowCenters,
+            ),
+            language.text(Key::CentresTip),
+        ));
+        layout_bar = layout_bar.push(tip(
+            Checkbox::new(
+                self.graph.show_transitions,
+                language.text(Key::Transitions),
+                Message::SetShowTransitions,
+            ),
+            language.text(Key::TransitionsTip),
+        ));
+        layout_bar = layout_bar.push(tip(
+            {
+                let add = Button::new(
+                    &mut self.add_centroid_state,
+                    Text::new(language.text(Key::AddCentroid)),
+                );
+                if self.graph.fix_points.is_empty() {
+                    add
+                } else {
+                    add.on_press(Message::AddCentroidVertex)
+                }
+            },
+            language.text(Key::AddCentroidTip),
+        ));
+        layout_bar = layout_bar.push(tip(
+            Checkbox::new(
+                self.graph.show_convergence,
+                language.text(Key::Convergence),
+                Message::SetShowConvergence,
+            ),
+            language.text(Key::ConvergenceTip),
+        ));
+        layout_bar = layout_bar.push(tip(
+            Checkbox::new(
+                self.graph.loupe,
+                language.text(Key::Loupe),
+                Message::SetLoupe,
+            ),
+            language.text(Key::LoupeTip),
+        ));
+        if show_subdivision {
+            layout_bar = layout_bar
+                .label(language.fill(Key::Levels, &[&subdivision_level]))
+                .push(
+                    Slider::new(
+                        &mut self.subdivision_level_state,
+                        1..=7,
+                        subdivision_level,
+                        Message::SetSubdivisionLevel,
+                    )
+                    .width(Length::FillPortion(1)),
+                )
+                .push(tip(
+                    Button::new(
+                        &mut self.export_gasket_state,
+                        Text::new(language.text(Key::ExportSvg)),
+                    )
+                    .on_press(Message::ExportGasketSvg),
+                    language.text(Key::ExportSvgTip),
+                ));
+        }
+
+        let background = self.graph.background;
+        let transparent = background.a == 0.0;
+        let theme = self.theme;
+        let mut style_bar = Bar::new()
+            .label(language.text(Key::Theme))
+            .push(pick_list(
+                &mut self.theme_state,
+                &Theme::ALL[..],
+                theme,
+                language,
+                Message::SelectTheme,
+            ))
+            .label(language.text(Key::Background))
+            .push(Checkbox::new(
+                transparent,
+                language.text(Key::Transparent),
+                move |transparent| {
+                    Message::SetBackground(Color {
+                        a: if transparent { 0.0 } else { 1.0 },
+                        ..background
+                    })
+                },
+            ));
+        if transparent.not() {
+            let channels = [background.r, background.g, background.b];
+            for (channel, (state, value)) in self
+                .background_states
+                .iter_mut()
+                .zip(channels.iter())
+                .enumerate()
+            {
+                style_bar = style_bar.then(Press::new(
+                    Slider::new(state, 0..=255, (value * 255.0).round() as u8, move |v| {
+                        let mut rgb = channels;
+                        rgb[channel] = v as f32 / 255.0;
+                        Message::SetBackground(Color::from_rgb(rgb[0], rgb[1], rgb[2]))
+                    })
+                    .on_release(Message::EndGesture)
+                    .width(Length::FillPortion(1)),
+                    Message::BeginGesture,
+                ));
+            }
+        }
+        let border = self.graph.border;
+        style_bar = style_bar.push(Checkbox::new(
+            border.shown,
+            language.text(Key::Border),
+            move |shown| Message::SetBorder(BorderStyle { shown, ..border }),
+        ));
+        if border.shown {
+            style_bar = style_bar
+                .label(language.fill(Key::BorderWidth, &[&format!("{:.1}", border.width)]))
+                .push(
+                    Slider::new(
+                        &mut self.border_width_state,
+                        0.5..=4.0,
+                        border.width,
+                        move |width| Message::SetBorder(BorderStyle { width, ..border }),
+                    )
+                    .step(0.5)
+                    .width(Length::FillPortion(1)),
+                )
+                .push(pick_list(
+                    &mut self.border_color_state,
+                    &BorderColor::ALL[..],
+                    Some(border.color),
+                    language,
+                    move |color| Message::SetBorder(BorderStyle { color, ..border }),
+                ));
+        }
+        style_bar = style_bar
+            .label(language.fill(Key::Margin, &[&format!("{:.0}", border.margin)]))
+            .push(tip(
+                Slider::new(
+                    &mut self.border_margin_state,
+                    0.0..=30.0,
+                    border.margin,
+                    move |margin| Message::SetBorder(BorderStyle { margin, ..border }),
+                )
+                .width(Length::FillPortion(1)),
+                language.text(Key::MarginTip),
+            ));
+
+        // The ratio turns the warning colour while it keeps the orbit from
+        // settling.
+        let mut ratio_label = Text::new(language.text(Key::Ratio));
+        if self.graph.diverged.is_some() && self.graph.ratio_diverges() {
+            ratio_label = ratio_label.color(palette::OVERLAY_COLOR);
+        }
+        let rule_bar = Bar::new()
+            .styled_label(ratio_label)
+            .push(tip(
+                Button::new(&mut self.ratio_minus_state, Text::new("-"))
+                    .on_press(Message::NudgeRatio(-1)),
+                language.text(Key::RatioMinusTip),
+            ))
+            .then(tip(
+                TextInput::new(
+                    &mut self.ratio_input_state,
+                    language.text(Key::RatioPlaceholder),
+                    self.ratio_text
+                        .as_deref()
+                        .unwrap_or(&format!("{}", (ratio * 1000.0).round() / 1000.0)),
+                    Message::SetRatioText,
+                )
+                .on_submit(Message::SubmitRatioText)
+                .padding(5)
+                .width(Length::Units(60)),
+                language.text(Key::RatioTextTip),
+            ))
+            .then(tip(
+                Button::new(&mut self.ratio_plus_state, Text::new("+"))
+                    .on_press(Message::NudgeRatio(1)),
+                language.text(Key::RatioPlusTip),
+            ))
+            .then(tip(
+                Wheel::new(
+                    &mut self.ratio_wheel,
+                    Press::new(
+                        Slider::new(
+                            &mut self.ratio_state,
+                            0.01..=MAX_RATIO - 0.01,
+                            ratio,
+                            Message::SetRatio,
+                        )
+                        .on_release(Message::ReleaseRuleSlider)
+                        .step(0.01)
+                        .width(Length::FillPortion(2)),
+                        Message::BeginGesture,
+                    ),
+                    move |direction, modifiers| {
+                        let step = if modifiers.shift { 0.001 } else { 0.01 };
+                        let ratio = ratio + direction as f32 * step;
+                        Message::SetRatio(
+                            ((ratio * 1000.0).round() / 1000.0).clamp(0.01, MAX_RATIO - 0.01),
+                        )
+                    },
+                ),
+                language.text(Key::RatioTip),
+            ))
+            .label(language.text(Key::Rule))
+            .push(tip(
+                pick_list(
+                    &mut self.rule_state,
+                    &Rule::ALL[..],
+                    Some(rule),
+                    language,
+                    Message::SetRule,
+                ),
+                language.text(Key::RuleTip),
+            ))
+            .label(language.text(Key::Restriction))
+            .push(tip(
+                pick_list(
+                    &mut self.restriction_state,
+                    &Restriction::ALL[..],
+                    Some(restriction),
+                    language,
+                    Message::SetRestriction,
+                ),
+                language.text(Key::RestrictionTip),
+            ))
+            .label(language.text(Key::Sequence))
+            .push(tip(
+                TextInput::new(
+                    &mut self.sequence_input_state,
+                    language.text(Key::RandomPicks),
+                    &sequence_text,
+                    Message::SetSequenceText,
+                )
+                .on_submit(Message::SubmitSequenceText)
+                .padding(5)
+                .width(Length::Units(100)),
+                language.text(Key::SequenceTip),
+            ))
+            .label(language.fill(Key::Rotation, &[&format!("{:.0}", rotation)]))
+            .push(tip(
+                Press::new(
+                    Slider::new(
+                        &mut self.rotation_state,
+                        0.0..=120.0,
+                        rotation,
+                        Message::SetRotation,
+                    )
+                    .on_release(Message::ReleaseRuleSlider)
+                    .width(Length::FillPortion(1)),
+                    Message::BeginGesture,
+                ),
+                language.text(Key::RotationTip),
+            ))
+            .label(language.fill(Key::Jitter, &[&format!("{:.1}", jitter)]))
+            .push(tip(
+                Press::new(
+                    Slider::new(
+                        &mut self.jitter_state,
+                        0.0..=5.0,
+                        jitter,
+                        Message::SetJitter,
+                    )
+                    .step(0.1)
+                    .on_release(Message::ReleaseRuleSlider)
+                    .width(Length::FillPortion(1)),
+                    Message::BeginGesture,
+                ),
+                language.text(Key::JitterTip),
+            ))
+            .label(language.fill(Key::AnchorBlend, &[&format!("{:.2}", anchor_blend)]))
+            .push(tip(
+                Press::new(
+                    Slider::new(
+                        &mut self.anchor_blend_state,
+                        0.0..=1.0,
+                        anchor_blend,
+                        Message::SetAnchorBlend,
+                    )
+                    .step(0.01)
+                    .on_release(Message::ReleaseRuleSlider)
+                    .width(Length::FillPortion(1)),
+                    Message::BeginGesture,
+                ),
+                language.text(Key::AnchorBlendTip),
+            ))
+            .push({
+                let clear = Button::new(
+                    &mut self.clear_anchors_state,
+                    Text::new(language.text(Key::ClearAnchors)),
+                );
+                if has_anchors {
+                    clear.on_press(Message::ClearAnchors)
+                } else {
+                    clear
+                }
+            })
+            .push(tip(
+                Button::new(
+                    &mut self.reseed_state,
+                    Text::new(language.text(Key::Reseed)),
+                )
+                .on_press(Message::Reseed),
+                language.text(Key::ReseedTip),
+            ))
+            .label(language.fill(Key::Seeds, &[&seed_count]))
+            .push(tip(
+                Slider::new(
+                    &mut self.seed_count_state,
+                    1..=MAX_SEEDS,
+                    seed_count,
+                    Message::SetSeedCount,
+                )
+                .width(Length::FillPortion(1)),
+                language.text(Key::SeedsTip),
+            ))
+            .label(language.text(Key::Start))
+            .push(tip(
+                pick_list(
+                    &mut self.orbit_start_state,
+                    &OrbitStart::ALL[..],
+                    Some(orbit_start),
+                    language,
+                    Message::SetOrbitStart,
+                ),
+                language.text(Key::StartTip),
+            ))
+            .label(language.text(Key::Generator))
+            .push(tip(
+                PickList::new(
+                    &mut self.generator_state,
+                    &Generator::ALL[..],
+                    Some(generator),
+                    Message::SetGenerator,
+                ),
+                language.text(Key::GeneratorTip),
+            ))
+            .push(tip(
+                Checkbox::new(balanced, language.text(Key::Balanced), Message::SetBalanced),
+                language.text(Key::BalancedTip),
+            ))
+            .label(language.fill(Key::Quota, &[&quota]))
+            .push(tip(
+                Slider::new(
+                    &mut self.quota_state,
+                    1..=MAX_QUOTA,
+                    quota,
+                    Message::SetQuota,
+                )
+                .width(Length::FillPortion(1)),
+                language.text(Key::QuotaTip),
+            ))
+            .push(tip(
+                Checkbox::new(
+                    self.auto_apply,
+                    language.text(Key::AutoApply),
+                    Message::SetAutoApply,
+                ),
+                language.text(Key::AutoApplyTip),
+            ))
+            .push({
+                let apply = Button::new(
+                    &mut self.apply_rules_state,
+                    Text::new(language.text(Key::Apply)),
+                );
+                if pending {
+                    apply.on_press(Message::ApplyRules)
+                } else {
+                    apply
+                }
+            })
+            .push(if pending {
+                Text::new(language.text(Key::PendingChanges)).color(palette::OVERLAY_COLOR)
+            } else {
+                Text::new("")
+            })
+            .push(tip(
+                Checkbox::new(
+                    show_last_pick,
+                    language.text(Key::ShowLastPick),
+                    Message::SetShowLastPick,
+                ),
+                language.text(Key::ShowLastPickTip),
+            ));
+
+        let session_bar = Bar::new()
+            .label(language.text(Key::Session))
+            .push(
+                TextInput::new(
+                    &mut self.session_path_state,
+                    language.text(Key::File),
+                    &self.session_path,
+                    Message::SetSessionPath,
+                )
+                .padding(5)
+                .width(Length::Units(200)),
+            )
+            .push(
+                Button::new(
+                    &mut self.save_session_state,
+                    Text::new(language.text(Key::Save)),
+                )
+                .on_press(Message::SaveSession),
+            )
+            .push(
+                Button::new(
+                    &mut self.load_session_state,
+                    Text::new(language.text(Key::Load)),
+                )
+                .on_press(Message::LoadSession),
+            )
+            .push(tip(
+                Checkbox::new(
+                    self.merge_duplicates,
+                    language.text(Key::MergeDuplicates),
+                    Message::SetMergeDuplicates,
+                ),
+                language.text(Key::MergeDuplicatesTip),
+            ))
+            .push(tip(
+                {
+                    let label = match changes.len() {
+                        0 => language.text(Key::NoChanges).to_string(),
+                        n => language.fill(Key::Changes, &[&n]),
+                    };
+                    let button = Button::new(&mut self.changes_state, Text::new(label));
+                    if self.saved.is_some() {
+                        button.on_press(Message::ToggleChanges)
+                    } else {
+                        button
+                    }
+                },
+                language.text(Key::ChangesTip),
+            ))
+            .push(tip(
+                Button::new(
+                    &mut self.tutorial_state,
+                    Text::new(language.text(Key::Tutorial)),
+                )
+                .on_press(Message::StartTutorial),
+                language.text(Key::TutorialTip),
+            ));
+
+        let has_ghost = self.graph.ghost.is_empty().not();
+        let ghost_bar = Bar::new()
+            .label(language.text(Key::Ghost))
+            .push(tip(
+                Button::new(
+                    &mut self.pin_ghost_state,
+                    Text::new(language.text(Key::PinAsGhost)),
+                )
+                .on_press(Message::PinGhost),
+                language.text(Key::PinAsGhostTip),
+            ))
+            .push({
+                let clear = Button::new(
+                    &mut self.clear_ghost_state,
+                    Text::new(language.text(Key::ClearGhost)),
+                );
+                if has_ghost {
+                    clear.on_press(Message::ClearGhost)
+                } else {
+                    clear
+                }
+            })
+            .push(tip(
+                Checkbox::new(
+                    self.graph.ghost_in_exports,
+                    language.text(Key::IncludeInExports),
+                    Message::SetGhostInExports,
+                ),
+                language.text(Key::IncludeInExportsTip),
+            ));
+
+        let mut slot_bar = Bar::new().label(language.text(Key::Slots));
+        for (i, state) in self.store_slot_states.iter_mut().enumerate() {
+            slot_bar = slot_bar.push(tip(
+                Button::new(
+                    state,
+                    Text::new(language.fill(Key::StoreSlot, &[&SLOT_NAMES[i]])),
+                )
+                .on_press(Message::StoreSlot(i)),
+                language.text(Key::StoreSlotTip),
+            ));
+        }
+        let slot_bar = slot_bar
+            .push(tip(
+                Button::new(
+                    &mut self.flip_slot_state,
+                    Text::new(language.text(Key::FlipSlots)),
+                )
+                .on_press(Message::FlipSlot),
+                language.text(Key::FlipSlotsTip),
+            ))
+            .push(Text::new(slot_text));
+
+        let script_bar = Bar::new()
+            .label(language.text(Key::Script))
+            .push(
+                TextInput::new(
+                    &mut self.script_path_state,
+                    language.text(Key::File),
+                    &self.script_path,
+                    Message::SetScriptPath,
+                )
+                .padding(5)
+                .width(Length::Units(200)),
+            )
+            .push(
+                Button::new(
+                    &mut self.load_script_state,
+                    Text::new(language.text(Key::Run)),
+                )
+                .on_press(Message::LoadScript),
+            )
+            .push(tip(
+                Checkbox::new(
+                    self.recording.is_some(),
+                    language.text(Key::Record),
+                    Message::SetRecording,
+                ),
+                language.text(Key::RecordTip),
+            ));
+
+        let mut choices_bar = Bar::new()
+            .label(language.text(Key::Choices))
+            .push(
+                TextInput::new(
+                    &mut self.choices_path_state,
+                    language.text(Key::File),
+                    &self.choices_path,
+                    Message::SetChoicesPath,
+                )
+                .padding(5)
+                .width(Length::Units(200)),
+            )
+            .push(tip(
+                Button::new(
+                    &mut self.export_choices_state,
+                    Text::new(language.text(Key::Export)),
+                )
+                .on_press(Message::ExportChoices),
+                language.text(Key::ExportChoicesTip),
+            ))
+            .push(tip(
+                Button::new(
+                    &mut self.import_choices_state,
+                    Text::new(language.text(Key::Import)),
+                )
+                .on_press(Message::ImportChoices),
+                language.text(Key::ImportChoicesTip),
+            ));
+        if let Some(sequence) = &self.graph.sequence {
+            choices_bar = choices_bar
+                .push(Text::new(
+                    language.fill(Key::ReplayingChoices, &[&sequence.picks.len()]),
+                ))
+                .push(
+                    Button::new(
+                        &mut self.stop_replaying_state,
+                        Text::new(language.text(Key::StopReplaying)),
+                    )
+                    .on_press(Message::StopReplaying),
+                );
+        }
+
+        let mut content = Column::new()
+            .width(Length::Fill)
+            .align_items(iced::Align::Center)
+            .push(toolbar.wrap(per_row))
+            .push(layout_bar.wrap(per_row))
+            .push(circle_bar.wrap(per_row))
+            .push(style_bar.wrap(per_row))
+            .push(rule_bar.wrap(per_row))
+            .push(ghost_bar.wrap(per_row))
+            .push(slot_bar.wrap(per_row))
+            .push(session_bar.wrap(per_row))
+            .push(script_bar.wrap(per_row))
+            .push(choices_bar.wrap(per_row));
+        if let Some(text) = &self.coordinate_text {
+            content = content.push(
+                Row::new()
+                    .padding(10)
+                    .spacing(10)
+                    .align_items(iced::Align::Center)
+                    .push(Text::new(language.text(Key::PlaceVertexAt)))
+                    .push(
+                        TextInput::new(
+                            &mut self.coordinate_input_state,
+                            "x,y",
+                            text,
+                            Message::SetCoordinateText,
+                        )
+                        .on_submit(Message::SubmitCoordinates)
+                        .padding(5)
+                        .width(Length::Units(150)),
+                    )
+                    .push(Text::new(
+                        language.fill(Key::CoordinateRange, &[&bound.width, &bound.height]),
+                    )),
+            );
+        }
+        let tutorial = self
+            .tutorial
+            .as_ref()
+            .and_then(|progress| Some((progress, progress.current()?)));
+        if let Some((progress, step)) = tutorial {
+            let mut row = Row::new()
+                .padding(10)
+                .spacing(10)
+                .align_items(iced::Align::Center)
+                .push(Text::new(language.fill(
+                    Key::TutorialStep,
+                    &[
+                        &(progress.step + 1) as &dyn Display,
+                        &progress.tutorial.steps.len(),
+                        &language.text(step.instruction),
+                    ],
+                )));
+            if let Some(hint) = tutorial_hint {
+                row = row.push(Text::new(language.text(hint)).color(palette::OVERLAY_COLOR));
+            }
+            content = content.push(
+                row.push(
+                    Button::new(
+                        &mut self.skip_tutorial_step_state,
+                        Text::new(language.text(Key::Skip)),
+                    )
+                    .on_press(Message::SkipTutorialStep),
+                )
+                .push(
+                    Button::new(
+                        &mut self.exit_tutorial_state,
+                        Text::new(language.text(Key::Exit)),
+                    )
+                    .on_press(Message::ExitTutorial),
+                ),
+            );
+        }
+        if let Some(recovered) = &self.recovered {
+            content = content.push(
+                Row::new()
+                    .padding(10)
+                    .spacing(10)
+                    .align_items(iced::Align::Center)
+                    .push(Text::new(
+                        language.fill(Key::Recovered, &[&recovered.fix_points.len()]),
+                    ))
+                    .push(
+                        Button::new(
+                            &mut self.restore_state,
+                            Text::new(language.text(Key::Restore)),
+                        )
+                        .on_press(Message::RestoreRecovery),
+                    )
+                    .push(
+                        Button::new(
+                            &mut self.discard_recovery_state,
+                            Text::new(language.text(Key::Discard)),
+                        )
+                        .on_press(Message::DiscardRecovery),
+                    ),
+            );
+        }
+        if self.show_changes {
+            let list = if changes.is_empty() {
+                Column::new().push(Text::new(language.text(Key::NoChangesSinceSave)))
+            } else {
+                changes.iter().fold(Column::new(), |list, line| {
+                    list.push(Text::new(line.as_str()))
+                })
+            };
+            content = content.push(
+                list.padding(10).spacing(5).push(
+                    Row::new()
+                        .spacing(10)
+                        .push(tip(
+                            {
+                                let revert = Button::new(
+                                    &mut self.revert_state,
+                                    Text::new(language.text(Key::RevertAll)),
+                                );
+                                if changes.is_empty() {
+                                    revert
+                                } else {
+                                    revert.on_press(Message::RevertSession)
+                                }
+                            },
+                            language.text(Key::RevertAllTip),
+                        ))
+                        .push(
+                            Button::new(
+                                &mut self.close_changes_state,
+                                Text::new(language.text(Key::Close)),
+                            )
+                            .on_press(Message::ToggleChanges),
+                        ),
+                ),
+            );
+        }
+        if self.show_recent && fix_point_is_empty {
+            let entries = self.recent.shown();
+            let thumbnails = &self.thumbnails;
+            self.recent_states
+                .resize_with(entries.len(), button::State::default);
+            let gallery = entries.iter().zip(&mut self.recent_states).fold(
+                Row::new().spacing(5),
+                |gallery, (entry, state)| {
+                    let name = entry.path.file_name().map_or_else(
+                        || entry.path.display().to_string(),
+                        |name| name.to_string_lossy().into_owned(),
+                    );
+                    let preview = match thumbnails.get(&entry.path) {
+                        Some(thumbnail) => iced::Element::from(
+                            Canvas::new(Preview(thumbnail))
+                                .width(Length::Units(thumbnail::SIZE as u16))
+                                .height(Length::Units(thumbnail::SIZE as u16)),
+                        ),
+                        None => iced::Space::new(
+                            Length::Units(thumbnail::SIZE as u16),
+                            Length::Units(thumbnail::SIZE as u16),
+                        )
+                        .into(),
+                    };
+                    gallery.push(
+                        Button::new(
+                            state,
+                            Column::new()
+                                .spacing(5)
+                                .align_items(iced::Align::Center)
+                                .push(preview)
+                                .push(Text::new(language.fill(
+                                    Key::RecentEntry,
+                                    &[
+                                        &name as &dyn Display,
+                                        &entry.vertices,
+                                        &entry.points,
+                                        &ago(entry.timestamp, language),
+                                    ],
+                                ))),
+                        )
+                        .width(Length::FillPortion(1))
+                        .on_press(Message::OpenRecent(entry.path.clone())),
+                    )
+                },
+            );
+            content = content.push(
+                Column::new()
+                    .padding(10)
+                    .spacing(5)
+                    .push(Text::new(language.text(Key::RecentSessions)))
+                    .push(gallery)
+                    .push(
+                        Button::new(
+                            &mut self.start_empty_state,
+                            Text::new(language.text(Key::StartEmpty)),
+                        )
+                        .on_press(Message::StartEmpty),
+                    ),
+            );
+        }
+        let selected_vertex = self.graph.selected_vertex;
+        let vertex_rows = self
+            .graph
+            .fix_points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let graph = &self.graph;
+                (
+                    *p,
+                    graph.is_enabled(i),
+                    graph.is_locked(i),
+                    graph.weight(i),
+                    graph.vertex_color(i),
+                )
+            })
+            .collect::<Vec<_>>();
+        self.vertex_row_states
+            .resize_with(vertex_rows.len(), Default::default);
+        let vertex_list = vertex_rows
+            .into_iter()
+            .zip(&mut self.vertex_row_states)
+            .enumerate()
+            .fold(
+                Column::new()
+                    .padding(10)
+                    .spacing(5)
+                    .width(Length::Units(VERTEX_LIST_WIDTH))
+                    .push(Text::new(language.text(Key::Vertices))),
+                |column, (i, ((p, enabled, locked, weight, color), state))| {
+                    let selected = selected_vertex == Some(i);
+                    column.push(
+                        Row::new()
+                            .spacing(10)
+                            .align_items(iced::Align::Center)
+                            .push(
+                                Button::new(
+                                    &mut state.select,
+                                    Text::new(vertex_label(i)).color(color),
+                                )
+                                .on_press(Message::SelectVertex(selected.not().then_some(i))),
+                            )
+                            .push(tip(
+                                Button::new(
+                                    &mut state.color,
+                                    Text::new(language.text(Key::VertexColour)).color(color),
+                                )
+                                .padding(3)
+                                .on_press(Message::CycleVertexColor(i)),
+                                language.text(Key::VertexColourTip),
+                            ))
+                            .push(Text::new(format!("#{}", i)).width(Length::Units(30)))
+                            .push(
+                                Text::new(format!(
+                                    "({}, {})",
+                                    Fixed::new(p.x, decimals),
+                                    Fixed::new(p.y, decimals)
+                                ))
+                                .width(Length::Units(70 + 20 * decimals as u16)),
+                            )
+                            .push(Text::new(
+                                language.fill(Key::WeightShort, &[&format!("{:.1}", weight)]),
+                            ))
+                            .push(Checkbox::new(
+                                enabled,
+                                language.text(Key::On),
+                                move |enabled| Message::SetVertexEnabled(i, enabled),
+                            ))
+                            .push(Checkbox::new(
+                                locked,
+                                language.text(Key::Lock),
+                                move |locked| Message::SetVertexLocked(i, locked),
+                            ))
+                            .push(
+                                Button::new(
+                                    &mut state.insert_before,
+                                    Text::new(language.text(Key::Before)),
+                                )
+                                .padding(3)
+                                .on_press(Message::ArmInsertion(Some(i))),
+                            )
+                            .push(
+                                Button::new(
+                                    &mut state.insert_after,
+                                    Text::new(language.text(Key::After)),
+                                )
+                                .padding(3)
+                                .on_press(Message::ArmInsertion(Some(i + 1))),
+                            )
+                            .push(
+                                Button::new(
+                                    &mut state.delete,
+                                    Text::new(language.text(Key::Delete)),
+                                )
+                                .padding(3)
+                                .on_press(Message::DeleteVertex(i)),
+                            ),
+                    )
+                },
+            );
+        let vertex_legend = self
+            .graph
+            .orbit_vertices
+            .iter()
+            .map(|i| (*i, self.graph.vertex_color(*i)))
+            .collect::<Vec<_>>();
+        let vertex_list = if self.graph.vertex_colors.is_empty() {
+            vertex_list
+        } else {
+            vertex_list.push(
+                Button::new(
+                    &mut self.reset_vertex_colors_state,
+                    Text::new(language.text(Key::ResetColours)),
+                )
+                .on_press(Message::ResetVertexColors),
+            )
+        };
+        let vertex_list = if self.graph.locked.contains(&true) {
+            vertex_list.push(tip(
+                Button::new(
+                    &mut self.unlock_all_state,
+                    Text::new(language.text(Key::UnlockAll)),
+                )
+                .on_press(Message::UnlockAll),
+                language.text(Key::UnlockAllTip),
+            ))
+        } else {
+            vertex_list
+        };
+        let canvas = Canvas::new(&mut self.graph)
+            .width(Length::Units(shown.width as u16))
+            .height(Length::Units(shown.height as u16));
+        content = match breakpoint {
+            Breakpoint::Wide => content.push(Row::new().push(canvas).push(vertex_list)),
+            Breakpoint::Narrow => content.push(canvas).push(vertex_list),
+        };
+        if point_coloring == PointColoring::Sector {
+            content = content.push(
+                (0..sector_count as usize).fold(
+                    Row::new()
+                        .padding(10)
+                        .spacing(10)
+                        .push(Text::new(language.text(Key::Legend))),
+                    |row, sector| {
+                        row.push(
+                            Text::new(language.fill(Key::Sector, &[&(sector + 1)]))
+                                .color(palette::categorical(sector)),
+                        )
+                    },
+                ),
+            );
+        }
+        if point_coloring == PointColoring::Vertex {
+            content = content.push(
+                vertex_legend.into_iter().fold(
+                    Row::new()
+                        .padding(10)
+                        .spacing(10)
+                        .push(Text::new(language.text(Key::Legend))),
+                    |row, (i, color)| row.push(Text::new(vertex_label(i)).color(color)),
+                ),
+            );
+        }
+        if point_coloring == PointColoring::Recency {
+            content = content.push(
+                [(0.0, Key::First), (0.5, Key::Halfway), (1.0, Key::Latest)]
+                    .into_iter()
+                    .fold(
+                        Row::new()
+                            .padding(10)
+                            .spacing(10)
+                            .push(Text::new(language.text(Key::Legend))),
+                        |row, (t, label)| {
+                            row.push(Text::new(language.text(label)).color(ramp.at(t)))
+                        },
+                    ),
+            );
+        }
+        if point_coloring == PointColoring::Density {
+            content = content.push(
+                [(0.0, Key::Sparse), (1.0, Key::Dense)].into_iter().fold(
+                    Row::new()
+                        .padding(10)
+                        .spacing(10)
+                        .push(Text::new(language.text(Key::Legend))),
+                    |row, (t, label)| row.push(Text::new(language.text(label)).color(ramp.at(t))),
+                ),
+            );
+        }
+        if seed_count > 1 {
+            content = content.push(
+                seed_counts.iter().enumerate().fold(
+                    Row::new()
+                        .padding(10)
+                        .spacing(10)
+                        .push(Text::new(language.text(Key::PointsPerSeed))),
+                    |row, (seed, count)| {
+                        let text = Text::new(format!("{}: {}", seed + 1, count));
+                        row.push(if point_coloring == PointColoring::Seed {
+                            text.color(palette::categorical(seed))
+                        } else {
+                            text
+                        })
+                    },
+                ),
+            );
+        }
+        if fix_point_is_empty.not() {
+            content = content
+                .push(
+                    Row::new()
+                        .padding(10)
+                        .spacing(20)
+                        .push(Text::new(language.fill(Key::MaxIter, &[&max_iter])))
+                        .push(
+                            Wheel::new(
+                                &mut self.max_iter_wheel,
+                                Slider::new(
+                                    &mut self.max_iter_state,
+                                    0..=iter_limit,
+                                    max_iter,
+                                    Message::SetMaxIter,
+                                )
+                                .width(Length::Fill),
+                                move |direction, modifiers| {
+                                    Message::SetMaxIter(
+                                        (max_iter + direction * iteration_step(modifiers))
+                                            .clamp(0, iter_limit),
+                                    )
+                                },
+                            )
+                            .min_width(MIN_SLIDER_WIDTH),
+                        )
+                        .push(tip(
+                            Checkbox::new(
+                                link_sliders,
+                                language.text(Key::Link),
+                                Message::SetLinkSliders,
+                            ),
+                            language.text(Key::LinkTip),
+                        ))
+                        .push(Text::new(language.text(Key::CurIterOnChange)))
+                        .push(tip(
+                            pick_list(
+                                &mut self.cur_iter_policy_state,
+                                &CurIterPolicy::ALL[..],
+                                Some(cur_iter_policy),
+                                language,
+                                Message::SetCurIterPolicy,
+                            ),
+                            language.text(Key::CurIterOnChangeTip),
+                        )),
+                )
+                .push(
+                    Row::new()
+                        .padding(10)
+                        .spacing(20)
+                        .push(Text::new(language.fill(
+                            Key::CurIter,
+                            &[
+                                &group_digits(cur_iter),
+                                &group_digits(max_iter),
+                                &format!("{:.0}", fraction),
+                            ],
+                        )))
+                        .push(
+                            Wheel::new(
+                                &mut self.cur_iter_wheel,
+                                Slider::new(
+                                    &mut self.cur_iter_state,
+                                    0..=iter_limit,
+                                    cur_iter,
+                                    Message::SetCurIter,
+                                )
+                                .width(Length::Fill),
+                                move |direction, modifiers| {
+                                    Message::SetCurIter(
+                                        (cur_iter + direction * iteration_step(modifiers))
+                                            .clamp(0, iter_limit),
+                                    )
+                                },
+                            )
+                            .min_width(MIN_SLIDER_WIDTH),
+                        )
+                        .push(tip(
+                            Slider::new(
+                                &mut self.cur_fraction_state,
+                                0.0..=100.0,
+                                fraction,
+                                Message::SetCurFraction,
+                            )
+                            .width(Length::Units(100)),
+                            language.text(Key::CurFractionTip),
+                        )),
+                )
+                .push({
+                    let extend_by = numeric::parse_count(&self.extend_text)
+                        .ok()
+                        .filter(|points| *points > 0 && extending.is_none());
+                    let mut input = TextInput::new(
+                        &mut self.extend_input_state,
+                        language.text(Key::PointCount),
+                        &self.extend_text,
+                        Message::SetExtendText,
+                    )
+                    .padding(5)
+                    .width(Length::Units(100));
+                    let mut add = Button::new(
+                        &mut self.extend_state,
+                        Text::new(language.text(Key::AddPoints)),
+                    );
+                    if let Some(points) = extend_by {
+                        input = input.on_submit(Message::ExtendBy(points));
+                        add = add.on_press(Message::ExtendBy(points));
+                    }
+                    let bar = Bar::new()
+                        .push(Text::new(language.text(Key::Add)))
+                        .then(input)
+                        .push(tip(add, language.text(Key::AddPointsTip)))
+                        .push(Checkbox::new(
+                            self.advance_on_extend,
+                            language.text(Key::MoveCurIterToEnd),
+                            Message::SetAdvanceOnExtend,
+                        ))
+                        .push(tip(
+                            Checkbox::new(
+                                self.refine_when_idle,
+                                language.text(Key::RefineWhenIdle),
+                                Message::SetRefineWhenIdle,
+                            ),
+                            language.text(Key::RefineWhenIdleTip),
+                        ))
+                        .push(PickList::new(
+                            &mut self.refine_batch_state,
+                            &REFINE_BATCHES[..],
+                            Some(self.refine_batch),
+                            Message::SetRefineBatch,
+                        ))
+                        .push(if self.refining {
+                            Text::new(language.fill(Key::Refining, &[&group_digits(max_iter)]))
+                                .color(palette::GUIDE_COLOR)
+                        } else {
+                            Text::new("")
+                        });
+                    match extending {
+                        Some(progress) => bar
+                            .push(ProgressBar::new(0.0..=1.0, progress).width(Length::Units(150))),
+                        None => bar,
+                    }
+                    .wrap(per_row)
+                })
+                .push(
+                    Bar::new()
+                        .push(
+                            Button::new(
+                                &mut self.play_state,
+                                Text::new(language.text(match (animations, playing) {
+                                    (false, _) => Key::JumpToEnd,
+                                    (true, true) => Key::Pause,
+                                    (true, false) => Key::Play,
+                                })),
+                            )
+                            .on_press(Message::TogglePlayback),
+                        )
+                        .push(tip(
+                            Checkbox::new(
+                                animations,
+                                language.text(Key::Animations),
+                                Message::SetAnimations,
+                            ),
+                            language.text(Key::AnimationsTip),
+                        ))
+                        .push(tip(
+                            Checkbox::new(
+                                suggest_points,
+                                language.text(Key::SuggestPoints),
+                                Message::SetSuggestPoints,
+                            ),
+                            language.text(Key::SuggestPointsTip),
+                        ))
+                        .push(tip(
+                            Checkbox::new(
+                                capture_on_complete,
+                                language.text(Key::CaptureOnComplete),
+                                Message::SetCaptureOnComplete,
+                            ),
+                            language.text(Key::CaptureOnCompleteTip),
+                        ))
+                        .push(
+                            TextInput::new(
+                                &mut self.capture_dir_state,
+                                language.text(Key::Directory),
+                                &self.capture_dir,
+                                Message::SetCaptureDir,
+                            )
+                            .padding(5)
+                            .width(Length::Units(200)),
+                        )
+                        .label(language.text(Key::Supersample))
+                        .push(tip(
+                            PickList::new(
+                                &mut self.supersample_state,
+                                &SUPERSAMPLE[..],
+                                Some(supersample),
+                                Message::SetSupersample,
+                            ),
+                            language.text(Key::SupersampleTip),
+                        ))
+                        .push(tip(
+                            Checkbox::new(
+                                additive_export,
+                                language.text(Key::AdditiveColour),
+                                Message::SetAdditiveExport,
+                            ),
+                            language.text(Key::AdditiveColourTip),
+                        ))
+                        .push(PickList::new(
+                            &mut self.point_cloud_state,
+                            &PointCloud::ALL[..],
+                            Some(point_cloud),
+                            Message::SetPointCloud,
+                        ))
+                        .push(tip(
+                            Button::new(
+                                &mut self.export_points_state,
+                                Text::new(language.text(Key::ExportPoints)),
+                            )
+                            .on_press(Message::ExportPointCloud),
+                            language.text(Key::ExportPointsTip),
+                        ))
+                        .push(tip(
+                            Button::new(
+                                &mut self.export_stats_state,
+                                Text::new(language.text(Key::ExportStats)),
+                            )
+                            .on_press(Message::ExportStats),
+                            language.text(Key::ExportStatsTip),
+                        ))
+                        .label(language.text(Key::SvgBudget))
+                        .push(tip(
+                            PickList::new(
+                                &mut self.svg_budget_state,
+                                &SVG_BUDGETS[..],
+                                Some(svg_budget),
+                                Message::SetSvgBudget,
+                            ),
+                            language.text(Key::SvgBudgetTip),
+                        ))
+                        .then(tip(
+                            Button::new(
+                                &mut self.export_svg_state,
+                                Text::new(language.text(Key::ExportSvg)),
+                            )
+                            .on_press(Message::ExportPointsSvg),
+                            language.text(Key::ExportSvgPointsTip),
+                        ))
+                        .wrap(per_row),
+                )
+                .push({
+                    let mut render = Button::new(
+                        &mut self.gallery_state,
+                        Text::new(language.text(Key::Gallery)),
+                    );
+                    if gallery_progress.is_none() {
+                        render = render.on_press(Message::RenderGallery);
+                    }
+                    let bar = Bar::new()
+                        .push(tip(render, language.text(Key::GalleryTip)))
+                        .label(language.text(Key::Varying))
+                        .push(pick_list(
+                            &mut self.gallery_parameter_state,
+                            &Parameter::ALL[..],
+                            Some(gallery.parameter),
+                            language,
+                            Message::SetGalleryParameter,
+                        ))
+                        .label(language.text(Key::Tiles))
+                        .push(PickList::new(
+                            &mut self.gallery_tiles_state,
+                            &GALLERY_TILES[..],
+                            Some(gallery.tiles),
+                            Message::SetGalleryTiles,
+                        ))
+                        .label(language.text(Key::PointsPerTile))
+                        .push(PickList::new(
+                            &mut self.gallery_iterations_state,
+                            &GALLERY_ITERATIONS[..],
+                            Some(gallery.iterations),
+                            Message::SetGalleryIterations,
+                        ));
+                    let bar = match gallery.parameter.range() {
+                        Some((low, high)) => bar
+                            .label(language.text(Key::From))
+                            .push(tip(
+                                TextInput::new(
+                                    &mut self.gallery_from_state,
+                                    &low.to_string(),
+                                    &self.gallery_from,
+                                    Message::SetGalleryFrom,
+                                )
+                                .padding(5)
+                                .width(Length::Units(60)),
+                                language.text(Key::FromTip),
+                            ))
+                            .label(language.text(Key::To))
+                            .push(tip(
+                                TextInput::new(
+                                    &mut self.gallery_to_state,
+                                    &high.to_string(),
+                                    &self.gallery_to,
+                                    Message::SetGalleryTo,
+                                )
+                                .padding(5)
+                                .width(Length::Units(60)),
+                                language.text(Key::ToTip),
+                            )),
+                        None => bar,
+                    };
+                    match gallery_progress {
+                        Some(progress) => bar
+                            .push(ProgressBar::new(0.0..=1.0, progress).width(Length::Units(150))),
+                        None => bar,
+                    }
+                    .wrap(per_row)
+                })
+                .push(
+                    Bar::new()
+                        .push({
+                            let replay = Button::new(
+                                &mut self.replay_state,
+                                Text::new(language.text(if replaying {
+                                    Key::StopReplay
+                                } else {
+                                    Key::ReplayOrbit
+                                })),
+                            );
+                            // Replay is nothing but an animation.
+                            if animations {
+                                replay.on_press(Message::ToggleReplay)
+                            } else {
+                                replay
+                            }
+                        })
+                        .label(language.fill(Key::Speed, &[&format!("{:.0}", replay_speed)]))
+                        .push(tip(
+                            Slider::new(
+                                &mut self.replay_speed_state,
+                                1.0..=30.0,
+                                replay_speed,
+                                Message::SetReplaySpeed,
+                            )
+                            .width(Length::FillPortion(1)),
+                            language.text(Key::SpeedTip),
+                        ))
+                        .label(language.fill(Key::Trail, &[&replay_trail]))
+                        .push(tip(
+                            Slider::new(
+                                &mut self.replay_trail_state,
+                                1..=50,
+                                replay_trail,
+                                Message::SetReplayTrail,
+                            )
+                            .width(Length::FillPortion(1)),
+                            language.text(Key::TrailTip),
+                        ))
+                        .wrap(per_row),
+                );
+
+            let total = counts.iter().sum::<usize>().max(1) as f32;
+            let weight_sum = applied_weights.iter().sum::<f32>();
+            self.weight_states
+                .resize_with(counts.len(), slider::State::default);
+            content = content.push(self.weight_states.iter_mut().zip(counts).enumerate().fold(
+                Column::new().padding(10).spacing(5),
+                |column, (i, (state, count))| {
+                    let weight = weights[i];
+                    let share = count as f32 / total;
+                    column.push(
+                        Row::new()
+                            .spacing(20)
+                            .align_items(iced::Align::Center)
+                            .push(Text::new(vertex_label(i)).width(Length::Units(30)))
+                            .push(Text::new(
+                                language.fill(Key::Weight, &[&format!("{:.1}", weight)]),
+                            ))
+                            .push(Press::new(
+                                Slider::new(state, 0.1..=5.0, weight, move |w| {
+                                    Message::SetVertexWeight(i, w)
+                                })
+                                .on_release(Message::ReleaseRuleSlider)
+                                .step(0.1)
+                                .width(Length::Units(150)),
+                                Message::BeginGesture,
+                            ))
+                            .push(ProgressBar::new(0.0..=1.0, share).width(Length::Units(200)))
+                            .push(Text::new({
+                                let share = format!("{:.1}", share * 100.0);
+                                let expected =
+                                    format!("{:.1}", applied_weights[i] / weight_sum * 100.0);
+                                match quota_share {
+                                    Some(quota) => language.fill(
+                                        Key::ShareWithQuota,
+                                        &[&share, &expected, &format!("{:.1}", quota * 100.0)],
+                                    ),
+                                    None => language.fill(Key::Share, &[&share, &expected]),
+                                }
+                            })),
+                    )
+                },
+            ));
+            content = content.push(
+                Row::new()
+                    .padding(10)
+                    .spacing(20)
+                    .align_items(iced::Align::Center)
+                    .push(Text::new(language.text(Key::OffCanvas)))
+                    .push(tip(
+                        pick_list(
+                            &mut self.out_of_bounds_state,
+                            &OutOfBounds::ALL[..],
+                            Some(out_of_bounds),
+                            language,
+                            Message::SetOutOfBounds,
+                        ),
+                        language.text(Key::OutOfBoundsTip),
+                    ))
+                    .push(tip(
+                        Checkbox::new(
+                            auto_frame,
+                            language.text(Key::AutoFrame),
+                            Message::SetAutoFrame,
+                        ),
+                        language.text(Key::AutoFrameTip),
+                    ))
+                    .push(Text::new(
+                        language.fill(Key::OutOfBounds, &[&outside, &visible]),
+                    ))
+                    .push(tip(
+                        Text::new(language.fill(
+                            Key::PixelsCovered,
+                            &[
+                                &group_digits(occupied as i32),
+                                &format!("{:.1}", occupied as f32 / screen_pixels.max(1.0) * 100.0),
+                            ],
+                        )),
+                        language.text(Key::PixelsCoveredTip),
+                    )),
+            );
+        }
+        if vertices.len() >= 2 {
+            // Normalized units measure lengths in canvas sides.
+            let unit = bound.width.min(bound.height);
+            let edges = geometry::edge_lengths(&vertices)
+                .iter()
+                .enumerate()
+                .map(|(i, length)| {
+                    format!(
+                        "{}{}: {} ({:.3})",
+                        vertex_label(i),
+                        vertex_label((i + 1) % vertices.len()),
+                        Fixed::new(*length, decimals).unit("px"),
+                        length / unit
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let area = geometry::signed_area(&vertices).abs();
+            let mut geometry_stats = Column::new()
+                .padding(10)
+                .spacing(5)
+                .push(Text::new(language.fill(Key::Edges, &[&edges])))
+                .push(Text::new(language.fill(
+                    Key::Perimeter,
+                    &[
+                        &Fixed::new(geometry::perimeter(&vertices), decimals) as &dyn Display,
+                        &format!("{:.3}", geometry::perimeter(&vertices) / unit),
+                        &Fixed::new(area, decimals),
+                        &format!("{:.4}", area / (unit * unit)),
+                    ],
+                )));
+            if geometry::is_self_intersecting(&vertices) {
+                geometry_stats = geometry_stats.push(
+                    Text::new(language.text(Key::SelfIntersecting)).color(palette::OVERLAY_COLOR),
+                );
+            }
+            content = content.push(geometry_stats);
+        }
+        if self.status.is_empty().not() {
+            content = content.push(Text::new(&self.status).size(16));
+        }
+        Scrollable::new(&mut self.scroll_state).push(content).into()
+    }
+}
+
+/// Names vertices A, B, ..., Z, AA, AB, ... in the order they were placed.
+fn vertex_label(index: usize) -> String {
+    let letter = (b'A' + (index % 26) as u8) as char;
+    if index < 26 {
+        letter.to_string()
+    } else {
+        format!("{}{}", vertex_label(index / 26 - 1), letter)
+    }
+}
+
+fn apply_viewport(frame: &mut Frame, viewport: Viewport) {
+    let [x, y] = viewport.offset;
+    frame.translate(Vector::new(x, y));
+    frame.scale(viewport.scale);
+}
+
+/// A rough age for a Unix `timestamp` in `language`, like "5 min ago".
+fn ago(timestamp: u64, language: Language) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    match now.saturating_sub(timestamp) {
+        s if s < 60 => language.text(Key::JustNow).to_string(),
+        s if s < 60 * 60 => language.fill(Key::MinutesAgo, &[&(s / 60)]),
+        s if s < 24 * 60 * 60 => language.fill(Key::HoursAgo, &[&(s / (60 * 60))]),
+        s => language.fill(Key::DaysAgo, &[&(s / (24 * 60 * 60))]),
+    }
+}
+
+/// Inserts `value` at `index` into a per-vertex list that may be shorter
+/// than the vertices, where missing entries already mean `value`.
+fn insert_at<T>(list: &mut Vec<T>, index: usize, value: T) {
+    if index <= list.len() {
+        list.insert(index, value);
+    }
+}
+
+/// Removes `index` from a per-vertex list that may be shorter than the
+/// vertices.
+fn remove_at<T>(list: &mut Vec<T>, index: usize) {
+    if index < list.len() {
+        list.remove(index);
+    }
+}
+
+/// `selection` after removing vertex `index`: its picks are dropped and later
+/// ones move down by one. Random picks stand in for a list left empty.
+fn without_vertex(selection: &Selection, index: usize) -> Selection {
+    let picks = match selection {
+        Selection::Random => return Selection::Random,
+        Selection::ManualSequence(picks) => picks,
+    };
+    let kept = (0..picks.iter().max().map_or(0, |max| max + 1))
+        .filter(|vertex| *vertex != index)
+        .collect::<Vec<_>>();
+    match selection.renumbered(&kept) {
+        Selection::ManualSequence(picks) if picks.is_empty() => Selection::Random,
+        renumbered => renumbered,
+    }
+}
+
+/// `percent` of `max_iter`, rounded but never outside `0..=max_iter`.
+fn percent_of(percent: f32, max_iter: i32) -> i32 {
+    ((percent / 100.0 * max_iter as f32).round() as i32).clamp(0, max_iter.max(0))
+}
+
+/// `n` with its digits in groups of three, like "10 000".
+fn group_digits(n: i32) -> String {
+    let digits = n.unsigned_abs().to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(' ');
+        }
+        grouped.push(digit);
+    }
+    if n < 0 {
+        grouped.insert(0, '-');
+    }
+    grouped
+}
+
+/// Where copied text goes: the system clipboard in the window.
+trait Clipboard {
+    fn write(&mut self, contents: String);
+}
+
+impl Clipboard for iced::Clipboard {
+    fn write(&mut self, contents: String) {
+        iced::Clipboard::write(self, contents)
+    }
+}
+
+/// Wraps `content` in a hover tooltip explaining it.
+fn tip<'a>(content: impl Into<iced::Element<'a, Message>>, text: &str) -> Tooltip<'a, Message> {
+    Tooltip::new(content, text, tooltip::Position::Bottom)
+        .size(16)
+        .gap(5)
+        .padding(5)
+        .style(palette::TooltipStyle)
+}
+
+/// An option of a pick list or a radio button, named in the language of
+/// the interface. The `Display` of the types stays English, for metadata
+/// and reports.
+trait Choice: Copy + Eq + 'static {
+    fn key(self) -> Key;
+}
+
+impl Choice for Tool {
+    fn key(self) -> Key {
+        match self {
+            Tool::Place => Key::ToolPlace,
+            Tool::Eraser => Key::ToolEraser,
+            Tool::Select => Key::ToolSelect,
+            Tool::Measure => Key::ToolMeasure,
+        }
+    }
+}
+
+impl Choice for RightClick {
+    fn key(self) -> Key {
+        match self {
+            RightClick::Menu => Key::RightClickMenu,
+            RightClick::RemoveLast => Key::RightClickRemoveLast,
+        }
+    }
+}
+
+impl Choice for VertexMarker {
+    fn key(self) -> Key {
+        match self {
+            VertexMarker::FilledCircle => Key::VertexMarkerFilledCircle,
+            VertexMarker::Ring => Key::VertexMarkerRing,
+            VertexMarker::Cross => Key::VertexMarkerCross,
+            VertexMarker::Square => Key::VertexMarkerSquare,
+        }
+    }
+}
+
+impl Choice for PointColoring {
+    fn key(self) -> Key {
+        match self {
+            PointColoring::Uniform => Key::PointColoringUniform,
+            PointColoring::Sector => Key::PointColoringSector,
+            PointColoring::Seed => Key::PointColoringSeed,
+            PointColoring::Forced => Key::PointColoringForced,
+            PointColoring::Vertex => Key::PointColoringVertex,
+            PointColoring::Recency => Key::PointColoringRecency,
+            PointColoring::Density => Key::PointColoringDensity,
+        }
+    }
+}
+
+impl Choice for PointShape {
+    fn key(self) -> Key {
+        match self {
+            PointShape::Square => Key::PointShapeSquare,
+            PointShape::Circle => Key::PointShapeCircle,
+            PointShape::Soft => Key::PointShapeSoft,
+        }
+    }
+}
+
+impl Choice for Ramp {
+    fn key(self) -> Key {
+        match self {
+            Ramp::Viridis => Key::RampViridis,
+            Ramp::Grayscale => Key::RampGrayscale,
+            Ramp::Fire => Key::RampFire,
+        }
+    }
+}
+
+impl Choice for CountScale {
+    fn key(self) -> Key {
+        match self {
+            CountScale::Linear => Key::CountScaleLinear,
+            CountScale::Log => Key::CountScaleLog,
+        }
+    }
+}
+
+impl Choice for Preset {
+    fn key(self) -> Key {
+        match self {
+            Preset::Triangle => Key::PresetTriangle,
+            Preset::Square => Key::PresetSquare,
+            Preset::Pentagon => Key::PresetPentagon,
+            Preset::Hexagon => Key::PresetHexagon,
+        }
+    }
+}
+
+impl Choice for Theme {
+    fn key(self) -> Key {
+        match self {
+            Theme::Light => Key::ThemeLight,
+            Theme::Dark => Key::ThemeDark,
+        }
+    }
+}
+
+impl Choice for BorderColor {
+    fn key(self) -> Key {
+        match self {
+            BorderColor::Points => Key::BorderColorPoints,
+            BorderColor::Grey => Key::BorderColorGrey,
+            BorderColor::Blue => Key::BorderColorBlue,
+            BorderColor::Red => Key::BorderColorRed,
+        }
+    }
+}
+
+impl Choice for Rule {
+    fn key(self) -> Key {
+        match self {
+            Rule::Vertex => Key::RuleVertex,
+            Rule::Midpoint => Key::RuleMidpoint,
+        }
+    }
+}
+
+impl Choice for Restriction {
+    fn key(self) -> Key {
+        match self {
+            Restriction::None => Key::RestrictionNone,
+            Restriction::NoRepeat => Key::RestrictionNoRepeat,
+        }
+    }
+}
+
+impl Choice for OrbitStart {
+    fn key(self) -> Key {
+        match self {
+            OrbitStart::Scattered => Key::OrbitStartScattered,
+            OrbitStart::Vertices => Key::OrbitStartVertices,
+        }
+    }
+}
+
+impl Choice for CurIterPolicy {
+    fn key(self) -> Key {
+        match self {
+            CurIterPolicy::Hold => Key::CurIterPolicyHold,
+            CurIterPolicy::FollowMax => Key::CurIterPolicyFollowMax,
+            CurIterPolicy::Proportional => Key::CurIterPolicyProportional,
+        }
+    }
+}
+
+impl Choice for Parameter {
+    fn key(self) -> Key {
+        match self {
+            Parameter::Ratio => Key::ParameterRatio,
+            Parameter::Rotation => Key::ParameterRotation,
+            Parameter::Restriction => Key::ParameterRestriction,
+            Parameter::VertexCount => Key::ParameterVertexCount,
+            Parameter::Seed => Key::ParameterSeed,
+        }
+    }
+}
+
+impl Choice for OutOfBounds {
+    fn key(self) -> Key {
+        match self {
+            OutOfBounds::Clip => Key::OutOfBoundsClip,
+            OutOfBounds::Clamp => Key::OutOfBoundsClamp,
+            OutOfBounds::Rescale => Key::OutOfBoundsRescale,
+        }
+    }
+}
+
+/// `value` with its name in `language`, for the widgets that show options
+/// by `Display`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Labelled<T> {
+    value: T,
+    language: Language,
+}
+
+impl<T: Choice> Display for Labelled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.language.text(self.value.key()))
+    }
+}
+
+/// A pick list of `options` named in `language`.
+fn pick_list<'a, T: Choice>(
+    state: &'a mut pick_list::State<Labelled<T>>,
+    options: &[T],
+    selected: Option<T>,
+    language: Language,
+    on_selected: impl Fn(T) -> Message + 'static,
+) -> PickList<'a, Labelled<T>, Message> {
+    let label = move |value| Labelled { value, language };
+    PickList::new(
+        state,
+        options.iter().copied().map(label).collect::<Vec<_>>(),
+        selected.map(label),
+        move |choice: Labelled<T>| on_selected(choice.value),
+    )
+}
+
+/// The message of a key or window event the widgets left over, if any.
+fn shortcut(event: iced_native::Event, status: iced_native::event::Status) -> Option<Message> {
+    use iced::keyboard::{Event::*, KeyCode};
+    // Keys typed into a text input are its own, not shortcuts. Every input
+    // captures them while focused, so none needs naming here.
+    if let (iced_native::Event::Keyboard(_), iced_native::event::Status::Captured) =
+        (&event, status)
+    {
+        return None;
+    }
+    match event {
+        iced_native::Event::Keyboard(KeyPressed {
+            key_code: KeyCode::Space,
+            ..
+        }) => Some(Message::Peek(true)),
+        iced_native::Event::Keyboard(KeyReleased {
+            key_code: KeyCode::Space,
+            ..
+        }) => Some(Message::Peek(false)),
+        iced_native::Event::Keyboard(KeyPressed {
+            key_code: KeyCode::Escape,
+            ..
+        }) => Some(Message::ArmInsertion(None)),
+        iced_native::Event::Keyboard(KeyPressed {
+            key_code: KeyCode::M,
+            ..
+        }) => Some(Message::HoldLoupe(true)),
+        iced_native::Event::Keyboard(KeyPressed {
+            key_code: KeyCode::P,
+            modifiers,
+        }) if modifiers.is_command_pressed().not() => Some(Message::OpenCoordinateEntry),
+        iced_native::Event::Keyboard(KeyReleased {
+            key_code: KeyCode::M,
+            ..
+        }) => Some(Message::HoldLoupe(false)),
+        iced_native::Event::Keyboard(KeyPressed {
+            key_code: KeyCode::Tab,
+            modifiers,
+        }) if modifiers.is_command_pressed().not() => Some(Message::FlipSlot),
+        iced_native::Event::Keyboard(KeyPressed {
+            key_code: KeyCode::Z,
+            modifiers,
+        }) if modifiers.is_command_pressed() => Some(if modifiers.shift {
+            Message::Redo
+        } else {
+            Message::Undo
+        }),
+        iced_native::Event::Keyboard(KeyPressed {
+            key_code: KeyCode::Y,
+            modifiers,
+        }) if modifiers.is_command_pressed() => Some(Message::Redo),
+        iced_native::Event::Keyboard(KeyPressed {
+            key_code: KeyCode::L,
+            modifiers,
+        }) if modifiers.is_command_pressed() => Some(Message::ToggleSelectedLock),
+        iced_native::Event::Keyboard(ModifiersChanged(modifiers)) => {
+            Some(Message::ModifiersChanged(modifiers))
+        }
+        iced_native::Event::Window(iced_native::window::Event::Resized { width, height }) => {
+            Some(Message::WindowResized(width, height))
+        }
+        iced_native::Event::Window(iced_native::window::Event::CloseRequested) => {
+            Some(Message::CloseRequested)
+        }
+        _ => None,
+    }
+}
+
+/// How far one wheel notch moves the iteration sliders.
+fn iteration_step(modifiers: iced::keyboard::Modifiers) -> i32 {
+    if modifiers.control {
+        500
+    } else if modifiers.shift {
+        50
+    } else {
+        1
+    }
+}
+
+fn color_to_array(color: Color) -> [f32; 4] {
+    [color.r, color.g, color.b, color.a]
+}
+
+/// The rectangle spanned by two opposite corners.
+fn rectangle_between(a: Point, b: Point) -> Rectangle {
+    Rectangle::new(
+        Point::new(a.x.min(b.x), a.y.min(b.y)),
+        Size::new((a.x - b.x).abs(), (a.y - b.y).abs()),
+    )
+}
+
+impl SierpinskiEmulator {
+    /// The text of `key` in the language of the interface.
+    fn text(&self, key: Key) -> &'static str {
+        self.config.language.text(key)
+    }
+
+    /// The text of `key` in the language of the interface, with `values` in
+    /// place of its placeholders.
+    fn fill(&self, key: Key, values: &[&dyn Display]) -> String {
+        self.config.language.fill(key, values)
+    }
+
+    /// What [`Application::update`] does, with any clipboard: notes the
+    /// input, handles the message and lets a running tutorial watch it.
+    fn dispatch(&mut self, message: Message, clipboard: &mut dyn Clipboard) -> Command<Message> {
+        let timer = matches!(
+            message,
+            Message::Tick
+                | Message::ScriptStep
+                | Message::TransitionTick(_)
+                | Message::ReplayTick
+                | Message::DebounceTick(_)
+                | Message::ExtendTick
+                | Message::AutoSave
+                | Message::GalleryTick
+                | Message::RefineTick
+        );
+        if timer.not() {
+            self.last_input = Instant::now();
+            self.refining = false;
+        }
+        if self.tutorial.is_none() {
+            return self.handle(message, clipboard);
+        }
+        let observed = message.clone();
+        let command = self.handle(message, clipboard);
+        let facts = self.facts();
+        if let Some(progress) = &mut self.tutorial {
+            progress.observe(&observed, &facts);
+            if progress.finished() {
+                let language = self.config.language;
+                self.status = language.fill(
+                    Key::TutorialFinished,
+                    &[&language.text(progress.tutorial.name)],
+                );
+                self.tutorial = None;
+            }
+        }
+        command
+    }
+
+    /// Handles `message` for [`dispatch`](Self::dispatch).
+    fn handle(&mut self, message: Message, clipboard: &mut dyn Clipboard) -> Command<Message> {
+        let undo_kind = undo_kind(&message);
+        let before = undo_kind.map(|_| self.snapshot());
+        let mut redraw = redraw(&message);
+        if matches!(
+            message,
+            Message::SetMaxIter(_)
+                | Message::SetCurIter(_)
+                | Message::SetCurFraction(_)
+                | Message::TogglePlayback
+                | Message::ExtendBy(_)
+        ) {
+            self.suggestion_pending = false;
+        }
+        if let (Some(recording), Some(step)) = (&mut self.recording, Step::from_message(&message)) {
+            recording.push(step);
+        }
+        match message {
+            Message::SetMaxIter(max_iter) => {
+                let old_max = self.graph.max_iter;
+                self.set_max_iter(max_iter);
+                if self.link_sliders {
+                    self.graph.cur_iter = self.graph.max_iter;
+                } else if let Some(percent) = self.cur_fraction {
+                    self.graph.cur_iter = percent_of(percent, self.graph.max_iter);
+                } else {
+                    self.graph.cur_iter = self.config.cur_iter_policy.apply(
+                        self.graph.cur_iter,
+                        old_max,
+                        self.graph.max_iter,
+                    );
+                }
+            }
+            Message::SetSuggestPoints(suggest_points) => {
+                self.config.suggest_points = suggest_points;
+                if let Err(e) = self.config.save() {
+                    self.status = self.fill(Key::PreferencesNotSaved, &[&e]);
+                }
+            }
+            Message::SetCurIterPolicy(policy) => {
+                self.config.cur_iter_policy = policy;
+                if let Err(e) = self.config.save() {
+                    self.status = self.fill(Key::PreferencesNotSaved, &[&e]);
+                }
+            }
+            Message::SetCurFraction(percent) => {
+                if self.link_sliders.not() {
+                    self.cur_fraction = Some(percent);
+                    self.graph.cur_iter = percent_of(percent, self.graph.max_iter);
+                }
+            }
+            Message::SetCurIter(cur_iter) => {
+                self.cur_fraction = None;
+                if self.link_sliders {
+                    self.set_max_iter(cur_iter);
+                    self.graph.cur_iter = self.graph.max_iter;
+                } else if cur_iter > self.graph.max_iter {
+                    self.graph.cur_iter = self.graph.max_iter;
+                } else {
+                    self.graph.cur_iter = cur_iter;
+                }
+            }
+            Message::Peek(true) => {
+                if self.peek_restore.is_none() && self.typing().not() {
+                    self.peek_restore = Some(self.graph.cur_iter);
+                    self.graph.cur_iter = self.graph.max_iter;
+                }
+            }
+            Message::Peek(false) => {
+                if let Some(cur_iter) = self.peek_restore.take() {
+                    self.graph.cur_iter = cur_iter.min(self.graph.max_iter);
+                }
+            }
+            Message::SetLinkSliders(link_sliders) => {
+                self.link_sliders = link_sliders;
+                if link_sliders {
+                    self.graph.cur_iter = self.graph.max_iter;
+                }
+            }
+            Message::AddFixPoint(point) => {
+                self.transition = None;
+                self.preset = None;
+                match self.insert_at.take() {
+                    Some(index) if index <= self.graph.fix_points.len() => {
+                        self.graph.insert_vertex(index, point);
+                        if let Some(rules) = &mut self.pending_rules {
+                            insert_at(&mut rules.weights, index, 1.0);
+                            rules.selection = rules.selection.inserted(index);
+                        }
+                        self.status = self.fill(Key::Inserted, &[&vertex_label(index)]);
+                    }
+                    _ => self.graph.push_vertex(point),
+                }
+                self.suggest_points();
+            }
+            Message::MoveFixPoint(index, point) => {
+                if index < self.graph.fix_points.len() && self.graph.is_locked(index).not() {
+                    self.transition = None;
+                    self.preset = None;
+                    self.graph.fix_points[index] = point;
+                    self.graph.regenerate();
+                }
+            }
+            Message::RemoveFixPoint => {
+                let last = self.graph.fix_points.len().saturating_sub(1);
+                if self.graph.is_locked(last) {
+                    self.status = self.fill(Key::LockedVertex, &[&vertex_label(last)]);
+                    return Command::none();
+                }
+                self.transition = None;
+                self.preset = None;
+                self.graph.pop_vertex();
+            }
+            Message::SelectVertex(selected) => {
+                self.graph.selected_vertex = selected;
+            }
+            Message::SetVertexEnabled(index, enabled) => {
+                if index < self.graph.fix_points.len() {
+                    self.graph
+                        .disabled
+                        .resize(self.graph.fix_points.len(), false);
+                    self.graph.disabled[index] = enabled.not();
+                    self.graph.regenerate();
+                }
+            }
+            Message::SetVertexLocked(index, locked) => {
+                if index < self.graph.fix_points.len() {
+                    self.graph.locked.resize(self.graph.fix_points.len(), false);
+                    self.graph.locked[index] = locked;
+                }
+            }
+            Message::ToggleSelectedLock => {
+                if let Some(index) = self.graph.selected_vertex {
+                    let locked = self.graph.is_locked(index).not();
+                    let key = if locked {
+                        Key::VertexLocked
+                    } else {
+                        Key::VertexUnlocked
+                    };
+                    self.status = self.fill(key, &[&vertex_label(index)]);
+                    return self.dispatch(Message::SetVertexLocked(index, locked), clipboard);
+                }
+            }
+            Message::UnlockAll => {
+                self.graph.locked.clear();
+            }
+            Message::ArmInsertion(Some(index)) => {
+                self.insert_at = Some(index);
+                self.status = self.fill(Key::InsertionArmed, &[&vertex_label(index)]);
+            }
+            Message::ArmInsertion(None) => {
+                if self.insert_at.take().is_some() {
+                    self.status = self.text(Key::InsertionCancelled).to_string();
+                }
+                // Escape closes the coordinate entry and the context menu too.
+                if self.coordinate_text.take().is_some() {
+                    self.coordinate_input_state = text_input::State::default();
+                }
+                self.graph.context_menu = None;
+            }
+            Message::SetContextMenu(menu) => {
+                self.graph.context_menu = menu;
+            }
+            Message::CopyCoordinates(point) => {
+                let decimals = self.graph.decimals;
+                let text = format!(
+                    "{},{}",
+                    Fixed::new(point.x, decimals),
+                    Fixed::new(point.y, decimals)
+                );
+                self.status = self.fill(Key::Copied, &[&text]);
+                clipboard.write(text);
+            }
+            Message::SetRightClick(right_click) => {
+                self.config.right_click = right_click;
+                self.graph.right_click = right_click;
+                if let Err(e) = self.config.save() {
+                    self.status = self.fill(Key::PreferencesNotSaved, &[&e]);
+                }
+            }
+            Message::SetDecimals(decimals) => {
+                self.config.decimals = decimals;
+                self.graph.decimals = self.config.places();
+                if let Err(e) = self.config.save() {
+                    self.status = self.fill(Key::PreferencesNotSaved, &[&e]);
+                }
+            }
+            Message::SetLanguage(language) => {
+                self.config.language = language;
+                self.graph.language = language;
+                if let Err(e) = self.config.save() {
+                    self.status = self.fill(Key::PreferencesNotSaved, &[&e]);
+                } else if language == Language::Chinese && self.cjk_font.not() {
+                    self.status = language.text(Key::RestartForFont).to_string();
+                }
+            }
+            Message::OpenCoordinateEntry => {
+                if self.typing().not() {
+                    self.coordinate_text = Some(String::new());
+                    self.coordinate_input_state = text_input::State::focused();
+                    self.status = self.text(Key::TypeCoordinates).to_string();
+                }
+            }
+            Message::SetCoordinateText(text) => {
+                self.coordinate_text = Some(text);
+            }
+            Message::SubmitCoordinates => {
+                let text = self.coordinate_text.clone().unwrap_or_default();
+                let size = [self.graph.bound.width, self.graph.bound.height];
+                let decimals = self.graph.decimals;
+                match script::parse_point(&text) {
+                    Ok(point) if bounds::contains(size, [point.x, point.y]) => {
+                        // Left open for the next vertex.
+                        self.coordinate_text = Some(String::new());
+                        self.status = self.fill(
+                            Key::PlacedVertex,
+                            &[
+                                &Fixed::new(point.x, decimals),
+                                &Fixed::new(point.y, decimals),
+                            ],
+                        );
+                        return self.dispatch(Message::AddFixPoint(point), clipboard);
+                    }
+                    Ok(point) => {
+                        self.status = self.fill(
+                            Key::OffCanvasPoint,
+                            &[
+                                &Fixed::new(point.x, decimals),
+                                &Fixed::new(point.y, decimals),
+                                &size[0],
+                                &size[1],
+                            ],
+                        );
+                    }
+                    Err(e) => self.status = e,
+                }
+            }
+            Message::DeleteVertex(index) => {
+                self.insert_at = None;
+                if self.graph.is_locked(index) {
+                    self.status = self.fill(Key::LockedVertex, &[&vertex_label(index)]);
+                } else if index < self.graph.fix_points.len() {
+                    self.transition = None;
+                    self.preset = None;
+                    self.graph.remove_vertex(index);
+                    if let Some(rules) = &mut self.pending_rules {
+                        remove_at(&mut rules.weights, index);
+                        rules.selection = without_vertex(&rules.selection, index);
+                    }
+                }
+            }
+            Message::DrawCurIter(cur_iter) => {
+                self.graph.cur_iter = cur_iter;
+            }
+            Message::SelectTool(tool) => {
+                self.graph.tool = tool;
+            }
+            Message::SetEraserRadius(radius) => {
+                self.graph.eraser_radius = radius;
+            }
+            Message::EraseAt(center) => {
+                self.graph.erase(center);
+            }
+            Message::ClearMask => {
+                self.graph.hidden.clear();
+                self.graph.heat.take();
+            }
+            Message::SetSelection(selection) => {
+                self.graph.selection = selection;
+            }
+            Message::SetMeasure(index, rectangle) => {
+                self.graph.measures.truncate(index);
+                self.graph.measures.extend(rectangle);
+            }
+            Message::SetCropToSelection(crop_to_selection) => {
+                self.crop_to_selection = crop_to_selection;
+            }
+            Message::ExportSelection => {
+                let path = self.capture_path("selection", "png");
+                self.status = match self.graph.selection {
+                    Some(selection) => {
+                        match self
+                            .graph
+                            .export_png(&path, Some(selection), self.crop_to_selection)
+                        {
+                            Ok(()) => self.fill(Key::Saved, &[&path.display()]),
+                            Err(e) => self.fill(Key::NotSaved, &[&path.display(), &e]),
+                        }
+                    }
+                    None => self.text(Key::NothingSelected).to_string(),
+                };
+            }
+            Message::CopySelection => {
+                if let Some(selection) = self.graph.selection {
+                    let points = self
+                        .graph
+                        .visible_points()
+                        .filter(|p| selection.contains(**p))
+                        .map(|p| format!("{},{}\n", p.x, p.y))
+                        .collect::<Vec<_>>();
+                    self.status = self.fill(Key::CopiedPoints, &[&points.len()]);
+                    clipboard.write(points.concat());
+                }
+            }
+            Message::SetVertexMarker(marker) => {
+                self.graph.vertex_marker = marker;
+            }
+            Message::SetPointColoring(point_coloring) => {
+                self.graph.point_coloring = point_coloring;
+            }
+            Message::SetRamp(ramp) => {
+                self.graph.ramp = ramp;
+            }
+            Message::SetCountScale(count_scale) => {
+                self.graph.count_scale = count_scale;
+            }
+            Message::SetClipPercentile(percentile) => {
+                self.graph.clip_percentile = percentile.round();
+            }
+            Message::SelectTheme(theme) => {
+                self.theme = Some(theme);
+                self.graph.background = theme.background();
+                self.graph.foreground = theme.foreground();
+            }
+            Message::SetBackground(background) => {
+                self.theme = None;
+                self.graph.background = background;
+            }
+            Message::SetBorder(border) => {
+                self.graph.border = border;
+            }
+            Message::SetOutOfBounds(out_of_bounds) => {
+                self.graph.out_of_bounds = out_of_bounds;
+            }
+            Message::SetAutoFrame(auto_frame) => {
+                self.graph.auto_frame = auto_frame;
+            }
+            Message::SetSectorCount(sector_count) => {
+                self.graph.sector_count = sector_count;
+            }
+            Message::SelectPreset(preset) => {
+                if self.move_fix_points(preset.vertices(self.graph.bound)) {
+                    self.preset = Some(preset);
+                }
+            }
+            Message::SetCircle(circle) => {
+                self.set_circle(circle);
+            }
+            Message::SetRatio(ratio) => {
+                self.set_ratio(ratio);
+            }
+            Message::NudgeRatio(direction) => {
+                let step = if self.modifiers.shift {
+                    RATIO_SHIFT_STEP
+                } else {
+                    RATIO_STEP
+                };
+                let ratio = self.rules().ratio + direction as f32 * step;
+                self.set_ratio(
+                    ((ratio * 1000.0).round() / 1000.0).clamp(RATIO_STEP, MAX_RATIO - RATIO_STEP),
+                );
+            }
+            Message::SetRatioText(text) => {
+                self.ratio_text = Some(text);
+            }
+            Message::SubmitRatioText => {
+                if let Some(text) = &self.ratio_text {
+                    match numeric::parse_between(text, 0.0, MAX_RATIO) {
+                        Ok(ratio) => self.set_ratio(ratio),
+                        Err(e) => self.status = self.fill(Key::RatioNotChanged, &[&e]),
+                    }
+                }
+            }
+            Message::SetSequenceText(text) => {
+                self.sequence_text = Some(text);
+            }
+            Message::SubmitSequenceText => {
+                if let Some(text) = self.sequence_text.take() {
+                    let vertices = self.graph.fix_points.len();
+                    match numeric::parse_indices(&text) {
+                        Ok(picks) if picks.iter().any(|pick| *pick >= vertices) => {
+                            self.status =
+                                self.fill(Key::SequenceOutOfRange, &[&vertices.saturating_sub(1)]);
+                        }
+                        Ok(picks) => self.edit_rules(|rules| {
+                            rules.selection = match picks.is_empty() {
+                                true => Selection::Random,
+                                false => Selection::ManualSequence(picks),
+                            }
+                        }),
+                        Err(e) => self.status = self.fill(Key::SequenceNotChanged, &[&e]),
+                    }
+                }
+            }
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+                self.graph.modifiers = modifiers;
+            }
+            Message::SetRule(rule) => {
+                if rule == Rule::Midpoint && self.graph.fix_points.len() < 2 {
+                    self.status = self.text(Key::MidpointNeedsTwo).to_string();
+                } else {
+                    self.edit_rules(|rules| rules.rule = rule);
+                }
+            }
+            Message::SetRestriction(restriction) => {
+                self.edit_rules(|rules| rules.restriction = restriction);
+            }
+            Message::SetJitter(jitter) => {
+                self.edit_rules(|rules| rules.jitter = jitter);
+            }
+            Message::SetRotation(rotation) => {
+                self.edit_rules(|rules| rules.rotation = rotation);
+            }
+            Message::SetAnchor(index, anchor) => {
+                let len = self.graph.fix_points.len();
+                if index < len {
+                    self.graph.anchors.resize(len, None);
+                    self.graph.anchors[index] = anchor;
+                    let key = match anchor {
+                        Some(_) => Key::Anchored,
+                        None => Key::AnchorRemoved,
+                    };
+                    self.status = self.fill(key, &[&vertex_label(index)]);
+                    self.graph.regenerate();
+                }
+            }
+            Message::ClearAnchors => {
+                self.graph.anchors.clear();
+                self.graph.regenerate();
+            }
+            Message::CycleVertexColor(index) => {
+                let len = self.graph.fix_points.len();
+                if index < len {
+                    let next = palette::vertex_color(&self.graph.vertex_colors, index) + 1;
+                    self.graph.vertex_colors.resize(len, None);
+                    self.graph.vertex_colors[index] = Some(next % palette::CATEGORICAL.len());
+                }
+            }
+            Message::ResetVertexColors => {
+                self.graph.vertex_colors.clear();
+            }
+            Message::SetAnchorBlend(anchor_blend) => {
+                self.edit_rules(|rules| rules.anchor_blend = anchor_blend);
+            }
+            Message::PinGhost => {
+                let visible = self.graph.visible_points().count();
+                let every = visible.div_ceil(MAX_GHOST_POINTS).max(1);
+                self.graph.ghost = self
+                    .graph
+                    .visible_points()
+                    .step_by(every)
+                    .copied()
+                    .collect();
+                self.status = self.fill(Key::PinnedGhost, &[&self.graph.ghost.len()]);
+            }
+            Message::ClearGhost => {
+                self.graph.ghost.clear();
+            }
+            Message::SetGhostInExports(ghost_in_exports) => {
+                self.graph.ghost_in_exports = ghost_in_exports;
+            }
+            Message::SetAutoApply(auto_apply) => {
+                self.auto_apply = auto_apply;
+                self.apply_at = None;
+                if auto_apply {
+                    self.apply_rules();
+                }
+            }
+            Message::ApplyRules => {
+                self.apply_rules();
+            }
+            Message::ReleaseRuleSlider => {
+                self.history.end_gesture();
+                if self.auto_apply {
+                    self.apply_rules();
+                }
+            }
+            Message::DebounceTick(now) => {
+                if self.apply_at.is_some_and(|at| at <= now) {
+                    self.apply_rules();
+                    redraw = Redraw::All;
+                }
+            }
+            Message::Reseed => {
+                self.graph.seed = rand::random();
+                self.graph.regenerate();
+            }
+            Message::SetSeedCount(seed_count) => {
+                // One sequence of picks can't feed several orbits.
+                self.graph.sequence = None;
+                self.graph.seed_count = seed_count;
+                self.graph.regenerate();
+            }
+            Message::SetOrbitStart(orbit_start) => {
+                self.graph.orbit_start = orbit_start;
+                self.graph.regenerate();
+            }
+            Message::SetGenerator(generator) => {
+                self.graph.generator = generator;
+                self.graph.regenerate();
+            }
+            Message::SetBalanced(balanced) => {
+                self.graph.balanced = balanced;
+                self.graph.regenerate();
+            }
+            Message::SetQuota(quota) => {
+                self.graph.quota = quota;
+                if self.graph.balanced {
+                    self.graph.regenerate();
+                }
+            }
+            Message::FitToCanvas => {
+                if self.graph.fix_points.is_empty().not() {
+                    self.move_fix_points(preset::fit(&self.graph.fix_points, self.graph.bound));
+                }
+            }
+            Message::SetShowSubdivision(show) => {
+                if show && self.graph.fix_points.len() != 3 {
+                    self.status = self.text(Key::SubdivisionNeedsThree).to_string();
+                } else {
+                    self.graph.show_subdivision = show;
+                }
+            }
+            Message::SetShowMaps(show_maps) => {
+                self.graph.show_maps = show_maps;
+            }
+            Message::SetShowCenters(show) => {
+                self.graph.show_centers = show;
+            }
+            Message::SetShowTransitions(show) => {
+                self.graph.show_transitions = show;
+            }
+            Message::AddCentroidVertex => {
+                if let Some(centroid) = self.graph.centroid() {
+                    self.insert_at = None;
+                    self.status = self.fill(
+                        Key::AddedCentroid,
+                        &[
+                            &Fixed::new(centroid.x, self.graph.decimals),
+                            &Fixed::new(centroid.y, self.graph.decimals),
+                            &vertex_label(self.graph.fix_points.len()),
+                        ],
+                    );
+                    return self.dispatch(Message::AddFixPoint(centroid), clipboard);
+                }
+            }
+            Message::SetShowConvergence(show) => {
+                self.graph.show_convergence = show;
+                if show {
+                    self.status = self.text(Key::ConvergenceHelp).to_string();
+                }
+            }
+            Message::PinConvergenceStart(start) => {
+                self.graph.convergence_start = start;
+            }
+            Message::SetLoupe(loupe) => {
+                self.graph.loupe = loupe;
+                self.graph.sync_point_grid();
+            }
+            Message::HoldLoupe(held) => {
+                // Typing an M into a text input shouldn't open it.
+                if held.not() || self.typing().not() {
+                    self.graph.loupe_held = held;
+                    self.graph.sync_point_grid();
+                }
+            }
+            Message::SetLoupeZoom(zoom) => {
+                self.graph.loupe_zoom = zoom.clamp(*LOUPE_ZOOMS.start(), *LOUPE_ZOOMS.end());
+            }
+            Message::SetDensitySizing(density_sizing) => {
+                self.graph.density_sizing = density_sizing;
+            }
+            Message::SetPointShape(point_shape) => {
+                self.graph.point_shape = point_shape;
+            }
+            Message::SetPointSize(point_size) => {
+                self.graph.point_size = point_size;
+            }
+            Message::SetPixelSnap(pixel_snap) => {
+                self.graph.pixel_snap = pixel_snap;
+            }
+            Message::SetShowLastPick(show) => {
+                self.graph.show_last_pick = show;
+            }
+            Message::SetSubdivisionLevel(level) => {
+                self.graph.subdivision_level = level;
+            }
+            Message::WindowResized(width, height) => {
+                self.resize((width, height));
+            }
+            Message::SetAnimations(animations) => {
+                // Asking for animations outranks the variable for this run.
+                self.config.animations = animations;
+                self.config.reduced_motion = false;
+                if animations.not() {
+                    if self.playing {
+                        self.jump_to_end();
+                    }
+                    if let Some(transition) = self.transition.take() {
+                        self.graph.fix_points = transition.target().to_vec();
+                        self.graph.regenerate();
+                    }
+                    self.graph.replay = None;
+                }
+                if let Err(e) = self.config.save() {
+                    self.status = self.fill(Key::PreferencesNotSaved, &[&e]);
+                }
+            }
+            Message::TransitionTick(now) => {
+                if let Some(transition) = &self.transition {
+                    if transition.is_finished(now) {
+                        self.graph.fix_points = transition.target().to_vec();
+                        self.transition = None;
+                        self.graph.regenerate();
+                    } else {
+                        self.graph.fix_points = transition.points_at(now);
+                    }
+                }
+            }
+            Message::SetExtendText(text) => {
+                self.extend_text = text;
+            }
+            Message::ExtendBy(points) => {
+                if self.graph.fix_points.is_empty() {
+                    self.status = self.text(Key::NoFixPoints).to_string();
+                } else if self.graph.max_iter >= MAX_EXTENDED_ITER {
+                    self.status = self.fill(Key::AtTheLimit, &[&group_digits(MAX_EXTENDED_ITER)]);
+                } else {
+                    let iterations = points.div_ceil(self.graph.seed_count as usize);
+                    let to = (self.graph.max_iter as usize + iterations)
+                        .min(MAX_EXTENDED_ITER as usize) as i32;
+                    self.extension = Some(Extension {
+                        from: self.graph.max_iter,
+                        to,
+                    });
+                }
+            }
+            Message::ExtendTick => {
+                if let Some(extension) = self.extension {
+                    let target = self.graph.point_count(extension.to);
+                    let len = self.graph.random_points.len();
+                    self.graph.extend_to((len + EXTEND_CHUNK).min(target));
+                    if self.graph.max_iter != extension.from {
+                        // The max iteration moved meanwhile, it wins.
+                        self.extension = None;
+                    } else if self.graph.random_points.len() == len {
+                        self.extension = None;
+                        if self.graph.diverged.is_none() {
+                            self.status = self.text(Key::NoPointsAdded).to_string();
+                        }
+                    } else if self.graph.random_points.len() >= target {
+                        self.extension = None;
+                        self.graph.max_iter = extension.to;
+                        if self.advance_on_extend || self.link_sliders {
+                            self.graph.cur_iter = extension.to;
+                        }
+                        let added = self.graph.point_count(extension.to - extension.from);
+                        self.status = self.fill(Key::AddedPoints, &[&group_digits(added as i32)]);
+                    }
+                }
+            }
+            Message::SetAdvanceOnExtend(advance_on_extend) => {
+                self.advance_on_extend = advance_on_extend;
+            }
+            Message::ShowSnap(readout) => {
+                self.status = readout;
+            }
+            Message::SetRefineWhenIdle(refine_when_idle) => {
+                self.refine_when_idle = refine_when_idle;
+            }
+            Message::SetRefineBatch(refine_batch) => {
+                self.refine_batch = refine_batch;
+            }
+            Message::RefineTick => {
+                let busy = self.busy();
+                let graph = &mut self.graph;
+                if busy
+                    || self.last_input.elapsed() < IDLE
+                    || graph.fix_points.is_empty()
+                    || graph.max_iter >= REFINE_LIMIT
+                {
+                    self.refining = false;
+                } else {
+                    // The orbits carry on where they were, so the points are
+                    // the ones a single run to the new max would give.
+                    let to = (graph.max_iter + self.refine_batch as i32).min(REFINE_LIMIT);
+                    let len = graph.random_points.len();
+                    graph.extend_to(graph.point_count(to));
+                    self.refining = graph.random_points.len() > len;
+                    if self.refining {
+                        let at_end = graph.cur_iter >= graph.max_iter;
+                        graph.max_iter =
+                            (graph.random_points.len() / graph.seed_count.max(1) as usize) as i32;
+                        if at_end || self.link_sliders {
+                            graph.cur_iter = graph.max_iter;
+                        }
+                    }
+                }
+            }
+            Message::TogglePlayback if self.config.animated().not() => {
+                self.jump_to_end();
+            }
+            Message::TogglePlayback => {
+                if self.playing.not() && self.graph.cur_iter >= self.graph.max_iter {
+                    self.graph.cur_iter = 0;
+                }
+                self.playing = self.playing.not() && self.graph.max_iter > 0;
+                self.quick_play = false;
+            }
+            Message::ToggleReplay => {
+                self.graph.replay = match self.graph.replay {
+                    None if self.config.animated() && self.graph.random_points.is_empty().not() => {
+                        self.playing = false;
+                        Some(0)
+                    }
+                    _ => None,
+                };
+            }
+            Message::ReplayTick => {
+                self.graph.replay = self
+                    .graph
+                    .replay
+                    .map(|index| index + self.graph.seed_count as usize)
+                    .filter(|index| *index < self.graph.random_points.len());
+                if self.graph.replay.is_none() {
+                    // The points come back.
+                    redraw = Redraw::All;
+                }
+            }
+            Message::SetReplaySpeed(speed) => {
+                self.graph.replay_speed = speed;
+            }
+            Message::SetReplayTrail(trail) => {
+                self.graph.replay_trail = trail;
+            }
+            Message::Tick => {
+                self.cur_fraction = None;
+                let ticks = if self.quick_play {
+                    SUGGESTION_TICKS
+                } else {
+                    PLAY_TICKS
+                };
+                let step = (self.graph.max_iter / ticks).max(1);
+                self.graph.cur_iter = (self.graph.cur_iter + step).min(self.graph.max_iter);
+                if self.graph.cur_iter == self.graph.max_iter {
+                    self.jump_to_end();
+                }
+            }
+            Message::SetCaptureOnComplete(capture_on_complete) => {
+                self.capture_on_complete = capture_on_complete;
+            }
+            Message::SetCaptureDir(capture_dir) => {
+                self.capture_dir = capture_dir;
+            }
+            Message::SetSupersample(supersample) => {
+                self.graph.supersample = supersample;
+            }
+            Message::SetAdditiveExport(additive_export) => {
+                self.graph.additive_export = additive_export;
+            }
+            Message::SetPointCloud(point_cloud) => {
+                self.point_cloud = point_cloud;
+            }
+            Message::ExportPointCloud => {
+                let path = self.capture_path("points", self.point_cloud.extension());
+                self.status = match self.graph.export_point_cloud(&path, self.point_cloud) {
+                    Ok(()) => self.fill(Key::Saved, &[&path.display()]),
+                    Err(e) => self.fill(Key::NotSaved, &[&path.display(), &e]),
+                };
+            }
+            Message::ExportStats => {
+                let path = self.capture_path("stats", "json");
+                self.status = match std::fs::write(&path, self.graph.report().to_json()) {
+                    Ok(()) => self.fill(Key::Saved, &[&path.display()]),
+                    Err(e) => self.fill(Key::NotSaved, &[&path.display(), &e]),
+                };
+            }
+            Message::SetSvgBudget(svg_budget) => {
+                self.svg_budget = svg_budget;
+            }
+            Message::ExportPointsSvg => {
+                let path = self.capture_path("points", "svg");
+                self.status = match self.graph.export_points_svg(&path, self.svg_budget) {
+                    Ok((kept, total)) if kept < total => {
+                        self.fill(Key::SavedThinned, &[&path.display(), &kept, &total])
+                    }
+                    Ok(_) => self.fill(Key::Saved, &[&path.display()]),
+                    Err(e) => self.fill(Key::NotSaved, &[&path.display(), &e]),
+                };
+            }
+            Message::ExportGasketSvg => {
+                let path = self.capture_path("gasket", "svg");
+                self.status = match self.graph.export_gasket_svg(&path) {
+                    Ok(()) => self.fill(Key::Saved, &[&path.display()]),
+                    Err(e) => self.fill(Key::NotSaved, &[&path.display(), &e]),
+                };
+            }
+            Message::SetGalleryParameter(parameter) => {
+                self.gallery.parameter = parameter;
+                // The ends of one parameter mean nothing for another.
+                self.gallery_from.clear();
+                self.gallery_to.clear();
+            }
+            Message::SetGalleryFrom(from) => {
+                self.gallery_from = from;
+            }
+            Message::SetGalleryTo(to) => {
+                self.gallery_to = to;
+            }
+            Message::SetGalleryTiles(tiles) => {
+                self.gallery.tiles = tiles;
+            }
+            Message::SetGalleryIterations(iterations) => {
+                self.gallery.iterations = iterations;
+            }
+            Message::RenderGallery if self.gallery_job.is_none() => {
+                match gallery_range(self.gallery.parameter, &self.gallery_from, &self.gallery_to) {
+                    Ok(range) => self.gallery.range = range,
+                    Err(e) => {
+                        self.status = self.fill(Key::GalleryRange, &[&e]);
+                        return Command::none();
+                    }
+                }
+                let path = self.capture_path("gallery", "png");
+                let tiles = self.gallery.variations().len();
+                let done = Arc::new(AtomicUsize::new(0));
+                let (sheet, game) = (self.gallery, self.graph.game());
+                let canvas = [self.graph.bound.width, self.graph.bound.height];
+                let style = RenderStyle {
+                    background: export::rgba8(self.graph.background),
+                    colors: vec![export::rgba8(self.graph.foreground)],
+                    point_size: 1,
+                    shape: self.graph.point_shape,
+                };
+                let (thread_path, thread_done) = (path.clone(), done.clone());
+                let handle = thread::spawn(move || {
+                    write_gallery(&thread_path, &sheet, &game, canvas, &style, |n| {
+                        thread_done.store(n, Ordering::Relaxed)
+                    })
+                });
+                self.status = self.fill(Key::RenderingGallery, &[&tiles]);
+                self.gallery_job = Some(GalleryJob {
+                    path,
+                    tiles,
+                    done,
+                    handle,
+                });
+            }
+            Message::RenderGallery => {}
+            Message::GalleryTick => {
+                if self
+                    .gallery_job
+                    .as_ref()
+                    .is_some_and(|job| job.handle.is_finished())
+                {
+                    let GalleryJob { path, handle, .. } = self.gallery_job.take().unwrap();
+                    self.status = match handle.join() {
+                        Ok(Ok(())) => self.fill(Key::Saved, &[&path.display()]),
+                        Ok(Err(e)) => self.fill(Key::NotSaved, &[&path.display(), &e]),
+                        Err(_) => self.text(Key::GalleryFailed).to_string(),
+                    };
+                }
+            }
+            Message::SetSessionPath(session_path) => {
+                self.session_path = session_path;
+            }
+            Message::SaveSession => match self.session().save(self.session_path.as_ref()) {
+                Ok(()) => {
+                    self.status = self.fill(Key::Saved, &[&self.session_path]);
+                    self.remember_session();
+                }
+                Err(e) => self.status = self.fill(Key::NotSaved, &[&self.session_path, &e]),
+            },
+            Message::SetMergeDuplicates(merge_duplicates) => {
+                self.merge_duplicates = merge_duplicates;
+            }
+            Message::LoadSession => {
+                self.load_session();
+            }
+            Message::OpenRecent(path) => {
+                if path.is_file() {
+                    self.session_path = path.display().to_string();
+                    self.load_session();
+                } else {
+                    self.recent.prune();
+                    self.status = self.fill(Key::RecentMissing, &[&path.display()]);
+                    self.save_recent();
+                }
+            }
+            Message::StartEmpty => {
+                self.show_recent = false;
+            }
+            Message::AutoSave if self.recovered.is_none() => {
+                if let Ok(json) = self.session().to_json() {
+                    if json != self.autosaved {
+                        if let Err(e) = recovery::save(&json) {
+                            self.status = self.fill(Key::RecoveryNotWritten, &[&e]);
+                        }
+                        self.autosaved = json;
+                    }
+                }
+            }
+            Message::AutoSave => {}
+            Message::ToggleChanges => {
+                self.show_changes = self.show_changes.not();
+            }
+            Message::RevertSession => {
+                if let Some(session) = self
+                    .saved
+                    .as_deref()
+                    .and_then(|json| Session::from_json(json).ok())
+                {
+                    self.apply_session(session);
+                    self.show_changes = false;
+                    self.status = self.fill(Key::Reverted, &[&self.session_path]);
+                }
+            }
+            Message::StoreSlot(i) => {
+                self.slots[i] = Some(self.slot());
+                self.active_slot = Some(i);
+                self.status = self.fill(Key::StoredSlot, &[&SLOT_NAMES[i]]);
+            }
+            Message::FlipSlot if self.typing() => {}
+            Message::FlipSlot => {
+                let to = match self.active_slot {
+                    Some(i) => 1 - i,
+                    None => 0,
+                };
+                self.status = match self.slots[to].clone() {
+                    Some(slot) => {
+                        self.apply_slot(slot);
+                        self.active_slot = Some(to);
+                        self.fill(Key::FlippedSlot, &[&SLOT_NAMES[to]])
+                    }
+                    None => self.fill(Key::EmptySlot, &[&SLOT_NAMES[to]]),
+                };
+            }
+            Message::RestoreRecovery => {
+                if let Some(session) = self.recovered.take() {
+                    self.apply_session(session);
+                    self.show_recent = false;
+                    self.status = self.text(Key::RestoredRecovery).to_string();
+                }
+            }
+            Message::CloseRequested => {
+                if self.recovered.is_none() {
+                    if let Err(e) = recovery::clear() {
+                        eprintln!("{}", self.fill(Key::RecoveryNotRemoved, &[&e]));
+                    }
+                }
+                self.exiting = true;
+            }
+            Message::DiscardRecovery => {
+                self.recovered = None;
+                if let Err(e) = recovery::clear() {
+                    self.status = self.fill(Key::RecoveryNotRemoved, &[&e]);
+                }
+            }
+            Message::StartTutorial => {
+                self.tutorial = Some(tutorial::Progress::new(&tutorial::TRIANGLE));
+                self.show_recent = false;
+            }
+            Message::SkipTutorialStep => {
+                if let Some(progress) = &mut self.tutorial {
+                    progress.skip();
+                }
+            }
+            Message::ExitTutorial => {
+                self.tutorial = None;
+            }
+            Message::ExportPng(path) => {
+                self.export_png(&path);
+            }
+            Message::SetScriptPath(script_path) => {
+                self.script_path = script_path;
+            }
+            Message::LoadScript => {
+                self.load_script();
+            }
+            Message::ScriptStep => {
+                return self.run_script(clipboard);
+            }
+            Message::SetRecording(recording) => {
+                if recording {
+                    self.recording = Some(vec![]);
+                } else if let Some(steps) = self.recording.take() {
+                    let text = steps
+                        .iter()
+                        .map(|step| format!("{}\n", step))
+                        .collect::<String>();
+                    self.status = match std::fs::write(&self.script_path, text) {
+                        Ok(()) => self.fill(Key::RecordedSteps, &[&steps.len(), &self.script_path]),
+                        Err(e) => self.fill(Key::NotSaved, &[&self.script_path, &e]),
+                    };
+                }
+            }
+            Message::SetChoicesPath(choices_path) => {
+                self.choices_path = choices_path;
+            }
+            Message::ExportChoices => {
+                self.export_choices();
+            }
+            Message::ImportChoices => {
+                self.import_choices();
+            }
+            Message::Undo => match self.history.undo(self.snapshot()) {
+                Some(snapshot) => self.restore(snapshot),
+                None => self.status = self.text(Key::NothingToUndo).to_string(),
+            },
+            Message::Redo => match self.history.redo(self.snapshot()) {
+                Some(snapshot) => self.restore(snapshot),
+                None => self.status = self.text(Key::NothingToRedo).to_string(),
+            },
+            Message::BeginGesture => {
+                self.history.begin_gesture();
+            }
+            Message::EndGesture => {
+                self.history.end_gesture();
+            }
+            Message::StopReplaying => {
+                self.graph.sequence = None;
+                self.graph.regenerate();
+                self.status = self.text(Key::RandomAgain).to_string();
+            }
+            Message::SetVertexWeight(index, weight) => {
+                let len = self.graph.fix_points.len();
+                // Drags on a weight ring repeat the same weight between steps.
+                if index < len && self.rules().weights.get(index) != Some(&weight) {
+                    self.edit_rules(|rules| {
+                        // Staged weights may predate added fix points.
+                        rules.weights.resize(len, 1.0);
+                        rules.weights[index] = weight;
+                    });
+                }
+            }
+        }
+        if let (Some(kind), Some(before)) = (undo_kind, before) {
+            if before != self.snapshot() {
+                self.history.record(before, kind, Instant::now());
+            }
+        }
+        if let Some(notice) = self.graph.sequence_dropped.take() {
+            self.status = notice;
+        }
+        if let Some(notice) = self.graph.divergence.take() {
+            self.status = notice;
+        }
+        self.graph.staged_weights = self
+            .pending_rules
+            .as_ref()
+            .map(|rules| rules.weights.clone());
+        match redraw {
+            Redraw::Nothing => {}
+            Redraw::Overlay => self.graph.static_cache.clear(),
+            Redraw::Tail => self.graph.redraw_tail(),
+            Redraw::All => self.graph.redraw(),
+        }
+
+        Command::none()
+    }
+
+    /// Moves the fix points to `target`, tweening there if animations are
+    /// on, and regenerates the points for the new layout. Locked vertices
+    /// stay where they are; a layout with another number of vertices isn't
+    /// taken while any is locked, which returns `false`.
+    fn move_fix_points(&mut self, mut target: Vec<Point>) -> bool {
+        if self.keep_locked(&mut target).not() {
+            return false;
+        }
+        if self.config.animated() {
+            self.transition = Some(Transition::new(&self.graph.fix_points, target));
+            self.graph.clear_points();
+        } else {
+            self.graph.fix_points = target;
+            self.graph.regenerate();
+        }
+        true
+    }
+
+    /// Puts the locked vertices of `target`, a new layout, back where they
+    /// are now. `false`, saying so in the status line, when that can't be
+    /// done because the layout has another number of vertices.
+    fn keep_locked(&mut self, target: &mut [Point]) -> bool {
+        let graph = &self.graph;
+        let locked = (0..graph.fix_points.len()).filter(|i| graph.is_locked(*i));
+        if target.len() != graph.fix_points.len() {
+            if let Some(index) = locked.clone().next() {
+                self.status = self.fill(Key::LockedLayout, &[&vertex_label(index)]);
+                return false;
+            }
+        }
+        for i in locked {
+            target[i] = graph.fix_points[i];
+        }
+        true
+    }
+
+    /// Puts the vertices on `circle` at once, so dragging its sliders
+    /// follows along. Moving or turning them keeps every vertex, its
+    /// weight and its anchor; another count starts over from new ones.
+    fn set_circle(&mut self, circle: Circle) {
+        let count_changed = circle.count != self.circle.count
+            || self.graph.fix_points.len() != circle.count as usize;
+        let mut vertices = circle.vertices(self.graph.bound);
+        if self.keep_locked(&mut vertices).not() {
+            return;
+        }
+        self.circle = circle;
+        self.transition = None;
+        self.preset = None;
+        let graph = &mut self.graph;
+        graph.fix_points = vertices;
+        if count_changed {
+            self.insert_at = None;
+            if let Some(rules) = &mut self.pending_rules {
+                rules.weights = vec![1.0; circle.count as usize];
+            }
+            graph.selected_vertex = None;
+            graph.weights.clear();
+            graph.disabled.clear();
+            graph.anchors.clear();
+            graph.vertex_colors.clear();
+        }
+        graph.regenerate();
+    }
+
+    /// Lays out for a window of `size`. Only how large the canvas is shown
+    /// changes, never the canvas itself.
+    fn resize(&mut self, size: (u32, u32)) {
+        self.window = size;
+        let beside = match Breakpoint::for_width(size.0) {
+            Breakpoint::Wide => VERTEX_LIST_WIDTH as f32,
+            Breakpoint::Narrow => 0.0,
+        };
+        let bound = self.graph.bound;
+        // The controls scroll, so only the width limits the canvas.
+        self.graph.display_scale = layout::canvas_scale(
+            [bound.width, bound.height],
+            [size.0 as f32 - beside - WINDOW_PADDING, f32::INFINITY],
+        );
+        self.graph.occupied.set(None);
+        self.graph.report.take();
+    }
+
+    /// Stops playback at the max iteration, capturing it if asked to.
+    fn jump_to_end(&mut self) {
+        self.playing = false;
+        self.quick_play = false;
+        self.cur_fraction = None;
+        if self.graph.max_iter > 0 {
+            self.graph.cur_iter = self.graph.max_iter;
+            if self.capture_on_complete {
+                self.capture();
+            }
+        }
+    }
+
+    /// What the tutorial steps check.
+    fn facts(&self) -> tutorial::Facts {
+        tutorial::Facts {
+            fix_points: self.graph.fix_points.iter().map(|p| [p.x, p.y]).collect(),
+            max_iter: self.graph.max_iter,
+            cur_iter: self.graph.cur_iter,
+        }
+    }
+
+    fn session(&self) -> Session {
+        Session {
+            fix_points: self.graph.fix_points.iter().map(|p| [p.x, p.y]).collect(),
+            max_iter: self.graph.max_iter,
+            cur_iter: self.graph.cur_iter,
+            vertex_marker: self.graph.vertex_marker,
+            point_coloring: self.graph.point_coloring,
+            sector_count: self.graph.sector_count,
+            weights: self.graph.weights.clone(),
+            ratio: self.graph.ratio,
+            rule: self.graph.rule,
+            disabled: self.graph.disabled.clone(),
+            locked: self.graph.locked.clone(),
+            anchors: self
+                .graph
+                .anchors
+                .iter()
+                .map(|anchor| anchor.map(|p| [p.x, p.y]))
+                .collect(),
+            vertex_colors: self.graph.vertex_colors.clone(),
+            anchor_blend: self.graph.anchor_blend,
+            restriction: self.graph.restriction,
+            selection: self.graph.picking.clone(),
+            rotation: self.graph.rotation,
+            jitter: self.graph.jitter,
+            seed: self.graph.seed,
+            generator: self.graph.generator,
+            seed_count: self.graph.seed_count,
+            orbit_start: self.graph.orbit_start,
+            balanced: self.graph.balanced,
+            quota: self.graph.quota,
+            out_of_bounds: self.graph.out_of_bounds,
+            auto_frame: self.graph.auto_frame,
+            background: color_to_array(self.graph.background),
+            foreground: color_to_array(self.graph.foreground),
+            border: self.graph.border,
+            circle: self.circle,
+            slots: self.slots.clone(),
+            active_slot: self.active_slot,
+            ..Session::default()
+        }
+    }
+
+    /// The parameters a slot keeps, including rules not applied yet.
+    fn slot(&self) -> Slot {
+        let rules = self.rules();
+        Slot {
+            ratio: rules.ratio,
+            rule: rules.rule,
+            restriction: rules.restriction,
+            selection: rules.selection,
+            rotation: rules.rotation,
+            weights: rules.weights,
+            anchor_blend: rules.anchor_blend,
+            jitter: rules.jitter,
+            vertex_colors: self.graph.vertex_colors.clone(),
+            point_coloring: self.graph.point_coloring,
+            background: color_to_array(self.graph.background),
+            foreground: color_to_array(self.graph.foreground),
+        }
+    }
+
+    /// Switches to the parameters of `slot` and regenerates. The seed stays,
+    /// so the points follow the same random draws as before.
+    fn apply_slot(&mut self, slot: Slot) {
+        self.pending_rules = None;
+        self.apply_at = None;
+        let mut weights = slot.weights;
+        weights.resize(self.graph.fix_points.len(), 1.0);
+        self.graph.set_rules(Rules {
+            ratio: slot.ratio,
+            rule: slot.rule,
+            restriction: slot.restriction,
+            selection: slot.selection,
+            rotation: slot.rotation,
+            weights,
+            anchor_blend: slot.anchor_blend,
+            jitter: slot.jitter,
+        });
+        self.graph.vertex_colors = slot.vertex_colors;
+        self.graph
+            .vertex_colors
+            .truncate(self.graph.fix_points.len());
+        self.graph.point_coloring = slot.point_coloring;
+        self.graph.background = Color::from(slot.background);
+        self.graph.foreground = Color::from(slot.foreground);
+        let (background, foreground) = (self.graph.background, self.graph.foreground);
+        self.theme = Theme::ALL
+            .iter()
+            .copied()
+            .find(|theme| theme.background() == background && theme.foreground() == foreground);
+        self.graph.regenerate();
+    }
+
+    /// The rules as edited, including any not applied yet.
+    fn rules(&self) -> Rules {
+        self.pending_rules
+            .clone()
+            .unwrap_or_else(|| self.graph.rules())
+    }
+
+    /// Whether a text input has the keyboard, so shortcuts stay out of it.
+    fn typing(&self) -> bool {
+        self.capture_dir_state.is_focused()
+            || self.session_path_state.is_focused()
+            || self.ratio_input_state.is_focused()
+            || self.sequence_input_state.is_focused()
+            || self.extend_input_state.is_focused()
+            || self.script_path_state.is_focused()
+            || self.choices_path_state.is_focused()
+            || self.coordinate_input_state.is_focused()
+            || self.gallery_from_state.is_focused()
+            || self.gallery_to_state.is_focused()
+    }
+
+    /// The state undo returns to, with the vertices a running transition
+    /// ends on and the rules staged.
+    fn snapshot(&self) -> Snapshot {
+        let graph = &self.graph;
+        Snapshot {
+            fix_points: match &self.transition {
+                Some(transition) => transition.target().to_vec(),
+                None => graph.fix_points.clone(),
+            },
+            disabled: graph.disabled.clone(),
+            locked: graph.locked.clone(),
+            anchors: graph.anchors.clone(),
+            vertex_colors: graph.vertex_colors.clone(),
+            rules: self.rules(),
+            background: graph.background,
+            foreground: graph.foreground,
+            theme: self.theme,
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.transition = None;
+        self.pending_rules = None;
+        self.apply_at = None;
+        self.theme = snapshot.theme;
+        let graph = &mut self.graph;
+        if graph.selected_vertex >= Some(snapshot.fix_points.len()) {
+            graph.selected_vertex = None;
+        }
+        graph.fix_points = snapshot.fix_points;
+        graph.disabled = snapshot.disabled;
+        graph.locked = snapshot.locked;
+        graph.anchors = snapshot.anchors;
+        graph.vertex_colors = snapshot.vertex_colors;
+        graph.set_rules(snapshot.rules);
+        graph.background = snapshot.background;
+        graph.foreground = snapshot.foreground;
+        graph.regenerate();
+    }
+
+    fn edit_rules(&mut self, edit: impl FnOnce(&mut Rules)) {
+        let mut rules = self.rules();
+        edit(&mut rules);
+        self.pending_rules = (rules != self.graph.rules()).then_some(rules);
+        if self.auto_apply.not() {
+            return;
+        }
+        if self.graph.point_count(self.graph.max_iter) >= DEBOUNCE_MIN_POINTS
+            && self.pending_rules.is_some()
+        {
+            self.apply_at = Some(Instant::now() + DEBOUNCE);
+        } else {
+            self.apply_rules();
+        }
+    }
+
+    /// Regenerates with the staged rules, if there are any.
+    fn apply_rules(&mut self) {
+        self.apply_at = None;
+        if let Some(rules) = self.pending_rules.take() {
+            self.graph.set_rules(rules);
+            self.graph.regenerate();
+        }
+    }
+
+    fn apply_session(&mut self, session: Session) {
+        // A loaded session is not a fresh canvas to suggest a run for.
+        self.suggestion_pending = false;
+        self.transition = None;
+        self.pending_rules = None;
+        self.apply_at = None;
+        self.cur_fraction = None;
+        self.playing = false;
+        self.preset = None;
+        self.circle = Circle {
+            count: session
+                .circle
+                .count
+                .clamp(*Circle::COUNTS.start(), *Circle::COUNTS.end()),
+            radius: session.circle.radius.clamp(10.0, 100.0),
+            phase: session.circle.phase.rem_euclid(360.0),
+        };
+        self.graph.apply_session(&session);
+        let (background, foreground) = (self.graph.background, self.graph.foreground);
+        self.theme = Theme::ALL
+            .iter()
+            .copied()
+            .find(|theme| theme.background() == background && theme.foreground() == foreground);
+        self.set_max_iter(session.max_iter.clamp(0, MAX_EXTENDED_ITER));
+        self.graph.cur_iter = session.cur_iter.clamp(0, self.graph.max_iter);
+        self.slots = session.slots;
+        self.active_slot = session.active_slot.filter(|i| *i < SLOT_NAMES.len());
+    }
+
+    /// What changed since the session was last saved or loaded, a line
+    /// each. Nothing before the first save or load.
+    fn changes(&self) -> Vec<String> {
+        let parse = |json: &str| serde_json::from_str::<serde_json::Value>(json).ok();
+        let saved = self.saved.as_deref().and_then(parse);
+        let current = saved
+            .as_ref()
+            .and_then(|_| self.session().to_json().ok())
+            .and_then(|json| parse(&json));
+        match (saved, current) {
+            (Some(saved), Some(current)) => diff::diff(&saved, &current)
+                .iter()
+                .filter(|change| ["version", "active_slot"].contains(&change.path()).not())
+                .map(|change| describe_change(change, self.config.language))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn load_session(&mut self) {
+        match Session::load(self.session_path.as_ref()) {
+            Ok(mut session) => {
+                self.status = self.fill(Key::Loaded, &[&self.session_path]);
+                let duplicates =
+                    geometry::near_duplicates(&session.fix_points, DUPLICATE_TOLERANCE);
+                if self.merge_duplicates && duplicates.is_empty().not() {
+                    session.remove_vertices(&duplicates);
+                    self.status += &self.fill(Key::MergedDuplicates, &[&duplicates.len()]);
+                } else if duplicates.is_empty().not() {
+                    self.status += &self.fill(Key::DuplicateVertices, &[&duplicates.len()]);
+                }
+                self.apply_session(session);
+                self.remember_session();
+            }
+            Err(e) => self.status = self.fill(Key::NotLoaded, &[&self.session_path, &e]),
+        }
+    }
+
+    /// Puts the current session file at the top of the recent sessions, and
+    /// lists changes against the session as it is now.
+    fn remember_session(&mut self) {
+        self.show_recent = false;
+        self.saved = self.session().to_json().ok();
+        self.recent.touch(
+            self.session_path.as_ref(),
+            self.graph.fix_points.len(),
+            self.graph.max_iter,
+        );
+        self.save_recent();
+    }
+
+    fn save_recent(&mut self) {
+        if let Err(e) = self.recent.save() {
+            self.status = self.fill(Key::RecentNotSaved, &[&self.status, &e]);
+        }
+        self.update_thumbnails();
+    }
+
+    /// Makes the thumbnails of new or changed recent sessions and drops those
+    /// of sessions no longer shown. Files that fail to load go without.
+    fn update_thumbnails(&mut self) {
+        let entries = self.recent.shown();
+        self.thumbnails
+            .retain(|path, _| entries.iter().any(|entry| entry.path == *path));
+        for entry in entries {
+            if self
+                .thumbnails
+                .get(&entry.path)
+                .is_some_and(|thumbnail| thumbnail.timestamp == entry.timestamp)
+            {
+                continue;
+            }
+            match Session::load(&entry.path)
+                .ok()
+                .and_then(|session| Thumbnail::new(&session, entry.timestamp))
+            {
+                Some(thumbnail) => {
+                    self.thumbnails.insert(entry.path.clone(), thumbnail);
+                }
+                None => {
+                    self.thumbnails.remove(&entry.path);
+                }
+            }
+        }
+    }
+
+    /// Every way of changing the ratio ends here, dropping a half-typed ratio
+    /// so the input follows the slider again.
+    fn set_ratio(&mut self, ratio: f32) {
+        self.ratio_text = None;
+        if ratio > 1.0 && self.rules().ratio <= 1.0 {
+            let key = match self.graph.out_of_bounds {
+                OutOfBounds::Clip => Key::RatioAboveOneClip,
+                OutOfBounds::Clamp => Key::RatioAboveOneClamp,
+                OutOfBounds::Rescale => Key::RatioAboveOneRescale,
+            };
+            self.status = self.text(key).to_string();
+        }
+        self.edit_rules(|rules| rules.ratio = ratio);
+    }
+
+    /// Whether points are being added or the view animated in steps, which
+    /// refining and the suggested first run wait for.
+    fn busy(&self) -> bool {
+        self.extension.is_some()
+            || self.transition.is_some()
+            || self.playing
+            || self.apply_at.is_some()
+            || self.script.is_empty().not()
+            || self.graph.replay.is_some()
+    }
+
+    /// The clocks the current state needs, by how often each ticks.
+    fn timers(&self) -> Vec<Timer> {
+        let mut timers: Vec<Timer> = vec![];
+        // Nothing animates without the preference, whatever state is left.
+        let animations = self.config.animated();
+        if animations && self.playing {
+            timers.push((Duration::from_millis(16), |_| Message::Tick));
+        }
+        if self.script.is_empty().not() {
+            timers.push((self.script_delay.max(Duration::from_millis(1)), |_| {
+                Message::ScriptStep
+            }));
+        }
+        if animations && self.transition.is_some() {
+            timers.push((Duration::from_millis(16), Message::TransitionTick));
+        }
+        if animations && self.graph.replay.is_some() {
+            timers.push((
+                Duration::from_secs_f32(1.0 / self.graph.replay_speed),
+                |_| Message::ReplayTick,
+            ));
+        }
+        if self.apply_at.is_some() {
+            timers.push((Duration::from_millis(16), Message::DebounceTick));
+        }
+        if self.extension.is_some() {
+            timers.push((Duration::from_millis(16), |_| Message::ExtendTick));
+        }
+        if self.recovered.is_none() {
+            timers.push((recovery::INTERVAL, |_| Message::AutoSave));
+        }
+        if self.gallery_job.is_some() {
+            timers.push((Duration::from_millis(100), |_| Message::GalleryTick));
+        }
+        if self.refine_when_idle {
+            timers.push((Duration::from_millis(100), |_| Message::RefineTick));
+        }
+        timers
+    }
+
+    /// Starts a first run of [`SUGGESTED_ITER`] iterations when the third
+    /// vertex is placed on a canvas without points, played up to with
+    /// animations on. It happens once, and not at all after the iteration
+    /// controls have been used or with the preference off.
+    fn suggest_points(&mut self) {
+        if self.config.suggest_points.not()
+            || self.suggestion_pending.not()
+            || self.graph.fix_points.len() != 3
+            || self.graph.max_iter != 0
+            || self.busy()
+        {
+            return;
+        }
+        self.suggestion_pending = false;
+        self.set_max_iter(SUGGESTED_ITER);
+        if self.graph.max_iter == 0 {
+            return;
+        }
+        self.status = self.fill(Key::SuggestedRun, &[&group_digits(self.graph.max_iter)]);
+        if self.link_sliders || self.config.animated().not() {
+            self.jump_to_end();
+        } else {
+            self.graph.cur_iter = 0;
+            self.playing = true;
+            self.quick_play = true;
+        }
+    }
+
+    /// Generation needs at least one fix point. Asking for points without
+    /// any leaves the iterations at zero and warns in the status line.
+    fn set_max_iter(&mut self, max_iter: i32) {
+        if max_iter > 0 && self.graph.fix_points.is_empty() {
+            self.graph.max_iter = 0;
+            self.graph.cur_iter = 0;
+            self.status = self.text(Key::NoFixPoints).to_string();
+            return;
+        }
+        self.graph.max_iter = max_iter;
+        self.graph.extend_to(self.graph.point_count(max_iter));
+    }
+
+    fn export_choices(&mut self) {
+        let path = &self.choices_path;
+        self.status = match self.graph.sequence() {
+            Ok(sequence) => match std::fs::write(path, sequence.encode()) {
+                Ok(()) => self.fill(Key::SavedChoices, &[&sequence.picks.len(), &path]),
+                Err(e) => self.fill(Key::NotSaved, &[&path, &e]),
+            },
+            Err(e) => self.fill(Key::ChoicesNotExported, &[&e]),
+        };
+    }
+
+    /// Replays the picks in `choices_path` from now on. A file for another
+    /// number of enabled vertices is rejected.
+    fn import_choices(&mut self) {
+        let path = self.choices_path.clone();
+        let sequence = std::fs::read(&path)
+            .map_err(Error::from)
+            .and_then(|bytes| Ok(Sequence::decode(&bytes)?))
+            .and_then(|sequence| {
+                sequence.replay(&self.graph.game())?;
+                Ok(sequence)
+            });
+        match sequence {
+            Ok(sequence) => {
+                let len = sequence.picks.len();
+                self.graph.rule = sequence.rule;
+                self.graph.seed_count = 1;
+                self.graph.sequence = Some(sequence);
+                self.graph.regenerate();
+                self.set_max_iter((len as i32).min(MAX_EXTENDED_ITER));
+                self.graph.cur_iter = self.graph.max_iter;
+                self.status = self.fill(Key::Replaying, &[&len, &path]);
+            }
+            Err(e) => self.status = self.fill(Key::NotImported, &[&path, &e]),
+        }
+    }
+
+    /// Parses the script at `script_path` and queues it to run. Invalid
+    /// scripts are rejected as a whole.
+    fn load_script(&mut self) {
+        match script::load(self.script_path.as_ref()) {
+            Ok(steps) => {
+                self.status = self.fill(Key::Running, &[&self.script_path]);
+                self.script = steps.into();
+                self.script_delay = Duration::ZERO;
+            }
+            Err(e) => self.status = self.fill(Key::NotRun, &[&self.script_path, &e]),
+        }
+    }
+
+    /// Performs queued script steps until the next pause.
+    fn run_script(&mut self, clipboard: &mut dyn Clipboard) -> Command<Message> {
+        let mut commands = vec![];
+        while let Some(step) = self.script.pop_front() {
+            match step.message() {
+                Some(message) => commands.push(self.dispatch(message, clipboard)),
+                None => {
+                    if let Step::Delay(delay) = step {
+                        self.script_delay = delay;
+                    }
+                }
+            }
+            if self.script_delay > Duration::ZERO {
+                break;
+            }
+        }
+        Command::batch(commands)
+    }
+
+    /// Saves the completed fractal as a PNG in `capture_dir` and reports the
+    /// outcome in the status line.
+    fn capture(&mut self) {
+        let path = self.capture_path("capture", "png");
+        self.export_png(&path);
+    }
+
+    /// A timestamped path in `capture_dir`.
+    fn capture_path(&self, kind: &str, extension: &str) -> PathBuf {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        PathBuf::from(&self.capture_dir)
+            .join(format!("sierpinski-{}-{}.{}", kind, timestamp, extension))
+    }
+
+    fn export_png(&mut self, path: &std::path::Path) {
+        self.status = match self.graph.export_png(path, None, false) {
+            Ok(()) => self.fill(Key::Saved, &[&path.display()]),
+            Err(e) => self.fill(Key::NotSaved, &[&path.display(), &e]),
+        };
+    }
+}
+
+/// A gallery contact sheet rendering on a thread of its own.
+#[derive(Debug)]
+struct GalleryJob {
+    path: PathBuf,
+    tiles: usize,
+    /// Tiles rendered so far.
+    done: Arc<AtomicUsize>,
+    handle: JoinHandle<Result<(), Error>>,
+}
+
+/// Iterations being added, from the max iteration when it started.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Extension {
+    from: i32,
+    to: i32,
+}
+
+/// Widget states of one row of the vertex list.
+#[derive(Debug, Default)]
+struct VertexRowState {
+    select: button::State,
+    color: button::State,
+    insert_before: button::State,
+    insert_after: button::State,
+    delete: button::State,
+}
+
+/// The parameters of the chaos game rule that need a regeneration when
+/// changed.
+#[derive(Debug, Clone, PartialEq)]
+struct Rules {
+    ratio: f32,
+    rule: Rule,
+    restriction: Restriction,
+    selection: Selection,
+    rotation: f32,
+    weights: Vec<f32>,
+    anchor_blend: f32,
+    jitter: f32,
+}
+
+/// What undo steps back through.
+#[derive(Debug, Clone, PartialEq)]
+struct Snapshot {
+    fix_points: Vec<Point>,
+    disabled: Vec<bool>,
+    locked: Vec<bool>,
+    anchors: Vec<Option<Point>>,
+    vertex_colors: Vec<Option<usize>>,
+    rules: Rules,
+    background: Color,
+    foreground: Color,
+    theme: Option<Theme>,
+}
+
+/// Edits that coalesce into one undo step when they follow each other
+/// quickly: the same message, for the same vertex where it has one.
+type UndoKind = (Discriminant<Message>, usize);
+
+/// How much of the canvas has to be drawn again after a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Redraw {
+    /// Nothing drawn from the caches changed.
+    Nothing,
+    /// Only the markers and guides over the points.
+    Overlay,
+    /// The points are as they were, only the current iteration moved; see
+    /// [`SierpinskiGraph::redraw_tail`].
+    Tail,
+    All,
+}
+
+/// What `message` leaves to be drawn again, unless handling it finds
+/// more: a debounce tick that regenerates redraws everything.
+fn redraw(message: &Message) -> Redraw {
+    match message {
+        // Timers that often do nothing, undo bookkeeping, and the context
+        // menu, which is drawn afresh every frame.
+        Message::DebounceTick(_)
+        | Message::GalleryTick
+        | Message::AutoSave
+        | Message::ScriptStep
+        | Message::BeginGesture
+        | Message::EndGesture
+        | Message::SetContextMenu(_)
+        | Message::CloseRequested => Redraw::Nothing,
+        Message::ReplayTick => Redraw::Overlay,
+        Message::SetMaxIter(_)
+        | Message::SetCurIter(_)
+        | Message::SetCurFraction(_)
+        | Message::Tick
+        | Message::Peek(_)
+        | Message::SetRatioText(_)
+        | Message::SetSequenceText(_)
+        | Message::ModifiersChanged(_)
+        | Message::SetExtendText(_)
+        | Message::ExtendTick
+        | Message::RefineTick
+        | Message::ShowSnap(_) => Redraw::Tail,
+        _ => Redraw::All,
+    }
+}
+
+/// Whether `message` can be undone, and if so the kind it coalesces by.
+/// Slider drags coalesce; clicks each make their own step.
+fn undo_kind(message: &Message) -> Option<Option<UndoKind>> {
+    let kind = |index| Some(Some((mem::discriminant(message), index)));
+    match message {
+        Message::SetRatio(_)
+        | Message::NudgeRatio(_)
+        | Message::SetRotation(_)
+        | Message::SetJitter(_)
+        | Message::SetAnchorBlend(_)
+        | Message::SetBackground(_)
+        | Message::SetCircle(_) => kind(0),
+        Message::SetVertexWeight(index, _) | Message::MoveFixPoint(index, _) => kind(*index),
+        Message::AddFixPoint(_)
+        | Message::RemoveFixPoint
+        | Message::SetVertexEnabled(..)
+        | Message::SetVertexLocked(..)
+        | Message::ToggleSelectedLock
+        | Message::UnlockAll
+        | Message::DeleteVertex(_)
+        | Message::SelectTheme(_)
+        | Message::SelectPreset(_)
+        | Message::SubmitRatioText
+        | Message::SubmitSequenceText
+        | Message::SetRule(_)
+        | Message::SetRestriction(_)
+        | Message::SetAnchor(..)
+        | Message::ClearAnchors
+        | Message::CycleVertexColor(_)
+        | Message::ResetVertexColors
+        | Message::FitToCanvas
+        | Message::StartEmpty
+        | Message::LoadSession
+        | Message::OpenRecent(_)
+        | Message::RevertSession
+        | Message::FlipSlot => Some(None),
+        _ => None,
+    }
+}
+
+/// The context menu opened by a right-click on the canvas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContextMenu {
+    /// Where the click was, on the canvas, which the actions use.
+    at: Point,
+    /// Where the click was on screen, which the menu opens next to.
+    origin: Point,
+}
+
+/// A mouse gesture in progress on the canvas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Interaction {
+    None,
+    Erasing,
+    Selecting {
+        anchor: Point,
+    },
+    /// Drawing measuring rectangle `index` from `anchor`.
+    Measuring {
+        index: usize,
+        anchor: Point,
+    },
+    /// Dragging fix point `index` around.
+    Moving {
+        index: usize,
+    },
+    /// Dragging the weight ring of fix point `index`, which had `weight`
+    /// when the drag started at height `anchor_y`.
+    Weighting {
+        index: usize,
+        anchor_y: f32,
+        weight: f32,
+    },
+}
+
+/// How often each vertex was picked right after each other one by the same
+/// orbit, over the points before `end`. `counts[from * vertices + to]`.
+#[derive(Debug, Clone, Default)]
+struct Transitions {
+    end: usize,
+    vertices: usize,
+    counts: Vec<usize>,
+}
+
+/// The visible points before `end` counted into cells of [`HEAT_CELL`],
+/// with each cell's level under the mapping it was made for.
+#[derive(Debug, Clone)]
+struct Heat {
+    end: usize,
+    grid: DensityGrid,
+    mapping: (CountScale, f32),
+    levels: Vec<f32>,
+}
+
+/// The sum of the visible points before `end`, for their mean.
+#[derive(Debug, Clone, Copy, Default)]
+struct Mass {
+    end: usize,
+    sum: [f64; 2],
+    count: usize,
+}
+
+#[derive(Debug)]
+struct SierpinskiGraph {
+    max_iter: i32,
+    cur_iter: i32,
+    fix_points: Vec<Point>,
+    random_points: Vec<Point>,
+    /// Parallel to `fix_points`, relative probability of picking each vertex.
+    weights: Vec<f32>,
+    ratio: f32,
+    /// Falls back to [`Rule::Vertex`] while there are too few fix points.
+    rule: Rule,
+    restriction: Restriction,
+    /// Random picks, or the vertices of a fixed list by index into
+    /// `fix_points`.
+    picking: Selection,
+    /// Degrees each new point is turned about the centroid.
+    rotation: f32,
+    /// Standard deviation of the random offset added to each new point, in
+    /// pixels. Zero keeps the points exact.
+    jitter: f32,
+    seed: u64,
+    generator: Generator,
+    /// How many orbits are generated, each from its own seed. Their points
+    /// are interleaved so every iteration adds one point per orbit.
+    seed_count: u8,
+    orbit_start: OrbitStart,
+    /// Sources of further points, positioned after the last of
+    /// `random_points`. The point at index `i` comes from orbit
+    /// `i % seed_count`.
+    orbits: Vec<Orbit>,
+    /// Parallel to `random_points`, index of the vertex each point moved to.
+    choices: Vec<usize>,
+    /// Parallel to `random_points`, the second vertex with the midpoint rule.
+    partners: Vec<Option<usize>>,
+    /// Balanced sampling, picking every enabled vertex at least `quota`
+    /// times per [`ChaosGame::QUOTA_ROUND`] steps of an orbit.
+    balanced: bool,
+    quota: u32,
+    /// Parallel to `random_points`, whether the quota rather than the
+    /// weights picked the vertex.
+    forced: Vec<bool>,
+    /// Imported picks the points follow instead of drawing their own, among
+    /// the enabled vertices and with the current ratio and the like.
+    sequence: Option<Sequence>,
+    /// The steps `sequence` makes from the start of the first orbit.
+    sequence_steps: Vec<sierpinski_triangle_emulator::Step>,
+    /// Why `sequence` was last dropped, for the status line.
+    sequence_dropped: Option<String>,
+    /// The iteration from which an orbit ran off towards infinity, when
+    /// one did. Generation stops there until the points are thrown away.
+    diverged: Option<usize>,
+    /// Why generation last stopped early, for the status line.
+    divergence: Option<String>,
+    /// Parallel to `fix_points`; `true` leaves a vertex out of the game. May
+    /// be shorter than `fix_points`, missing entries are enabled.
+    disabled: Vec<bool>,
+    /// Parallel to `fix_points` like `disabled`; `true` keeps a vertex from
+    /// being moved or removed.
+    locked: Vec<bool>,
+    /// Parallel to `fix_points`, the anchor each step towards the vertex
+    /// bends towards, if it has one.
+    anchors: Vec<Option<Point>>,
+    /// Parallel to `fix_points`, the palette colour picked for each vertex;
+    /// see [`palette::vertex_color`].
+    vertex_colors: Vec<Option<usize>>,
+    /// How far the steps bend towards the anchors.
+    anchor_blend: f32,
+    /// How large the canvas is shown relative to `bound`, below 1 when the
+    /// window is too narrow for it. Layout only; the points keep their
+    /// canvas coordinates.
+    display_scale: f32,
+    /// Held modifiers, Shift turns clicks into anchor edits.
+    modifiers: iced::keyboard::Modifiers,
+    /// Indices of the enabled fix points, in the order the orbit numbers
+    /// its vertices.
+    orbit_vertices: Vec<usize>,
+    /// Highlighted from the vertex list.
+    selected_vertex: Option<usize>,
+    /// Parallel to `random_points`; `true` marks a point hidden by the eraser.
+    /// May be shorter than `random_points`, missing entries are visible.
+    hidden: Vec<bool>,
+    tool: Tool,
+    vertex_marker: VertexMarker,
+    point_coloring: PointColoring,
+    sector_count: u8,
+    eraser_radius: f32,
+    /// Region picked with the select tool.
+    selection: Option<Rectangle>,
+    /// The measuring rectangles, the whole shape first and its copy second.
+    measures: Vec<Rectangle>,
+    interaction: Interaction,
+    /// The weights waiting to be applied, so the weight rings follow edits
+    /// before they regenerate the points.
+    staged_weights: Option<Vec<f32>>,
+    /// Outline the deterministic construction over the points, only drawn
+    /// with exactly three fix points.
+    show_subdivision: bool,
+    /// Whether to draw the copies of the vertex hull under every map.
+    show_maps: bool,
+    /// Whether to mark the centroid of the vertices and the mean of the
+    /// visible points.
+    show_centers: bool,
+    /// Whether to draw the chords of the transitions between vertices.
+    show_transitions: bool,
+    show_convergence: bool,
+    /// Where the convergence demo starts, instead of the centroid.
+    convergence_start: Option<Point>,
+    context_menu: Option<ContextMenu>,
+    right_click: RightClick,
+    /// Decimal places of the coordinates and lengths shown, from the config.
+    decimals: usize,
+    /// The language of the menu, the overlays and the notices, from the
+    /// config.
+    language: Language,
+    /// Whether the loupe is shown, by the checkbox or by holding M.
+    loupe: bool,
+    loupe_held: bool,
+    loupe_zoom: f32,
+    /// The generated points by where they are, kept while the loupe is
+    /// shown.
+    point_grid: Option<PointGrid>,
+    subdivision_level: u32,
+    out_of_bounds: OutOfBounds,
+    /// Zoom and centre the view on the attractor, see
+    /// [`attractor_bounds`](Self::attractor_bounds).
+    auto_frame: bool,
+    /// Debugging aid: label the newest visible point with the index of the
+    /// vertex it moved towards.
+    show_last_pick: bool,
+    /// Scale points by how sparse their neighbourhood is, see
+    /// [`DensityGrid`].
+    density_sizing: bool,
+    point_shape: PointShape,
+    /// Side of a point in pixels, before density sizing.
+    point_size: u8,
+    /// Draw points on whole screen pixels, unless zoomed in too far.
+    pixel_snap: bool,
+    /// PNG exports are rendered this many times larger and shrunk back.
+    supersample: u8,
+    /// Pixels of a PNG export per canvas pixel, before supersampling.
+    export_scale: f32,
+    /// How many points of every run are written to stderr with the vertex
+    /// each jumped towards, for debugging; none in normal use.
+    debug_orbit: usize,
+    /// PNG exports add up the vertex colours of the points in every pixel
+    /// instead of drawing each point over the others.
+    additive_export: bool,
+    /// Canvas and export background, fully transparent when `a` is zero.
+    background: Color,
+    /// Colour of uniformly coloured points, and of the border by default.
+    foreground: Color,
+    border: BorderStyle,
+    bound: Size<f32>,
+    /// While replaying, the index of the orbit point the marker is at; the
+    /// accumulated points are hidden.
+    replay: Option<usize>,
+    /// Replayed steps per second.
+    replay_speed: f32,
+    /// How many earlier steps stay visible behind the marker.
+    replay_trail: u8,
+    /// Faint snapshot of earlier points to compare against, not part of any
+    /// statistics.
+    ghost: Vec<Point>,
+    ghost_in_exports: bool,
+    /// The background and the ghost.
+    ghost_cache: Cache,
+    /// The visible points before `layer_start`.
+    cache: Cache,
+    /// The visible points from `layer_start` on, so a growing `cur_iter` only
+    /// redraws the newest points.
+    tail_cache: Cache,
+    layer_start: usize,
+    /// Everything drawn over the points: border, guides and fix points.
+    static_cache: Cache,
+    /// How many screen pixels the visible points cover, counted when first
+    /// asked for after a redraw.
+    occupied: std::cell::Cell<Option<usize>>,
+    /// The readout of where a snapped click would place a vertex, as last
+    /// sent to the status line.
+    snap_readout: Option<String>,
+    /// The statistics of the visible points, made when first asked for
+    /// after a redraw.
+    report: std::cell::RefCell<Option<Report>>,
+    /// The running sum of [`center_of_mass`](Self::center_of_mass), kept
+    /// while only `cur_iter` moves.
+    mass: std::cell::Cell<Option<Mass>>,
+    /// The running counts of [`transitions`](Self::transitions), kept while
+    /// only `cur_iter` moves.
+    transitions: std::cell::RefCell<Transitions>,
+    /// The ramp of the recency and density colourings.
+    ramp: Ramp,
+    /// How the density colouring maps counts to the ramp, and the
+    /// percentile of the counts that gets its end.
+    count_scale: CountScale,
+    clip_percentile: f32,
+    /// The counts of [`heat_level`](Self::heat_level), kept while the same
+    /// points are visible so a new mapping only maps them again.
+    heat: std::cell::RefCell<Option<Heat>>,
+}
+
+impl Program<Message> for SierpinskiGraph {
+    fn update(
+        &mut self,
+        event: iced::canvas::Event,
+        bounds: iced::Rectangle,
+        cursor: iced::canvas::Cursor,
+    ) -> (iced::canvas::event::Status, Option<Message>) {
+        if let (Some(menu), Event::Mouse(iced::mouse::Event::ButtonPressed(button))) =
+            (self.context_menu, event)
+        {
+            let item = cursor
+                .position_in(&bounds)
+                .filter(|_| button == iced::mouse::Button::Left)
+                .and_then(|position| self.context_menu_item_at(menu, bounds.size(), position));
+            match item {
+                Some(message) => {
+                    self.context_menu = None;
+                    return (event::Status::Captured, Some(message));
+                }
+                // A click elsewhere on the canvas only dismisses the menu, a
+                // right-click opens it again there.
+                None if cursor.is_over(&bounds) && button == iced::mouse::Button::Left => {
+                    self.context_menu = None;
+                    return (event::Status::Captured, None);
+                }
+                None => self.context_menu = None,
+            }
+        }
+        if let Event::Mouse(iced::mouse::Event::ButtonReleased(iced::mouse::Button::Left)) = event {
+            let interaction = std::mem::replace(&mut self.interaction, Interaction::None);
+            match interaction {
+                Interaction::Weighting { .. } => {
+                    return (event::Status::Captured, Some(Message::ReleaseRuleSlider));
+                }
+                Interaction::Moving { .. } => {
+                    return (event::Status::Captured, Some(Message::EndGesture));
+                }
+                _ => {}
+            }
+        }
+
+        let cursor_position = if let Some(position) = cursor.position_in(&bounds) {
+            let [x, y] = self.viewport().invert([position.x, position.y]);
+            Point::new(x, y)
+        } else {
+            return (event::Status::Ignored, None);
+        };
+
+        match event {
+            Event::Mouse(iced::mouse::Event::WheelScrolled { delta }) if self.loupe_shown() => {
+                let y = match delta {
+                    iced::mouse::ScrollDelta::Lines { y, .. }
+                    | iced::mouse::ScrollDelta::Pixels { y, .. } => y,
+                };
+                let zoom = self.loupe_zoom + y.signum();
+                (event::Status::Captured, Some(Message::SetLoupeZoom(zoom)))
+            }
+            Event::Mouse(mouse_event) => {
+                // The weight rings take clicks before the tool does.
+                if let (iced::mouse::Event::ButtonPressed(iced::mouse::Button::Left), Some(index)) =
+                    (mouse_event, self.weight_ring_at(cursor_position))
+                {
+                    self.interaction = Interaction::Weighting {
+                        index,
+                        anchor_y: cursor_position.y,
+                        weight: self.shown_weight(index),
+                    };
+                    return (event::Status::Captured, Some(Message::BeginGesture));
+                }
+                // Ctrl-clicks move the start of the convergence demo.
+                if self.show_convergence && self.modifiers.control {
+                    let start = match mouse_event {
+                        iced::mouse::Event::ButtonPressed(iced::mouse::Button::Left) => {
+                            Some(Some(cursor_position))
+                        }
+                        iced::mouse::Event::ButtonPressed(iced::mouse::Button::Right) => Some(None),
+                        _ => None,
+                    };
+                    if let Some(start) = start {
+                        return (
+                            event::Status::Captured,
+                            Some(Message::PinConvergenceStart(start)),
+                        );
+                    }
+                }
+                // Alt-clicks cycle the colour of the selected vertex.
+                if let (
+                    true,
+                    Some(index),
+                    iced::mouse::Event::ButtonPressed(iced::mouse::Button::Left),
+                ) = (self.modifiers.alt, self.selected_vertex, mouse_event)
+                {
+                    return (
+                        event::Status::Captured,
+                        Some(Message::CycleVertexColor(index)),
+                    );
+                }
+                // Shift-clicks edit the anchor of the selected vertex.
+                if let (true, Some(index)) = (self.modifiers.shift, self.selected_vertex) {
+                    let anchor = match mouse_event {
+                        iced::mouse::Event::ButtonPressed(iced::mouse::Button::Left) => {
+                            Some(Some(cursor_position))
+                        }
+                        iced::mouse::Event::ButtonPressed(iced::mouse::Button::Right) => Some(None),
+                        _ => None,
+                    };
+                    if let Some(anchor) = anchor {
+                        return (
+                            event::Status::Captured,
+                            Some(Message::SetAnchor(index, anchor)),
+                        );
+                    }
+                }
+                let message = match mouse_event {
+                    iced::mouse::Event::ButtonPressed(iced::mouse::Button::Left) => {
+                        match self.tool {
+                            Tool::Place => match self.vertex_at(cursor_position) {
+                                Some(index) => {
+                                    self.interaction = Interaction::Moving { index };
+                                    Some(Message::BeginGesture)
+                                }
+                                None => {
+                                    let position =
+                                        self.snap(cursor_position).unwrap_or(cursor_position);
+                                    Some(Message::AddFixPoint(self.placeable(position)))
+                                }
+                            },
+                            Tool::Eraser => {
+                                self.interaction = Interaction::Erasing;
+                                Some(Message::EraseAt(cursor_position))
+                            }
+                            Tool::Select => {
+                                self.interaction = Interaction::Selecting {
+                                    anchor: cursor_position,
+                                };
+                                Some(Message::SetSelection(None))
+                            }
+                            Tool::Measure => {
+                                // The second rectangle follows the first, a
+                                // third press starts over.
+                                let index = (self.measures.len() == 1) as usize;
+                                self.interaction = Interaction::Measuring {
+                                    index,
+                                    anchor: cursor_position,
+                                };
+                                Some(Message::SetMeasure(index, None))
+                            }
+                        }
+                    }
+                    iced::mouse::Event::CursorMoved { .. } => match self.interaction {
+                        Interaction::None if self.tool == Tool::Place => {
+                            let readout = self
+                                .snap(cursor_position)
+                                .map(|position| self.snap_readout(self.placeable(position)));
+                            if readout == self.snap_readout {
+                                None
+                            } else {
+                                self.snap_readout = readout.clone();
+                                readout.map(Message::ShowSnap)
+                            }
+                        }
+                        Interaction::None => None,
+                        Interaction::Erasing => Some(Message::EraseAt(cursor_position)),
+                        Interaction::Moving { index } => Some(Message::MoveFixPoint(
+                            index,
+                            self.placeable(cursor_position),
+                        )),
+                        Interaction::Selecting { anchor } => Some(Message::SetSelection(Some(
+                            rectangle_between(anchor, cursor_position),
+                        ))),
+                        Interaction::Measuring { index, anchor } => Some(Message::SetMeasure(
+                            index,
+                            Some(rectangle_between(anchor, cursor_position)),
+                        )),
+                        Interaction::Weighting {
+                            index,
+                            anchor_y,
+                            weight,
+                        } => {
+                            let weight = weight + (anchor_y - cursor_position.y) * WEIGHT_PER_PIXEL;
+                            Some(Message::SetVertexWeight(
+                                index,
+                                ((weight * 10.0).round() / 10.0).clamp(0.1, 5.0),
+                            ))
+                        }
+                    },
+                    iced::mouse::Event::ButtonPressed(iced::mouse::Button::Right) => {
+                        let menu = match self.right_click {
+                            RightClick::Menu => self.modifiers.shift.not(),
+                            RightClick::RemoveLast => self.modifiers.shift,
+                        };
+                        if menu {
+                            let origin = cursor.position_in(&bounds).unwrap_or(Point::ORIGIN);
+                            Some(Message::SetContextMenu(Some(ContextMenu {
+                                at: cursor_position,
+                                origin,
+                            })))
+                        } else {
+                            Some(Message::RemoveFixPoint)
+                        }
+                    }
+                    _ => None,
+                };
+                (event::Status::Captured, message)
+            }
+            _ => (event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        bounds: iced::Rectangle,
+        cursor: iced::canvas::Cursor,
+    ) -> Vec<iced::canvas::Geometry> {
+        let centroid = self.centroid();
+        let viewport = self.viewport();
+        let draw_points = |frame: &mut Frame, range: std::ops::Range<usize>| {
+            if self.replay.is_some() {
+                return;
+            }
+            let size = [self.bound.width, self.bound.height];
+            let density = self.density_sizing.then(|| {
+                DensityGrid::new(
+                    size,
+                    DENSITY_CELL,
+                    self.visible_points().map(|p| [p.x, p.y]),
+                )
+            });
+            let snap = self.pixel_snap && viewport.scale <= PIXEL_SNAP_MAX_SCALE;
+            frame.with_save(|frame| {
+                apply_viewport(frame, viewport);
+                self.indexed_points_in(range).for_each(|(i, p)| {
+                    let position = match self.out_of_bounds {
+                        OutOfBounds::Clamp => {
+                            let [x, y] = bounds::clamp(size, [p.x, p.y]);
+                            Point::new(x, y)
+                        }
+                        OutOfBounds::Clip | OutOfBounds::Rescale => *p,
+                    };
+                    let side = density
+                        .as_ref()
+                        .map_or(1.0, |density| density.scale([p.x, p.y]))
+                        * self.point_size as f32;
+                    let offset = (side - 1.0) / 2.0;
+                    let corner = [position.x - offset, position.y - offset];
+                    // The middle of the pixel the point is in.
+                    let center = Point::new(position.x + 0.5, position.y + 0.5);
+                    let color = self.point_color(i, *p, centroid);
+                    match self.point_shape {
+                        PointShape::Square if snap => {
+                            // Whole screen pixels, filled so nothing spills over.
+                            let [x, y] = viewport.snap(corner);
+                            let side = (side * viewport.scale).round().max(1.0) / viewport.scale;
+                            let path = Path::rectangle(Point::new(x, y), Size::new(side, side));
+                            frame.fill(&path, color)
+                        }
+                        PointShape::Square => {
+                            let path = Path::rectangle(
+                                Point::new(corner[0], corner[1]),
+                                Size::new(side, side),
+                            );
+                            frame.stroke(&path, Stroke::default().with_color(color))
+                        }
+                        PointShape::Circle => frame.fill(&Path::circle(center, side / 2.0), color),
+                        PointShape::Soft => {
+                            // Two circles stand in for the fade of exports.
+                            let radius = PointShape::soft_radius(side);
+                            let halo = Color {
+                                a: color.a * svg::SOFT_HALO,
+                                ..color
+                            };
+                            frame.fill(&Path::circle(center, radius), halo);
+                            frame.fill(&Path::circle(center, radius / 2.0), color);
+                        }
+                    }
+                });
+            });
+        };
+        let cur_iter = self.point_count(self.cur_iter);
+        let layer_start = self.layer_start.min(cur_iter);
+        let ghost = self.ghost_cache.draw(bounds.size(), |frame| {
+            if self.background.a > 0.0 {
+                frame.fill(
+                    &Path::rectangle(Point::ORIGIN, frame.size()),
+                    self.background,
+                );
+            }
+            frame.with_save(|frame| {
+                apply_viewport(frame, viewport);
+                for p in &self.ghost {
+                    frame.fill(
+                        &Path::rectangle(*p, Size::new(1.0, 1.0)),
+                        palette::GHOST_COLOR,
+                    );
+                }
+            });
+        });
+        let points = self.cache.draw(bounds.size(), |frame| {
+            draw_points(frame, 0..layer_start);
+        });
+        let tail = self.tail_cache.draw(bounds.size(), |frame| {
+            draw_points(frame, layer_start..cur_iter);
+        });
+        let overlay = self.static_cache.draw(bounds.size(), |frame| {
+            if self.border.shown {
+                // Inset by half the width so the whole stroke is on the canvas.
+                let width = self.border.width;
+                frame.stroke(
+                    &Path::rectangle(
+                        Point::new(width / 2.0, width / 2.0),
+                        Size::new(frame.width() - width, frame.height() - width),
+                    ),
+                    Stroke::default()
+                        .with_color(self.border.color.color(self.foreground))
+                        .with_width(width),
+                );
+            }
+            frame.with_save(|frame| {
+                apply_viewport(frame, viewport);
+                // The region vertices can be placed in, inside the margin.
+                if self.border.margin > 0.0 && self.tool == Tool::Place {
+                    frame.stroke(
+                        &Path::rectangle(Point::ORIGIN, self.bound),
+                        Stroke::default()
+                            .with_color(self.palette().guide)
+                            .with_width(1.0 / viewport.scale),
+                    );
+                }
+                if let (PointColoring::Sector, Some(centroid)) = (self.point_coloring, centroid) {
+                    let reach = frame.width().hypot(frame.height()) / viewport.scale;
+                    let guides = Path::new(|builder| {
+                        for sector in 0..self.sector_count {
+                            let angle = self.sector_boundary(sector);
+                            builder.move_to(centroid);
+                            builder.line_to(Point::new(
+                                centroid.x + reach * angle.cos(),
+                                centroid.y + reach * angle.sin(),
+                            ));
+                        }
+                    });
+                    frame.stroke(&guides, Stroke::default().with_color(self.palette().guide));
+                    frame.fill(&Path::circle(centroid, 3.0), self.palette().guide);
+                }
+                if let (true, [a, b, c]) = (self.show_subdivision, self.fix_points.as_slice()) {
+                    let corners = [[a.x, a.y], [b.x, b.y], [c.x, c.y]];
+                    let outlines = Path::new(|builder| {
+                        for [a, b, c] in gasket::triangles(corners, self.subdivision_level) {
+                            builder.move_to(Point::new(a[0], a[1]));
+                            builder.line_to(Point::new(b[0], b[1]));
+                            builder.line_to(Point::new(c[0], c[1]));
+                            builder.close();
+                        }
+                    });
+                    frame.stroke(
+                        &outlines,
+                        Stroke::default()
+                            .with_color(palette::OVERLAY_COLOR)
+                            .with_width(0.5),
+                    );
+                }
+                if self.show_maps {
+                    self.draw_maps(frame);
+                }
+                self.draw_anchors(frame);
+                if self.show_transitions {
+                    self.draw_transitions(frame);
+                }
+                if self.show_convergence {
+                    self.draw_convergence(frame);
+                }
+                if let Some(p) = self.selected_vertex.and_then(|i| self.fix_points.get(i)) {
+                    frame.stroke(
+                        &Path::circle(*p, 9.0),
+                        Stroke::default()
+                            .with_color(palette::OVERLAY_COLOR)
+                            .with_width(2.0),
+                    );
+                }
+                self.fix_points.iter().enumerate().for_each(|(i, p)| {
+                    let color = if self.is_enabled(i).not() {
+                        self.palette().guide
+                    } else {
+                        self.marker_color(i)
+                    };
+                    match self.vertex_marker {
+                        VertexMarker::FilledCircle => frame.fill(&Path::circle(*p, 5.0), color),
+                        VertexMarker::Ring => frame.stroke(
+                            &Path::circle(*p, 5.0),
+                            Stroke::default().with_color(color).with_width(1.5),
+                        ),
+                        VertexMarker::Cross => {
+                            let cross = Path::new(|builder| {
+                                builder.move_to(Point::new(p.x - 5.0, p.y - 5.0));
+                                builder.line_to(Point::new(p.x + 5.0, p.y + 5.0));
+                                builder.move_to(Point::new(p.x - 5.0, p.y + 5.0));
+                                builder.line_to(Point::new(p.x + 5.0, p.y - 5.0));
+                            });
+                            frame
+                                .stroke(&cross, Stroke::default().with_color(color).with_width(1.5))
+                        }
+                        VertexMarker::Square => frame.fill(
+                            &Path::rectangle(
+                                Point::new(p.x - 5.0, p.y - 5.0),
+                                Size::new(10.0, 10.0),
+                            ),
+                            color,
+                        ),
+                    }
+                });
+                self.draw_locks(frame);
+                if self.show_centers {
+                    self.draw_centers(frame);
+                }
+                self.draw_weight_rings(frame);
+                if let Some(index) = self.replay.filter(|i| *i < self.random_points.len()) {
+                    self.draw_replay(frame, index);
+                }
+                if self.show_last_pick {
+                    let last = self
+                        .point_count(self.cur_iter)
+                        .min(self.random_points.len());
+                    if let Some(index) = last.checked_sub(1) {
+                        let p = self.random_points[index];
+                        let vertex = self.choices[index];
+                        let content = match self.partners[index] {
+                            Some(partner) => format!(
+                                "{}+{} ({}, {})",
+                                vertex_label(vertex),
+                                vertex_label(partner),
+                                vertex,
+                                partner
+                            ),
+                            None => format!("{} ({})", vertex_label(vertex), vertex),
+                        };
+                        frame.fill(&Path::circle(p, 2.0), palette::OVERLAY_COLOR);
+                        frame.fill_text(canvas::Text {
+                            content,
+                            position: Point::new(p.x + 4.0, p.y - 16.0),
+                            color: palette::OVERLAY_COLOR,
+                            size: 14.0,
+                            ..canvas::Text::default()
+                        });
+                    }
+                }
+            });
+        });
+
+        let mut geoms = vec![ghost, points, tail, overlay];
+        if self.tool == Tool::Eraser {
+            if let Some(position) = cursor.position_in(&bounds) {
+                let [x, y] = viewport.invert([position.x, position.y]);
+                let position = Point::new(x, y);
+                let mut frame = Frame::new(bounds.size());
+                apply_viewport(&mut frame, viewport);
+                frame.stroke(
+                    &Path::circle(position, self.eraser_radius),
+                    Stroke::default().with_color(palette::OVERLAY_COLOR),
+                );
+                geoms.push(frame.into_geometry());
+            }
+        }
+        if self.tool == Tool::Place {
+            let snapped = cursor.position_in(&bounds).and_then(|position| {
+                let [x, y] = viewport.invert([position.x, position.y]);
+                self.snap(Point::new(x, y))
+            });
+            if let (Some(snapped), Some(last)) = (snapped, self.fix_points.last()) {
+                let snapped = self.placeable(snapped);
+                let mut frame = Frame::new(bounds.size());
+                apply_viewport(&mut frame, viewport);
+                frame.stroke(
+                    &Path::line(*last, snapped),
+                    Stroke::default().with_color(self.palette().guide),
+                );
+                frame.stroke(
+                    &Path::circle(snapped, 4.0),
+                    Stroke::default().with_color(palette::OVERLAY_COLOR),
+                );
+                geoms.push(frame.into_geometry());
+            }
+        }
+        if let (Tool::Select, Some(selection)) = (self.tool, self.selection) {
+            let mut frame = Frame::new(bounds.size());
+            apply_viewport(&mut frame, viewport);
+            frame.stroke(
+                &Path::rectangle(selection.position(), selection.size()),
+                Stroke::default().with_color(self.palette().primary),
+            );
+            geoms.push(frame.into_geometry());
+        }
+        if let (true, Some(position)) = (self.loupe_shown(), cursor.position_in(&bounds)) {
+            let mut frame = Frame::new(bounds.size());
+            self.draw_loupe(&mut frame, viewport, position, centroid);
+            geoms.push(frame.into_geometry());
+        }
+        if self.tool == Tool::Measure && self.measures.is_empty().not() {
+            let mut frame = Frame::new(bounds.size());
+            apply_viewport(&mut frame, viewport);
+            for (i, measure) in self.measures.iter().enumerate() {
+                let color = palette::categorical(i);
+                frame.stroke(
+                    &Path::rectangle(measure.position(), measure.size()),
+                    Stroke::default().with_color(color).with_width(1.5),
+                );
+                frame.fill_text(canvas::Text {
+                    content: (i + 1).to_string(),
+                    position: Point::new(measure.x + 3.0, measure.y + 2.0),
+                    color,
+                    ..canvas::Text::default()
+                });
+            }
+            geoms.push(frame.into_geometry());
+        }
+        if let Some(menu) = self.context_menu {
+            let mut frame = Frame::new(bounds.size());
+            self.draw_context_menu(&mut frame, menu, cursor.position_in(&bounds));
+            geoms.push(frame.into_geometry());
+        }
+        geoms
+    }
 }
 
-#[derive(Debug)]
-struct SierpinskiGraph {
-    max_iter: i32,
-    cur_iter: i32,
-    fix_points: Vec<Point>,
-    random_points: Vec<Point>,
-    bound: Size<f32>,
-    cache: Cache,
-}
+impl SierpinskiGraph {
+    fn new() -> SierpinskiGraph {
+        let seed = rand::random();
+        SierpinskiGraph {
+            max_iter: 0,
+            cur_iter: 0,
+            fix_points: vec![],
+            random_points: vec![],
+            weights: vec![],
+            ratio: 0.5,
+            rule: Rule::Vertex,
+            restriction: Restriction::None,
+            picking: Selection::Random,
+            rotation: 0.0,
+            jitter: 0.0,
+            seed,
+            generator: Generator::ChaCha,
+            seed_count: 1,
+            orbit_start: OrbitStart::Scattered,
+            orbits: vec![],
+            choices: vec![],
+            partners: vec![],
+            balanced: false,
+            quota: DEFAULT_QUOTA,
+            forced: vec![],
+            sequence: None,
+            sequence_steps: vec![],
+            sequence_dropped: None,
+            diverged: None,
+            divergence: None,
+            disabled: vec![],
+            locked: vec![],
+            anchors: vec![],
+            vertex_colors: vec![],
+            anchor_blend: 0.5,
+            display_scale: 1.0,
+            modifiers: iced::keyboard::Modifiers::default(),
+            orbit_vertices: vec![],
+            selected_vertex: None,
+            hidden: vec![],
+            tool: Tool::Place,
+            vertex_marker: VertexMarker::FilledCircle,
+            point_coloring: PointColoring::Uniform,
+            sector_count: 3,
+            eraser_radius: 20.0,
+            selection: None,
+            measures: vec![],
+            interaction: Interaction::None,
+            staged_weights: None,
+            bound: Size::new(600.0, 600.0),
+            background: Theme::Light.background(),
+            border: BorderStyle::default(),
+            foreground: Theme::Light.foreground(),
+            show_subdivision: false,
+            show_maps: false,
+            show_centers: false,
+            show_transitions: false,
+            show_convergence: false,
+            convergence_start: None,
+            context_menu: None,
+            right_click: RightClick::Menu,
+            decimals: 1,
+            language: Language::English,
+            loupe: false,
+            loupe_held: false,
+            loupe_zoom: 4.0,
+            point_grid: None,
+            show_last_pick: false,
+            density_sizing: false,
+            point_shape: PointShape::Square,
+            point_size: 1,
+            pixel_snap: false,
+            supersample: 1,
+            export_scale: 1.0,
+            debug_orbit: 0,
+            additive_export: false,
+            out_of_bounds: OutOfBounds::Clip,
+            auto_frame: false,
+            subdivision_level: 3,
+            replay: None,
+            replay_speed: 4.0,
+            replay_trail: 8,
+            ghost: vec![],
+            ghost_in_exports: false,
+            ghost_cache: Cache::new(),
+            cache: Cache::new(),
+            tail_cache: Cache::new(),
+            layer_start: 0,
+            static_cache: Cache::new(),
+            occupied: std::cell::Cell::new(None),
+            snap_readout: None,
+            report: std::cell::RefCell::new(None),
+            mass: std::cell::Cell::new(None),
+            transitions: std::cell::RefCell::new(Transitions::default()),
+            ramp: Ramp::Viridis,
+            count_scale: CountScale::Linear,
+            clip_percentile: 99.0,
+            heat: std::cell::RefCell::new(None),
+        }
+    }
+
+    /// Takes the vertices, rules and style of `session`, each brought into
+    /// the range its control allows, and starts over without points. A
+    /// hand-edited file gets no further than the controls could take it.
+    fn apply_session(&mut self, session: &Session) {
+        self.fix_points = session
+            .fix_points
+            .iter()
+            .map(|[x, y]| Point::new(*x, *y))
+            .collect();
+        self.max_iter = 0;
+        self.cur_iter = 0;
+        self.vertex_marker = session.vertex_marker;
+        self.point_coloring = session.point_coloring;
+        self.sector_count = session.sector_count.clamp(3, 12);
+        self.weights = session.weights.iter().map(|w| w.clamp(0.1, 5.0)).collect();
+        self.ratio = if session.ratio > 0.0 && session.ratio < MAX_RATIO {
+            session.ratio
+        } else {
+            session.ratio.clamp(0.01, 1.0)
+        };
+        self.rule = session.rule;
+        self.disabled = session.disabled.clone();
+        self.locked = session.locked.clone();
+        self.anchors = session
+            .anchors
+            .iter()
+            .map(|anchor| anchor.map(|[x, y]| Point::new(x, y)))
+            .collect();
+        self.vertex_colors = session.vertex_colors.clone();
+        self.anchor_blend = session.anchor_blend.clamp(0.0, 1.0);
+        self.selected_vertex = None;
+        self.restriction = session.restriction;
+        self.picking = session.selection.clone();
+        self.rotation = session.rotation.clamp(0.0, 120.0);
+        self.jitter = session.jitter.clamp(0.0, 5.0);
+        self.seed = session.seed;
+        self.generator = session.generator;
+        self.seed_count = session.seed_count.clamp(1, MAX_SEEDS);
+        self.orbit_start = session.orbit_start;
+        self.balanced = session.balanced;
+        self.quota = session.quota.clamp(1, MAX_QUOTA);
+        self.out_of_bounds = session.out_of_bounds;
+        self.auto_frame = session.auto_frame;
+        self.background = Color::from(session.background);
+        self.foreground = Color::from(session.foreground);
+        self.border = BorderStyle {
+            width: session.border.width.clamp(0.5, 4.0),
+            margin: session.border.margin.clamp(0.0, 30.0),
+            ..session.border
+        };
+        self.regenerate();
+    }
+
+    fn redraw(&mut self) {
+        self.layer_start = self.point_count(self.cur_iter);
+        self.occupied.set(None);
+        self.mass.set(None);
+        self.transitions.take();
+        self.report.take();
+        self.ghost_cache.clear();
+        self.cache.clear();
+        self.tail_cache.clear();
+        self.static_cache.clear();
+    }
+
+    /// Like [`redraw`](Self::redraw) after changes that only moved
+    /// `cur_iter`: while it grows only the newest points are drawn again, a
+    /// long tail is folded back into the first layer.
+    fn redraw_tail(&mut self) {
+        let cur_iter = self.point_count(self.cur_iter);
+        // Rescaling and density sizing change every drawn point when points
+        // are added.
+        let restyled = self.out_of_bounds == OutOfBounds::Rescale
+            || self.auto_frame
+            || self.density_sizing
+            || matches!(
+                self.point_coloring,
+                PointColoring::Recency | PointColoring::Density
+            );
+        if restyled || cur_iter < self.layer_start || cur_iter - self.layer_start > TAIL_LAYER_LEN {
+            self.redraw();
+        } else {
+            self.tail_cache.clear();
+            self.static_cache.clear();
+            self.occupied.set(None);
+            self.report.take();
+        }
+    }
+
+    /// Throws away the generated points and generates `max_iter` new ones for
+    /// the current fix points.
+    fn regenerate(&mut self) {
+        self.clear_points();
+        self.extend_to(self.point_count(self.max_iter));
+    }
+
+    /// Adds a vertex after the others. Like every edit of the vertices, it
+    /// drops the generated points and sets both iterations back to 0.
+    fn push_vertex(&mut self, point: Point) {
+        self.fix_points.push(point);
+        self.restart();
+    }
+
+    /// Inserts a vertex as `index`, at most the vertex count, moving the
+    /// settings of the vertices from there on along with them.
+    fn insert_vertex(&mut self, index: usize, point: Point) {
+        palette::pin_vertex_colors(&mut self.vertex_colors, self.fix_points.len());
+        self.fix_points.insert(index, point);
+        insert_at(&mut self.weights, index, 1.0);
+        insert_at(&mut self.disabled, index, false);
+        insert_at(&mut self.locked, index, false);
+        insert_at(&mut self.anchors, index, None);
+        insert_at(&mut self.vertex_colors, index, None);
+        self.picking = self.picking.inserted(index);
+        self.selected_vertex = None;
+        self.restart();
+    }
+
+    /// Removes the last vertex.
+    fn pop_vertex(&mut self) {
+        self.fix_points.pop();
+        self.selected_vertex = None;
+        self.restart();
+    }
+
+    /// Removes vertex `index` together with its settings.
+    fn remove_vertex(&mut self, index: usize) {
+        palette::pin_vertex_colors(&mut self.vertex_colors, self.fix_points.len());
+        self.fix_points.remove(index);
+        remove_at(&mut self.weights, index);
+        remove_at(&mut self.disabled, index);
+        remove_at(&mut self.locked, index);
+        remove_at(&mut self.anchors, index);
+        remove_at(&mut self.vertex_colors, index);
+        self.picking = without_vertex(&self.picking, index);
+        self.selected_vertex = None;
+        self.restart();
+    }
+
+    /// Drops the points an edit of the vertices left out of date and sets
+    /// both iterations back to 0. Clearing the points also drops the vertex
+    /// indices recorded for them, which an insertion or removal would have
+    /// shifted.
+    fn restart(&mut self) {
+        self.clear_points();
+        self.max_iter = 0;
+        self.cur_iter = 0;
+    }
+
+    /// Drops the generated points together with everything kept parallel to
+    /// them.
+    fn clear_points(&mut self) {
+        self.random_points.clear();
+        self.heat.take();
+        self.diverged = None;
+        self.replay = None;
+        self.choices.clear();
+        self.partners.clear();
+        self.forced.clear();
+        self.hidden.clear();
+        // Weights of removed vertices must not carry over to new ones.
+        self.weights.truncate(self.fix_points.len());
+        self.disabled.truncate(self.fix_points.len());
+        self.locked.truncate(self.fix_points.len());
+        self.anchors.truncate(self.fix_points.len());
+        self.vertex_colors.truncate(self.fix_points.len());
+        self.orbit_vertices = (0..self.fix_points.len())
+            .filter(|i| self.is_enabled(*i))
+            .collect();
+        self.orbits = (0..self.seed_count)
+            .map(|i| self.orbit_game(i).build())
+            .collect();
+        if self.debug_orbit > 0 {
+            eprintln!(
+                "orbit: seed {} (+1 for each further orbit), {}, {} orbit(s), vertices {}",
+                self.seed,
+                self.generator,
+                self.seed_count,
+                self.orbit_vertices
+                    .iter()
+                    .map(usize::to_string)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            );
+        }
+        if let Some(grid) = &mut self.point_grid {
+            *grid = PointGrid::new([self.bound.width, self.bound.height], LOUPE_CELL);
+        }
+        self.sequence_steps.clear();
+        if let Some(sequence) = &self.sequence {
+            match sequence.replay(&self.orbit_game(0)) {
+                Ok(steps) => self.sequence_steps = steps,
+                Err(e) => {
+                    self.sequence_dropped = Some(self.language.fill(Key::StoppedReplaying, &[&e]));
+                    self.sequence = None;
+                }
+            }
+        }
+    }
+
+    /// Writes the last generated point to stderr, as generated by `orbit`.
+    fn dump_step(&self, orbit: usize) {
+        let index = self.random_points.len() - 1;
+        let p = self.random_points[index];
+        let partner = match self.partners[index] {
+            Some(partner) => format!("+{}", partner),
+            None => String::new(),
+        };
+        eprintln!(
+            "orbit: point {} of orbit {} at {}, {} towards vertex {}{}{}",
+            index,
+            orbit,
+            p.x,
+            p.y,
+            self.choices[index],
+            partner,
+            if self.forced[index] { " (forced)" } else { "" }
+        );
+    }
 
-impl Program<Message> for SierpinskiGraph {
-    fn update(
-        &mut self,
-        event: iced::canvas::Event,
-        bounds: iced::Rectangle,
-        cursor: iced::canvas::Cursor,
-    ) -> (iced::canvas::event::Status, Option<Message>) {
-        let cursor_position = if let Some(position) = cursor.position_in(&bounds) {
-            position
-        } else {
-            return (event::Status::Ignored, None);
+    /// The game of orbit `i`, with its own seed and start.
+    fn orbit_game(&self, i: u8) -> ChaosGame {
+        let seed = self.seed.wrapping_add(i as u64);
+        let game = self.game().seed(seed);
+        let start = match self.orbit_start {
+            // The first orbit starts on the first vertex as it always has,
+            // the others somewhere of their own.
+            OrbitStart::Scattered if i == 0 => None,
+            OrbitStart::Scattered => self.start_point(seed),
+            OrbitStart::Vertices => self.start_vertex(seed),
         };
+        match start {
+            Some(start) => game.start(start),
+            None => game,
+        }
+    }
 
-        match event {
-            Event::Mouse(mouse_event) => {
-                let message = match mouse_event {
-                    iced::mouse::Event::ButtonPressed(iced::mouse::Button::Left) => {
-                        Some(Message::AddFixPoint(cursor_position))
+    /// The picks of every point so far, among the enabled vertices.
+    fn sequence(&self) -> Result<Sequence, String> {
+        if self.sequence.is_none() && self.seed_count > 1 {
+            return Err(self.language.text(Key::SingleSeedOnly).to_string());
+        }
+        let enabled = |vertex: usize| {
+            self.orbit_vertices
+                .iter()
+                .position(|i| *i == vertex)
+                .unwrap_or(0)
+        };
+        Sequence::new(
+            self.orbit_vertices.len(),
+            self.orbit_rule(),
+            self.choices
+                .iter()
+                .zip(&self.partners)
+                .map(|(vertex, partner)| (enabled(*vertex), partner.map(enabled))),
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    /// A point drawn from `seed` inside the box around the enabled vertices.
+    fn start_point(&self, seed: u64) -> Option<[f32; 2]> {
+        let vertices = self
+            .orbit_vertices
+            .iter()
+            .map(|i| self.fix_points[*i])
+            .collect::<Vec<_>>();
+        let first = vertices.first()?;
+        let (min, max) = vertices.iter().fold((*first, *first), |(min, max), p| {
+            (
+                Point::new(min.x.min(p.x), min.y.min(p.y)),
+                Point::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        });
+        let mut rng = StdRng::seed_from_u64(seed);
+        Some([
+            min.x + rng.gen::<f32>() * (max.x - min.x),
+            min.y + rng.gen::<f32>() * (max.y - min.y),
+        ])
+    }
+
+    /// An enabled vertex drawn from `seed` by the weights, or uniformly if
+    /// no vertex could be drawn by them.
+    fn start_vertex(&self, seed: u64) -> Option<[f32; 2]> {
+        let vertices = &self.orbit_vertices;
+        if vertices.is_empty() {
+            return None;
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let index = match WeightedIndex::new(vertices.iter().map(|i| self.weight(*i))) {
+            Ok(weights) => weights.sample(&mut rng),
+            Err(_) => rng.gen_range(0..vertices.len()),
+        };
+        let p = self.fix_points[vertices[index]];
+        Some([p.x, p.y])
+    }
+
+    fn is_enabled(&self, index: usize) -> bool {
+        !self.disabled.get(index).copied().unwrap_or(false)
+    }
+
+    fn is_locked(&self, index: usize) -> bool {
+        self.locked.get(index).copied().unwrap_or(false)
+    }
+
+    /// The rule the orbits follow.
+    fn orbit_rule(&self) -> Rule {
+        match self.rule {
+            Rule::Midpoint if self.orbit_vertices.len() < 2 => Rule::Vertex,
+            rule => rule,
+        }
+    }
+
+    /// The chaos game for the enabled vertices and rule settings.
+    fn game(&self) -> ChaosGame {
+        let vertices = &self.orbit_vertices;
+        ChaosGame::new(
+            vertices
+                .iter()
+                .map(|i| [self.fix_points[*i].x, self.fix_points[*i].y]),
+        )
+        .ratio(self.ratio)
+        .rule(self.orbit_rule())
+        .weights(vertices.iter().map(|i| self.weight(*i)))
+        .restriction(self.restriction)
+        .selection(self.picking.renumbered(vertices))
+        .rotation(self.rotation)
+        .anchors(
+            vertices
+                .iter()
+                .map(|i| self.anchors.get(*i).copied().flatten().map(|p| [p.x, p.y])),
+        )
+        .anchor_blend(self.anchor_blend)
+        .quota(if self.balanced { self.quota } else { 0 })
+        .jitter(self.jitter)
+        .seed(self.seed)
+        .generator(self.generator)
+    }
+
+    /// The least share of the first picks balanced sampling gives each
+    /// enabled vertex, `None` when it is off.
+    fn quota_share(&self) -> Option<f32> {
+        let enabled = self.orbit_vertices.len().max(1);
+        self.balanced.then(|| {
+            let quota = (self.quota as usize).min(ChaosGame::QUOTA_ROUND / enabled);
+            quota as f32 / ChaosGame::QUOTA_ROUND as f32
+        })
+    }
+
+    /// How many points `iterations` iterations of every orbit make.
+    fn point_count(&self, iterations: i32) -> usize {
+        iterations.max(0) as usize * self.seed_count as usize
+    }
+
+    /// Generates points until there are `len` of them.
+    fn extend_to(&mut self, len: usize) {
+        let size = [self.bound.width, self.bound.height];
+        while self.random_points.len() < len && self.diverged.is_none() {
+            let orbit = self.random_points.len() % self.orbits.len().max(1);
+            let step = match self.sequence {
+                Some(_) => self.sequence_steps.get(self.random_points.len()).copied(),
+                None => self.orbits.get_mut(orbit).and_then(Orbit::step),
+            };
+            match step {
+                Some(step) if bounds::diverged(size, step.point) => {
+                    // The point is never kept, so nothing NaN or huge gets
+                    // drawn or exported.
+                    let iterations = self.random_points.len() / self.orbits.len().max(1);
+                    self.diverged = Some(iterations);
+                    let key = if self.ratio_diverges() {
+                        Key::DivergedRatio
+                    } else {
+                        Key::DivergedVertices
+                    };
+                    self.divergence =
+                        Some(self.language.fill(key, &[&group_digits(iterations as i32)]));
+                }
+                Some(step) => {
+                    if let Some(grid) = &mut self.point_grid {
+                        grid.insert(self.random_points.len(), step.point);
                     }
-                    iced::mouse::Event::ButtonPressed(iced::mouse::Button::Right) => {
-                        Some(Message::RemoveFixPoint)
+                    self.random_points
+                        .push(Point::new(step.point[0], step.point[1]));
+                    // The orbit only knows the enabled vertices.
+                    self.choices.push(self.orbit_vertices[step.vertex]);
+                    self.partners
+                        .push(step.partner.map(|partner| self.orbit_vertices[partner]));
+                    self.forced.push(step.forced);
+                    if self.random_points.len() <= self.debug_orbit {
+                        self.dump_step(orbit);
                     }
-                    _ => None,
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Whether the ratio alone sends the points away: every step ends
+    /// at least as far from the vertex as it started.
+    fn ratio_diverges(&self) -> bool {
+        (1.0 - self.ratio).abs() >= 1.0
+    }
+
+    fn rules(&self) -> Rules {
+        Rules {
+            ratio: self.ratio,
+            rule: self.rule,
+            restriction: self.restriction,
+            selection: self.picking.clone(),
+            rotation: self.rotation,
+            weights: (0..self.fix_points.len()).map(|i| self.weight(i)).collect(),
+            anchor_blend: self.anchor_blend,
+            jitter: self.jitter,
+        }
+    }
+
+    fn set_rules(&mut self, rules: Rules) {
+        self.ratio = rules.ratio;
+        self.rule = rules.rule;
+        self.restriction = rules.restriction;
+        self.picking = rules.selection;
+        self.rotation = rules.rotation;
+        self.weights = rules.weights;
+        self.anchor_blend = rules.anchor_blend;
+        self.jitter = rules.jitter;
+    }
+
+    fn weight(&self, index: usize) -> f32 {
+        self.weights.get(index).copied().unwrap_or(1.0)
+    }
+
+    /// The weight the canvas shows, staged or else applied.
+    fn shown_weight(&self, index: usize) -> f32 {
+        self.staged_weights
+            .as_ref()
+            .and_then(|weights| weights.get(index))
+            .copied()
+            .unwrap_or_else(|| self.weight(index))
+    }
+
+    /// The probability of picking fix point `index`, zero while it is
+    /// disabled.
+    fn probability(&self, index: usize) -> f32 {
+        let total = (0..self.fix_points.len())
+            .filter(|i| self.is_enabled(*i))
+            .map(|i| self.shown_weight(i))
+            .sum::<f32>();
+        if self.is_enabled(index) && total > 0.0 {
+            self.shown_weight(index) / total
+        } else {
+            0.0
+        }
+    }
+
+    /// The unlocked fix point a press at `position` grabs, the nearest
+    /// within [`VERTEX_GRAB_RADIUS`].
+    fn vertex_at(&self, position: Point) -> Option<usize> {
+        self.fix_points
+            .iter()
+            .enumerate()
+            .filter(|(i, p)| self.is_locked(*i).not() && p.distance(position) <= VERTEX_GRAB_RADIUS)
+            .min_by(|(_, a), (_, b)| a.distance(position).total_cmp(&b.distance(position)))
+            .map(|(i, _)| i)
+    }
+
+    /// The fix point whose weight ring is under `position`, if any. Rings
+    /// are only shown with at least two fix points.
+    fn weight_ring_at(&self, position: Point) -> Option<usize> {
+        if self.fix_points.len() < 2 {
+            return None;
+        }
+        self.fix_points
+            .iter()
+            .position(|p| (p.distance(position) - WEIGHT_RING_RADIUS).abs() <= WEIGHT_RING_REACH)
+    }
+
+    /// The actions the context menu offers for a click at `at`.
+    fn context_menu_items(&self, at: Point) -> Vec<(String, Message)> {
+        let mut items = vec![(
+            self.language.text(Key::AddVertexHere).to_string(),
+            Message::AddFixPoint(at),
+        )];
+        let nearest = self
+            .fix_points
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.is_locked(*i).not())
+            .min_by(|(_, a), (_, b)| a.distance(at).total_cmp(&b.distance(at)));
+        if let Some((index, _)) = nearest {
+            items.push((
+                self.language
+                    .fill(Key::RemoveNearestVertex, &[&vertex_label(index)]),
+                Message::DeleteVertex(index),
+            ));
+        }
+        if self.show_convergence {
+            items.push((
+                self.language.text(Key::StartConvergenceHere).to_string(),
+                Message::PinConvergenceStart(Some(at)),
+            ));
+        }
+        items.push((
+            self.language.fill(
+                Key::CopyCoordinatesHere,
+                &[
+                    &Fixed::new(at.x, self.decimals),
+                    &Fixed::new(at.y, self.decimals),
+                ],
+            ),
+            Message::CopyCoordinates(at),
+        ));
+        items
+    }
+
+    /// Where `menu` is drawn on a canvas of `size` on screen: next to the
+    /// click, moved back in where it would stick out.
+    fn context_menu_bounds(&self, menu: ContextMenu, size: Size) -> Rectangle {
+        let height = self.context_menu_items(menu.at).len() as f32 * MENU_ROW;
+        let x = menu.origin.x.min(size.width - MENU_WIDTH).max(0.0);
+        let y = menu.origin.y.min(size.height - height).max(0.0);
+        Rectangle::new(Point::new(x, y), Size::new(MENU_WIDTH, height))
+    }
+
+    /// The message of the menu item under `position`, on screen.
+    fn context_menu_item_at(
+        &self,
+        menu: ContextMenu,
+        size: Size,
+        position: Point,
+    ) -> Option<Message> {
+        let area = self.context_menu_bounds(menu, size);
+        if area.contains(position).not() {
+            return None;
+        }
+        let row = ((position.y - area.y) / MENU_ROW) as usize;
+        self.context_menu_items(menu.at)
+            .into_iter()
+            .nth(row)
+            .map(|(_, message)| message)
+    }
+
+    fn draw_context_menu(&self, frame: &mut Frame, menu: ContextMenu, cursor: Option<Point>) {
+        let area = self.context_menu_bounds(menu, frame.size());
+        let palette = self.palette();
+        frame.fill_rectangle(area.position(), area.size(), palette.background);
+        for (row, (label, _)) in self.context_menu_items(menu.at).into_iter().enumerate() {
+            let top = Point::new(area.x, area.y + row as f32 * MENU_ROW);
+            let hovered = cursor.is_some_and(|cursor| {
+                Rectangle::new(top, Size::new(MENU_WIDTH, MENU_ROW)).contains(cursor)
+            });
+            if hovered {
+                frame.fill_rectangle(
+                    top,
+                    Size::new(MENU_WIDTH, MENU_ROW),
+                    Color {
+                        a: 0.2,
+                        ..palette::OVERLAY_COLOR
+                    },
+                );
+            }
+            frame.fill_text(canvas::Text {
+                content: label,
+                position: Point::new(top.x + 8.0, top.y + 5.0),
+                color: palette.text,
+                size: 14.0,
+                ..canvas::Text::default()
+            });
+        }
+        frame.stroke(
+            &Path::rectangle(area.position(), area.size()),
+            Stroke::default().with_color(palette::OVERLAY_COLOR),
+        );
+    }
+
+    /// Around every fix point, a faint full ring with an arc over it as long
+    /// as the point's share of the picks, starting at the top and running
+    /// clockwise. The ring being dragged is labelled with its weight.
+    fn draw_weight_rings(&self, frame: &mut Frame) {
+        if self.fix_points.len() < 2 {
+            return;
+        }
+        for (i, p) in self.fix_points.iter().enumerate() {
+            frame.stroke(
+                &Path::circle(*p, WEIGHT_RING_RADIUS),
+                Stroke::default()
+                    .with_color(self.palette().guide)
+                    .with_width(3.0),
+            );
+            let probability = self.probability(i);
+            if probability > 0.0 {
+                let start_angle = -std::f32::consts::FRAC_PI_2;
+                let arc = Path::new(|builder| {
+                    builder.arc(canvas::path::Arc {
+                        center: *p,
+                        radius: WEIGHT_RING_RADIUS,
+                        start_angle,
+                        end_angle: start_angle + std::f32::consts::TAU * probability,
+                    })
+                });
+                frame.stroke(
+                    &arc,
+                    Stroke::default()
+                        .with_color(self.palette().primary)
+                        .with_width(3.0),
+                );
+            }
+        }
+        if let Interaction::Weighting { index, .. } = self.interaction {
+            if let Some(p) = self.fix_points.get(index) {
+                frame.fill_text(canvas::Text {
+                    content: self.language.fill(
+                        Key::WeightReadout,
+                        &[
+                            &vertex_label(index),
+                            &format!("{:.1}", self.shown_weight(index)),
+                            &format!("{:.1}", self.probability(index) * 100.0),
+                        ],
+                    ),
+                    position: Point::new(p.x + WEIGHT_RING_RADIUS + 4.0, p.y - 8.0),
+                    color: palette::OVERLAY_COLOR,
+                    size: 14.0,
+                    ..canvas::Text::default()
+                });
+            }
+        }
+    }
+
+    /// How often each vertex was picked across the generated points, both
+    /// picks counting with the midpoint rule.
+    fn selection_counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.fix_points.len()];
+        for i in self.choices.iter().chain(self.partners.iter().flatten()) {
+            if let Some(count) = counts.get_mut(*i) {
+                *count += 1;
+            }
+        }
+        counts
+    }
+
+    /// The points up to `cur_iter` that have not been hidden by the eraser.
+    fn visible_points(&self) -> impl Iterator<Item = &Point> {
+        self.visible_points_in(0..self.point_count(self.cur_iter))
+    }
+
+    /// The visible points among those with an index in `range`.
+    fn visible_points_in(&self, range: std::ops::Range<usize>) -> impl Iterator<Item = &Point> {
+        self.indexed_points_in(range).map(|(_, p)| p)
+    }
+
+    /// Like [`visible_points_in`](Self::visible_points_in), with the index of
+    /// every point.
+    fn indexed_points_in(
+        &self,
+        range: std::ops::Range<usize>,
+    ) -> impl Iterator<Item = (usize, &Point)> {
+        let end = range.end.min(self.random_points.len());
+        let start = range.start.min(end);
+        self.random_points[start..end]
+            .iter()
+            .enumerate()
+            .map(move |(i, p)| (start + i, p))
+            .filter(move |(i, _)| !self.hidden.get(*i).copied().unwrap_or(false))
+    }
+
+    /// How many visible points each orbit contributed.
+    fn seed_counts(&self) -> Vec<usize> {
+        let n = self.seed_count as usize;
+        let mut counts = vec![0; n];
+        for (i, _) in self.indexed_points_in(0..self.point_count(self.cur_iter)) {
+            counts[i % n] += 1;
+        }
+        counts
+    }
+
+    /// How many visible points lie off the canvas.
+    fn count_outside(&self) -> usize {
+        let size = [self.bound.width, self.bound.height];
+        bounds::count_outside(size, self.visible_points().map(|p| [p.x, p.y]))
+    }
+
+    /// How many distinct screen pixels the visible points fall in, where
+    /// they are drawn: pinned to the edge when clamping, zoomed out when
+    /// rescaling, and at the size the canvas is shown at.
+    fn occupied_pixels(&self) -> usize {
+        if let Some(occupied) = self.occupied.get() {
+            return occupied;
+        }
+        let size = [self.bound.width, self.bound.height];
+        let viewport = self.viewport();
+        let points = self.visible_points().map(|p| {
+            let p = match self.out_of_bounds {
+                OutOfBounds::Clamp => bounds::clamp(size, [p.x, p.y]),
+                OutOfBounds::Clip | OutOfBounds::Rescale => [p.x, p.y],
+            };
+            viewport.apply(p)
+        });
+        let screen = [size[0] * self.display_scale, size[1] * self.display_scale];
+        let occupied = DensityGrid::new(screen, 1.0, points).occupied();
+        self.occupied.set(Some(occupied));
+        occupied
+    }
+
+    /// The statistics of the visible points, in canvas coordinates, with
+    /// the picks of the points up to `cur_iter`.
+    fn report(&self) -> Report {
+        if let Some(report) = &*self.report.borrow() {
+            return report.clone();
+        }
+        let points = self
+            .visible_points()
+            .map(|p| [p.x, p.y])
+            .collect::<Vec<_>>();
+        let shown = self.point_count(self.cur_iter).min(self.choices.len());
+        let mut counts = vec![0; self.fix_points.len()];
+        for i in self.choices[..shown].iter().chain(
+            self.partners[..shown.min(self.partners.len())]
+                .iter()
+                .flatten(),
+        ) {
+            if let Some(count) = counts.get_mut(*i) {
+                *count += 1;
+            }
+        }
+        let report = Report::new(&points, self.occupied_pixels(), counts);
+        *self.report.borrow_mut() = Some(report.clone());
+        report
+    }
+
+    /// Where a vertex placed at `position` goes with a snapping modifier
+    /// held, `None` without: Shift turns it about the last vertex to the
+    /// nearest multiple of [`SNAP_ANGLE`], Ctrl moves it to the length of the
+    /// first edge from there. Shift while a vertex is selected edits its
+    /// anchor, and Ctrl with the convergence demo moves its start, instead.
+    fn snap(&self, position: Point) -> Option<Point> {
+        let last = self.fix_points.last()?;
+        let by_angle = self.modifiers.shift && self.selected_vertex.is_none();
+        let by_length =
+            self.modifiers.control && self.show_convergence.not() && self.fix_points.len() >= 2;
+        if by_angle.not() && by_length.not() {
+            return None;
+        }
+        let origin = [last.x, last.y];
+        let mut snapped = [position.x, position.y];
+        if by_angle {
+            snapped = geometry::snap_angle(origin, snapped, SNAP_ANGLE);
+        }
+        if by_length {
+            let edge = self.fix_points[0].distance(self.fix_points[1]);
+            snapped = geometry::at_distance(origin, snapped, edge);
+        }
+        Some(Point::new(snapped[0], snapped[1]))
+    }
+
+    /// Where a vertex at `position` is, and its angle and distance from the
+    /// last vertex. The angle runs counter-clockwise on screen.
+    fn snap_readout(&self, position: Point) -> String {
+        let last = self.fix_points.len().saturating_sub(1);
+        let origin = self.fix_points.get(last).map_or([0.0, 0.0], |p| [p.x, p.y]);
+        let p = [position.x, position.y];
+        self.language.fill(
+            Key::PlacesAt,
+            &[
+                &Fixed::new(position.x, self.decimals),
+                &Fixed::new(position.y, self.decimals),
+                &(-geometry::angle(origin, p)).round().rem_euclid(360.0),
+                &Fixed::new(geometry::distance(origin, p), self.decimals).unit("px"),
+                &vertex_label(last),
+            ],
+        )
+    }
+
+    /// Where a click at `position` places a vertex: pinned to the canvas, so
+    /// a click into the margin doesn't put one outside it, unless rescaling
+    /// shows what lies beyond.
+    fn placeable(&self, position: Point) -> Point {
+        match self.out_of_bounds {
+            OutOfBounds::Rescale => position,
+            OutOfBounds::Clip | OutOfBounds::Clamp => Point::new(
+                position.x.clamp(0.0, self.bound.width),
+                position.y.clamp(0.0, self.bound.height),
+            ),
+        }
+    }
+
+    /// The box the attractor covers as drawn: that of the visible points,
+    /// or of the fix points while there are none, pinned to the canvas
+    /// unless rescaling shows what lies beyond.
+    fn attractor_bounds(&self) -> Option<([f32; 2], [f32; 2])> {
+        let corners = |points: &mut dyn Iterator<Item = &Point>| {
+            stats::bounding_box(points.map(|p| [p.x, p.y]))
+        };
+        let (min, max) =
+            corners(&mut self.visible_points()).or_else(|| corners(&mut self.fix_points.iter()))?;
+        let size = [self.bound.width, self.bound.height];
+        match self.out_of_bounds {
+            OutOfBounds::Rescale => Some((min, max)),
+            OutOfBounds::Clip | OutOfBounds::Clamp => {
+                Some((bounds::clamp(size, min), bounds::clamp(size, max)))
+            }
+        }
+    }
+
+    /// Maps canvas coordinates to the screen: framed on the attractor with
+    /// auto-frame, else zoomed out to fit every visible point and fix point
+    /// when rescaling, shrunk into the border margin, then to the size the
+    /// canvas is shown at. Clicks go through its inverse, so they still land
+    /// on canvas coordinates.
+    fn viewport(&self) -> Viewport {
+        let size = [self.bound.width, self.bound.height];
+        let framed = self.auto_frame.then(|| self.attractor_bounds()).flatten();
+        let fit = match (framed, self.out_of_bounds) {
+            (Some((min, max)), _) => Viewport::frame(size, min, max, AUTO_FRAME_MARGIN),
+            (None, OutOfBounds::Rescale) => Viewport::fit(
+                size,
+                self.visible_points()
+                    .chain(&self.fix_points)
+                    .map(|p| [p.x, p.y]),
+            ),
+            (None, OutOfBounds::Clip | OutOfBounds::Clamp) => Viewport::IDENTITY,
+        };
+        fit.then(Viewport::inset(size, self.border.margin))
+            .then(Viewport {
+                scale: self.display_scale,
+                offset: [0.0, 0.0],
+            })
+    }
+
+    fn loupe_shown(&self) -> bool {
+        self.loupe || self.loupe_held
+    }
+
+    /// Files the points for the loupe while it is shown, and forgets them
+    /// once it's hidden.
+    fn sync_point_grid(&mut self) {
+        match (self.loupe_shown(), &self.point_grid) {
+            (true, None) => {
+                let mut grid = PointGrid::new([self.bound.width, self.bound.height], LOUPE_CELL);
+                for (i, p) in self.random_points.iter().enumerate() {
+                    grid.insert(i, [p.x, p.y]);
+                }
+                self.point_grid = Some(grid);
+            }
+            (false, Some(_)) => self.point_grid = None,
+            _ => {}
+        }
+    }
+
+    /// Draws the visible points around `cursor`, on screen, magnified into a
+    /// circle in the top right corner, or the top left while the cursor is
+    /// in the way.
+    fn draw_loupe(
+        &self,
+        frame: &mut Frame,
+        viewport: Viewport,
+        cursor: Point,
+        centroid: Option<Point>,
+    ) {
+        let grid = match &self.point_grid {
+            Some(grid) => grid,
+            None => return,
+        };
+        let reach = LOUPE_GAP + 2.0 * LOUPE_RADIUS;
+        let right = Point::new(
+            frame.width() - LOUPE_GAP - LOUPE_RADIUS,
+            LOUPE_GAP + LOUPE_RADIUS,
+        );
+        let center = if cursor.x > frame.width() - reach && cursor.y < reach {
+            Point::new(LOUPE_GAP + LOUPE_RADIUS, right.y)
+        } else {
+            right
+        };
+        let zoom = self.loupe_zoom;
+        // The source circle on screen, and on the canvas.
+        let source = LOUPE_RADIUS / zoom;
+        let [x, y] = viewport.invert([cursor.x, cursor.y]);
+        let visible = self
+            .point_count(self.cur_iter)
+            .min(self.random_points.len());
+
+        // Opaque, so the points beneath don't show through.
+        let background = if self.background.a > 0.0 {
+            self.background
+        } else {
+            Color::WHITE
+        };
+        frame.fill(&Path::circle(center, LOUPE_RADIUS), background);
+        for i in grid.near([x, y], source / viewport.scale) {
+            if i >= visible || self.hidden.get(i).copied().unwrap_or(false) {
+                continue;
+            }
+            let p = self.random_points[i];
+            let [px, py] = viewport.apply([p.x, p.y]);
+            let (dx, dy) = (px - cursor.x, py - cursor.y);
+            if dx.hypot(dy) > source {
+                continue;
+            }
+            frame.fill(
+                &Path::rectangle(
+                    Point::new(center.x + dx * zoom, center.y + dy * zoom),
+                    Size::new(1.0, 1.0),
+                ),
+                self.point_color(i, p, centroid),
+            );
+        }
+        let ring = Stroke::default()
+            .with_color(palette::OVERLAY_COLOR)
+            .with_width(1.5);
+        frame.stroke(&Path::circle(center, LOUPE_RADIUS), ring);
+        frame.stroke(&Path::circle(cursor, source), ring);
+        frame.fill_text(canvas::Text {
+            content: format!("{}×", zoom),
+            position: Point::new(center.x - 10.0, center.y + LOUPE_RADIUS - 20.0),
+            color: palette::OVERLAY_COLOR,
+            size: 14.0,
+            ..canvas::Text::default()
+        });
+    }
+
+    /// Hides every drawn point within `eraser_radius` of `center`.
+    fn erase(&mut self, center: Point) {
+        let cur_iter = self
+            .point_count(self.cur_iter)
+            .min(self.random_points.len());
+        if self.hidden.len() < cur_iter {
+            self.hidden.resize(cur_iter, false);
+        }
+        let radius = self.eraser_radius;
+        self.random_points[0..cur_iter]
+            .iter()
+            .zip(self.hidden.iter_mut())
+            .filter(|(p, _)| p.distance(center) <= radius)
+            .for_each(|(_, hidden)| *hidden = true);
+        self.heat.take();
+    }
+
+    fn centroid(&self) -> Option<Point> {
+        geometry::centroid(&self.vertices()).map(|[x, y]| Point::new(x, y))
+    }
+
+    /// The mean of the visible points. The sum behind it runs on while
+    /// `cur_iter` grows and starts over when it shrinks.
+    fn center_of_mass(&self) -> Option<Point> {
+        let end = self
+            .point_count(self.cur_iter)
+            .min(self.random_points.len());
+        let mass = match self.mass.get() {
+            Some(mass) if mass.end <= end => mass,
+            _ => Mass::default(),
+        };
+        let mass = self
+            .visible_points_in(mass.end..end)
+            .fold(Mass { end, ..mass }, |mass, p| Mass {
+                sum: [mass.sum[0] + p.x as f64, mass.sum[1] + p.y as f64],
+                count: mass.count + 1,
+                ..mass
+            });
+        self.mass.set(Some(mass));
+        (mass.count > 0).then(|| {
+            let n = mass.count as f64;
+            Point::new((mass.sum[0] / n) as f32, (mass.sum[1] / n) as f32)
+        })
+    }
+
+    /// Where the cell of `p` is between the sparsest and the densest, under
+    /// the count scale and clip percentile. The points are counted again
+    /// only when others are visible.
+    fn heat_level(&self, p: Point) -> f32 {
+        let end = self
+            .point_count(self.cur_iter)
+            .min(self.random_points.len());
+        let mapping = (self.count_scale, self.clip_percentile);
+        let mut heat = self.heat.borrow_mut();
+        let heat = match &mut *heat {
+            Some(heat) if heat.end == end => heat,
+            heat => heat.insert(Heat {
+                end,
+                grid: DensityGrid::new(
+                    [self.bound.width, self.bound.height],
+                    HEAT_CELL,
+                    self.visible_points().map(|p| [p.x, p.y]),
+                ),
+                mapping,
+                levels: vec![],
+            }),
+        };
+        if heat.levels.is_empty() || heat.mapping != mapping {
+            heat.levels = render::heat_levels(heat.grid.counts(), mapping.0, mapping.1);
+            heat.mapping = mapping;
+        }
+        heat.grid
+            .cell([p.x, p.y])
+            .map_or(0.0, |cell| heat.levels[cell])
+    }
+
+    /// The transition counts up to `cur_iter`, counting on from the last
+    /// call while it only grows. A point's pick follows the pick of the point
+    /// `seed_count` before it, the one before it on its own orbit.
+    fn transitions(&self) -> std::cell::Ref<'_, Transitions> {
+        let end = self.point_count(self.cur_iter).min(self.choices.len());
+        let vertices = self.fix_points.len();
+        {
+            let mut transitions = self.transitions.borrow_mut();
+            if transitions.end > end || transitions.vertices != vertices {
+                *transitions = Transitions {
+                    end: 0,
+                    vertices,
+                    counts: vec![0; vertices * vertices],
                 };
-                (event::Status::Captured, message)
             }
-            _ => (event::Status::Ignored, None),
+            let step = self.seed_count.max(1) as usize;
+            for i in transitions.end.max(step)..end {
+                let (from, to) = (self.choices[i - step], self.choices[i]);
+                if from < vertices && to < vertices {
+                    transitions.counts[from * vertices + to] += 1;
+                }
+            }
+            transitions.end = end;
+        }
+        self.transitions.borrow()
+    }
+
+    /// A chord from every vertex to each one picked right after it, as wide
+    /// as [`TRANSITION_WIDTH`] and opaque for the most frequent transition
+    /// and thinner and fainter for the others. Each direction keeps to its
+    /// own side of the line between the two, and a vertex picked twice in a
+    /// row gets a loop outside it.
+    fn draw_transitions(&self, frame: &mut Frame) {
+        let transitions = self.transitions();
+        let most = transitions.counts.iter().copied().max().unwrap_or(0);
+        if most == 0 {
+            return;
+        }
+        let centroid = self.centroid().unwrap_or(Point::ORIGIN);
+        for (index, count) in transitions.counts.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+            let (from, to) = (index / transitions.vertices, index % transitions.vertices);
+            let share = *count as f32 / most as f32;
+            let stroke = Stroke::default()
+                .with_color(Color {
+                    a: 0.15 + 0.85 * share,
+                    ..self.vertex_color(from)
+                })
+                .with_width((TRANSITION_WIDTH * share).max(0.5));
+            let (a, b) = (self.fix_points[from], self.fix_points[to]);
+            if from == to {
+                let out = Vector::new(a.x - centroid.x, a.y - centroid.y);
+                let length = out.x.hypot(out.y).max(f32::EPSILON);
+                let center = a + Vector::new(out.x / length * 10.0, out.y / length * 10.0);
+                frame.stroke(&Path::circle(center, 7.0), stroke);
+                continue;
+            }
+            let length = (b.x - a.x).hypot(b.y - a.y);
+            if length == 0.0 {
+                continue;
+            }
+            let side = Vector::new(
+                (b.y - a.y) / length * TRANSITION_WIDTH,
+                (a.x - b.x) / length * TRANSITION_WIDTH,
+            );
+            frame.stroke(&Path::line(a + side, b + side), stroke);
+        }
+    }
+
+    fn vertices(&self) -> Vec<[f32; 2]> {
+        self.fix_points.iter().map(|p| [p.x, p.y]).collect()
+    }
+
+    /// The convex hull of the enabled vertices mapped by every map of the
+    /// game, each copy faintly shaded and outlined in the colour of its
+    /// vertex. The edges are split so maps bent by anchors show curved.
+    fn draw_maps(&self, frame: &mut Frame) {
+        let game = self.game();
+        let hull = geometry::convex_hull(game.vertices());
+        if hull.len() < 3 {
+            return;
+        }
+        let shape = geometry::edges(&hull)
+            .flat_map(|([ax, ay], [bx, by])| {
+                (0..MAP_EDGE_STEPS).map(move |i| {
+                    let t = i as f32 / MAP_EDGE_STEPS as f32;
+                    [ax + (bx - ax) * t, ay + (by - ay) * t]
+                })
+            })
+            .collect::<Vec<_>>();
+        for (i, image) in game.images(&shape).iter().enumerate() {
+            let outline = Path::new(|builder| {
+                for (k, [x, y]) in image.iter().enumerate() {
+                    if k == 0 {
+                        builder.move_to(Point::new(*x, *y));
+                    } else {
+                        builder.line_to(Point::new(*x, *y));
+                    }
+                }
+                builder.close();
+            });
+            // The images follow the enabled vertices, or their pairs.
+            let color = match self.rule {
+                Rule::Vertex => self.vertex_color(self.orbit_vertices.get(i).copied().unwrap_or(i)),
+                Rule::Midpoint => palette::OVERLAY_COLOR,
+            };
+            frame.fill(&outline, Color { a: 0.12, ..color });
+            frame.stroke(
+                &outline,
+                Stroke::default().with_color(Color { a: 0.6, ..color }),
+            );
+        }
+    }
+
+    /// The centroid of the vertices as a cross and the mean of the visible
+    /// points as a diamond.
+    fn draw_centers(&self, frame: &mut Frame) {
+        let stroke = Stroke::default()
+            .with_color(palette::OVERLAY_COLOR)
+            .with_width(1.5);
+        if let Some(c) = self.centroid() {
+            let cross = Path::new(|builder| {
+                builder.move_to(Point::new(c.x - 6.0, c.y));
+                builder.line_to(Point::new(c.x + 6.0, c.y));
+                builder.move_to(Point::new(c.x, c.y - 6.0));
+                builder.line_to(Point::new(c.x, c.y + 6.0));
+            });
+            frame.stroke(&cross, stroke);
+        }
+        if let Some(m) = self.center_of_mass() {
+            let diamond = Path::new(|builder| {
+                builder.move_to(Point::new(m.x, m.y - 5.0));
+                builder.line_to(Point::new(m.x + 5.0, m.y));
+                builder.line_to(Point::new(m.x, m.y + 5.0));
+                builder.line_to(Point::new(m.x - 5.0, m.y));
+                builder.close();
+            });
+            frame.stroke(&diamond, stroke);
+        }
+    }
+
+    /// The first points of an orbit from the pinned start, or from the
+    /// centroid, which lies off the attractor for the classic triangle.
+    /// Each is smaller and fainter than the one before as the orbit settles.
+    fn draw_convergence(&self, frame: &mut Frame) {
+        let start = match self.convergence_start.or_else(|| self.centroid()) {
+            Some(start) => start,
+            None => return,
+        };
+        let points = self
+            .game()
+            .start([start.x, start.y])
+            .points(CONVERGENCE_POINTS);
+        let path = Path::new(|builder| {
+            builder.move_to(start);
+            for [x, y] in &points {
+                builder.line_to(Point::new(*x, *y));
+            }
+        });
+        frame.stroke(
+            &path,
+            Stroke::default()
+                .with_color(Color {
+                    a: 0.3,
+                    ..palette::OVERLAY_COLOR
+                })
+                .with_width(1.0),
+        );
+        frame.stroke(
+            &Path::circle(start, 5.0),
+            Stroke::default()
+                .with_color(palette::OVERLAY_COLOR)
+                .with_width(1.5),
+        );
+        for (age, [x, y]) in points.iter().enumerate() {
+            let fade = 1.0 - age as f32 / CONVERGENCE_POINTS as f32;
+            frame.fill(
+                &Path::circle(Point::new(*x, *y), 0.75 + 3.25 * fade),
+                Color {
+                    a: 0.15 + 0.85 * fade,
+                    ..palette::OVERLAY_COLOR
+                },
+            );
+        }
+    }
+
+    /// A small padlock above the right of every locked vertex.
+    fn draw_locks(&self, frame: &mut Frame) {
+        let color = self.palette().guide;
+        for (i, p) in self.fix_points.iter().enumerate() {
+            if self.is_locked(i).not() {
+                continue;
+            }
+            let body = Point::new(p.x + 6.0, p.y - 12.0);
+            frame.fill(&Path::rectangle(body, Size::new(7.0, 5.0)), color);
+            let shackle = Path::new(|builder| {
+                builder.move_to(Point::new(body.x + 1.5, body.y));
+                builder.arc(canvas::path::Arc {
+                    center: Point::new(body.x + 3.5, body.y - 1.0),
+                    radius: 2.0,
+                    start_angle: std::f32::consts::PI,
+                    end_angle: std::f32::consts::TAU,
+                });
+                builder.line_to(Point::new(body.x + 5.5, body.y));
+            });
+            frame.stroke(
+                &shackle,
+                Stroke::default().with_color(color).with_width(1.2),
+            );
+        }
+    }
+
+    /// Every anchor as a small hollow square, dashed back to its vertex.
+    fn draw_anchors(&self, frame: &mut Frame) {
+        let anchors = self
+            .fix_points
+            .iter()
+            .zip(&self.anchors)
+            .filter_map(|(vertex, anchor)| anchor.map(|anchor| (*vertex, anchor)));
+        for (vertex, anchor) in anchors {
+            let length = vertex.distance(anchor);
+            let (dx, dy) = (
+                (anchor.x - vertex.x) / length,
+                (anchor.y - vertex.y) / length,
+            );
+            let dashes = Path::new(|builder| {
+                let mut along = 0.0;
+                while along < length {
+                    let end = (along + ANCHOR_DASH).min(length);
+                    builder.move_to(Point::new(vertex.x + dx * along, vertex.y + dy * along));
+                    builder.line_to(Point::new(vertex.x + dx * end, vertex.y + dy * end));
+                    along += 2.0 * ANCHOR_DASH;
+                }
+            });
+            let stroke = Stroke::default()
+                .with_color(palette::OVERLAY_COLOR)
+                .with_width(1.0);
+            frame.stroke(&dashes, stroke);
+            frame.stroke(
+                &Path::rectangle(
+                    Point::new(anchor.x - 3.0, anchor.y - 3.0),
+                    Size::new(6.0, 6.0),
+                ),
+                stroke.with_width(1.5),
+            );
+        }
+    }
+
+    /// The angle at which `sector` starts, measured like `atan2` from -π.
+    fn sector_boundary(&self, sector: u8) -> f32 {
+        -std::f32::consts::PI + std::f32::consts::TAU * sector as f32 / self.sector_count as f32
+    }
+
+    /// The colours of the canvas roles, from the theme that suits the
+    /// background, whether a theme set it or it was picked by hand.
+    fn palette(&self) -> palette::Palette {
+        Theme::for_background(self.background).palette()
+    }
+
+    /// The colour picked for vertex `i`.
+    fn vertex_color(&self, i: usize) -> Color {
+        palette::categorical(palette::vertex_color(&self.vertex_colors, i))
+    }
+
+    /// The colour of the marker of vertex `i`, which takes the vertex colour
+    /// while the points do.
+    fn marker_color(&self, i: usize) -> Color {
+        if self.point_coloring == PointColoring::Vertex {
+            self.vertex_color(i)
+        } else {
+            self.palette().primary
+        }
+    }
+
+    /// The colour of the point at `index`.
+    fn point_color(&self, index: usize, p: Point, centroid: Option<Point>) -> Color {
+        match (self.point_coloring, centroid) {
+            (PointColoring::Seed, _) => palette::categorical(index % self.seed_count as usize),
+            (PointColoring::Forced, _) => match self.forced.get(index) {
+                Some(true) => palette::categorical(1),
+                _ => Color {
+                    a: self.foreground.a * 0.25,
+                    ..self.foreground
+                },
+            },
+            (PointColoring::Sector, Some(c)) => {
+                let angle = (p.y - c.y).atan2(p.x - c.x) + std::f32::consts::PI;
+                let sector = (angle / std::f32::consts::TAU * self.sector_count as f32) as usize;
+                palette::categorical(sector % self.sector_count as usize)
+            }
+            (PointColoring::Vertex, _) => match self.choices.get(index) {
+                Some(vertex) => self.vertex_color(*vertex),
+                None => self.foreground,
+            },
+            (PointColoring::Recency, _) => {
+                let newest = self
+                    .point_count(self.cur_iter)
+                    .min(self.random_points.len())
+                    .saturating_sub(1);
+                self.ramp.at(index as f32 / newest.max(1) as f32)
+            }
+            (PointColoring::Density, _) => self.ramp.at(self.heat_level(p)),
+            _ => self.foreground,
         }
     }
 
-    fn draw(
-        &self,
-        bounds: iced::Rectangle,
-        _cursor: iced::canvas::Cursor,
-    ) -> Vec<iced::canvas::Geometry> {
-        let geom = self.cache.draw(bounds.size(), |frame| {
+    /// The colour of each of the first `len` points for additive exports:
+    /// the colour of the vertex it jumped towards mixed half and half with
+    /// that of the point before on its orbit, so the colours follow the
+    /// paths the orbit takes through the copies.
+    fn blended_vertex_colors(&self, len: usize) -> Vec<[f32; 3]> {
+        let stride = self.seed_count.max(1) as usize;
+        let mut last: Vec<Option<[f32; 3]>> = vec![None; stride];
+        (0..len.min(self.random_points.len()))
+            .map(|i| {
+                let color = self
+                    .choices
+                    .get(i)
+                    .map_or(self.foreground, |vertex| self.vertex_color(*vertex));
+                let color = [color.r, color.g, color.b];
+                let mixed = match last[i % stride] {
+                    Some(before) => [0, 1, 2].map(|c| (before[c] + color[c]) / 2.0),
+                    None => color,
+                };
+                last[i % stride] = Some(mixed);
+                mixed
+            })
+            .collect()
+    }
+
+    /// The replay marker at orbit point `index`: the line from the point
+    /// before on the same orbit towards the picked vertex it jumped along,
+    /// and a trail fading out over the steps before it.
+    fn draw_replay(&self, frame: &mut Frame, index: usize) {
+        // Consecutive points of one orbit lie `stride` apart.
+        let stride = self.seed_count as usize;
+        let trail = self.replay_trail as usize;
+        for steps in 1..=trail {
+            let i = match index.checked_sub(steps * stride) {
+                Some(i) => i,
+                None => break,
+            };
+            let age = steps as f32 / (trail + 1) as f32;
+            frame.stroke(
+                &Path::line(self.random_points[i], self.random_points[i + stride]),
+                Stroke::default()
+                    .with_color(Color {
+                        a: 1.0 - age,
+                        ..palette::OVERLAY_COLOR
+                    })
+                    .with_width(1.5),
+            );
+            frame.fill(
+                &Path::circle(self.random_points[i], 2.0),
+                Color {
+                    a: 1.0 - age,
+                    ..self.foreground
+                },
+            );
+        }
+        let p = self.random_points[index];
+        if let (Some(from), Some(vertex)) = (
+            index.checked_sub(stride).map(|i| self.random_points[i]),
+            self.fix_points.get(self.choices[index]),
+        ) {
             frame.stroke(
-                &Path::rectangle(Point::ORIGIN, frame.size()),
-                Stroke::default(),
+                &Path::line(from, *vertex),
+                Stroke::default().with_color(self.palette().guide),
             );
-            self.random_points[0..self.cur_iter as usize]
+        }
+        frame.fill(&Path::circle(p, 5.0), palette::OVERLAY_COLOR);
+    }
+
+    /// Writes the deterministic construction over the three fix points at
+    /// the subdivision level as SVG, in the point and background colours.
+    fn export_gasket_svg(&self, path: &std::path::Path) -> std::io::Result<()> {
+        let corners = match self.fix_points.as_slice() {
+            [a, b, c] => [[a.x, a.y], [b.x, b.y], [c.x, c.y]],
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    self.language.text(Key::GasketNeedsThree),
+                ))
+            }
+        };
+        let svg = gasket::to_svg(
+            corners,
+            self.subdivision_level,
+            [self.bound.width, self.bound.height],
+            export::rgba8(self.foreground),
+            export::rgba8(self.background),
+        );
+        std::fs::write(path, svg)
+    }
+
+    /// Writes the visible points as SVG, within the border like PNGs, thinned
+    /// evenly to at most `budget`. Returns how many were written out of how
+    /// many.
+    fn export_points_svg(
+        &self,
+        path: &std::path::Path,
+        budget: usize,
+    ) -> std::io::Result<(usize, usize)> {
+        let size = [self.bound.width, self.bound.height];
+        let inset = Viewport::inset(size, self.border.margin);
+        let centroid = self.centroid();
+        let points = self
+            .indexed_points_in(0..self.point_count(self.cur_iter))
+            .collect::<Vec<_>>();
+        let kept = svg::decimate(points.len(), budget).map(|k| {
+            let (i, p) = points[k];
+            (
+                inset.apply([p.x, p.y]),
+                export::rgba8(self.point_color(i, *p, centroid)),
+            )
+        });
+        let document = svg::to_svg(
+            kept,
+            size,
+            export::rgba8(self.background),
+            self.point_shape,
+            self.point_size as f32,
+        );
+        std::fs::write(path, document)?;
+        Ok((points.len().min(budget), points.len()))
+    }
+
+    /// Writes the visible points as a point cloud in the z = 0 plane, with y
+    /// pointing up as 3D tools expect.
+    fn export_point_cloud(
+        &self,
+        path: &std::path::Path,
+        format: PointCloud,
+    ) -> std::io::Result<()> {
+        let vertices = self
+            .visible_points()
+            .map(|p| [p.x, self.bound.height - p.y, 0.0])
+            .collect::<Vec<_>>();
+        format.write(path, &vertices)
+    }
+
+    /// Rasterizes the visible points into a PNG. With a `region` only points
+    /// inside it are drawn and `crop` shrinks the image to the region.
+    fn export_png(
+        &self,
+        path: &std::path::Path,
+        region: Option<Rectangle>,
+        crop: bool,
+    ) -> std::io::Result<()> {
+        let (size, origin) = match region {
+            Some(region) if crop => (region.size(), region.position()),
+            _ => (self.bound, Point::ORIGIN),
+        };
+        let factor = self.supersample.max(1);
+        let scale = factor as f32 * self.export_scale;
+        // Crops show the region as it is, without the border around it.
+        let framed = !(crop && region.is_some());
+        let inset = if framed {
+            Viewport::inset([size.width, size.height], self.border.margin)
+        } else {
+            Viewport::IDENTITY
+        };
+        let to_raster = |p: Point| {
+            let [x, y] = inset.apply([p.x - origin.x, p.y - origin.y]);
+            Point::new(x * scale, y * scale)
+        };
+        let raster_size = Size::new(
+            (size.width.floor() * self.export_scale).round() * factor as f32,
+            (size.height.floor() * self.export_scale).round() * factor as f32,
+        );
+        if raster_size.width > MAX_RASTER_SIDE || raster_size.height > MAX_RASTER_SIDE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                self.language.fill(
+                    Key::ExportTooLarge,
+                    &[&raster_size.width, &raster_size.height, &MAX_RASTER_SIDE],
+                ),
+            ));
+        }
+        let mut raster = Raster::new(raster_size, self.background);
+        // A canvas pixel covers this many raster pixels each way.
+        let block = scale.round().max(1.0) as u32;
+        let centroid = self.centroid();
+        if self.ghost_in_exports {
+            self.ghost
                 .iter()
+                .filter(|p| region.is_none_or(|region| region.contains(**p)))
                 .for_each(|p| {
-                    let path = Path::rectangle(*p, Size::new(1_f32, 1_f32));
-                    frame.stroke(&path, Stroke::default())
+                    let corner = to_raster(*p);
+                    for dy in 0..block {
+                        for dx in 0..block {
+                            raster.blend(
+                                Point::new(corner.x + dx as f32, corner.y + dy as f32),
+                                palette::GHOST_COLOR,
+                            );
+                        }
+                    }
                 });
-            self.fix_points.iter().for_each(|p| {
-                let path = Path::circle(*p, 5.0);
-                frame.fill(&path, Color::from_rgb8(0x12, 0x93, 0xD8));
-            });
+        }
+        let points = self
+            .indexed_points_in(0..self.point_count(self.cur_iter))
+            .filter(|(_, p)| region.is_none_or(|region| region.contains(**p)))
+            .collect::<Vec<_>>();
+        let style = RenderStyle {
+            background: export::rgba8(self.background),
+            colors: points
+                .iter()
+                .map(|(i, p)| export::rgba8(self.point_color(*i, **p, centroid)))
+                .collect(),
+            point_size: block * self.point_size as u32,
+            shape: self.point_shape,
+        };
+        let raster_points = points
+            .iter()
+            .map(|(_, p)| {
+                let p = to_raster(**p);
+                [p.x, p.y]
+            })
+            .collect::<Vec<_>>();
+        if self.additive_export {
+            let colors = self.blended_vertex_colors(self.point_count(self.cur_iter));
+            let image = raster.image_mut();
+            let mut accumulation = Accumulation::new(image.width, image.height);
+            for ((i, _), p) in points.iter().zip(&raster_points) {
+                accumulation.add(*p, style.point_size, colors[*i]);
+            }
+            accumulation.tone_map_onto(image);
+        } else {
+            render::render_onto(
+                raster.image_mut(),
+                &raster_points,
+                raster_points.len(),
+                &style,
+            );
+        }
+        self.fix_points.iter().enumerate().for_each(|(i, p)| {
+            raster.fill_circle(to_raster(*p), 5.0 * scale, self.marker_color(i))
         });
-
-        vec![geom]
+        if framed && self.border.shown {
+            raster.frame(
+                (self.border.width * scale).round().max(1.0) as u32,
+                self.border.color.color(self.foreground),
+            );
+        }
+        let raster = raster.downsample(factor as u32);
+        let fix_points = self
+            .fix_points
+            .iter()
+            .map(|p| format!("{},{}", p.x, p.y))
+            .collect::<Vec<_>>()
+            .join(" ");
+        raster.write_png(
+            path,
+            &[
+                ("Software", "Sierpinski Triangle Emulator".to_string()),
+                ("FixPoints", fix_points),
+                ("MaxIter", self.max_iter.to_string()),
+                ("Ratio", self.ratio.to_string()),
+                ("Rule", self.rule.to_string()),
+                ("Restriction", self.restriction.to_string()),
+                ("Selection", self.picking.to_string()),
+                ("Rotation", self.rotation.to_string()),
+                ("Jitter", self.jitter.to_string()),
+                ("Seed", self.seed.to_string()),
+                ("Generator", self.generator.to_string()),
+                ("Seeds", self.seed_count.to_string()),
+            ],
+        )
     }
 }
 
-impl SierpinskiGraph {
-    fn new() -> SierpinskiGraph {
-        SierpinskiGraph {
-            max_iter: 0,
-            cur_iter: 0,
-            fix_points: vec![],
-            random_points: vec![],
-            bound: Size::new(600.0, 600.0),
-            cache: Cache::new(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Drops what is copied.
+    struct NoClipboard;
+
+    impl Clipboard for NoClipboard {
+        fn write(&mut self, _: String) {}
+    }
+
+    /// An emulator without a window, keeping its preferences and recent
+    /// sessions in a directory of the test run rather than the user's.
+    fn emulator() -> SierpinskiEmulator {
+        static CONFIG: std::sync::Once = std::sync::Once::new();
+        CONFIG.call_once(|| {
+            let dir = std::env::temp_dir().join(format!("sierpinski-tests-{}", std::process::id()));
+            std::env::set_var("XDG_CONFIG_HOME", dir);
+            std::env::remove_var("SIERPINSKI_REDUCED_MOTION");
+        });
+        let mut emulator = SierpinskiEmulator::new(Flags::default()).0;
+        // The recovery file some other test left for a moment.
+        emulator.recovered = None;
+        emulator
+    }
+
+    fn send(emulator: &mut SierpinskiEmulator, messages: impl IntoIterator<Item = Message>) {
+        for message in messages {
+            let _ = emulator.dispatch(message, &mut NoClipboard);
         }
     }
 
-    fn redraw(&mut self) {
-        self.cache.clear();
+    /// A file under the system's temporary directory, unique to the test
+    /// run.
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("sierpinski-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn loading_a_session_without_vertices_generates_nothing() {
+        let path = temp_file("no-vertices.json");
+        std::fs::write(
+            &path,
+            r#"{ "version": 1, "fix_points": [], "max_iter": 5000, "cur_iter": 5000 }"#,
+        )
+        .unwrap();
+        let mut emulator = emulator();
+        emulator.session_path = path.display().to_string();
+        send(&mut emulator, [Message::LoadSession]);
+        std::fs::remove_file(&path).unwrap();
+        assert!(emulator.graph.random_points.is_empty());
+        assert_eq!((emulator.graph.max_iter, emulator.graph.cur_iter), (0, 0));
+        assert!(
+            emulator.status.contains("no fix points"),
+            "{}",
+            emulator.status
+        );
+
+        // Asking for points afterwards is refused the same way.
+        emulator.status.clear();
+        send(
+            &mut emulator,
+            [Message::SetMaxIter(1_000), Message::SetCurIter(500)],
+        );
+        assert!(emulator.graph.random_points.is_empty());
+        assert_eq!((emulator.graph.max_iter, emulator.graph.cur_iter), (0, 0));
+        assert!(
+            emulator.status.contains("no fix points"),
+            "{}",
+            emulator.status
+        );
+    }
+
+    /// An emulator with a triangle of vertices, placed without the
+    /// messages so nothing is generated or suggested yet.
+    fn triangle() -> SierpinskiEmulator {
+        let mut emulator = emulator();
+        emulator.graph.fix_points = vec![
+            Point::new(300.0, 40.0),
+            Point::new(40.0, 560.0),
+            Point::new(560.0, 560.0),
+        ];
+        emulator.graph.clear_points();
+        emulator
+    }
+
+    #[test]
+    fn a_slider_burst_regenerates_once() {
+        let mut emulator = triangle();
+        // Two orbits make 24 000 points, past the debounce threshold.
+        send(
+            &mut emulator,
+            [Message::SetSeedCount(2), Message::SetMaxIter(12_000)],
+        );
+        let points = emulator.graph.random_points.clone();
+        assert_eq!(points.len(), 24_000);
+        send(
+            &mut emulator,
+            (1..=20).map(|i| Message::SetRatio(0.5 - i as f32 * 0.005)),
+        );
+        // The drag shows the value but leaves the points alone.
+        assert_eq!(emulator.rules().ratio, 0.4);
+        assert_eq!(emulator.graph.ratio, 0.5);
+        assert_eq!(emulator.graph.random_points, points);
+
+        send(&mut emulator, [Message::DebounceTick(Instant::now())]);
+        assert_eq!(emulator.graph.random_points, points, "applied early");
+        send(
+            &mut emulator,
+            [Message::DebounceTick(Instant::now() + DEBOUNCE)],
+        );
+        assert_eq!(emulator.graph.ratio, 0.4);
+        let regenerated = emulator.graph.random_points.clone();
+        assert_ne!(regenerated, points);
+        assert_eq!(regenerated.len(), 24_000);
+        assert!(emulator.apply_at.is_none() && emulator.pending_rules.is_none());
+
+        // Nothing is left to apply later.
+        send(
+            &mut emulator,
+            [Message::DebounceTick(Instant::now() + DEBOUNCE * 2)],
+        );
+        assert_eq!(emulator.graph.random_points, regenerated);
+    }
+
+    #[test]
+    fn small_runs_regenerate_on_every_edit() {
+        let mut emulator = triangle();
+        send(&mut emulator, [Message::SetMaxIter(1_000)]);
+        send(&mut emulator, [Message::SetRatio(0.45)]);
+        assert_eq!(emulator.graph.ratio, 0.45);
+        assert!(emulator.apply_at.is_none());
+    }
+
+    #[test]
+    fn timers_and_bookkeeping_leave_the_canvas_alone() {
+        for message in [
+            Message::DebounceTick(Instant::now()),
+            Message::GalleryTick,
+            Message::AutoSave,
+            Message::BeginGesture,
+            Message::EndGesture,
+            Message::SetContextMenu(None),
+        ] {
+            assert_eq!(redraw(&message), Redraw::Nothing, "{:?}", message);
+        }
+        assert_eq!(redraw(&Message::ReplayTick), Redraw::Overlay);
+        assert_eq!(redraw(&Message::Tick), Redraw::Tail);
+        assert_eq!(redraw(&Message::SetRatio(0.4)), Redraw::All);
+    }
+
+    /// A graph with a triangle of vertices and `iter` iterations generated.
+    fn generated(iter: i32) -> SierpinskiGraph {
+        let mut graph = SierpinskiGraph::new();
+        graph.fix_points = triangle().graph.fix_points;
+        graph.max_iter = iter;
+        graph.cur_iter = iter;
+        graph.regenerate();
+        assert_eq!(graph.random_points.len(), iter as usize);
+        graph
+    }
+
+    fn assert_restarted(graph: &SierpinskiGraph) {
+        assert!(graph.random_points.is_empty());
+        assert_eq!((graph.max_iter, graph.cur_iter), (0, 0));
+    }
+
+    #[test]
+    fn vertex_edits_drop_the_points_and_the_iterations() {
+        let mut graph = generated(500);
+        let fourth = Point::new(300.0, 300.0);
+        graph.push_vertex(fourth);
+        assert_eq!(graph.fix_points.len(), 4);
+        assert_eq!(graph.fix_points[3], fourth);
+        assert_restarted(&graph);
+
+        let mut graph = generated(500);
+        let first = graph.fix_points[0];
+        graph.pop_vertex();
+        assert_eq!(graph.fix_points.len(), 2);
+        assert_eq!(graph.fix_points[0], first);
+        assert_restarted(&graph);
+
+        let mut graph = generated(500);
+        let rest = graph.fix_points[1..].to_vec();
+        graph.remove_vertex(0);
+        assert_eq!(graph.fix_points, rest);
+        assert_restarted(&graph);
+    }
+
+    #[test]
+    fn adding_and_removing_by_message_keeps_the_vertices_in_order() {
+        let mut emulator = emulator();
+        let [a, b] = [Point::new(10.0, 10.0), Point::new(20.0, 20.0)];
+        send(
+            &mut emulator,
+            [
+                Message::AddFixPoint(a),
+                Message::AddFixPoint(b),
+                Message::SetMaxIter(100),
+            ],
+        );
+        assert_eq!(emulator.graph.random_points.len(), 100);
+        send(&mut emulator, [Message::RemoveFixPoint]);
+        assert_eq!(emulator.graph.fix_points, [a]);
+        assert_restarted(&emulator.graph);
+        send(&mut emulator, [Message::AddFixPoint(b)]);
+        assert_eq!(emulator.graph.fix_points, [a, b]);
+        assert_restarted(&emulator.graph);
+    }
+
+    /// Inserts a fourth vertex as `index` into a triangle with points,
+    /// replaying their choices if `replaying`. Returns the vertices before the
+    /// insertion and the new one.
+    fn insert_into_triangle(
+        index: usize,
+        replaying: bool,
+    ) -> (SierpinskiEmulator, Vec<Point>, Point) {
+        let mut emulator = triangle();
+        emulator.graph.weights = vec![2.0, 3.0, 4.0];
+        send(&mut emulator, [Message::SetMaxIter(200)]);
+        assert_eq!(emulator.graph.choices.len(), 200);
+        if replaying {
+            emulator.graph.sequence = Some(emulator.graph.sequence().unwrap());
+            emulator.graph.regenerate();
+            assert!(emulator.graph.sequence.is_some());
+        }
+
+        let old = emulator.graph.fix_points.clone();
+        let new = Point::new(300.0, 300.0);
+        send(
+            &mut emulator,
+            [
+                Message::ArmInsertion(Some(index)),
+                Message::AddFixPoint(new),
+            ],
+        );
+        (emulator, old, new)
+    }
+
+    #[test]
+    fn inserting_reletters_the_vertices_after_it() {
+        for (index, letters) in [
+            (0, ["B", "C", "D"]),
+            (1, ["A", "C", "D"]),
+            (3, ["A", "B", "C"]),
+        ] {
+            let (emulator, old, new) = insert_into_triangle(index, false);
+            let graph = &emulator.graph;
+            assert_eq!(graph.fix_points.len(), 4);
+            assert_eq!(graph.fix_points[index], new);
+            assert_eq!(emulator.status, format!("inserted {}", vertex_label(index)));
+            let relettered = old
+                .iter()
+                .map(|point| {
+                    vertex_label(graph.fix_points.iter().position(|p| p == point).unwrap())
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(relettered, letters, "inserting at {}", index);
+            assert!(emulator.insert_at.is_none());
+        }
+    }
+
+    #[test]
+    fn inserting_moves_the_settings_and_drops_recorded_picks() {
+        let (emulator, _, _) = insert_into_triangle(1, true);
+        let graph = &emulator.graph;
+        assert_eq!(graph.weights, [2.0, 1.0, 3.0, 4.0]);
+        assert_restarted(graph);
+        assert!(graph.choices.is_empty());
+        // The replayed picks were numbered for three vertices.
+        assert!(graph.sequence.is_none());
+        assert!(emulator.status.starts_with("stopped replaying choices"));
+    }
+
+    #[test]
+    fn manual_picks_follow_inserted_and_deleted_vertices() {
+        let mut emulator = triangle();
+        emulator.graph.picking = Selection::ManualSequence(vec![0, 1, 2, 1]);
+        send(
+            &mut emulator,
+            [
+                Message::ArmInsertion(Some(1)),
+                Message::AddFixPoint(Point::new(300.0, 300.0)),
+            ],
+        );
+        assert_eq!(
+            emulator.graph.picking,
+            Selection::ManualSequence(vec![0, 2, 3, 2])
+        );
+        send(&mut emulator, [Message::DeleteVertex(2)]);
+        assert_eq!(
+            emulator.graph.picking,
+            Selection::ManualSequence(vec![0, 2])
+        );
+        send(
+            &mut emulator,
+            [Message::DeleteVertex(1), Message::DeleteVertex(1)],
+        );
+        assert_eq!(emulator.graph.picking, Selection::ManualSequence(vec![0]));
+        // Without any pick left the vertices are picked at random again.
+        send(&mut emulator, [Message::DeleteVertex(0)]);
+        assert_eq!(emulator.graph.picking, Selection::Random);
+    }
+
+    #[test]
+    fn typed_coordinates_are_echoed_with_the_chosen_decimals() {
+        let mut emulator = emulator();
+        emulator.graph.decimals = 2;
+        send(
+            &mut emulator,
+            [
+                Message::OpenCoordinateEntry,
+                Message::SetCoordinateText("12.3456,7".to_string()),
+                Message::SubmitCoordinates,
+            ],
+        );
+        assert_eq!(emulator.graph.fix_points, [Point::new(12.3456, 7.0)]);
+        assert!(
+            emulator
+                .status
+                .starts_with("placed a vertex at 12.35,7.00,"),
+            "{}",
+            emulator.status
+        );
+    }
+
+    fn place_triangle(emulator: &mut SierpinskiEmulator) {
+        send(
+            emulator,
+            triangle()
+                .graph
+                .fix_points
+                .into_iter()
+                .map(Message::AddFixPoint),
+        );
     }
 
-    fn gen_rand_point(&self) -> Point {
-        let dest_point_idx = rand::thread_rng().gen_range(0..self.fix_points.len());
-        let dest_point = self.fix_points[dest_point_idx];
-        let cur_point = self
+    #[test]
+    fn the_third_vertex_starts_a_first_run() {
+        let mut emulator = emulator();
+        emulator.config.animations = true;
+        place_triangle(&mut emulator);
+        assert_eq!(emulator.graph.max_iter, SUGGESTED_ITER);
+        assert_eq!(emulator.graph.random_points.len(), SUGGESTED_ITER as usize);
+        assert_eq!(emulator.graph.cur_iter, 0);
+        assert!(emulator.playing && emulator.quick_play);
+
+        // Only once: a fourth vertex resets as usual.
+        send(
+            &mut emulator,
+            [Message::AddFixPoint(Point::new(300.0, 300.0))],
+        );
+        assert_restarted(&emulator.graph);
+    }
+
+    #[test]
+    fn without_animations_the_first_run_is_shown_whole() {
+        let mut emulator = emulator();
+        emulator.config.animations = false;
+        place_triangle(&mut emulator);
+        assert_eq!(emulator.graph.cur_iter, SUGGESTED_ITER);
+        assert!(emulator.playing.not());
+    }
+
+    #[test]
+    fn no_first_run_without_suggestions() {
+        let mut emulator = emulator();
+        emulator.config.suggest_points = false;
+        place_triangle(&mut emulator);
+        assert_eq!(emulator.graph.fix_points.len(), 3);
+        assert_restarted(&emulator.graph);
+    }
+
+    #[test]
+    fn canvas_sizes_past_u32_are_rejected() {
+        assert_eq!(canvas_size(600), Some(600));
+        assert_eq!(canvas_size(*CANVAS_SIZES.end() as usize + 1), None);
+        // Would wrap to 600 if truncated.
+        assert_eq!(canvas_size((1 << 32) + 600), None);
+    }
+
+    /// Whether `timers` run anything that moves by itself.
+    fn animates(timers: &[Timer]) -> bool {
+        timers.iter().any(|(_, message)| {
+            matches!(
+                message(Instant::now()),
+                Message::Tick | Message::TransitionTick(_) | Message::ReplayTick
+            )
+        })
+    }
+
+    #[test]
+    fn reduced_motion_runs_no_animation_timer() {
+        let mut emulator = triangle();
+        send(&mut emulator, [Message::SetMaxIter(100)]);
+        emulator.playing = true;
+        emulator.graph.replay = Some(0);
+        assert!(animates(&emulator.timers()));
+
+        emulator.config.reduced_motion = true;
+        assert!(animates(&emulator.timers()).not());
+        // The other clocks keep going.
+        assert!(emulator
+            .timers()
+            .iter()
+            .any(|(_, message)| matches!(message(Instant::now()), Message::AutoSave)));
+    }
+
+    #[test]
+    fn reduced_motion_is_not_saved() {
+        let config = Config {
+            reduced_motion: true,
+            ..Config::default()
+        };
+        assert!(config.animated().not());
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains("reduced_motion").not(), "{}", json);
+        let loaded: Config = serde_json::from_str(&json).unwrap();
+        assert!(loaded.animations && loaded.animated());
+    }
+
+    /// Adds `points` with [`Message::ExtendBy`] and ticks until the
+    /// extension is done, returning how many ticks it took.
+    fn extend(emulator: &mut SierpinskiEmulator, points: usize) -> usize {
+        send(emulator, [Message::ExtendBy(points)]);
+        let mut ticks = 0;
+        while emulator.extension.is_some() {
+            assert!(ticks < 100, "the extension never finished");
+            send(emulator, [Message::ExtendTick]);
+            ticks += 1;
+        }
+        ticks
+    }
+
+    #[test]
+    fn consecutive_extensions_add_up() {
+        let mut emulator = triangle();
+        emulator.advance_on_extend = false;
+        send(&mut emulator, [Message::SetMaxIter(1_000)]);
+        let cur_iter = emulator.graph.cur_iter;
+
+        // In chunks, so the window stays responsive.
+        assert_eq!(extend(&mut emulator, 50_000), 3);
+        assert_eq!(emulator.graph.max_iter, 51_000);
+        assert_eq!(emulator.graph.random_points.len(), 51_000);
+        assert_eq!(emulator.graph.choices.len(), 51_000);
+        assert_eq!(emulator.graph.cur_iter, cur_iter);
+        assert_eq!(
+            emulator.status,
+            format!("added {} points", group_digits(50_000))
+        );
+
+        extend(&mut emulator, 30_000);
+        assert_eq!(emulator.graph.max_iter, 81_000);
+        assert_eq!(emulator.graph.random_points.len(), 81_000);
+        assert_eq!(emulator.graph.cur_iter, cur_iter);
+    }
+
+    #[test]
+    fn extensions_can_advance_the_current_iteration() {
+        let mut emulator = triangle();
+        emulator.advance_on_extend = true;
+        send(&mut emulator, [Message::SetMaxIter(1_000)]);
+        extend(&mut emulator, 2_000);
+        assert_eq!(emulator.graph.cur_iter, 3_000);
+        extend(&mut emulator, 500);
+        assert_eq!(emulator.graph.cur_iter, 3_500);
+        assert_eq!(emulator.graph.random_points.len(), 3_500);
+    }
+
+    #[test]
+    fn extensions_round_up_to_whole_iterations_of_every_orbit() {
+        let mut emulator = triangle();
+        send(
+            &mut emulator,
+            [Message::SetSeedCount(2), Message::SetMaxIter(100)],
+        );
+        extend(&mut emulator, 101);
+        assert_eq!(emulator.graph.max_iter, 151);
+        assert_eq!(emulator.graph.random_points.len(), 302);
+    }
+
+    #[test]
+    fn extending_nothing_leaves_the_counters_alone() {
+        let mut emulator = emulator();
+        assert_eq!(extend(&mut emulator, 1_000), 0);
+        assert_eq!(emulator.graph.max_iter, 0);
+        assert!(emulator.graph.random_points.is_empty());
+        assert!(emulator.status.starts_with("no fix points"));
+    }
+
+    #[test]
+    fn file_errors_exit_with_their_sysexits_code() {
+        let missing = Session::load(&temp_file("missing.json")).unwrap_err();
+        assert_eq!(exit_code(&missing), 74);
+        let newer = Session::from_json(r#"{"version": 99}"#).unwrap_err();
+        assert_eq!(exit_code(&newer), 78);
+        let script = script::parse("add 1,2\njump 3").unwrap_err();
+        assert_eq!(exit_code(&script), 65);
+        let empty = ChaosGame::new(Vec::<[f32; 2]>::new())
+            .try_points(10)
+            .unwrap_err();
+        assert_eq!(exit_code(&empty), 65);
+    }
+
+    #[test]
+    fn a_broken_session_is_reported_in_the_status_line() {
+        let path = temp_file("broken.json");
+        std::fs::write(&path, "{ \"version\": 1,").unwrap();
+        let mut emulator = emulator();
+        emulator.session_path = path.display().to_string();
+        send(&mut emulator, [Message::LoadSession]);
+        let _ = std::fs::remove_file(&path);
+        assert!(
+            emulator.status.contains("invalid session file"),
+            "{}",
+            emulator.status
+        );
+        assert!(emulator.graph.fix_points.is_empty());
+    }
+
+    #[test]
+    fn closing_removes_the_recovery_file_unless_one_is_on_offer() {
+        let json = triangle().session().to_json().unwrap();
+        recovery::save(&json).unwrap();
+        let mut emulator = emulator();
+        send(&mut emulator, [Message::CloseRequested]);
+        assert!(emulator.should_exit());
+        assert!(recovery::load().is_none());
+
+        // Closing before restoring or discarding it keeps it for next time.
+        recovery::save(&json).unwrap();
+        let mut undecided = self::emulator();
+        undecided.recovered = recovery::load();
+        assert!(undecided.should_exit().not());
+        send(&mut undecided, [Message::CloseRequested]);
+        assert!(undecided.should_exit());
+        assert!(recovery::load().is_some());
+        recovery::clear().unwrap();
+    }
+
+    #[test]
+    fn statuses_options_and_the_menu_follow_the_language() {
+        let mut emulator = emulator();
+        // Set directly, so the saved preferences stay English for the other
+        // tests.
+        emulator.config.language = Language::Chinese;
+        emulator.graph.language = Language::Chinese;
+        send(&mut emulator, [Message::Undo]);
+        assert_eq!(emulator.status, "没有可撤销的操作");
+        let rule = Labelled {
+            value: Rule::Midpoint,
+            language: Language::Chinese,
+        };
+        assert_eq!(rule.to_string(), "两点的中点");
+        // Metadata and reports keep the English names.
+        assert_eq!(rule.value.to_string(), "midpoint of two");
+        let items = emulator.graph.context_menu_items(Point::new(10.0, 10.0));
+        assert_eq!(items[0].0, "在此添加顶点");
+    }
+
+    #[test]
+    fn keys_typed_into_an_input_are_no_shortcuts() {
+        use iced::keyboard::{Event::KeyPressed, KeyCode, Modifiers};
+        use iced_native::event::Status;
+        let tab = || {
+            iced_native::Event::Keyboard(KeyPressed {
+                key_code: KeyCode::Tab,
+                modifiers: Modifiers::default(),
+            })
+        };
+        assert!(matches!(
+            shortcut(tab(), Status::Ignored),
+            Some(Message::FlipSlot)
+        ));
+        assert!(shortcut(tab(), Status::Captured).is_none());
+
+        let mut emulator = emulator();
+        assert!(emulator.typing().not());
+        emulator.gallery_from_state = text_input::State::focused();
+        assert!(emulator.typing());
+        emulator.gallery_from_state = text_input::State::default();
+        emulator.gallery_to_state = text_input::State::focused();
+        assert!(emulator.typing());
+    }
+
+    #[test]
+    fn a_batch_draws_what_the_window_exports() {
+        let fixture =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/session-v1-slots.json");
+        let dir = temp_file("batch-like-the-window");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::copy(&fixture, dir.join("slots.json")).unwrap();
+        let batch = Batch {
+            dir: dir.clone(),
+            out_dir: None,
+            iterations: None,
+            canvas: [600, 600],
+        };
+        let rendered = batch.run(&batch.sessions().unwrap(), |_, _| {});
+        let rendered = rendered[0].as_ref().unwrap();
+
+        let mut emulator = emulator();
+        emulator.graph.bound = Size::new(600.0, 600.0);
+        emulator.session_path = fixture.display().to_string();
+        send(&mut emulator, [Message::LoadSession]);
+        assert_eq!(emulator.graph.seed_count, 2);
+        let exported = dir.join("window.png");
+        emulator.graph.export_png(&exported, None, false).unwrap();
+        assert_eq!(
+            std::fs::read(rendered).unwrap(),
+            std::fs::read(&exported).unwrap()
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_batch_keeps_hand_edited_sessions_in_range() {
+        let dir = temp_file("batch-limits");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("huge.json"),
+            r#"{ "version": 1, "fix_points": [[10, 10], [290, 10], [150, 250]],
+                 "max_iter": 2000000000, "seed_count": 200 }"#,
+        )
+        .unwrap();
+        let batch = Batch {
+            dir: dir.clone(),
+            out_dir: None,
+            // Stands in for the max iter, which would take a while.
+            iterations: Some(1_000),
+            canvas: [300, 300],
+        };
+        let session = Session::load(&dir.join("huge.json")).unwrap();
+        let mut graph = SierpinskiGraph::new();
+        graph.apply_session(&session);
+        assert_eq!(graph.seed_count, MAX_SEEDS);
+        assert!(batch.run(&batch.sessions().unwrap(), |_, _| {})[0].is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_vertex_drag_undoes_in_one_step() {
+        let mut emulator = triangle();
+        let start = emulator.graph.fix_points.clone();
+        send(&mut emulator, [Message::BeginGesture]);
+        send(
+            &mut emulator,
+            (1..=50).map(|i| Message::MoveFixPoint(0, Point::new(300.0 + i as f32, 40.0))),
+        );
+        send(&mut emulator, [Message::EndGesture]);
+        assert_eq!(emulator.graph.fix_points[0], Point::new(350.0, 40.0));
+
+        send(&mut emulator, [Message::Undo]);
+        assert_eq!(emulator.graph.fix_points, start);
+        send(&mut emulator, [Message::Undo]);
+        assert_eq!(emulator.status, "nothing to undo");
+    }
+
+    #[test]
+    fn the_place_tool_drags_vertices_it_is_pressed_on() {
+        use iced::canvas::{Cursor, Event};
+        use iced::mouse::{Button, Event::*};
+        let mut graph = triangle().graph;
+        let bounds = Rectangle::new(Point::ORIGIN, graph.bound);
+        let mut update =
+            |event, at| graph.update(Event::Mouse(event), bounds, Cursor::Available(at));
+        let near = Point::new(302.0, 42.0);
+        assert!(matches!(
+            update(ButtonPressed(Button::Left), near).1,
+            Some(Message::BeginGesture)
+        ));
+        let to = Point::new(320.0, 80.0);
+        assert!(matches!(
+            update(CursorMoved { position: to }, to).1,
+            Some(Message::MoveFixPoint(0, at)) if at == to
+        ));
+        assert!(matches!(
+            update(ButtonReleased(Button::Left), to).1,
+            Some(Message::EndGesture)
+        ));
+        // Away from every vertex a press places one.
+        let away = Point::new(200.0, 300.0);
+        assert!(matches!(
+            update(ButtonPressed(Button::Left), away).1,
+            Some(Message::AddFixPoint(_))
+        ));
+    }
+
+    #[test]
+    fn recency_runs_the_ramp_up_to_the_current_iteration() {
+        let mut emulator = triangle();
+        send(
+            &mut emulator,
+            [
+                Message::SetMaxIter(1_000),
+                Message::SetPointColoring(PointColoring::Recency),
+            ],
+        );
+        let graph = &emulator.graph;
+        let color = |graph: &SierpinskiGraph, i: usize| {
+            graph.point_color(i, graph.random_points[i], graph.centroid())
+        };
+        assert_eq!(color(graph, 0), graph.ramp.at(0.0));
+        assert_eq!(color(graph, 999), graph.ramp.at(1.0));
+
+        // Scrubbing back makes the newest visible point the newest colour.
+        send(&mut emulator, [Message::SetCurIter(500)]);
+        assert_eq!(color(&emulator.graph, 499), emulator.graph.ramp.at(1.0));
+    }
+
+    #[test]
+    fn forbidden_transitions_get_no_chord() {
+        let mut emulator = triangle();
+        send(
+            &mut emulator,
+            [
+                Message::SetMaxIter(3_000),
+                Message::SetCurIter(3_000),
+                Message::SetShowTransitions(true),
+            ],
+        );
+        let counts = |emulator: &SierpinskiEmulator| emulator.graph.transitions().counts.clone();
+        let diagonal = |counts: &[usize]| counts[0] + counts[4] + counts[8];
+        // Every point after the first follows one pick.
+        assert_eq!(counts(&emulator).iter().sum::<usize>(), 2_999);
+        assert!(diagonal(&counts(&emulator)) > 0);
+
+        send(
+            &mut emulator,
+            [Message::SetRestriction(Restriction::NoRepeat)],
+        );
+        assert_eq!(diagonal(&counts(&emulator)), 0);
+        assert_eq!(counts(&emulator).iter().sum::<usize>(), 2_999);
+
+        // Scrubbing back counts again from the start.
+        send(&mut emulator, [Message::SetCurIter(1_000)]);
+        assert_eq!(counts(&emulator).iter().sum::<usize>(), 999);
+    }
+
+    #[test]
+    fn the_scale_factor_scales_the_window_and_png_exports() {
+        let mut emulator = triangle();
+        assert_eq!(emulator.scale_factor(), 1.0);
+        emulator.config.scale_factor = Some(10.0);
+        assert_eq!(emulator.scale_factor(), 4.0);
+        emulator.config.scale_factor = Some(2.0);
+        assert_eq!(emulator.scale_factor(), 2.0);
+
+        let width = |emulator: &SierpinskiEmulator, name: &str| {
+            let path = temp_file(name);
+            emulator.graph.export_png(&path, None, false).unwrap();
+            let decoder = png::Decoder::new(std::fs::File::open(&path).unwrap());
+            let width = decoder.read_info().unwrap().info().width;
+            std::fs::remove_file(&path).unwrap();
+            width
+        };
+        let plain = width(&emulator, "unscaled.png");
+        emulator.graph.export_scale = emulator.config.scale() as f32;
+        assert_eq!(width(&emulator, "scaled.png"), 2 * plain);
+    }
+
+    #[test]
+    fn the_canvas_palette_follows_the_background() {
+        let mut emulator = triangle();
+        assert_eq!(emulator.graph.palette(), Theme::Light.palette());
+
+        send(&mut emulator, [Message::SelectTheme(Theme::Dark)]);
+        assert_eq!(emulator.graph.palette(), Theme::Dark.palette());
+        assert_eq!(emulator.graph.foreground, Theme::Dark.foreground());
+
+        // A background picked by hand keeps the guides readable too.
+        send(
+            &mut emulator,
+            [Message::SetBackground(Color::from_rgb(0.9, 0.9, 0.8))],
+        );
+        assert_eq!(emulator.theme, None);
+        assert_eq!(emulator.graph.palette(), Theme::Light.palette());
+        send(
+            &mut emulator,
+            [Message::SetBackground(Color::from_rgb(0.1, 0.0, 0.2))],
+        );
+        assert_eq!(emulator.graph.palette(), Theme::Dark.palette());
+    }
+
+    #[test]
+    fn flipping_slots_swaps_between_the_stored_settings() {
+        let mut emulator = triangle();
+        send(
+            &mut emulator,
+            [Message::SetMaxIter(1_000), Message::StoreSlot(1)],
+        );
+
+        // Only B is stored, so flipping from it finds A empty.
+        send(&mut emulator, [Message::FlipSlot]);
+        assert_eq!(
+            emulator.status,
+            emulator.fill(Key::EmptySlot, &[&SLOT_NAMES[0]])
+        );
+
+        let points = emulator.graph.random_points.clone();
+        send(
+            &mut emulator,
+            [
+                Message::StoreSlot(0),
+                Message::SetRatio(0.3),
+                Message::StoreSlot(1),
+            ],
+        );
+        assert_ne!(emulator.graph.random_points, points);
+        send(&mut emulator, [Message::FlipSlot]);
+        assert_eq!(emulator.graph.ratio, 0.5);
+        assert_eq!(emulator.graph.random_points, points);
+        assert_eq!(emulator.active_slot, Some(0));
+        send(&mut emulator, [Message::FlipSlot]);
+        assert_eq!(emulator.graph.ratio, 0.3);
+        assert_eq!(emulator.active_slot, Some(1));
+    }
+
+    #[test]
+    fn a_diverging_orbit_stops_generation() {
+        // Past MAX_RATIO, which only a hand-edited session gets to.
+        let mut emulator = triangle();
+        emulator.graph.ratio = 3.0;
+        emulator.graph.clear_points();
+        send(&mut emulator, [Message::SetMaxIter(1_000)]);
+
+        let graph = &emulator.graph;
+        let stopped = graph.diverged.expect("the orbit runs away");
+        assert!(graph.random_points.len() < 1_000);
+        assert!(graph
             .random_points
-            .last()
-            .or_else(|| Some(&self.fix_points[0]))
-            .unwrap();
-        let new_point = Point::new(
-            (dest_point.x + cur_point.x) / 2_f32,
-            (dest_point.y + cur_point.y) / 2_f32,
+            .iter()
+            .all(|p| p.x.is_finite() && p.y.is_finite()));
+        assert_eq!(
+            emulator.status,
+            graph
+                .language
+                .fill(Key::DivergedRatio, &[&group_digits(stopped as i32)])
+        );
+
+        // It stays stopped until the points are regenerated.
+        let kept = graph.random_points.len();
+        send(&mut emulator, [Message::SetMaxIter(2_000)]);
+        assert_eq!(emulator.graph.random_points.len(), kept);
+    }
+
+    #[test]
+    fn vertex_colours_cycle_undo_and_reset() {
+        let mut emulator = triangle();
+        let colors = |emulator: &SierpinskiEmulator| {
+            (0..3)
+                .map(|i| emulator.graph.vertex_color(i))
+                .collect::<Vec<_>>()
+        };
+        let order = colors(&emulator);
+        assert_eq!(order, (0..3).map(palette::categorical).collect::<Vec<_>>());
+
+        send(&mut emulator, [Message::CycleVertexColor(1)]);
+        assert_eq!(colors(&emulator), [order[0], order[2], order[2]]);
+        // The picked colour wraps around the palette.
+        send(
+            &mut emulator,
+            std::iter::repeat_n(Message::CycleVertexColor(1), palette::CATEGORICAL.len()),
+        );
+        assert_eq!(emulator.graph.vertex_color(1), order[2]);
+        assert_eq!(emulator.session().vertex_colors, [None, Some(2), None]);
+
+        // Each pick is its own undo step, so one step back is one colour back.
+        send(&mut emulator, [Message::Undo]);
+        assert_eq!(emulator.graph.vertex_color(1), palette::categorical(1));
+        send(&mut emulator, [Message::ResetVertexColors]);
+        assert_eq!(colors(&emulator), order);
+        assert!(emulator.session().vertex_colors.is_empty());
+    }
+
+    #[test]
+    fn the_centre_of_mass_follows_scrubbing_and_the_centroid_becomes_a_vertex() {
+        let mut emulator = triangle();
+        send(
+            &mut emulator,
+            [Message::SetMaxIter(2_000), Message::SetCurIter(2_000)],
+        );
+        let mean = |graph: &SierpinskiGraph, n: usize| {
+            let sum = graph.random_points[..n].iter().fold([0.0, 0.0], |sum, p| {
+                [sum[0] + p.x as f64, sum[1] + p.y as f64]
+            });
+            Point::new((sum[0] / n as f64) as f32, (sum[1] / n as f64) as f32)
+        };
+        let close = |a: Point, b: Point| a.distance(b) < 1e-3;
+        assert!(close(
+            emulator.graph.center_of_mass().unwrap(),
+            mean(&emulator.graph, 2_000)
+        ));
+        // Scrubbing back starts the sum over, forward runs it on.
+        for cur_iter in [500, 1_500] {
+            send(&mut emulator, [Message::SetCurIter(cur_iter)]);
+            let expected = mean(&emulator.graph, cur_iter as usize);
+            assert!(close(emulator.graph.center_of_mass().unwrap(), expected));
+        }
+
+        send(&mut emulator, [Message::AddCentroidVertex]);
+        let vertices = &emulator.graph.fix_points;
+        assert_eq!(vertices.len(), 4);
+        assert!(close(vertices[3], Point::new(300.0, 1_160.0 / 3.0)));
+    }
+
+    #[test]
+    fn auto_frame_zooms_onto_the_attractor() {
+        let mut emulator = emulator();
+        emulator.graph.fix_points = vec![
+            Point::new(250.0, 250.0),
+            Point::new(350.0, 250.0),
+            Point::new(300.0, 330.0),
+        ];
+        emulator.graph.clear_points();
+        send(
+            &mut emulator,
+            [Message::SetMaxIter(2_000), Message::SetCurIter(2_000)],
+        );
+        let shown = |graph: &SierpinskiGraph| {
+            let viewport = graph.viewport();
+            stats::bounding_box(graph.visible_points().map(|p| viewport.apply([p.x, p.y]))).unwrap()
+        };
+        let unframed = shown(&emulator.graph);
+        assert!(unframed.0[0] >= 250.0 && unframed.1[0] <= 350.0);
+
+        send(&mut emulator, [Message::SetAutoFrame(true)]);
+        assert!(emulator.session().auto_frame);
+        let (min, max) = shown(&emulator.graph);
+        // The box is wider than tall, so it fills the width less the margin.
+        assert!((min[0] - AUTO_FRAME_MARGIN).abs() < 0.5, "{:?}", min);
+        assert!(
+            (max[0] - (600.0 - AUTO_FRAME_MARGIN)).abs() < 0.5,
+            "{:?}",
+            max
         );
-        new_point
+        assert!(((min[1] + max[1]) / 2.0 - 300.0).abs() < 0.5);
+
+        send(&mut emulator, [Message::SetAutoFrame(false)]);
+        assert_eq!(shown(&emulator.graph), unframed);
+    }
+
+    #[test]
+    fn density_settings_remap_without_regenerating() {
+        let mut emulator = triangle();
+        send(
+            &mut emulator,
+            [
+                Message::SetMaxIter(5_000),
+                Message::SetCurIter(5_000),
+                Message::SetPointColoring(PointColoring::Density),
+            ],
+        );
+        let points = emulator.graph.random_points.clone();
+        let levels = |graph: &SierpinskiGraph| {
+            graph.heat_level(graph.random_points[0]);
+            let heat = graph.heat.borrow();
+            let heat = heat.as_ref().unwrap();
+            (heat.grid.counts().to_vec(), heat.levels.clone())
+        };
+        let (counts, linear) = levels(&emulator.graph);
+
+        send(&mut emulator, [Message::SetCountScale(CountScale::Log)]);
+        let (log_counts, log) = levels(&emulator.graph);
+        assert_eq!(log_counts, counts);
+        assert_ne!(log, linear);
+        send(&mut emulator, [Message::SetClipPercentile(50.0)]);
+        assert_ne!(levels(&emulator.graph).1, log);
+
+        send(&mut emulator, [Message::SetRamp(Ramp::Fire)]);
+        let graph = &emulator.graph;
+        let p = graph.random_points[0];
+        assert_eq!(
+            graph.point_color(0, p, graph.centroid()),
+            Ramp::Fire.at(graph.heat_level(p))
+        );
+        assert_eq!(graph.random_points, points);
+    }
+
+    #[test]
+    fn growing_the_current_iteration_only_draws_the_new_points() {
+        let mut emulator = triangle();
+        send(
+            &mut emulator,
+            [Message::SetMaxIter(200_000), Message::SetCurIter(199_000)],
+        );
+        let bounds = Rectangle::new(Point::ORIGIN, emulator.graph.bound);
+        let draw = |emulator: &SierpinskiEmulator| {
+            let start = Instant::now();
+            emulator.graph.draw(bounds, canvas::Cursor::Unavailable);
+            start.elapsed()
+        };
+        let full = draw(&emulator);
+        send(&mut emulator, [Message::SetCurIter(199_100)]);
+        let tail = draw(&emulator);
+        assert_eq!(emulator.graph.layer_start, 199_000);
+        println!("199,000 points drawn in {:?}, 100 more in {:?}", full, tail);
+        // 100 points against 199,000, far enough apart that no scheduling
+        // hiccup closes the gap.
+        assert!(tail * 20 < full, "{:?} against {:?}", tail, full);
+    }
+
+    #[test]
+    fn oversized_exports_are_refused() {
+        let mut emulator = triangle();
+        send(&mut emulator, [Message::SetMaxIter(100)]);
+        emulator.graph.bound = Size::new(4096.0, 4096.0);
+        emulator.graph.supersample = 4;
+        emulator.graph.export_scale = 2.0;
+        let path = temp_file("oversized.png");
+        let e = emulator.graph.export_png(&path, None, false).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(e.to_string().contains("32768 × 32768"), "{}", e);
+        assert!(!path.exists());
+
+        // A crop is measured by the region, not the canvas.
+        let region = Rectangle::new(Point::ORIGIN, Size::new(600.0, 600.0));
+        emulator
+            .graph
+            .export_png(&path, Some(region), true)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
     }
 }
@@ -0,0 +1,164 @@
+//! Validation of numbers typed into text inputs, and how measured ones
+//! are shown.
+
+use std::fmt::{self, Display};
+
+/// The most decimal places [`Fixed`] is asked for; pixels don't get more
+/// precise than that.
+pub const MAX_PLACES: usize = 4;
+
+/// Parses a decimal number, also accepting a comma as the decimal separator
+/// as many locales write it. Surrounding whitespace is ignored.
+///
+/// ```
+/// use sierpinski_triangle_emulator::numeric::parse_decimal;
+///
+/// assert_eq!(parse_decimal("0.375"), Ok(0.375));
+/// assert_eq!(parse_decimal(" 0,375 "), Ok(0.375));
+/// assert_eq!(parse_decimal("-1,5"), Ok(-1.5));
+/// assert!(parse_decimal("1,000.5").is_err());
+/// assert!(parse_decimal("0,3,7").is_err());
+/// assert!(parse_decimal("").is_err());
+/// assert!(parse_decimal("NaN").is_err());
+/// ```
+pub fn parse_decimal(text: &str) -> Result<f32, String> {
+    let text = text.trim();
+    let invalid = || format!("`{}` is not a valid number", text);
+    let normalized = if text.contains('.') {
+        text.to_string()
+    } else {
+        match text.matches(',').count() {
+            0 | 1 => text.replacen(',', ".", 1),
+            _ => return Err(invalid()),
+        }
+    };
+    normalized
+        .parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(invalid)
+}
+
+/// Parses a decimal number strictly between `min` and `max`.
+///
+/// ```
+/// use sierpinski_triangle_emulator::numeric::parse_between;
+///
+/// assert_eq!(parse_between("1,25", 0.0, 1.5), Ok(1.25));
+/// assert!(parse_between("0", 0.0, 1.5).is_err());
+/// assert!(parse_between("1.5", 0.0, 1.5).is_err());
+/// assert!(parse_between("-0,1", 0.0, 1.5).is_err());
+/// ```
+pub fn parse_between(text: &str, min: f32, max: f32) -> Result<f32, String> {
+    let value = parse_decimal(text)?;
+    if value > min && value < max {
+        Ok(value)
+    } else {
+        Err(format!("{} is not between {} and {}", value, min, max))
+    }
+}
+
+/// Parses a whole, non-negative count. Digits may be grouped with
+/// underscores, apostrophes, spaces or commas, so `100,000` is a hundred
+/// thousand rather than a decimal.
+///
+/// ```
+/// use sierpinski_triangle_emulator::numeric::parse_count;
+///
+/// assert_eq!(parse_count("100000"), Ok(100_000));
+/// assert_eq!(parse_count(" 100,000 "), Ok(100_000));
+/// assert_eq!(parse_count("1_000'000"), Ok(1_000_000));
+/// assert!(parse_count("-5").is_err());
+/// assert!(parse_count("2.5").is_err());
+/// assert!(parse_count("").is_err());
+/// ```
+pub fn parse_count(text: &str) -> Result<usize, String> {
+    let text = text.trim();
+    let digits = text
+        .chars()
+        .filter(|c| !matches!(c, '_' | '\'' | ' ' | ','))
+        .collect::<String>();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("`{}` is not a whole number", text));
+    }
+    digits
+        .parse()
+        .map_err(|_| format!("`{}` is too large", text))
+}
+
+/// A list of vertex indices, such as `0,1,2,0`, separated by commas or
+/// spaces. Empty text is an empty list.
+///
+/// ```
+/// use sierpinski_triangle_emulator::numeric::parse_indices;
+///
+/// assert_eq!(parse_indices("0,1,2,0"), Ok(vec![0, 1, 2, 0]));
+/// assert_eq!(parse_indices(" 2 1, 0 "), Ok(vec![2, 1, 0]));
+/// assert_eq!(parse_indices(""), Ok(vec![]));
+/// assert!(parse_indices("0,-1").is_err());
+/// assert!(parse_indices("0,a").is_err());
+/// ```
+pub fn parse_indices(text: &str) -> Result<Vec<usize>, String> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|index| !index.is_empty())
+        .map(|index| {
+            index
+                .parse()
+                .map_err(|_| format!("`{}` is not a vertex number", index))
+        })
+        .collect()
+}
+
+/// A number shown with a fixed number of decimal places, and a unit after
+/// it if it has one. Values that round to zero show without a minus sign,
+/// so a coordinate at -0.01 reads `0.0` rather than `-0.0`.
+///
+/// ```
+/// use sierpinski_triangle_emulator::numeric::Fixed;
+///
+/// assert_eq!(Fixed::new(12.345, 1).to_string(), "12.3");
+/// assert_eq!(Fixed::new(12.345, 0).to_string(), "12");
+/// assert_eq!(Fixed::new(-0.01, 1).to_string(), "0.0");
+/// assert_eq!(Fixed::new(-0.5, 1).to_string(), "-0.5");
+/// assert_eq!(Fixed::new(3.0, 2).unit("px").to_string(), "3.00 px");
+/// // More places than are meaningful are capped.
+/// assert_eq!(Fixed::new(1.0, 9).to_string(), "1.0000");
+/// assert_eq!(Fixed::new(f32::NAN, 1).to_string(), "NaN");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fixed {
+    value: f32,
+    places: usize,
+    unit: Option<&'static str>,
+}
+
+impl Fixed {
+    pub fn new(value: f32, places: usize) -> Fixed {
+        Fixed {
+            value,
+            places: places.min(MAX_PLACES),
+            unit: None,
+        }
+    }
+
+    pub fn unit(self, unit: &'static str) -> Fixed {
+        Fixed {
+            unit: Some(unit),
+            ..self
+        }
+    }
+}
+
+impl Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shown = format!("{:.*}", self.places, self.value);
+        match shown.strip_prefix('-') {
+            Some(digits) if digits.chars().all(|c| c == '0' || c == '.') => write!(f, "{}", digits),
+            _ => write!(f, "{}", shown),
+        }?;
+        match self.unit {
+            Some(unit) => write!(f, " {}", unit),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::fmt::{self, Display};
+
+use iced::Color;
+
+const fn rgb8(r: u8, g: u8, b: u8) -> Color {
+    Color::from_rgb(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
+}
+
+pub const FIX_POINT_COLOR: Color = rgb8(0x12, 0x93, 0xD8);
+
+/// Faint colour for construction guides.
+pub const GUIDE_COLOR: Color = Color::from_rgba(0.5, 0.5, 0.5, 0.4);
+
+/// A pinned earlier attractor behind the current points.
+pub const GHOST_COLOR: Color = Color::from_rgba(0.55, 0.55, 0.55, 0.35);
+
+/// Thin teaching overlays drawn over the points.
+pub const OVERLAY_COLOR: Color = rgb8(0xD8, 0x3A, 0x12);
+
+/// The box behind tooltips, readable over either theme.
+pub struct TooltipStyle;
+
+impl iced::container::StyleSheet for TooltipStyle {
+    fn style(&self) -> iced::container::Style {
+        iced::container::Style {
+            text_color: Some(Color::BLACK),
+            background: Some(rgb8(0xFA, 0xF7, 0xE8).into()),
+            border_radius: 3.0,
+            border_width: 1.0,
+            border_color: GUIDE_COLOR,
+        }
+    }
+}
+
+/// Distinct colours for categorical colouring, such as sectors.
+pub const CATEGORICAL: [Color; 12] = [
+    rgb8(0x4E, 0x79, 0xA7),
+    rgb8(0xF2, 0x8E, 0x2B),
+    rgb8(0xE1, 0x57, 0x59),
+    rgb8(0x76, 0xB7, 0xB2),
+    rgb8(0x59, 0xA1, 0x4F),
+    rgb8(0xED, 0xC9, 0x48),
+    rgb8(0xB0, 0x7A, 0xA1),
+    rgb8(0xFF, 0x9D, 0xA7),
+    rgb8(0x9C, 0x75, 0x5F),
+    rgb8(0xBA, 0xB0, 0xAC),
+    rgb8(0x17, 0xBE, 0xCF),
+    rgb8(0x8C, 0x56, 0x4B),
+];
+
+/// The colour ramps for ordered values, such as how recent or how
+/// crowded points are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Ramp {
+    /// Dark purple through teal to yellow.
+    #[default]
+    Viridis,
+    Grayscale,
+    /// Black through red and orange to pale yellow.
+    Fire,
+}
+
+/// The stops of each ramp, evenly spaced from its low to its high end.
+const VIRIDIS: [Color; 5] = [
+    rgb8(0x44, 0x01, 0x54),
+    rgb8(0x3B, 0x52, 0x8B),
+    rgb8(0x21, 0x91, 0x8C),
+    rgb8(0x5E, 0xC9, 0x62),
+    rgb8(0xFD, 0xE7, 0x25),
+];
+const GRAYSCALE: [Color; 2] = [rgb8(0x10, 0x10, 0x10), rgb8(0xF0, 0xF0, 0xF0)];
+const FIRE: [Color; 5] = [
+    rgb8(0x00, 0x00, 0x00),
+    rgb8(0x78, 0x0A, 0x0A),
+    rgb8(0xE0, 0x3C, 0x00),
+    rgb8(0xFF, 0xA0, 0x14),
+    rgb8(0xFF, 0xF5, 0xB4),
+];
+
+impl Ramp {
+    pub const ALL: [Ramp; 3] = [Ramp::Viridis, Ramp::Grayscale, Ramp::Fire];
+
+    fn stops(self) -> &'static [Color] {
+        match self {
+            Ramp::Viridis => &VIRIDIS,
+            Ramp::Grayscale => &GRAYSCALE,
+            Ramp::Fire => &FIRE,
+        }
+    }
+
+    /// The colour `t` of the way along the ramp, `t` clamped to 0 to 1.
+    pub fn at(self, t: f32) -> Color {
+        let stops = self.stops();
+        let position = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let low = (position as usize).min(stops.len() - 2);
+        let (a, b, f) = (stops[low], stops[low + 1], position - low as f32);
+        Color::from_rgb(
+            a.r + (b.r - a.r) * f,
+            a.g + (b.g - a.g) * f,
+            a.b + (b.b - a.b) * f,
+        )
+    }
+}
+
+impl Display for Ramp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ramp::Viridis => write!(f, "viridis"),
+            Ramp::Grayscale => write!(f, "grayscale"),
+            Ramp::Fire => write!(f, "fire"),
+        }
+    }
+}
+
+/// The categorical colour for `index`, wrapping around the palette.
+pub fn categorical(index: usize) -> Color {
+    CATEGORICAL[index % CATEGORICAL.len()]
+}
+
+/// The palette index of vertex `i` given the picked `colors`, where a
+/// missing entry or `None` follows palette order.
+pub fn vertex_color(colors: &[Option<usize>], i: usize) -> usize {
+    colors.get(i).copied().flatten().unwrap_or(i) % CATEGORICAL.len()
+}
+
+/// Writes out the palette order of every one of `len` vertices still
+/// following it, so each keeps its colour when vertices are inserted or
+/// removed before it.
+pub fn pin_vertex_colors(colors: &mut Vec<Option<usize>>, len: usize) {
+    *colors = (0..len).map(|i| Some(vertex_color(colors, i))).collect();
+}
+
+/// The colour a theme gives each role on the canvas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub background: Color,
+    /// The points, and the border unless it has a colour of its own.
+    pub text: Color,
+    /// The vertex markers and what edits them: selections, weight arcs.
+    pub primary: Color,
+    /// Construction guides, disabled vertices and the canvas outline.
+    pub guide: Color,
+}
+
+/// Quick presets for the canvas background and the colour of the points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 2] = [Theme::Light, Theme::Dark];
+
+    pub fn palette(self) -> Palette {
+        match self {
+            Theme::Light => Palette {
+                background: Color::WHITE,
+                text: Color::BLACK,
+                primary: FIX_POINT_COLOR,
+                guide: GUIDE_COLOR,
+            },
+            Theme::Dark => Palette {
+                background: rgb8(0x1E, 0x1E, 0x24),
+                text: rgb8(0xE8, 0xE8, 0xE8),
+                primary: rgb8(0x5A, 0xB8, 0xF0),
+                guide: Color::from_rgba(0.7, 0.7, 0.7, 0.4),
+            },
+        }
+    }
+
+    /// The theme whose roles read best over `background`: dark for a dark
+    /// background, by its relative luminance, light otherwise.
+    pub fn for_background(background: Color) -> Theme {
+        let luminance = 0.2126 * background.r + 0.7152 * background.g + 0.0722 * background.b;
+        if luminance < 0.5 {
+            Theme::Dark
+        } else {
+            Theme::Light
+        }
+    }
+
+    pub fn background(self) -> Color {
+        self.palette().background
+    }
+
+    pub fn foreground(self) -> Color {
+        self.palette().text
+    }
+}
+
+impl Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Theme::Light => write!(f, "light"),
+            Theme::Dark => write!(f, "dark"),
+        }
+    }
+}
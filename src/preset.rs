@@ -0,0 +1,127 @@
+use std::fmt::{self, Display};
+
+use iced::{Point, Size};
+use serde::{Deserialize, Serialize};
+
+/// Regular polygon vertex layouts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Triangle,
+    Square,
+    Pentagon,
+    Hexagon,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 4] = [
+        Preset::Triangle,
+        Preset::Square,
+        Preset::Pentagon,
+        Preset::Hexagon,
+    ];
+
+    fn sides(self) -> usize {
+        match self {
+            Preset::Triangle => 3,
+            Preset::Square => 4,
+            Preset::Pentagon => 5,
+            Preset::Hexagon => 6,
+        }
+    }
+
+    /// The vertices of the polygon centred in `bound`, first vertex on top.
+    pub fn vertices(self, bound: Size<f32>) -> Vec<Point> {
+        Circle {
+            count: self.sides() as u8,
+            ..Circle::default()
+        }
+        .vertices(bound)
+    }
+}
+
+/// Evenly spaced vertices on a circle centred in the canvas, for the
+/// polygons the presets don't have.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Circle {
+    /// How many vertices, from 3 to 24.
+    pub count: u8,
+    /// Percent of half the shorter side of the canvas.
+    pub radius: f32,
+    /// Degrees clockwise the first vertex is turned from the top.
+    pub phase: f32,
+}
+
+impl Circle {
+    pub const COUNTS: std::ops::RangeInclusive<u8> = 3..=24;
+
+    pub fn vertices(self, bound: Size<f32>) -> Vec<Point> {
+        let center = Point::new(bound.width / 2.0, bound.height / 2.0);
+        let radius = bound.width.min(bound.height) / 2.0 * self.radius / 100.0;
+        let count = self.count.max(1) as usize;
+        (0..count)
+            .map(|i| {
+                let angle = (self.phase - 90.0).to_radians()
+                    + std::f32::consts::TAU * i as f32 / count as f32;
+                Point::new(
+                    center.x + radius * angle.cos(),
+                    center.y + radius * angle.sin(),
+                )
+            })
+            .collect()
+    }
+}
+
+impl Default for Circle {
+    fn default() -> Circle {
+        Circle {
+            count: 3,
+            radius: 90.0,
+            phase: 0.0,
+        }
+    }
+}
+
+impl Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Preset::Triangle => write!(f, "triangle"),
+            Preset::Square => write!(f, "square"),
+            Preset::Pentagon => write!(f, "pentagon"),
+            Preset::Hexagon => write!(f, "hexagon"),
+        }
+    }
+}
+
+/// Scales and centres `points` uniformly so their bounding box fills 90% of
+/// `bound`. A single point is just moved to the centre.
+pub fn fit(points: &[Point], bound: Size<f32>) -> Vec<Point> {
+    let (min, max) = points.iter().fold(
+        (
+            Point::new(f32::INFINITY, f32::INFINITY),
+            Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+        ),
+        |(min, max), p| {
+            (
+                Point::new(min.x.min(p.x), min.y.min(p.y)),
+                Point::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        },
+    );
+    let (width, height) = (max.x - min.x, max.y - min.y);
+    let scale = if width > 0.0 || height > 0.0 {
+        (bound.width * 0.9 / width).min(bound.height * 0.9 / height)
+    } else {
+        0.0
+    };
+    let center = Point::new((min.x + max.x) / 2.0, (min.y + max.y) / 2.0);
+    points
+        .iter()
+        .map(|p| {
+            Point::new(
+                bound.width / 2.0 + (p.x - center.x) * scale,
+                bound.height / 2.0 + (p.y - center.y) * scale,
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,99 @@
+//! A wrapper that sends a message when the left mouse button goes down on a
+//! widget, before the widget itself sees the press. Sliders only report
+//! their release, so this marks where a drag starts.
+
+use std::hash::Hash;
+
+use iced_native::{
+    event, layout, mouse, overlay, Clipboard, Element, Event, Hasher, Layout, Length, Point,
+    Rectangle, Widget,
+};
+
+pub struct Press<'a, Message, Renderer> {
+    content: Element<'a, Message, Renderer>,
+    on_press: Message,
+}
+
+impl<'a, Message, Renderer> Press<'a, Message, Renderer> {
+    pub fn new(content: impl Into<Element<'a, Message, Renderer>>, on_press: Message) -> Self {
+        Press {
+            content: content.into(),
+            on_press,
+        }
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for Press<'a, Message, Renderer>
+where
+    Message: Clone,
+    Renderer: iced_native::Renderer,
+{
+    fn width(&self) -> Length {
+        self.content.width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.height()
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        self.content.layout(renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        defaults: &Renderer::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) -> Renderer::Output {
+        self.content
+            .draw(renderer, defaults, layout, cursor_position, viewport)
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.content.hash_layout(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        messages: &mut Vec<Message>,
+    ) -> event::Status {
+        if let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event {
+            if layout.bounds().contains(cursor_position) {
+                messages.push(self.on_press.clone());
+            }
+        }
+        self.content.on_event(
+            event,
+            layout,
+            cursor_position,
+            renderer,
+            clipboard,
+            messages,
+        )
+    }
+
+    fn overlay(&mut self, layout: Layout<'_>) -> Option<overlay::Element<'_, Message, Renderer>> {
+        self.content.overlay(layout)
+    }
+}
+
+impl<'a, Message, Renderer> From<Press<'a, Message, Renderer>> for Element<'a, Message, Renderer>
+where
+    Message: 'a + Clone,
+    Renderer: 'a + iced_native::Renderer,
+{
+    fn from(press: Press<'a, Message, Renderer>) -> Element<'a, Message, Renderer> {
+        Element::new(press)
+    }
+}
@@ -0,0 +1,164 @@
+//! The most recently saved or loaded session files, kept in the user's
+//! config directory so they can be reopened on the next launch.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+/// How many sessions are remembered.
+const CAPACITY: usize = 10;
+/// How many of them the startup picker offers.
+pub const SHOWN: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub path: PathBuf,
+    /// Seconds since the Unix epoch of the last save or load.
+    pub timestamp: u64,
+    pub vertices: usize,
+    pub points: i32,
+}
+
+/// Most recent first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Recent {
+    pub entries: Vec<Entry>,
+}
+
+impl Recent {
+    /// The list stored in the config directory, empty if there is none yet
+    /// or it can't be read.
+    pub fn load() -> Recent {
+        file()
+            .and_then(|file| fs::read_to_string(file).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let file =
+            file().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::from)?;
+        fs::write(file, json)
+    }
+
+    /// The most recent entries, as many as the picker offers.
+    pub fn shown(&self) -> &[Entry] {
+        &self.entries[..self.entries.len().min(SHOWN)]
+    }
+
+    /// Moves `path` to the front with fresh details, dropping the oldest
+    /// entries beyond the capacity.
+    pub fn touch(&mut self, path: &Path, vertices: usize, points: i32) {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.entries.retain(|entry| entry.path != path);
+        self.entries.insert(
+            0,
+            Entry {
+                path,
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs()),
+                vertices,
+                points,
+            },
+        );
+        self.entries.truncate(CAPACITY);
+    }
+
+    /// Drops the entries whose file no longer exists and returns their paths.
+    pub fn prune(&mut self) -> Vec<PathBuf> {
+        let (kept, missing) = self
+            .entries
+            .drain(..)
+            .partition::<Vec<_>, _>(|entry| entry.path.is_file());
+        self.entries = kept;
+        missing.into_iter().map(|entry| entry.path).collect()
+    }
+}
+
+/// `recent.json` in the platform's config directory.
+fn file() -> Option<PathBuf> {
+    Some(config::dir()?.join("recent.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(recent: &Recent) -> Vec<&Path> {
+        recent
+            .entries
+            .iter()
+            .map(|entry| entry.path.as_path())
+            .collect()
+    }
+
+    #[test]
+    fn touch_moves_to_the_front_and_caps() {
+        let mut recent = Recent::default();
+        for i in 0..12 {
+            recent.touch(Path::new(&format!("missing-{}.json", i)), i, 1_000);
+        }
+        assert_eq!(recent.entries.len(), CAPACITY);
+        assert_eq!(recent.entries[0].path, Path::new("missing-11.json"));
+        assert_eq!(recent.entries[9].path, Path::new("missing-2.json"));
+
+        // Touching an entry again moves it up with its new details, once.
+        recent.touch(Path::new("missing-5.json"), 7, 20_000);
+        assert_eq!(recent.entries.len(), CAPACITY);
+        assert_eq!(
+            paths(&recent)[..3],
+            [
+                Path::new("missing-5.json"),
+                Path::new("missing-11.json"),
+                Path::new("missing-10.json")
+            ]
+        );
+        assert_eq!(
+            (recent.entries[0].vertices, recent.entries[0].points),
+            (7, 20_000)
+        );
+        assert_eq!(
+            paths(&recent)
+                .iter()
+                .filter(|p| **p == Path::new("missing-5.json"))
+                .count(),
+            1
+        );
+        assert_eq!(recent.shown().len(), SHOWN);
+        assert_eq!(recent.shown()[1].path, Path::new("missing-11.json"));
+    }
+
+    #[test]
+    fn prune_drops_missing_files_in_order() {
+        let dir = std::env::temp_dir().join(format!("sierpinski-recent-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // Entries hold canonical paths, which the temporary directory may
+        // not be.
+        let dir = fs::canonicalize(dir).unwrap();
+        let kept = [dir.join("a.json"), dir.join("c.json")];
+        for path in &kept {
+            fs::write(path, "{}").unwrap();
+        }
+        let gone = dir.join("b.json");
+        let mut recent = Recent::default();
+        for path in [&kept[1], &gone, &kept[0]] {
+            recent.touch(path, 3, 100);
+        }
+        let missing = recent.prune();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(missing, [gone]);
+        assert_eq!(paths(&recent), [kept[0].as_path(), kept[1].as_path()]);
+        assert!(Recent::default().prune().is_empty());
+    }
+}
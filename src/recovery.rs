@@ -0,0 +1,40 @@
+//! A copy of the session written every so often while it changes, so a
+//! crash loses little work. A clean exit removes it, so finding one on
+//! startup means the last run ended without one.
+
+use std::{fs, io, path::PathBuf, time::Duration};
+
+use crate::{config, session::Session};
+
+/// How often the session is written, if it changed since the last time.
+pub const INTERVAL: Duration = Duration::from_secs(30);
+
+/// The session the last run left behind, if it didn't exit cleanly and the
+/// file can still be read.
+pub fn load() -> Option<Session> {
+    let json = fs::read_to_string(file()?).ok()?;
+    Session::from_json(&json).ok()
+}
+
+/// Writes `json`, a session as [`Session::to_json`] makes it.
+pub fn save(json: &str) -> io::Result<()> {
+    let file =
+        file().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(file, json)
+}
+
+/// Removes the file, if there is one.
+pub fn clear() -> io::Result<()> {
+    match file().map(fs::remove_file) {
+        Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// `recovery.json` in the platform's config directory.
+fn file() -> Option<PathBuf> {
+    Some(config::dir()?.join("recovery.json"))
+}
@@ -0,0 +1,771 @@
+//! Rasterizing points into an RGBA image without iced, so exports come out
+//! pixel for pixel the same for the same points and style.
+
+use std::fmt::{self, Display};
+
+/// An 8-bit RGBA image, rows top to bottom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    /// `width * height` pixels of four bytes each.
+    pub data: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// An image filled with `background`.
+    ///
+    /// # Panics
+    ///
+    /// If its bytes don't fit in `usize`.
+    pub fn new(width: u32, height: u32, background: [u8; 4]) -> RgbaImage {
+        let len = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .expect("image too large");
+        RgbaImage {
+            width,
+            height,
+            data: background.iter().copied().cycle().take(len).collect(),
+        }
+    }
+
+    /// Where the pixel at column `x` of row `y` starts in `data`.
+    fn offset(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 4
+    }
+
+    /// The pixel at column `x` of row `y`.
+    ///
+    /// # Panics
+    ///
+    /// If the pixel is outside the image.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        assert!(x < self.width && y < self.height, "pixel outside the image");
+        let offset = self.offset(x, y);
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.data[offset..offset + 4]);
+        pixel
+    }
+
+    /// Sets the pixel containing `point`, ignoring points outside the image.
+    pub fn plot(&mut self, [x, y]: [f32; 2], color: [u8; 4]) {
+        if !(x >= 0.0 && y >= 0.0) {
+            return;
+        }
+        let (x, y) = (x as u32, y as u32);
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let offset = self.offset(x, y);
+        self.data[offset..offset + 4].copy_from_slice(&color);
+    }
+
+    /// Sets every pixel of the `size` by `size` square with its top left
+    /// corner in the pixel containing `point`.
+    pub fn fill_square(&mut self, [x, y]: [f32; 2], size: u32, color: [u8; 4]) {
+        for dy in 0..size {
+            for dx in 0..size {
+                self.plot([x.floor() + dx as f32, y.floor() + dy as f32], color);
+            }
+        }
+    }
+
+    /// Composites `color` over the pixel containing `point`, ignoring points
+    /// outside the image.
+    pub fn blend(&mut self, [x, y]: [f32; 2], color: [u8; 4]) {
+        if !(x >= 0.0 && y >= 0.0) {
+            return;
+        }
+        let (x, y) = (x as u32, y as u32);
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let offset = self.offset(x, y);
+        let below = &mut self.data[offset..offset + 4];
+        let [r, g, b, a] = color.map(|c| c as f32 / 255.0);
+        let below_alpha = below[3] as f32 / 255.0;
+        let alpha = a + below_alpha * (1.0 - a);
+        for (channel, value) in below.iter_mut().take(3).zip([r, g, b]) {
+            let under = *channel as f32 / 255.0;
+            let mixed = if alpha > 0.0 {
+                (value * a + under * below_alpha * (1.0 - a)) / alpha
+            } else {
+                0.0
+            };
+            *channel = (mixed * 255.0).round() as u8;
+        }
+        below[3] = (alpha * 255.0).round() as u8;
+    }
+
+    /// Sets every pixel whose offset from the pixel containing `center` is
+    /// within `radius`.
+    pub fn fill_circle(&mut self, [x, y]: [f32; 2], radius: f32, color: [u8; 4]) {
+        let r = radius.ceil() as i32;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if ((dx * dx + dy * dy) as f32) <= radius * radius {
+                    self.plot([x + dx as f32, y + dy as f32], color);
+                }
+            }
+        }
+    }
+
+    /// Copies `image` over this one with its top left corner at `[x, y]`,
+    /// clipping whatever falls outside.
+    pub fn blit(&mut self, image: &RgbaImage, [x, y]: [u32; 2]) {
+        let width = image.width.min(self.width.saturating_sub(x));
+        for row in 0..image.height.min(self.height.saturating_sub(y)) {
+            let from = image.offset(0, row);
+            let to = self.offset(x, y + row);
+            self.data[to..to + width as usize * 4]
+                .copy_from_slice(&image.data[from..from + width as usize * 4]);
+        }
+    }
+
+    /// Writes `text` in a blocky font with its top left corner at `point`,
+    /// each font pixel `scale` pixels square. Letters come out upper case;
+    /// characters the font lacks leave a gap.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::render::RgbaImage;
+    ///
+    /// let mut image = RgbaImage::new(40, 12, [255, 255, 255, 255]);
+    /// image.text([1.0, 1.0], "0.5", 2, [0, 0, 0, 255]);
+    /// assert_eq!(RgbaImage::text_width("0.5", 2), 22);
+    /// // The top left corner of the zero, and the gap before the dot.
+    /// assert_eq!(image.pixel(1, 1), [0, 0, 0, 255]);
+    /// assert_eq!(image.pixel(7, 1), [255, 255, 255, 255]);
+    /// ```
+    pub fn text(&mut self, [x, y]: [f32; 2], text: &str, scale: u32, color: [u8; 4]) {
+        let advance = (GLYPH_WIDTH + 1) * scale;
+        for (i, c) in text.chars().enumerate() {
+            let rows = match GLYPHS.iter().find(|(g, _)| *g == c.to_ascii_uppercase()) {
+                Some((_, rows)) => rows,
+                None => continue,
+            };
+            let left = x + (i as u32 * advance) as f32;
+            for (row, bits) in rows.iter().enumerate() {
+                for column in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                        self.fill_square(
+                            [
+                                left + (column * scale) as f32,
+                                y + (row as u32 * scale) as f32,
+                            ],
+                            scale,
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// How wide [`text`](Self::text) writes `text`, in pixels.
+    pub fn text_width(text: &str, scale: u32) -> u32 {
+        let n = text.chars().count() as u32;
+        (n * (GLYPH_WIDTH + 1)).saturating_sub(1) * scale
+    }
+}
+
+const GLYPH_WIDTH: u32 = 3;
+
+/// A three by five pixel font, each row a bit per column with the leftmost
+/// column highest.
+#[rustfmt::skip]
+const GLYPHS: [(char, [u8; 5]); 43] = [
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b011, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b001, 0b010, 0b010]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('.', [0b000, 0b000, 0b000, 0b000, 0b010]),
+    (',', [0b000, 0b000, 0b000, 0b010, 0b100]),
+    (':', [0b000, 0b010, 0b000, 0b010, 0b000]),
+    ('-', [0b000, 0b000, 0b111, 0b000, 0b000]),
+    ('+', [0b000, 0b010, 0b111, 0b010, 0b000]),
+    ('/', [0b001, 0b001, 0b010, 0b100, 0b100]),
+    ('%', [0b101, 0b001, 0b010, 0b100, 0b101]),
+    ('A', [0b010, 0b101, 0b111, 0b101, 0b101]),
+    ('B', [0b110, 0b101, 0b110, 0b101, 0b110]),
+    ('C', [0b011, 0b100, 0b100, 0b100, 0b011]),
+    ('D', [0b110, 0b101, 0b101, 0b101, 0b110]),
+    ('E', [0b111, 0b100, 0b110, 0b100, 0b111]),
+    ('F', [0b111, 0b100, 0b110, 0b100, 0b100]),
+    ('G', [0b011, 0b100, 0b101, 0b101, 0b011]),
+    ('H', [0b101, 0b101, 0b111, 0b101, 0b101]),
+    ('I', [0b111, 0b010, 0b010, 0b010, 0b111]),
+    ('J', [0b001, 0b001, 0b001, 0b101, 0b010]),
+    ('K', [0b101, 0b101, 0b110, 0b101, 0b101]),
+    ('L', [0b100, 0b100, 0b100, 0b100, 0b111]),
+    ('M', [0b101, 0b111, 0b111, 0b101, 0b101]),
+    ('N', [0b110, 0b101, 0b101, 0b101, 0b101]),
+    ('O', [0b010, 0b101, 0b101, 0b101, 0b010]),
+    ('P', [0b110, 0b101, 0b110, 0b100, 0b100]),
+    ('Q', [0b010, 0b101, 0b101, 0b110, 0b011]),
+    ('R', [0b110, 0b101, 0b110, 0b101, 0b101]),
+    ('S', [0b011, 0b100, 0b010, 0b001, 0b110]),
+    ('T', [0b111, 0b010, 0b010, 0b010, 0b010]),
+    ('U', [0b101, 0b101, 0b101, 0b101, 0b111]),
+    ('V', [0b101, 0b101, 0b101, 0b101, 0b010]),
+    ('W', [0b101, 0b101, 0b111, 0b111, 0b101]),
+    ('X', [0b101, 0b101, 0b010, 0b101, 0b101]),
+    ('Y', [0b101, 0b101, 0b010, 0b010, 0b010]),
+    ('Z', [0b111, 0b001, 0b010, 0b100, 0b111]),
+];
+
+/// What each point looks like.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointShape {
+    Square,
+    /// A disc as wide as the square would be.
+    Circle,
+    /// A splat fading from the full colour in the middle to transparent,
+    /// reaching one pixel beyond the square.
+    Soft,
+}
+
+impl PointShape {
+    pub const ALL: [PointShape; 3] = [PointShape::Square, PointShape::Circle, PointShape::Soft];
+
+    /// The radius a [`Soft`](Self::Soft) point of side `size` fades over.
+    pub fn soft_radius(size: f32) -> f32 {
+        size / 2.0 + 1.0
+    }
+}
+
+impl Display for PointShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointShape::Square => write!(f, "square"),
+            PointShape::Circle => write!(f, "circle"),
+            PointShape::Soft => write!(f, "soft"),
+        }
+    }
+}
+
+/// How [`render_at`] draws the points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderStyle {
+    pub background: [u8; 4],
+    /// The colour of each point. Points past the end use the last colour,
+    /// so a single colour paints them all.
+    pub colors: Vec<[u8; 4]>,
+    /// Side of the square each point covers, in pixels. Circles are as
+    /// wide, soft points fade out a little further.
+    pub point_size: u32,
+    pub shape: PointShape,
+}
+
+/// The coverage of a soft point at every pixel offset from the pixel its
+/// centre is in, worked out once per render rather than per point.
+struct SoftKernel {
+    reach: i32,
+    /// `(2 * reach + 1)` squared weights, rows top to bottom.
+    weights: Vec<f32>,
+}
+
+impl SoftKernel {
+    fn new(size: u32) -> SoftKernel {
+        let radius = PointShape::soft_radius(size as f32);
+        let reach = radius.ceil() as i32;
+        let weights = (-reach..=reach)
+            .flat_map(|dy| (-reach..=reach).map(move |dx| (dx, dy)))
+            .map(|(dx, dy)| {
+                let falloff = (1.0 - ((dx * dx + dy * dy) as f32).sqrt() / radius).max(0.0);
+                falloff * falloff
+            })
+            .collect();
+        SoftKernel { reach, weights }
+    }
+
+    fn stamp(&self, image: &mut RgbaImage, [x, y]: [f32; 2], [r, g, b, a]: [u8; 4]) {
+        let side = 2 * self.reach + 1;
+        for (i, weight) in self.weights.iter().enumerate() {
+            if *weight > 0.0 {
+                let (dx, dy) = (i as i32 % side - self.reach, i as i32 / side - self.reach);
+                let alpha = (a as f32 * weight).round() as u8;
+                image.blend([x + dx as f32, y + dy as f32], [r, g, b, alpha]);
+            }
+        }
+    }
+}
+
+/// Rasterizes exactly the first `n` of `points` onto a `size` image filled
+/// with the background. Points off the image are skipped.
+///
+/// ```
+/// use sierpinski_triangle_emulator::{
+///     render::{render_at, PointShape, RenderStyle},
+///     ChaosGame,
+/// };
+///
+/// let points = ChaosGame::new([[32.0, 2.0], [2.0, 62.0], [62.0, 62.0]])
+///     .seed(7)
+///     .build()
+///     .take(2000)
+///     .collect::<Vec<_>>();
+/// let style = RenderStyle {
+///     background: [255, 255, 255, 255],
+///     colors: vec![[0, 0, 0, 255]],
+///     point_size: 1,
+///     shape: PointShape::Square,
+/// };
+/// let image = render_at(&points, 1500, &style, (64, 64));
+/// assert_eq!(image.data.len(), 64 * 64 * 4);
+///
+/// // Golden checksum of the pixels; it changes when the rasterizer or the
+/// // orbit for this seed does.
+/// let fnv1a = image.data.iter().fold(0xcbf29ce484222325_u64, |hash, byte| {
+///     (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+/// });
+/// assert_eq!(fnv1a, 14217128054958969644);
+///
+/// // The centre of the gasket stays empty, and no more pixels are dark than
+/// // points were drawn.
+/// assert_eq!(image.pixel(32, 42), [255, 255, 255, 255]);
+/// let dark = image.data.chunks(4).filter(|pixel| pixel[0] == 0).count();
+/// assert!(dark > 500 && dark <= 1500);
+///
+/// // Identical inputs give identical images.
+/// assert_eq!(image, render_at(&points, 1500, &style, (64, 64)));
+///
+/// // A soft point is fully dark in the middle and fades towards its edge.
+/// let soft = RenderStyle {
+///     point_size: 4,
+///     shape: PointShape::Soft,
+///     ..style
+/// };
+/// let image = render_at(&[[10.0, 10.0]], 1, &soft, (32, 32));
+/// assert_eq!(image.pixel(12, 12), [0, 0, 0, 255]);
+/// let edge = image.pixel(14, 12);
+/// assert!(edge[0] > 0 && edge[0] < 255);
+/// assert_eq!(image.pixel(16, 12), [255, 255, 255, 255]);
+/// ```
+pub fn render_at(
+    points: &[[f32; 2]],
+    n: usize,
+    style: &RenderStyle,
+    (width, height): (u32, u32),
+) -> RgbaImage {
+    let mut image = RgbaImage::new(width, height, style.background);
+    render_onto(&mut image, points, n, style);
+    image
+}
+
+/// Like [`render_at`], but drawing over an existing image and leaving its
+/// background as it is.
+///
+/// Every shape is snapped to the pixel grid alike: a point covers the
+/// square of its side whose top left pixel contains it, and circles and
+/// soft points are centred on that square. A point of side 1 lights
+/// exactly the pixel it is in, wherever in it the point lies.
+///
+/// ```
+/// use sierpinski_triangle_emulator::render::{render_at, PointShape, RenderStyle};
+///
+/// for shape in [PointShape::Square, PointShape::Circle] {
+///     let style = RenderStyle {
+///         background: [255, 255, 255, 255],
+///         colors: vec![[0, 0, 0, 255]],
+///         point_size: 1,
+///         shape,
+///     };
+///     for point in [[3.0, 5.0], [3.7, 5.9]] {
+///         let image = render_at(&[point], 1, &style, (8, 8));
+///         let lit = (0..64)
+///             .filter(|i| image.pixel(i % 8, i / 8) != [255, 255, 255, 255])
+///             .collect::<Vec<_>>();
+///         assert_eq!(lit, [5 * 8 + 3], "{} at {:?}", shape, point);
+///     }
+///     // A point of side 3 is centred on the middle of its square.
+///     let style = RenderStyle { point_size: 3, ..style };
+///     let image = render_at(&[[3.6, 5.2]], 1, &style, (8, 8));
+///     assert_eq!(image.pixel(4, 6), [0, 0, 0, 255]);
+///     assert_eq!(image.pixel(6, 6), [255, 255, 255, 255]);
+/// }
+/// ```
+pub fn render_onto(image: &mut RgbaImage, points: &[[f32; 2]], n: usize, style: &RenderStyle) {
+    let last = style.colors.last().copied().unwrap_or([0, 0, 0, 255]);
+    let size = style.point_size;
+    let kernel = (style.shape == PointShape::Soft).then(|| SoftKernel::new(size));
+    let half = size as f32 / 2.0;
+    for (i, [x, y]) in points.iter().take(n).enumerate() {
+        let color = style.colors.get(i).copied().unwrap_or(last);
+        // Flooring before adding the half keeps the centre in the middle
+        // pixel of the square, where adding first could push it a pixel on.
+        let center = [x.floor() + half, y.floor() + half];
+        match (style.shape, &kernel) {
+            (PointShape::Circle, _) => image.fill_circle(center, half, color),
+            (PointShape::Soft, Some(kernel)) => kernel.stamp(image, center, color),
+            _ => image.fill_square([*x, *y], size, color),
+        }
+    }
+}
+
+/// Colours of many points added up per pixel in floating point, for
+/// exports where crowded pixels glow brighter and mix the colours of the
+/// points landing there instead of the last one painting over the rest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accumulation {
+    width: u32,
+    height: u32,
+    /// Per pixel, the red, green and blue sums and the number of points.
+    sums: Vec<[f32; 4]>,
+}
+
+impl Accumulation {
+    /// How much the brightest pixels are lifted over the sparse ones, like
+    /// the gamma of a display.
+    pub const GAMMA: f32 = 2.2;
+
+    /// No points yet over a `width` by `height` image.
+    ///
+    /// # Panics
+    ///
+    /// If its pixels don't fit in `usize`.
+    pub fn new(width: u32, height: u32) -> Accumulation {
+        let pixels = (width as usize)
+            .checked_mul(height as usize)
+            .expect("image too large");
+        Accumulation {
+            width,
+            height,
+            sums: vec![[0.0; 4]; pixels],
+        }
+    }
+
+    /// Adds `color`, its channels from 0 to 1, to every pixel of the `size`
+    /// by `size` square with its top left corner in the pixel containing
+    /// `point`. Pixels off the image are skipped.
+    pub fn add(&mut self, [x, y]: [f32; 2], size: u32, [r, g, b]: [f32; 3]) {
+        if !(x >= 0.0 && y >= 0.0) {
+            return;
+        }
+        let (x, y) = (x as u32, y as u32);
+        for py in y..(y + size).min(self.height) {
+            for px in x..(x + size).min(self.width) {
+                let sum = &mut self.sums[py as usize * self.width as usize + px as usize];
+                *sum = [sum[0] + r, sum[1] + g, sum[2] + b, sum[3] + 1.0];
+            }
+        }
+    }
+
+    /// Tone-maps the sums onto `image`, which must be as large: each pixel
+    /// gets the average colour of its points, over what is there with an
+    /// opacity growing with the logarithm of how many points it holds, so
+    /// the most crowded pixel is opaque and lone points still show.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::render::{Accumulation, RgbaImage};
+    ///
+    /// let mut accumulation = Accumulation::new(4, 1);
+    /// for _ in 0..9 {
+    ///     accumulation.add([0.0, 0.0], 1, [1.0, 0.0, 0.0]);
+    /// }
+    /// accumulation.add([1.0, 0.0], 1, [1.0, 0.0, 0.0]);
+    /// accumulation.add([2.0, 0.0], 1, [1.0, 0.0, 0.0]);
+    /// accumulation.add([2.0, 0.0], 1, [0.0, 0.0, 1.0]);
+    /// let mut image = RgbaImage::new(4, 1, [0, 0, 0, 255]);
+    /// accumulation.tone_map_onto(&mut image);
+    /// assert_eq!(image.pixel(0, 0), [255, 0, 0, 255]);
+    /// let [r, _, _, _] = image.pixel(1, 0);
+    /// assert!(r > 0 && r < 255);
+    /// // Red and blue met in the third pixel.
+    /// let [r, g, b, _] = image.pixel(2, 0);
+    /// assert!(r == b && r > 0 && g == 0);
+    /// assert_eq!(image.pixel(3, 0), [0, 0, 0, 255]);
+    /// ```
+    pub fn tone_map_onto(&self, image: &mut RgbaImage) {
+        let most = self.sums.iter().map(|sum| sum[3]).fold(0.0, f32::max);
+        if most <= 0.0 {
+            return;
+        }
+        let scale = (1.0 + most).ln();
+        for (i, [r, g, b, n]) in self.sums.iter().enumerate() {
+            if *n <= 0.0 {
+                continue;
+            }
+            let alpha = ((1.0 + n).ln() / scale).powf(1.0 / Self::GAMMA);
+            let channel = |sum: f32| ((sum / n).clamp(0.0, 1.0) * 255.0).round() as u8;
+            let width = self.width as usize;
+            let (x, y) = ((i % width) as u32, (i / width) as u32);
+            image.blend(
+                [x as f32, y as f32],
+                [
+                    channel(*r),
+                    channel(*g),
+                    channel(*b),
+                    (alpha * 255.0).round() as u8,
+                ],
+            );
+        }
+    }
+}
+
+/// Points binned into square cells, for drawing points in crowded cells
+/// smaller and in sparse cells larger so the picture has an even weight.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DensityGrid {
+    cell: f32,
+    columns: usize,
+    rows: usize,
+    counts: Vec<u32>,
+    /// Average count over the cells holding any points.
+    mean: f32,
+}
+
+impl DensityGrid {
+    /// The smallest and largest [`scale`](Self::scale).
+    pub const SCALES: (f32, f32) = (0.5, 3.0);
+
+    /// Bins the `points` on a `size` canvas into cells `cell` wide. Points
+    /// off the canvas are left out.
+    pub fn new(
+        [width, height]: [f32; 2],
+        cell: f32,
+        points: impl IntoIterator<Item = [f32; 2]>,
+    ) -> DensityGrid {
+        let cell = cell.max(1.0);
+        let columns = (width / cell).ceil().max(1.0) as usize;
+        let rows = (height / cell).ceil().max(1.0) as usize;
+        let mut grid = DensityGrid {
+            cell,
+            columns,
+            rows,
+            counts: vec![0; columns * rows],
+            mean: 0.0,
+        };
+        for point in points {
+            if let Some(i) = grid.index(point) {
+                grid.counts[i] += 1;
+            }
+        }
+        let (sum, occupied) = grid
+            .counts
+            .iter()
+            .filter(|count| **count > 0)
+            .fold((0, 0), |(sum, occupied), count| (sum + count, occupied + 1));
+        if occupied > 0 {
+            grid.mean = sum as f32 / occupied as f32;
+        }
+        grid
+    }
+
+    /// How many cells hold at least one point.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::render::DensityGrid;
+    ///
+    /// // One pixel cells: two points share the first, one is off the canvas.
+    /// let points = [[0.2, 0.3], [0.7, 0.9], [5.5, 2.0], [20.0, 1.0]];
+    /// assert_eq!(DensityGrid::new([10.0, 10.0], 1.0, points).occupied(), 2);
+    /// ```
+    pub fn occupied(&self) -> usize {
+        self.counts.iter().filter(|count| **count > 0).count()
+    }
+
+    /// The cells' counts, row by row.
+    pub fn counts(&self) -> &[u32] {
+        &self.counts
+    }
+
+    /// The index into [`counts`](Self::counts) of the cell `point` is in,
+    /// `None` off the canvas.
+    pub fn cell(&self, point: [f32; 2]) -> Option<usize> {
+        self.index(point)
+    }
+
+    fn index(&self, [x, y]: [f32; 2]) -> Option<usize> {
+        if !(x >= 0.0 && y >= 0.0) {
+            return None;
+        }
+        let (column, row) = ((x / self.cell) as usize, (y / self.cell) as usize);
+        (column < self.columns && row < self.rows).then(|| row * self.columns + column)
+    }
+
+    /// How much to scale a point at `point`: the square root of how many
+    /// times sparser than average its cell is, within [`SCALES`](Self::SCALES).
+    /// Points off the canvas keep their size.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::render::DensityGrid;
+    ///
+    /// // Four points crowd the top left cell, one sits alone bottom right.
+    /// let points = [[1.0, 1.0], [2.0, 2.0], [3.0, 1.0], [1.0, 3.0], [15.0, 15.0]];
+    /// let grid = DensityGrid::new([16.0, 16.0], 8.0, points);
+    /// assert!(grid.scale([1.0, 1.0]) < 1.0);
+    /// assert!(grid.scale([15.0, 15.0]) > 1.0);
+    /// assert_eq!(grid.scale([40.0, 1.0]), 1.0);
+    /// ```
+    pub fn scale(&self, point: [f32; 2]) -> f32 {
+        match self.index(point).map(|i| self.counts[i]) {
+            Some(count) if count > 0 => {
+                let (min, max) = Self::SCALES;
+                (self.mean / count as f32).sqrt().clamp(min, max)
+            }
+            _ => 1.0,
+        }
+    }
+}
+
+/// How a heatmap spreads cell counts over its ramp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CountScale {
+    /// In proportion to the count.
+    #[default]
+    Linear,
+    /// By the logarithm of the count, which lifts the sparse cells.
+    Log,
+}
+
+impl CountScale {
+    pub const ALL: [CountScale; 2] = [CountScale::Linear, CountScale::Log];
+}
+
+impl Display for CountScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CountScale::Linear => write!(f, "linear"),
+            CountScale::Log => write!(f, "log"),
+        }
+    }
+}
+
+/// The level from 0 to 1 of each of `counts`, for colouring a heatmap. The
+/// count at `percentile` (0 to 100) among the cells holding any points maps
+/// to 1, and so does every count above it, so a few crowded cells can't
+/// wash out the rest. Below it counts map by `scale`, the logarithmic one
+/// taking 1 plus the count. Empty cells are 0.
+///
+/// ```
+/// use sierpinski_triangle_emulator::render::{heat_levels, CountScale};
+///
+/// // Nothing counted yet maps to nothing.
+/// assert_eq!(heat_levels(&[0; 4], CountScale::Linear, 99.0), [0.0; 4]);
+/// assert_eq!(heat_levels(&[0; 4], CountScale::Log, 99.0), [0.0; 4]);
+///
+/// // A single hot cell is at the top, whatever the scale and percentile.
+/// let hot = [0, 0, 7, 0];
+/// for scale in CountScale::ALL {
+///     assert_eq!(heat_levels(&hot, scale, 50.0), [0.0, 0.0, 1.0, 0.0]);
+/// }
+///
+/// // Linear is proportional up to the clip; log lifts the sparse cells.
+/// let grid = [0, 1, 2, 4, 8];
+/// assert_eq!(heat_levels(&grid, CountScale::Linear, 100.0), [0.0, 0.125, 0.25, 0.5, 1.0]);
+/// let log = heat_levels(&grid, CountScale::Log, 100.0);
+/// assert_eq!((log[0], log[4]), (0.0, 1.0));
+/// assert!(log[1] > 0.3 && log[1] < log[2] && log[2] < log[3]);
+///
+/// // One ultra-dense cell among a hundred sparse ones: at the 100th
+/// // percentile it washes them out, at the 99th it is clipped instead.
+/// let mut skewed = vec![2; 100];
+/// skewed.push(10_000);
+/// assert_eq!(heat_levels(&skewed, CountScale::Linear, 100.0)[0], 0.0002);
+/// let clipped = heat_levels(&skewed, CountScale::Linear, 99.0);
+/// assert_eq!((clipped[0], clipped[100]), (1.0, 1.0));
+/// ```
+pub fn heat_levels(counts: &[u32], scale: CountScale, percentile: f32) -> Vec<f32> {
+    let mut occupied = counts
+        .iter()
+        .copied()
+        .filter(|count| *count > 0)
+        .collect::<Vec<_>>();
+    occupied.sort_unstable();
+    // Nearest rank: the smallest count at least `percentile` of the cells
+    // don't exceed.
+    let rank = (percentile.clamp(0.0, 100.0) / 100.0 * occupied.len() as f32).ceil() as usize;
+    let clip = match occupied.get(rank.clamp(1, occupied.len().max(1)) - 1) {
+        Some(clip) => *clip as f32,
+        None => return vec![0.0; counts.len()],
+    };
+    let map = |count: f32| match scale {
+        CountScale::Linear => count,
+        CountScale::Log => count.ln_1p(),
+    };
+    counts
+        .iter()
+        .map(|count| (map(*count as f32) / map(clip)).min(1.0))
+        .collect()
+}
+
+/// The indices of points binned into square cells, for finding the points
+/// around a spot without going through all of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointGrid {
+    cell: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl PointGrid {
+    /// An empty grid over a `size` canvas with cells `cell` wide.
+    pub fn new([width, height]: [f32; 2], cell: f32) -> PointGrid {
+        let cell = cell.max(1.0);
+        let columns = (width / cell).ceil().max(1.0) as usize;
+        let rows = (height / cell).ceil().max(1.0) as usize;
+        PointGrid {
+            cell,
+            columns,
+            rows,
+            cells: vec![vec![]; columns * rows],
+        }
+    }
+
+    /// Files point `index` at `point`. Points off the canvas are left out.
+    pub fn insert(&mut self, index: usize, [x, y]: [f32; 2]) {
+        if !(x >= 0.0 && y >= 0.0) {
+            return;
+        }
+        let (column, row) = ((x / self.cell) as usize, (y / self.cell) as usize);
+        if column < self.columns && row < self.rows {
+            self.cells[row * self.columns + column].push(index);
+        }
+    }
+
+    /// The indices in the cells touching the square around the circle of
+    /// `radius` about `center`: every point in the circle, and some more
+    /// near it, in no particular order.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::render::PointGrid;
+    ///
+    /// let points = [[5.0, 5.0], [12.0, 6.0], [90.0, 90.0], [55.0, 10.0]];
+    /// let mut grid = PointGrid::new([100.0, 100.0], 10.0);
+    /// for (i, p) in points.iter().enumerate() {
+    ///     grid.insert(i, *p);
+    /// }
+    /// let mut near = grid.near([8.0, 8.0], 5.0).collect::<Vec<_>>();
+    /// near.sort();
+    /// assert_eq!(near, [0, 1]);
+    /// assert_eq!(grid.near([-50.0, -50.0], 5.0).count(), 0);
+    /// ```
+    pub fn near(&self, [x, y]: [f32; 2], radius: f32) -> impl Iterator<Item = usize> + '_ {
+        let span = |from: f32, to: f32, len: usize| {
+            let last = len as f32 - 1.0;
+            let from = (from / self.cell).floor().clamp(0.0, last) as usize;
+            let to = (to / self.cell).floor().clamp(0.0, last) as usize;
+            from..=to
+        };
+        // A square wholly off the grid touches no cell.
+        let off = x + radius < 0.0
+            || y + radius < 0.0
+            || x - radius >= self.columns as f32 * self.cell
+            || y - radius >= self.rows as f32 * self.cell;
+        let columns = span(x - radius, x + radius, self.columns);
+        let rows = span(y - radius, y + radius, self.rows).filter(move |_| !off);
+        rows.flat_map(move |row| {
+            columns
+                .clone()
+                .flat_map(move |column| self.cells[row * self.columns + column].iter().copied())
+        })
+    }
+}
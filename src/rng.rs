@@ -0,0 +1,195 @@
+//! The random number generators an orbit can draw its picks from.
+//!
+//! The chaos game only needs uniform draws, so any decent generator gives
+//! the same attractor; switching between them shows just that. Every
+//! generator is seeded from the same `u64` through
+//! [`SeedableRng::seed_from_u64`], so a seed means the same for each of
+//! them, though each turns it into a sequence of its own.
+
+use std::fmt::{self, Display};
+
+use rand::{
+    rngs::{SmallRng, StdRng},
+    RngCore, SeedableRng,
+};
+use serde::{Deserialize, Serialize};
+
+/// Which generator an orbit draws from.
+///
+/// ```
+/// use sierpinski_triangle_emulator::{rng::Generator, ChaosGame};
+///
+/// let game = ChaosGame::new([[0.0, 0.0], [100.0, 0.0], [0.0, 100.0]]).seed(4);
+/// for generator in Generator::ALL {
+///     let game = game.clone().generator(generator);
+///     let mut orbit = game.build();
+///     let mut counts = [0; 3];
+///     for _ in 0..30_000 {
+///         counts[orbit.step().unwrap().vertex] += 1;
+///     }
+///     // Every generator picks each vertex about a third of the time...
+///     assert!(counts.iter().all(|n| (9_000..11_000).contains(n)), "{}", generator);
+///     // ...and replays the same orbit from the same seed.
+///     assert_eq!(game.points(100), game.points(100));
+/// }
+/// let pcg = game.clone().generator(Generator::Pcg);
+/// assert_ne!(pcg.points(10), game.points(10));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Generator {
+    /// ChaCha with 12 rounds, `rand`'s standard generator.
+    #[default]
+    ChaCha,
+    /// Xoshiro256++, `rand`'s small fast generator (Xoshiro128++ on 32-bit
+    /// targets).
+    Xoshiro,
+    /// PCG32, a 64-bit linear congruential generator with a permuted
+    /// 32-bit output.
+    Pcg,
+}
+
+impl Generator {
+    pub const ALL: [Generator; 3] = [Generator::ChaCha, Generator::Xoshiro, Generator::Pcg];
+}
+
+impl Display for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Generator::ChaCha => write!(f, "ChaCha12"),
+            Generator::Xoshiro => write!(f, "Xoshiro256++"),
+            Generator::Pcg => write!(f, "PCG32"),
+        }
+    }
+}
+
+/// PCG32 (XSH RR), as in Melissa O'Neill's reference implementation.
+///
+/// ```
+/// use rand::{RngCore, SeedableRng};
+/// use sierpinski_triangle_emulator::rng::Pcg32;
+///
+/// // The first output of the reference demo, seeded with 42 on stream 54.
+/// let mut rng = Pcg32::new(42, 54);
+/// assert_eq!(rng.next_u32(), 0xa15c02b7);
+/// assert_eq!(Pcg32::seed_from_u64(7).next_u64(), Pcg32::seed_from_u64(7).next_u64());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+
+impl Pcg32 {
+    /// The generator at `state` on the sequence selected by `stream`.
+    pub fn new(state: u64, stream: u64) -> Pcg32 {
+        let mut pcg = Pcg32 {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        pcg.step();
+        pcg.state = pcg.state.wrapping_add(state);
+        pcg.step();
+        pcg
+    }
+
+    fn step(&mut self) {
+        self.state = self
+            .state
+            .wrapping_mul(MULTIPLIER)
+            .wrapping_add(self.increment);
+    }
+}
+
+impl RngCore for Pcg32 {
+    fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.step();
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let low = self.next_u32() as u64;
+        (self.next_u32() as u64) << 32 | low
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for Pcg32 {
+    /// The state followed by the stream, little endian.
+    type Seed = [u8; 16];
+
+    fn from_seed(seed: [u8; 16]) -> Pcg32 {
+        let (state, stream) = seed.split_at(8);
+        Pcg32::new(
+            u64::from_le_bytes(state.try_into().unwrap()),
+            u64::from_le_bytes(stream.try_into().unwrap()),
+        )
+    }
+}
+
+/// One of the generators, dispatched by hand so orbits stay `Clone`.
+/// ChaCha is boxed, its state being ten times that of the others.
+#[derive(Debug, Clone)]
+pub(crate) enum Source {
+    ChaCha(Box<StdRng>),
+    Xoshiro(SmallRng),
+    Pcg(Pcg32),
+}
+
+impl Source {
+    /// `generator` seeded with `seed`, or from the operating system without
+    /// one.
+    pub(crate) fn new(generator: Generator, seed: Option<u64>) -> Source {
+        fn seeded<R: SeedableRng>(seed: Option<u64>) -> R {
+            match seed {
+                Some(seed) => R::seed_from_u64(seed),
+                None => R::from_entropy(),
+            }
+        }
+        match generator {
+            Generator::ChaCha => Source::ChaCha(Box::new(seeded(seed))),
+            Generator::Xoshiro => Source::Xoshiro(seeded(seed)),
+            Generator::Pcg => Source::Pcg(seeded(seed)),
+        }
+    }
+
+    fn inner(&mut self) -> &mut dyn RngCore {
+        match self {
+            Source::ChaCha(rng) => rng.as_mut(),
+            Source::Xoshiro(rng) => rng,
+            Source::Pcg(rng) => rng,
+        }
+    }
+}
+
+impl RngCore for Source {
+    fn next_u32(&mut self) -> u32 {
+        self.inner().next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.inner().next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.inner().fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.inner().try_fill_bytes(dest)
+    }
+}
@@ -0,0 +1,146 @@
+//! Plain-text scripts of editing actions, one per line:
+//!
+//! ```text
+//! # comments and blank lines are ignored
+//! add 100,500
+//! add 500,500
+//! add 300,100
+//! delay 200
+//! max_iter 5000
+//! cur_iter 5000
+//! export out.png
+//! ```
+//!
+//! `delay` sets the pause before each following step, in milliseconds.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use iced::Point;
+use sierpinski_triangle_emulator::Error;
+
+use crate::Message;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Add(Point),
+    Remove,
+    MaxIter(i32),
+    CurIter(i32),
+    Export(PathBuf),
+    Delay(Duration),
+}
+
+impl Step {
+    /// The message performing this step, `None` for `delay`.
+    pub fn message(&self) -> Option<Message> {
+        match self {
+            Step::Add(point) => Some(Message::AddFixPoint(*point)),
+            Step::Remove => Some(Message::RemoveFixPoint),
+            Step::MaxIter(max_iter) => Some(Message::SetMaxIter(*max_iter)),
+            Step::CurIter(cur_iter) => Some(Message::SetCurIter(*cur_iter)),
+            Step::Export(path) => Some(Message::ExportPng(path.clone())),
+            Step::Delay(_) => None,
+        }
+    }
+
+    /// The step that replays `message`, if it is one a script can express.
+    pub fn from_message(message: &Message) -> Option<Step> {
+        match message {
+            Message::AddFixPoint(point) => Some(Step::Add(*point)),
+            Message::RemoveFixPoint => Some(Step::Remove),
+            Message::SetMaxIter(max_iter) => Some(Step::MaxIter(*max_iter)),
+            Message::SetCurIter(cur_iter) => Some(Step::CurIter(*cur_iter)),
+            Message::ExportPng(path) => Some(Step::Export(path.clone())),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Add(point) => write!(f, "add {},{}", point.x, point.y),
+            Step::Remove => write!(f, "remove"),
+            Step::MaxIter(max_iter) => write!(f, "max_iter {}", max_iter),
+            Step::CurIter(cur_iter) => write!(f, "cur_iter {}", cur_iter),
+            Step::Export(path) => write!(f, "export {}", path.display()),
+            Step::Delay(delay) => write!(f, "delay {}", delay.as_millis()),
+        }
+    }
+}
+
+/// Parses a whole script, so nothing runs unless every line is valid.
+pub fn parse(text: &str) -> Result<Vec<Step>, Error> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line, text)| parse_step(text).map_err(|message| Error::Parse { line, message }))
+        .collect()
+}
+
+/// Reads and parses the script at `path`.
+pub fn load(path: &Path) -> Result<Vec<Step>, Error> {
+    parse(&std::fs::read_to_string(path)?)
+}
+
+fn parse_step(line: &str) -> Result<Step, String> {
+    let (command, argument) = match line.split_once(char::is_whitespace) {
+        Some((command, argument)) => (command, argument.trim()),
+        None => (line, ""),
+    };
+    match command {
+        "add" => Ok(Step::Add(parse_point(argument)?)),
+        "remove" => Ok(Step::Remove),
+        "max_iter" => Ok(Step::MaxIter(parse_number(argument)?)),
+        "cur_iter" => Ok(Step::CurIter(parse_number(argument)?)),
+        "export" if !argument.is_empty() => Ok(Step::Export(PathBuf::from(argument))),
+        "export" => Err("expected `export <file>`".to_string()),
+        "delay" => Ok(Step::Delay(Duration::from_millis(parse_number(argument)?))),
+        _ => Err(format!("unknown command `{}`", command)),
+    }
+}
+
+/// Parses `x,y` as scripts write a vertex, which is also what the
+/// coordinate entry takes.
+pub fn parse_point(text: &str) -> Result<Point, String> {
+    let (x, y) = text
+        .split_once(',')
+        .ok_or_else(|| format!("expected `x,y`, got `{}`", text.trim()))?;
+    Ok(Point::new(parse_number(x)?, parse_number(y)?))
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("`{}` is not a valid number", text.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_errors_count_comments_and_blank_lines() {
+        let script = "# a triangle\nadd 100,500\n\nadd 500,x\n";
+        match parse(script).unwrap_err() {
+            Error::Parse { line, message } => {
+                assert_eq!(line, 4);
+                assert!(message.contains('x'), "{}", message);
+            }
+            e => panic!("not a parse error: {:?}", e),
+        }
+        let e = parse("jump 3").unwrap_err();
+        assert!(e.to_string().starts_with("line 1: "), "{}", e);
+    }
+
+    #[test]
+    fn missing_scripts_are_io_errors() {
+        let missing = load(Path::new("/nonexistent/script.txt")).unwrap_err();
+        assert!(matches!(missing, Error::Io(_)), "{:?}", missing);
+    }
+}
@@ -0,0 +1,204 @@
+//! The vertex picks of an orbit as a compact binary file, so a run can be
+//! reproduced exactly without relying on the random number generator
+//! staying the same across versions.
+//!
+//! A file is the magic bytes `SCHS`, a format version byte, the vertex
+//! count and the rule (0 for [`Rule::Vertex`], 1 for [`Rule::Midpoint`]),
+//! followed by one byte per pick: the vertex, and with the midpoint rule
+//! the partner right after it.
+
+use std::fmt;
+
+use crate::{ChaosGame, Rule, Step};
+
+const MAGIC: &[u8; 4] = b"SCHS";
+
+const VERSION: u8 = 1;
+
+/// The picks of the points of one orbit in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sequence {
+    /// How many vertices the picks index into, at most 256.
+    pub vertex_count: usize,
+    pub rule: Rule,
+    /// Each step's vertex and, with the midpoint rule, its partner.
+    pub picks: Vec<(u8, Option<u8>)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Not a sequence file, or a version this build can't read.
+    Format(String),
+    /// The sequence picks among a different number of vertices.
+    VertexCount { expected: usize, found: usize },
+    /// The picks can't be stored in a byte each.
+    TooManyVertices(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Format(reason) => write!(f, "invalid sequence file: {}", reason),
+            Error::VertexCount { expected, found } => write!(
+                f,
+                "the sequence picks among {} vertices but {} are enabled",
+                expected, found
+            ),
+            Error::TooManyVertices(count) => write!(
+                f,
+                "{} vertices are too many to store, a sequence holds at most 256",
+                count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Sequence {
+    /// The vertex and partner `picks` of an orbit among `vertex_count`
+    /// vertices. Partners are dropped with [`Rule::Vertex`], and a missing
+    /// one is the vertex itself with [`Rule::Midpoint`].
+    pub fn new(
+        vertex_count: usize,
+        rule: Rule,
+        picks: impl IntoIterator<Item = (usize, Option<usize>)>,
+    ) -> Result<Sequence, Error> {
+        if vertex_count > 256 {
+            return Err(Error::TooManyVertices(vertex_count));
+        }
+        Ok(Sequence {
+            vertex_count,
+            rule,
+            picks: picks
+                .into_iter()
+                .map(|(vertex, partner)| {
+                    let partner = match rule {
+                        Rule::Vertex => None,
+                        Rule::Midpoint => Some(partner.unwrap_or(vertex) as u8),
+                    };
+                    (vertex as u8, partner)
+                })
+                .collect(),
+        })
+    }
+
+    /// The file contents.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::{sequence::Sequence, ChaosGame, Rule};
+    ///
+    /// let game = ChaosGame::new([[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]])
+    ///     .rule(Rule::Midpoint)
+    ///     .seed(4);
+    /// let mut orbit = game.build();
+    /// let steps = (0..100).map(|_| orbit.step().unwrap()).collect::<Vec<_>>();
+    /// let picks = steps.iter().map(|step| (step.vertex, step.partner));
+    /// let sequence = Sequence::new(3, Rule::Midpoint, picks).unwrap();
+    ///
+    /// let bytes = sequence.encode();
+    /// assert_eq!(&bytes[..7], b"SCHS\x01\x03\x01");
+    /// assert_eq!(bytes.len(), 7 + 2 * 100);
+    /// assert_eq!(Sequence::decode(&bytes), Ok(sequence.clone()));
+    ///
+    /// // Replaying the decoded picks lands on the very same points.
+    /// let decoded = Sequence::decode(&bytes).unwrap();
+    /// assert_eq!(decoded.replay(&game).unwrap(), steps);
+    ///
+    /// assert!(Sequence::decode(b"PNG").is_err());
+    /// assert!(Sequence::decode(&bytes[..8]).is_err());
+    /// ```
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        // 256 vertices wrap to 0, which decodes back to 256.
+        bytes.push(self.vertex_count as u8);
+        bytes.push(match self.rule {
+            Rule::Vertex => 0,
+            Rule::Midpoint => 1,
+        });
+        for (vertex, partner) in &self.picks {
+            bytes.push(*vertex);
+            bytes.extend(partner);
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Sequence, Error> {
+        let format = |reason: &str| Err(Error::Format(reason.to_string()));
+        let (header, body) = match bytes.strip_prefix(MAGIC) {
+            Some(rest) if rest.len() >= 3 => rest.split_at(3),
+            _ => return format("it does not start with a sequence header"),
+        };
+        if header[0] != VERSION {
+            return format(&format!("unknown format version {}", header[0]));
+        }
+        let vertex_count = match header[1] {
+            0 => 256,
+            count => count as usize,
+        };
+        let rule = match header[2] {
+            0 => Rule::Vertex,
+            1 => Rule::Midpoint,
+            rule => return format(&format!("unknown rule {}", rule)),
+        };
+        if body.iter().any(|pick| *pick as usize >= vertex_count) {
+            return format("a pick is past the vertex count");
+        }
+        let picks = match rule {
+            Rule::Vertex => body.iter().map(|vertex| (*vertex, None)).collect(),
+            Rule::Midpoint if body.len() % 2 != 0 => {
+                return format("a midpoint pick is missing its partner")
+            }
+            Rule::Midpoint => body
+                .chunks(2)
+                .map(|pair| (pair[0], Some(pair[1])))
+                .collect(),
+        };
+        Ok(Sequence {
+            vertex_count,
+            rule,
+            picks,
+        })
+    }
+
+    /// The steps of `game` moving by these picks with this rule. The game
+    /// must have exactly as many vertices as the picks index into.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::{
+    ///     sequence::{Error, Sequence},
+    ///     ChaosGame, Rule,
+    /// };
+    ///
+    /// let sequence = Sequence {
+    ///     vertex_count: 3,
+    ///     rule: Rule::Vertex,
+    ///     picks: vec![(1, None), (2, None)],
+    /// };
+    /// let square = ChaosGame::new([[0.0, 0.0], [4.0, 0.0], [4.0, 4.0], [0.0, 4.0]]);
+    /// assert_eq!(
+    ///     sequence.replay(&square),
+    ///     Err(Error::VertexCount { expected: 3, found: 4 })
+    /// );
+    ///
+    /// let triangle = ChaosGame::new([[0.0, 0.0], [4.0, 0.0], [0.0, 4.0]]);
+    /// let points = sequence.replay(&triangle).unwrap();
+    /// assert_eq!(points[0].point, [2.0, 0.0]);
+    /// assert_eq!(points[1].point, [1.0, 2.0]);
+    /// ```
+    pub fn replay(&self, game: &ChaosGame) -> Result<Vec<Step>, Error> {
+        let found = game.vertices().len();
+        if found != self.vertex_count {
+            return Err(Error::VertexCount {
+                expected: self.vertex_count,
+                found,
+            });
+        }
+        Ok(game.clone().rule(self.rule).replay(
+            self.picks
+                .iter()
+                .map(|(vertex, partner)| (*vertex as usize, partner.map(usize::from))),
+        ))
+    }
+}
@@ -0,0 +1,404 @@
+//! Saving and loading the editing state as versioned JSON.
+//!
+//! Every file carries a `version`. Loading upgrades older versions to the
+//! current layout step by step in [`migrate`] before deserializing, so a
+//! format change only needs a new arm there. Saving always writes
+//! [`CURRENT_VERSION`].
+
+use std::{fs, ops::Not, path::Path};
+
+use iced::Color;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use sierpinski_triangle_emulator::{
+    render::{PointShape, RenderStyle},
+    rng::Generator,
+    ChaosGame, Error, Restriction, Rule, Selection,
+};
+
+use crate::{
+    export::rgba8, palette, preset::Circle, BorderStyle, OrbitStart, OutOfBounds, PointColoring,
+    VertexMarker, DEFAULT_QUOTA,
+};
+
+pub const CURRENT_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub version: u64,
+    pub fix_points: Vec<[f32; 2]>,
+    pub max_iter: i32,
+    pub cur_iter: i32,
+    pub vertex_marker: VertexMarker,
+    pub point_coloring: PointColoring,
+    pub sector_count: u8,
+    pub weights: Vec<f32>,
+    pub ratio: f32,
+    pub rule: Rule,
+    /// Parallel to `fix_points`, `true` for vertices left out of the game.
+    pub disabled: Vec<bool>,
+    /// Parallel to `fix_points`, `true` for vertices that can't be moved or
+    /// removed.
+    pub locked: Vec<bool>,
+    /// Parallel to `fix_points`, the anchor of each vertex that has one.
+    pub anchors: Vec<Option<[f32; 2]>>,
+    /// Parallel to `fix_points`, the palette colour picked for each vertex;
+    /// `None` and missing entries follow palette order.
+    pub vertex_colors: Vec<Option<usize>>,
+    pub anchor_blend: f32,
+    pub restriction: Restriction,
+    /// By vertex index, including the disabled ones.
+    pub selection: Selection,
+    pub rotation: f32,
+    /// Standard deviation of the offset added to each point, in pixels.
+    pub jitter: f32,
+    pub seed: u64,
+    pub generator: Generator,
+    /// Orbits generated side by side, the n-th from `seed + n`.
+    pub seed_count: u8,
+    pub orbit_start: OrbitStart,
+    /// Balanced sampling, and its quota per 1000 points even when off.
+    pub balanced: bool,
+    pub quota: u32,
+    pub out_of_bounds: OutOfBounds,
+    /// Whether the view zooms to the attractor's bounding box.
+    pub auto_frame: bool,
+    /// RGBA, each channel from 0 to 1.
+    pub background: [f32; 4],
+    pub foreground: [f32; 4],
+    pub border: BorderStyle,
+    /// The controls of the circle generator, whether or not the vertices
+    /// still come from it.
+    pub circle: Circle,
+    /// The parameter sets stored in slots A and B.
+    pub slots: [Option<Slot>; 2],
+    /// The slot last stored into or flipped to.
+    pub active_slot: Option<usize>,
+}
+
+/// A set of parameters to flip to and back for comparison: everything the
+/// points depend on but the vertices, and their colours.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Slot {
+    pub ratio: f32,
+    pub rule: Rule,
+    pub restriction: Restriction,
+    #[serde(default)]
+    pub selection: Selection,
+    pub rotation: f32,
+    /// By vertex index; vertices past the end weigh 1.
+    pub weights: Vec<f32>,
+    pub anchor_blend: f32,
+    pub jitter: f32,
+    pub vertex_colors: Vec<Option<usize>>,
+    pub point_coloring: PointColoring,
+    /// RGBA, each channel from 0 to 1.
+    pub background: [f32; 4],
+    pub foreground: [f32; 4],
+}
+
+impl Default for Session {
+    fn default() -> Session {
+        Session {
+            version: CURRENT_VERSION,
+            fix_points: vec![],
+            max_iter: 0,
+            cur_iter: 0,
+            vertex_marker: VertexMarker::FilledCircle,
+            point_coloring: PointColoring::Uniform,
+            sector_count: 3,
+            weights: vec![],
+            ratio: 0.5,
+            rule: Rule::Vertex,
+            disabled: vec![],
+            locked: vec![],
+            anchors: vec![],
+            vertex_colors: vec![],
+            anchor_blend: 0.5,
+            restriction: Restriction::None,
+            selection: Selection::Random,
+            rotation: 0.0,
+            jitter: 0.0,
+            seed: 0,
+            generator: Generator::ChaCha,
+            seed_count: 1,
+            orbit_start: OrbitStart::Scattered,
+            balanced: false,
+            quota: DEFAULT_QUOTA,
+            out_of_bounds: OutOfBounds::Clip,
+            auto_frame: false,
+            background: [1.0, 1.0, 1.0, 1.0],
+            foreground: [0.0, 0.0, 0.0, 1.0],
+            border: BorderStyle::default(),
+            circle: Circle::default(),
+            slots: [None, None],
+            active_slot: None,
+        }
+    }
+}
+
+/// A JSON error with where it is, or without when it is about the data
+/// rather than the syntax.
+fn json_error(e: serde_json::Error) -> Error {
+    match e.line() {
+        0 => Error::InvalidConfig(format!("invalid session file: {}", e)),
+        line => {
+            // serde_json ends its message with the position, which the
+            // error carries by itself.
+            let text = e.to_string();
+            let message = text.rsplit_once(" at line ").map_or(&text[..], |(m, _)| m);
+            Error::Parse {
+                line,
+                message: format!("invalid session file, column {}: {}", e.column(), message),
+            }
+        }
+    }
+}
+
+impl Session {
+    pub fn load(path: &Path) -> Result<Session, Error> {
+        Session::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// The indices of the vertices taking part in the game.
+    pub fn enabled(&self) -> Vec<usize> {
+        (0..self.fix_points.len())
+            .filter(|i| !self.disabled.get(*i).copied().unwrap_or(false))
+            .collect()
+    }
+
+    /// How renders made without the window draw the points: one pixel each,
+    /// in the foreground colour on the background.
+    pub fn style(&self) -> RenderStyle {
+        let [r, g, b, a] = self.background;
+        let [fr, fg, fb, fa] = self.foreground;
+        RenderStyle {
+            background: rgba8(Color::from_rgba(r, g, b, a)),
+            colors: vec![rgba8(Color::from_rgba(fr, fg, fb, fa))],
+            point_size: 1,
+            shape: PointShape::Square,
+        }
+    }
+
+    /// The chaos game of the first orbit, over the enabled vertices.
+    pub fn game(&self) -> ChaosGame {
+        let enabled = self.enabled();
+        let rule = match self.rule {
+            Rule::Midpoint if enabled.len() < 2 => Rule::Vertex,
+            rule => rule,
+        };
+        ChaosGame::new(enabled.iter().map(|i| self.fix_points[*i]))
+            .ratio(self.ratio)
+            .rule(rule)
+            .weights(
+                enabled
+                    .iter()
+                    .map(|i| self.weights.get(*i).copied().unwrap_or(1.0)),
+            )
+            .restriction(self.restriction)
+            .selection(self.selection.renumbered(&enabled))
+            .rotation(self.rotation)
+            .jitter(self.jitter)
+            .anchors(
+                enabled
+                    .iter()
+                    .map(|i| self.anchors.get(*i).copied().flatten()),
+            )
+            .anchor_blend(self.anchor_blend)
+            .quota(if self.balanced { self.quota } else { 0 })
+            .seed(self.seed)
+            .generator(self.generator)
+    }
+
+    /// Drops the vertices at `indices` together with their weights, enabled
+    /// flags, locks, anchors and colours.
+    pub fn remove_vertices(&mut self, indices: &[usize]) {
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+        if indices.is_empty().not() {
+            palette::pin_vertex_colors(&mut self.vertex_colors, self.fix_points.len());
+        }
+        for &i in indices.iter().rev() {
+            if i < self.fix_points.len() {
+                self.fix_points.remove(i);
+            }
+            if i < self.weights.len() {
+                self.weights.remove(i);
+            }
+            if i < self.disabled.len() {
+                self.disabled.remove(i);
+            }
+            if i < self.locked.len() {
+                self.locked.remove(i);
+            }
+            if i < self.anchors.len() {
+                self.anchors.remove(i);
+            }
+            if i < self.vertex_colors.len() {
+                self.vertex_colors.remove(i);
+            }
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Session, Error> {
+        let value: Value = serde_json::from_str(json).map_err(json_error)?;
+        let version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .filter(|version| *version > 0)
+            .ok_or_else(|| {
+                Error::InvalidConfig("invalid session file: no version field".to_string())
+            })?;
+        if version > CURRENT_VERSION {
+            return Err(Error::InvalidConfig(format!(
+                "session version {} is newer than supported version {}, please upgrade",
+                version, CURRENT_VERSION
+            )));
+        }
+        let mut session: Session =
+            serde_json::from_value(migrate(value, version)).map_err(json_error)?;
+        session.version = CURRENT_VERSION;
+        Ok(session)
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(&Session {
+            version: CURRENT_VERSION,
+            ..self.clone()
+        })
+        .map_err(json_error)
+    }
+}
+
+/// Upgrades a session file of `version` to the layout of [`CURRENT_VERSION`].
+///
+/// Version 1 is the only format there has been, so there is nothing to
+/// upgrade yet. Every field since the first release was added with a serde
+/// default, and version 1 files of any age load as they are; the fixtures
+/// under `tests/fixtures` are two of them. A field that is renamed or changes
+/// meaning needs a new version and a step here, as `if version < N { ... }`,
+/// with a fixture of the old layout.
+fn migrate(value: Value, version: u64) -> Value {
+    debug_assert!(version <= CURRENT_VERSION);
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The layout the first release of sessions wrote: the vertices, the
+    /// iterations and the marker and colouring only. Both fixtures are
+    /// version 1, the only version there is.
+    const FIRST: &str = include_str!("../tests/fixtures/session-v1-first.json");
+    /// The layout from before the manual selection, vertex locks and
+    /// auto-frame were added, with a slot of its own.
+    const SLOTS: &str = include_str!("../tests/fixtures/session-v1-slots.json");
+
+    #[test]
+    fn first_layout_loads_with_defaults_for_later_fields() {
+        let session = Session::from_json(FIRST).unwrap();
+        assert_eq!(session.fix_points.len(), 3);
+        assert_eq!(session.fix_points[1], [40.0, 560.0]);
+        assert_eq!((session.max_iter, session.cur_iter), (20_000, 15_000));
+        assert_eq!(session.vertex_marker, VertexMarker::Ring);
+        assert_eq!(session.point_coloring, PointColoring::Sector);
+        assert_eq!(session.ratio, 0.5);
+        assert_eq!(session.rule, Rule::Vertex);
+        assert_eq!(session.enabled(), [0, 1, 2]);
+        assert!(session.locked.is_empty());
+        assert_eq!(session.selection, Selection::Random);
+        assert_eq!(session.seed_count, 1);
+
+        let points = session.game().try_points(1_000).unwrap();
+        assert_eq!(points.len(), 1_000);
+        assert!(points
+            .iter()
+            .all(|[x, y]| (40.0..=560.0).contains(x) && (40.0..=560.0).contains(y)));
+    }
+
+    #[test]
+    fn slots_layout_keeps_its_parameters() {
+        let session = Session::from_json(SLOTS).unwrap();
+        assert_eq!(session.fix_points.len(), 4);
+        assert_eq!(session.enabled(), [0, 1, 2]);
+        assert_eq!(session.weights, [1.0, 2.0, 1.0, 1.0]);
+        assert_eq!(session.anchors[1], Some([520.0, 80.0]));
+        assert_eq!(session.vertex_colors[1], Some(3));
+        assert_eq!(session.restriction, Restriction::NoRepeat);
+        assert_eq!(session.rotation, 12.0);
+        assert_eq!((session.seed, session.generator), (42, Generator::Pcg));
+        assert_eq!(session.seed_count, 2);
+        assert_eq!(session.out_of_bounds, OutOfBounds::Clamp);
+        assert_eq!(session.circle.count, 5);
+        assert_eq!(session.active_slot, Some(0));
+        let slot = session.slots[0].as_ref().unwrap();
+        assert_eq!(
+            (slot.ratio, slot.selection.clone()),
+            (0.45, Selection::Random)
+        );
+        assert!(session.slots[1].is_none());
+        assert!(session.locked.is_empty());
+        assert!(session.auto_frame.not());
+
+        // The disabled vertex is left out of the game.
+        assert_eq!(session.game().try_points(500).unwrap().len(), 500);
+    }
+
+    #[test]
+    fn saving_writes_the_current_version() {
+        for fixture in [FIRST, SLOTS] {
+            let json = Session::from_json(fixture).unwrap().to_json().unwrap();
+            let value: Value = serde_json::from_str(&json).unwrap();
+            assert_eq!(value["version"], CURRENT_VERSION);
+            assert!(value.get("locked").is_some());
+            let again = Session::from_json(&json).unwrap();
+            assert_eq!(again.to_json().unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn newer_and_unversioned_files_are_rejected() {
+        let newer = FIRST.replacen("\"version\": 1", "\"version\": 99", 1);
+        let e = Session::from_json(&newer).unwrap_err().to_string();
+        assert!(e.contains("version 99 is newer"), "{}", e);
+        let unversioned = FIRST.replacen("\"version\": 1,", "", 1);
+        let e = Session::from_json(&unversioned).unwrap_err().to_string();
+        assert!(e.contains("no version field"), "{}", e);
+    }
+
+    #[test]
+    fn removed_vertices_take_their_locks_along() {
+        let mut session = Session::from_json(FIRST).unwrap();
+        session.locked = vec![false, true, false];
+        session.remove_vertices(&[0]);
+        assert_eq!(session.fix_points.len(), 2);
+        assert_eq!(session.locked, [true, false]);
+    }
+
+    #[test]
+    fn file_errors_say_what_went_wrong() {
+        let missing = Session::load(Path::new("/nonexistent/session.json")).unwrap_err();
+        assert!(matches!(missing, Error::Io(_)), "{:?}", missing);
+
+        // Broken syntax points at the line...
+        let syntax = Session::from_json("{\n  \"version\": 1,\n  \"max_iter\": ]\n}").unwrap_err();
+        assert!(
+            matches!(syntax, Error::Parse { line: 3, .. }),
+            "{:?}",
+            syntax
+        );
+        // ...while well-formed JSON of the wrong shape has none to point at.
+        let shape = Session::from_json(r#"{"version": 1, "max_iter": "many"}"#).unwrap_err();
+        assert!(matches!(shape, Error::InvalidConfig(_)), "{:?}", shape);
+        assert!(shape.to_string().starts_with("invalid session file"));
+    }
+}
@@ -0,0 +1,171 @@
+//! Numbers that sum up a run, for a machine-readable report next to the
+//! image.
+//!
+//! [`box_dimension`] estimates the fractal dimension of the points by box
+//! counting: cover them with a grid, halve the boxes again and again, and
+//! see how fast the number of boxes in use grows. It grows by the factor
+//! 2^d per halving for a set of dimension d, so the slope of the counts
+//! against the box size on a log-log scale is the dimension.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+/// How many times the box side is halved, from the whole bounding box. The
+/// first halvings say little about the fine structure and the last are
+/// limited by the number of points, so the fit uses [`FIT_FROM`] on.
+pub const HALVINGS: u32 = 7;
+/// The first halving the fit of [`box_dimension`] uses.
+pub const FIT_FROM: u32 = 2;
+
+/// The smallest box containing every finite one of `points`, as its
+/// corners with the smallest and the largest coordinates.
+///
+/// ```
+/// use sierpinski_triangle_emulator::stats;
+///
+/// let points = [[1.0, 5.0], [3.0, -2.0], [f32::NAN, 0.0]];
+/// assert_eq!(stats::bounding_box(points), Some(([1.0, -2.0], [3.0, 5.0])));
+/// assert_eq!(stats::bounding_box([]), None);
+/// ```
+pub fn bounding_box(points: impl IntoIterator<Item = [f32; 2]>) -> Option<([f32; 2], [f32; 2])> {
+    points
+        .into_iter()
+        .filter(|[x, y]| x.is_finite() && y.is_finite())
+        .fold(None, |bounds, [x, y]| match bounds {
+            None => Some(([x, y], [x, y])),
+            Some((min, max)) => Some((
+                [f32::min(min[0], x), f32::min(min[1], y)],
+                [f32::max(max[0], x), f32::max(max[1], y)],
+            )),
+        })
+}
+
+/// The mean of the finite ones of `points`.
+///
+/// ```
+/// use sierpinski_triangle_emulator::stats;
+///
+/// assert_eq!(stats::mean([[0.0, 0.0], [4.0, 2.0]]), Some([2.0, 1.0]));
+/// assert_eq!(stats::mean([]), None);
+/// ```
+pub fn mean(points: impl IntoIterator<Item = [f32; 2]>) -> Option<[f32; 2]> {
+    let (sum, n) = points
+        .into_iter()
+        .filter(|[x, y]| x.is_finite() && y.is_finite())
+        .fold(([0.0_f64; 2], 0_usize), |(sum, n), [x, y]| {
+            ([sum[0] + x as f64, sum[1] + y as f64], n + 1)
+        });
+    (n > 0).then(|| [(sum[0] / n as f64) as f32, (sum[1] / n as f64) as f32])
+}
+
+/// The box-counting dimension of `points`, fitted by least squares over the
+/// halvings from [`FIT_FROM`] to [`HALVINGS`] of their bounding square.
+/// `None` for fewer than two distinct points.
+///
+/// ```
+/// use sierpinski_triangle_emulator::{stats, ChaosGame};
+///
+/// let triangle = ChaosGame::new([[0.0, 0.0], [1000.0, 0.0], [500.0, 866.0]])
+///     .seed(1)
+///     .points(100_000);
+/// // log 3 / log 2, the dimension of the Sierpinski triangle.
+/// let dimension = stats::box_dimension(triangle.iter().copied()).unwrap();
+/// assert!((dimension - 1.585).abs() < 0.05, "{}", dimension);
+///
+/// let diagonal = (0..10_000).map(|i| [i as f32, i as f32]);
+/// assert!((stats::box_dimension(diagonal).unwrap() - 1.0).abs() < 0.01);
+/// assert_eq!(stats::box_dimension([[1.0, 1.0], [1.0, 1.0]]), None);
+/// ```
+pub fn box_dimension(points: impl IntoIterator<Item = [f32; 2]> + Clone) -> Option<f32> {
+    let (min, max) = bounding_box(points.clone())?;
+    let extent = (max[0] - min[0]).max(max[1] - min[1]);
+    if extent <= 0.0 {
+        return None;
+    }
+    let samples = (FIT_FROM..=HALVINGS)
+        .map(|halvings| {
+            let boxes = (1_u32 << halvings) as f32;
+            let occupied = points
+                .clone()
+                .into_iter()
+                .filter(|[x, y]| x.is_finite() && y.is_finite())
+                .map(|[x, y]| {
+                    let cell = |v: f32, min: f32| ((v - min) / extent * boxes).min(boxes - 1.0);
+                    (cell(x, min[0]) as u32, cell(y, min[1]) as u32)
+                })
+                .collect::<HashSet<_>>()
+                .len();
+            (halvings as f64, (occupied as f64).log2())
+        })
+        .collect::<Vec<_>>();
+    // Box side and count are both powers of two, so the slope of log2 of the
+    // count over the halvings is the dimension.
+    let n = samples.len() as f64;
+    let mean_x = samples.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = samples.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance = samples
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum::<f64>();
+    let variance = samples
+        .iter()
+        .map(|(x, _)| (x - mean_x).powi(2))
+        .sum::<f64>();
+    Some((covariance / variance) as f32)
+}
+
+/// A corner-to-corner box, as written into a [`Report`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Bounds {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+/// The summary of a run written as JSON next to its images.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub point_count: usize,
+    /// See [`box_dimension`].
+    pub box_dimension: Option<f32>,
+    /// Distinct screen pixels the points are drawn on.
+    pub occupied_pixels: usize,
+    pub bounding_box: Option<Bounds>,
+    pub centroid: Option<[f32; 2]>,
+    /// How often each vertex was picked, in vertex order.
+    pub selection_counts: Vec<usize>,
+}
+
+impl Report {
+    /// The report of `points`, with the counts only the caller knows.
+    ///
+    /// ```
+    /// use sierpinski_triangle_emulator::stats::Report;
+    ///
+    /// let report = Report::new(&[[0.0, 0.0], [2.0, 4.0]], 2, vec![1, 1]);
+    /// assert_eq!(report.point_count, 2);
+    /// assert_eq!(report.centroid, Some([1.0, 2.0]));
+    /// assert!(report.to_json().contains("\"selection_counts\": [\n    1,\n    1\n  ]"));
+    /// ```
+    pub fn new(
+        points: &[[f32; 2]],
+        occupied_pixels: usize,
+        selection_counts: Vec<usize>,
+    ) -> Report {
+        Report {
+            point_count: points.len(),
+            box_dimension: box_dimension(points.iter().copied()),
+            occupied_pixels,
+            bounding_box: bounding_box(points.iter().copied())
+                .map(|(min, max)| Bounds { min, max }),
+            centroid: mean(points.iter().copied()),
+            selection_counts,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        // Numbers and lists of them always serialize; non-finite ones come
+        // out as null.
+        serde_json::to_string_pretty(self).expect("a report serializes")
+    }
+}
@@ -0,0 +1,111 @@
+//! Points as an SVG document of squares or circles, for vector editors.
+//!
+//! Every point is an element of its own, so a long run makes a file too
+//! large to open. [`decimate`] thins the points evenly to a budget first;
+//! the orbit visits the attractor all over from early on, so an even
+//! sample of it still shows the whole shape.
+
+use crate::render::PointShape;
+
+/// Opacity of the outer circle of a soft point, relative to the point.
+pub const SOFT_HALO: f32 = 0.3;
+
+/// The indices of `budget` of `len` items spread evenly from the first on,
+/// or all of them if there are no more than `budget`.
+///
+/// ```
+/// use sierpinski_triangle_emulator::svg::decimate;
+///
+/// assert_eq!(decimate(10, 4).collect::<Vec<_>>(), [0, 2, 5, 7]);
+/// assert_eq!(decimate(3, 100).collect::<Vec<_>>(), [0, 1, 2]);
+/// assert_eq!(decimate(1_000_000, 1000).count(), 1000);
+/// ```
+pub fn decimate(len: usize, budget: usize) -> impl Iterator<Item = usize> {
+    let kept = len.min(budget);
+    (0..kept).map(move |i| (i as u64 * len as u64 / kept as u64) as usize)
+}
+
+/// The `points`, each with its RGBA colour, as `shape` marks of side `side`
+/// with their top left corner at the point, on a `size` canvas. Soft points
+/// are two concentric circles, a faint one as wide as the fade and a solid
+/// one half as wide. A fully transparent `background` leaves it out.
+///
+/// ```
+/// use sierpinski_triangle_emulator::{render::PointShape, svg};
+///
+/// let points = [([1.0, 2.0], [255, 0, 0, 255]), ([3.5, 0.0], [0, 0, 0, 128])];
+/// let document = svg::to_svg(points, [10.0, 10.0], [255, 255, 255, 255], PointShape::Square, 1.0);
+/// assert!(document.starts_with("<svg "));
+/// assert_eq!(document.matches("<rect").count(), 3);
+/// assert!(document.contains(r##"<rect x="1" y="2" width="1" height="1" fill="#ff0000"/>"##));
+/// assert!(document.contains(r#"fill-opacity="0.5""#));
+///
+/// let document = svg::to_svg(points, [10.0, 10.0], [0, 0, 0, 0], PointShape::Soft, 2.0);
+/// assert_eq!(document.matches("<circle").count(), 4);
+/// assert!(document.contains(r##"<circle cx="2" cy="3" r="1" fill="#ff0000"/>"##));
+/// ```
+pub fn to_svg(
+    points: impl IntoIterator<Item = ([f32; 2], [u8; 4])>,
+    [width, height]: [f32; 2],
+    background: [u8; 4],
+    shape: PointShape,
+    side: f32,
+) -> String {
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    svg.push('\n');
+    if background[3] > 0 {
+        svg += &format!(
+            "<rect width=\"{}\" height=\"{}\" {}/>\n",
+            width,
+            height,
+            fill(background)
+        );
+    }
+    let half = side / 2.0;
+    let circle = |[x, y]: [f32; 2], r: f32, color: [u8; 4]| {
+        format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>\n",
+            x + half,
+            y + half,
+            r,
+            fill(color)
+        )
+    };
+    for ([x, y], color) in points {
+        svg += &match shape {
+            PointShape::Square => format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>\n",
+                x,
+                y,
+                side,
+                side,
+                fill(color)
+            ),
+            PointShape::Circle => circle([x, y], half, color),
+            PointShape::Soft => {
+                let [r, g, b, a] = color;
+                let faint = [r, g, b, (a as f32 * SOFT_HALO).round() as u8];
+                circle([x, y], PointShape::soft_radius(side), faint)
+                    + &circle([x, y], PointShape::soft_radius(side) / 2.0, color)
+            }
+        };
+    }
+    svg += "</svg>\n";
+    svg
+}
+
+fn fill([r, g, b, a]: [u8; 4]) -> String {
+    let opacity = match a {
+        255 => String::new(),
+        // Two places are plenty for 256 levels.
+        _ => format!(
+            r#" fill-opacity="{}""#,
+            (a as f32 / 255.0 * 100.0).round() / 100.0
+        ),
+    };
+    format!(r##"fill="#{:02x}{:02x}{:02x}"{}"##, r, g, b, opacity)
+}
@@ -0,0 +1,110 @@
+//! Small previews of the recent sessions for the gallery shown on launch,
+//! rasterized on the CPU once and then drawn into a canvas of their own.
+
+use iced::{
+    canvas::{Cache, Cursor, Geometry, Program},
+    Color, Point, Rectangle, Size,
+};
+use sierpinski_triangle_emulator::{
+    bounds::Viewport,
+    render::{self, RgbaImage},
+};
+
+use crate::session::Session;
+
+/// Side of a thumbnail, in pixels.
+pub const SIZE: u32 = 64;
+/// Points rasterized into a thumbnail, enough to make out the shape.
+const POINTS: usize = 4000;
+/// Pixels left free around the vertices.
+const MARGIN: f32 = 4.0;
+
+#[derive(Debug)]
+pub struct Thumbnail {
+    /// The timestamp of the recent entry the thumbnail was made for. The
+    /// entry gets a newer one whenever its session is saved or loaded.
+    pub timestamp: u64,
+    image: RgbaImage,
+    background: [u8; 4],
+    cache: Cache,
+}
+
+impl Thumbnail {
+    /// Plays the first orbit of `session` and rasterizes it scaled so the
+    /// vertices fill the thumbnail. `None` without any enabled vertex.
+    pub fn new(session: &Session, timestamp: u64) -> Option<Thumbnail> {
+        let enabled = session.enabled();
+        let points = session.game().try_points(POINTS).ok()?;
+
+        let viewport = fit(enabled.iter().map(|i| session.fix_points[*i]));
+        let points = points
+            .into_iter()
+            .map(|p| viewport.apply(p))
+            .collect::<Vec<_>>();
+        let style = session.style();
+        Some(Thumbnail {
+            timestamp,
+            image: render::render_at(&points, points.len(), &style, (SIZE, SIZE)),
+            background: style.background,
+            cache: Cache::new(),
+        })
+    }
+}
+
+/// The viewport fitting the box around `vertices` into the thumbnail,
+/// centred.
+fn fit(vertices: impl Iterator<Item = [f32; 2]>) -> Viewport {
+    let (min, max) = vertices.fold(
+        ([f32::INFINITY; 2], [f32::NEG_INFINITY; 2]),
+        |(min, max), [x, y]| {
+            (
+                [min[0].min(x), min[1].min(y)],
+                [max[0].max(x), max[1].max(y)],
+            )
+        },
+    );
+    let inner = SIZE as f32 - 2.0 * MARGIN;
+    let extent = (max[0] - min[0]).max(max[1] - min[1]);
+    if !extent.is_finite() || extent <= 0.0 {
+        return Viewport::IDENTITY;
+    }
+    let scale = inner / extent;
+    Viewport {
+        scale,
+        offset: [
+            (SIZE as f32 - (max[0] - min[0]) * scale) / 2.0 - min[0] * scale,
+            (SIZE as f32 - (max[1] - min[1]) * scale) / 2.0 - min[1] * scale,
+        ],
+    }
+}
+
+/// Draws a [`Thumbnail`] without handling any events, so a button around
+/// it still gets the clicks.
+pub struct Preview<'a>(pub &'a Thumbnail);
+
+impl<Message> Program<Message> for Preview<'_> {
+    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        let Thumbnail {
+            image, background, ..
+        } = self.0;
+        vec![self.0.cache.draw(bounds.size(), |frame| {
+            frame.fill_rectangle(Point::ORIGIN, frame.size(), color(*background));
+            for y in 0..image.height {
+                for x in 0..image.width {
+                    let pixel = image.pixel(x, y);
+                    if pixel != *background {
+                        frame.fill_rectangle(
+                            Point::new(x as f32, y as f32),
+                            Size::new(1.0, 1.0),
+                            color(pixel),
+                        );
+                    }
+                }
+            }
+        })]
+    }
+}
+
+fn color([r, g, b, a]: [u8; 4]) -> Color {
+    Color::from_rgba8(r, g, b, a as f32 / 255.0)
+}
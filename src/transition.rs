@@ -0,0 +1,54 @@
+use std::time::{Duration, Instant};
+
+use iced::Point;
+
+const DURATION: Duration = Duration::from_millis(300);
+
+/// An eased interpolation of the fix points from one layout to another.
+#[derive(Debug)]
+pub struct Transition {
+    from: Vec<Point>,
+    to: Vec<Point>,
+    started: Instant,
+}
+
+impl Transition {
+    /// Targets without a counterpart in `from` grow out of the last point of
+    /// `from`, extra points of `from` are dropped right away.
+    pub fn new(from: &[Point], to: Vec<Point>) -> Transition {
+        let from = to
+            .iter()
+            .enumerate()
+            .map(|(i, target)| from.get(i).or_else(|| from.last()).unwrap_or(target))
+            .copied()
+            .collect();
+        Transition {
+            from,
+            to,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn is_finished(&self, now: Instant) -> bool {
+        now.duration_since(self.started) >= DURATION
+    }
+
+    pub fn target(&self) -> &[Point] {
+        &self.to
+    }
+
+    pub fn points_at(&self, now: Instant) -> Vec<Point> {
+        let t = (now.duration_since(self.started).as_secs_f32() / DURATION.as_secs_f32()).min(1.0);
+        // ease-in-out cubic
+        let t = if t < 0.5 {
+            4.0 * t * t * t
+        } else {
+            1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+        };
+        self.from
+            .iter()
+            .zip(self.to.iter())
+            .map(|(a, b)| Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t))
+            .collect()
+    }
+}
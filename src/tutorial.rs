@@ -0,0 +1,253 @@
+//! Guided walks through building a fractal a step at a time. Each step
+//! shows an instruction and waits for the message that carries it out,
+//! so the user builds the fractal with the real controls. A tutorial is
+//! nothing but a list of steps; another one only needs another list.
+
+use sierpinski_triangle_emulator::{geometry, i18n::Key};
+
+use crate::Message;
+
+/// The state a step checks once a message has been handled.
+pub struct Facts {
+    pub fix_points: Vec<[f32; 2]>,
+    pub max_iter: i32,
+    pub cur_iter: i32,
+}
+
+/// What a step waits for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Goal {
+    /// Exactly this many vertices placed, spread out rather than nearly in
+    /// a line.
+    PlaceVertices(usize),
+    /// The max iteration raised to at least this.
+    MaxIter(i32),
+    /// The current iteration moved back below the max.
+    ScrubCurIter,
+}
+
+/// Below this ratio of area to squared perimeter the vertices count as
+/// nearly in a line. An equilateral triangle has about 0.048.
+const SPREAD: f32 = 0.01;
+
+impl Goal {
+    /// Whether `message`, just handled, leaves `facts` reaching the goal.
+    pub fn reached(self, message: &Message, facts: &Facts) -> bool {
+        match self {
+            Goal::PlaceVertices(count) => {
+                matches!(message, Message::AddFixPoint(_))
+                    && facts.fix_points.len() == count
+                    && spread(&facts.fix_points)
+            }
+            Goal::MaxIter(at_least) => {
+                matches!(message, Message::SetMaxIter(_)) && facts.max_iter >= at_least
+            }
+            Goal::ScrubCurIter => {
+                matches!(message, Message::SetCurIter(_) | Message::SetCurFraction(_))
+                    && facts.cur_iter < facts.max_iter
+            }
+        }
+    }
+
+    /// Why the goal isn't reached yet when the state stands in its way.
+    pub fn hint(self, facts: &Facts) -> Option<Key> {
+        match self {
+            Goal::PlaceVertices(count) if facts.fix_points.len() > count => {
+                Some(Key::TutorialTooManyVertices)
+            }
+            Goal::PlaceVertices(count)
+                if facts.fix_points.len() == count && !spread(&facts.fix_points) =>
+            {
+                Some(Key::TutorialInALine)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn spread(vertices: &[[f32; 2]]) -> bool {
+    let perimeter = geometry::perimeter(vertices);
+    perimeter > 0.0 && geometry::signed_area(vertices).abs() / (perimeter * perimeter) >= SPREAD
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    pub instruction: Key,
+    pub goal: Goal,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Tutorial {
+    pub name: Key,
+    pub steps: &'static [Step],
+}
+
+pub const TRIANGLE: Tutorial = Tutorial {
+    name: Key::TutorialTriangle,
+    steps: &[
+        Step {
+            instruction: Key::TutorialPlaceVertices,
+            goal: Goal::PlaceVertices(3),
+        },
+        Step {
+            instruction: Key::TutorialRaiseMaxIter,
+            goal: Goal::MaxIter(1000),
+        },
+        Step {
+            instruction: Key::TutorialScrubCurIter,
+            goal: Goal::ScrubCurIter,
+        },
+        Step {
+            instruction: Key::TutorialFillIn,
+            goal: Goal::MaxIter(10000),
+        },
+    ],
+};
+
+/// A tutorial under way.
+#[derive(Debug)]
+pub struct Progress {
+    pub tutorial: &'static Tutorial,
+    /// Index of the current step; past the last one once finished.
+    pub step: usize,
+}
+
+impl Progress {
+    pub fn new(tutorial: &'static Tutorial) -> Progress {
+        Progress { tutorial, step: 0 }
+    }
+
+    pub fn current(&self) -> Option<&'static Step> {
+        self.tutorial.steps.get(self.step)
+    }
+
+    /// Moves on if `message` reached the current goal.
+    pub fn observe(&mut self, message: &Message, facts: &Facts) {
+        if self
+            .current()
+            .is_some_and(|step| step.goal.reached(message, facts))
+        {
+            self.step += 1;
+        }
+    }
+
+    pub fn skip(&mut self) {
+        self.step += 1;
+    }
+
+    pub fn finished(&self) -> bool {
+        self.current().is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iced::Point;
+
+    use super::*;
+
+    fn facts(fix_points: &[[f32; 2]], max_iter: i32, cur_iter: i32) -> Facts {
+        Facts {
+            fix_points: fix_points.to_vec(),
+            max_iter,
+            cur_iter,
+        }
+    }
+
+    const TRIANGLE_VERTICES: [[f32; 2]; 3] = [[300.0, 40.0], [40.0, 560.0], [560.0, 560.0]];
+
+    fn add() -> Message {
+        Message::AddFixPoint(Point::ORIGIN)
+    }
+
+    #[test]
+    fn observe_only_advances_on_the_message_reaching_the_goal() {
+        let mut progress = Progress::new(&TRIANGLE);
+        let placed = facts(&TRIANGLE_VERTICES, 0, 0);
+        // The vertices are there, but something else was done.
+        progress.observe(&Message::SetMaxIter(0), &placed);
+        assert_eq!(progress.step, 0);
+        progress.observe(&add(), &facts(&TRIANGLE_VERTICES[..2], 0, 0));
+        assert_eq!(progress.step, 0);
+        progress.observe(&add(), &placed);
+        assert_eq!(progress.step, 1);
+
+        progress.observe(
+            &Message::SetMaxIter(999),
+            &facts(&TRIANGLE_VERTICES, 999, 999),
+        );
+        assert_eq!(progress.step, 1);
+        progress.observe(&add(), &facts(&TRIANGLE_VERTICES, 1000, 1000));
+        assert_eq!(progress.step, 1);
+        progress.observe(
+            &Message::SetMaxIter(1000),
+            &facts(&TRIANGLE_VERTICES, 1000, 1000),
+        );
+        assert_eq!(progress.step, 2);
+
+        // Scrubbing has to leave the current iteration below the max.
+        progress.observe(
+            &Message::SetCurIter(1000),
+            &facts(&TRIANGLE_VERTICES, 1000, 1000),
+        );
+        assert_eq!(progress.step, 2);
+        progress.observe(
+            &Message::SetCurFraction(0.5),
+            &facts(&TRIANGLE_VERTICES, 1000, 500),
+        );
+        assert_eq!(progress.current().unwrap().instruction, Key::TutorialFillIn);
+    }
+
+    #[test]
+    fn vertices_in_a_line_get_a_hint_and_no_progress() {
+        let mut progress = Progress::new(&TRIANGLE);
+        let in_a_line = facts(&[[40.0, 300.0], [300.0, 302.0], [560.0, 300.0]], 0, 0);
+        progress.observe(&add(), &in_a_line);
+        assert_eq!(progress.step, 0);
+        let goal = progress.current().unwrap().goal;
+        assert_eq!(goal.hint(&in_a_line), Some(Key::TutorialInALine));
+        assert_eq!(goal.hint(&facts(&TRIANGLE_VERTICES, 0, 0)), None);
+        // All on one spot is no triangle either.
+        assert_eq!(
+            goal.hint(&facts(&[[300.0, 300.0]; 3], 0, 0)),
+            Some(Key::TutorialInALine)
+        );
+    }
+
+    #[test]
+    fn too_many_vertices_get_a_hint() {
+        let goal = Goal::PlaceVertices(3);
+        let four = [
+            TRIANGLE_VERTICES[0],
+            TRIANGLE_VERTICES[1],
+            TRIANGLE_VERTICES[2],
+            [300.0, 300.0],
+        ];
+        assert_eq!(
+            goal.hint(&facts(&four, 0, 0)),
+            Some(Key::TutorialTooManyVertices)
+        );
+        assert!(!goal.reached(&add(), &facts(&four, 0, 0)));
+        // Fewer is simply not done yet.
+        assert_eq!(goal.hint(&facts(&TRIANGLE_VERTICES[..2], 0, 0)), None);
+        assert_eq!(Goal::MaxIter(1000).hint(&facts(&four, 0, 0)), None);
+    }
+
+    #[test]
+    fn skipping_every_step_finishes() {
+        let mut progress = Progress::new(&TRIANGLE);
+        for step in TRIANGLE.steps {
+            assert!(!progress.finished());
+            assert_eq!(progress.current(), Some(step));
+            progress.skip();
+        }
+        assert!(progress.finished());
+        assert_eq!(progress.current(), None);
+        // Nothing moves a finished tutorial on.
+        progress.observe(
+            &Message::SetMaxIter(10000),
+            &facts(&TRIANGLE_VERTICES, 10000, 10000),
+        );
+        assert_eq!(progress.step, TRIANGLE.steps.len());
+    }
+}
@@ -0,0 +1,170 @@
+//! A wrapper that turns mouse wheel scrolling over a widget into messages,
+//! so sliders can be nudged without dragging.
+
+use std::hash::Hash;
+
+use iced_native::{
+    event, keyboard, layout, mouse, overlay, Clipboard, Element, Event, Hasher, Layout, Length,
+    Point, Rectangle, Size, Widget,
+};
+
+/// Remembers the keyboard modifiers held while scrolling.
+#[derive(Debug, Default)]
+pub struct State {
+    modifiers: keyboard::Modifiers,
+}
+
+pub struct Wheel<'a, Message, Renderer> {
+    state: &'a mut State,
+    content: Element<'a, Message, Renderer>,
+    /// The narrowest the content is laid out, however little room is left.
+    min_width: u32,
+    /// Called with +1 for scrolling up, -1 for scrolling down.
+    on_scroll: Box<dyn Fn(i32, keyboard::Modifiers) -> Message + 'a>,
+}
+
+impl<'a, Message, Renderer> Wheel<'a, Message, Renderer> {
+    pub fn new<F>(
+        state: &'a mut State,
+        content: impl Into<Element<'a, Message, Renderer>>,
+        on_scroll: F,
+    ) -> Self
+    where
+        F: 'a + Fn(i32, keyboard::Modifiers) -> Message,
+    {
+        Wheel {
+            state,
+            content: content.into(),
+            min_width: 0,
+            on_scroll: Box::new(on_scroll),
+        }
+    }
+
+    /// Keeps the content at least `min_width` wide, so a slider filling
+    /// what is left of a row stays usable in a narrow window.
+    pub fn min_width(mut self, min_width: u32) -> Self {
+        self.min_width = min_width;
+        self
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for Wheel<'a, Message, Renderer>
+where
+    Renderer: iced_native::Renderer,
+{
+    fn width(&self) -> Length {
+        self.content.width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.height()
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        let min_width = self.min_width as f32;
+        let limits = layout::Limits::new(
+            Size::new(limits.min().width.max(min_width), limits.min().height),
+            Size::new(limits.max().width.max(min_width), limits.max().height),
+        );
+        self.content.layout(renderer, &limits)
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        defaults: &Renderer::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) -> Renderer::Output {
+        self.content
+            .draw(renderer, defaults, layout, cursor_position, viewport)
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.min_width.hash(state);
+        self.content.hash_layout(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        messages: &mut Vec<Message>,
+    ) -> event::Status {
+        match event {
+            Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                self.state.modifiers = modifiers;
+            }
+            Event::Mouse(mouse::Event::WheelScrolled { delta })
+                if layout.bounds().contains(cursor_position) =>
+            {
+                let y = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } | mouse::ScrollDelta::Pixels { y, .. } => y,
+                };
+                if y != 0.0 {
+                    messages.push((self.on_scroll)(y.signum() as i32, self.state.modifiers));
+                }
+                return event::Status::Captured;
+            }
+            _ => {}
+        }
+        self.content.on_event(
+            event,
+            layout,
+            cursor_position,
+            renderer,
+            clipboard,
+            messages,
+        )
+    }
+
+    fn overlay(&mut self, layout: Layout<'_>) -> Option<overlay::Element<'_, Message, Renderer>> {
+        self.content.overlay(layout)
+    }
+}
+
+impl<'a, Message, Renderer> From<Wheel<'a, Message, Renderer>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+    Renderer: 'a + iced_native::Renderer,
+{
+    fn from(wheel: Wheel<'a, Message, Renderer>) -> Element<'a, Message, Renderer> {
+        Element::new(wheel)
+    }
+}
+
+// iced_native only has its null renderer in debug builds.
+#[cfg(all(test, debug_assertions))]
+mod tests {
+    use iced_native::{renderer::Null, slider, Row, Slider, Text};
+
+    use super::*;
+
+    /// The width the slider gets after a 300 px label in a 320 px row.
+    fn slider_width(min_width: u32) -> f32 {
+        let mut wheel = State::default();
+        let mut slider = slider::State::new();
+        let slider = Slider::new(&mut slider, 0..=100, 50, |_| ()).width(Length::Fill);
+        let row: Element<'_, (), Null> = Row::new()
+            .push(Text::new("").width(Length::Units(300)))
+            .push(Wheel::new(&mut wheel, slider, |_, _| ()).min_width(min_width))
+            .into();
+        let limits = layout::Limits::new(Size::ZERO, Size::new(320.0, 100.0));
+        row.layout(&Null::new(), &limits).children()[1]
+            .bounds()
+            .width
+    }
+
+    #[test]
+    fn a_narrow_row_keeps_the_minimum_width() {
+        assert_eq!(slider_width(0), 20.0);
+        assert_eq!(slider_width(160), 160.0);
+    }
+}
@@ -0,0 +1,123 @@
+//! Drives `--batch` through the binary over the fixture sessions.
+
+use std::{
+    fs,
+    ops::Not,
+    path::{Path, PathBuf},
+};
+
+use assert_cmd::{assert::Assert, Command};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+/// An empty directory under the system's temporary directory, unique to the
+/// test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("sierpinski-batch-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A directory with both fixture sessions, one without vertices, one that
+/// isn't JSON and a file that isn't a session at all.
+fn sessions(name: &str) -> PathBuf {
+    let dir = temp_dir(name);
+    for fixture in ["session-v1-first.json", "session-v1-slots.json"] {
+        fs::copy(Path::new(FIXTURES).join(fixture), dir.join(fixture)).unwrap();
+    }
+    fs::write(
+        dir.join("empty.json"),
+        r#"{"version": 1, "fix_points": []}"#,
+    )
+    .unwrap();
+    fs::write(dir.join("broken.json"), "{ \"version\": 1,").unwrap();
+    fs::write(dir.join("notes.txt"), "not a session").unwrap();
+    dir
+}
+
+fn run(args: &[&str], config: &Path) -> Assert {
+    Command::cargo_bin("sierpinski-triangle-emulator")
+        .unwrap()
+        .args(args)
+        .env("XDG_CONFIG_HOME", config)
+        .assert()
+}
+
+fn stdout(assert: &Assert) -> String {
+    String::from_utf8_lossy(&assert.get_output().stdout).into_owned()
+}
+
+fn png(path: &Path) -> bool {
+    fs::read(path).is_ok_and(|bytes| bytes.starts_with(b"\x89PNG"))
+}
+
+#[test]
+fn renders_next_to_the_sessions_and_sums_up_the_failures() {
+    let dir = sessions("next-to");
+    let assert = run(&["--batch", dir.to_str().unwrap(), "--iters", "2000"], &dir).code(1);
+    let stdout = stdout(&assert);
+    assert!(stdout.starts_with("2 succeeded, 2 failed\n"), "{}", stdout);
+    assert!(stdout.contains("broken.json: line 1"), "{}", stdout);
+    assert!(stdout.contains("empty.json: the game needs"), "{}", stdout);
+
+    assert!(png(&dir.join("session-v1-first.png")));
+    assert!(png(&dir.join("session-v1-slots.png")));
+    assert!(dir.join("empty.png").exists().not());
+    assert!(dir.join("broken.png").exists().not());
+    assert!(dir.join("notes.png").exists().not());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn writes_into_the_out_dir() {
+    let dir = sessions("out-dir");
+    for broken in ["empty.json", "broken.json"] {
+        fs::remove_file(dir.join(broken)).unwrap();
+    }
+    let out = dir.join("images");
+    run(
+        &[
+            "--batch",
+            dir.to_str().unwrap(),
+            "--out-dir",
+            out.to_str().unwrap(),
+            "--iters",
+            "2000",
+            "--width",
+            "300",
+            "--height",
+            "200",
+        ],
+        &dir,
+    )
+    .success()
+    .stdout("2 succeeded, 0 failed\n");
+    assert!(png(&out.join("session-v1-first.png")));
+    assert!(png(&out.join("session-v1-slots.png")));
+    assert!(dir.join("session-v1-first.png").exists().not());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn a_missing_directory_exits_with_an_io_error() {
+    let dir = temp_dir("missing");
+    let assert = run(&["--batch", dir.join("nowhere").to_str().unwrap()], &dir).code(74);
+    assert!(String::from_utf8_lossy(&assert.get_output().stderr).contains("nowhere"));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn bad_arguments_exit_with_the_usage() {
+    let dir = temp_dir("usage");
+    for args in [
+        &["--iters", "2000"][..],
+        &["--batch", dir.to_str().unwrap(), "--width", "4294967896"],
+        &["--batch"],
+    ] {
+        let assert = run(args, &dir).code(2);
+        assert!(String::from_utf8_lossy(&assert.get_output().stderr).contains("usage:"));
+    }
+    let _ = fs::remove_dir_all(&dir);
+}